@@ -204,7 +204,7 @@ fn opcode_categorization() {
     for opcode in all_opcodes {
         let group = opcode.group();
         let name = format!("{}", opcode);
-        categories.entry(group).or_insert_with(Vec::new).push(name);
+        categories.entry(group).or_default().push(name);
     }
 
     // Sort categories by group name for consistent output
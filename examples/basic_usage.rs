@@ -2,7 +2,7 @@
 //!
 //! Run with: cargo run --example basic_usage
 
-use eot::{forks::*, Fork, OpCode};
+use eot::{disassemble, forks::*, Fork, OpCode};
 
 fn main() {
     println!("🚀 EOT (EVM Opcode Table) - Basic Usage Example\n");
@@ -163,27 +163,27 @@ fn gas_cost_analysis() {
     println!("==============================");
 
     // Analyze a simple contract sequence
-    let contract_opcodes = vec![
-        0x60, // PUSH1
-        0x60, // PUSH1
+    let bytecode = [
+        0x60, 0x01, // PUSH1 0x01
+        0x60, 0x02, // PUSH1 0x02
         0x01, // ADD
-        0x60, // PUSH1
+        0x60, 0x00, // PUSH1 0x00
         0x55, // SSTORE
-        0x60, // PUSH1
+        0x60, 0x00, // PUSH1 0x00
         0x54, // SLOAD
         0xf3, // RETURN
     ];
 
-    println!("Analyzing contract opcode sequence:");
+    println!("Analyzing contract bytecode:");
     let mut total_gas = 0u64;
 
-    for (i, &byte) in contract_opcodes.iter().enumerate() {
-        if Cancun::has_opcode(byte) {
-            let opcode = Cancun::from(byte);
-            let gas = opcode.gas_cost();
+    // Decode first so PUSH immediates are not mistaken for opcodes
+    for instruction in disassemble(&bytecode) {
+        if Cancun::has_opcode(instruction.opcode) {
+            let gas = Cancun::from(instruction.opcode).gas_cost();
             total_gas += gas as u64;
 
-            println!("  {}: {} (0x{:02x}) - {} gas", i + 1, opcode, byte, gas);
+            println!("  pc {}: {} - {} gas", instruction.pc, instruction, gas);
         }
     }
 
@@ -3,7 +3,7 @@
 //! This example shows how to build a basic contract analyzer using EOT
 //! Run with: cargo run --example contract_analyzer
 
-use eot::{forks::*, Fork, Group, OpCode, OpcodeRegistry};
+use eot::{disassemble, forks::*, Fork, Group, OpCode, OpcodeRegistry};
 use std::collections::HashMap;
 
 fn main() {
@@ -262,14 +262,16 @@ impl ContractAnalyzer {
             opcode_groups: HashMap::new(),
         };
 
-        for &byte in bytecode {
+        // Decode first so PUSH immediates are not mistaken for opcodes
+        let opcodes: Vec<u8> = disassemble(bytecode).iter().map(|i| i.opcode).collect();
+        for &byte in &opcodes {
             if Cancun::has_opcode(byte) {
                 let opcode = Cancun::from(byte);
                 self.analyze_opcode(opcode, &mut analysis);
             }
         }
 
-        analysis.min_fork_required = find_minimum_fork(bytecode, &self.registry);
+        analysis.min_fork_required = find_minimum_fork(&opcodes, &self.registry);
         analysis
     }
 
@@ -289,7 +291,7 @@ impl ContractAnalyzer {
             0x5c | 0x5d => analysis.storage_ops += 1, // TLOAD, TSTORE
 
             // Memory operations
-            0x51 | 0x52 | 0x53 => analysis.memory_ops += 1, // MLOAD, MSTORE, MSTORE8
+            0x51..=0x53 => analysis.memory_ops += 1, // MLOAD, MSTORE, MSTORE8
 
             // Arithmetic operations
             0x01..=0x0b => analysis.arithmetic_ops += 1, // ADD through SIGNEXTEND
@@ -328,19 +330,7 @@ fn get_security_description(pattern: &str) -> &'static str {
 }
 
 fn find_minimum_fork(opcodes: &[u8], registry: &OpcodeRegistry) -> Fork {
-    let forks = [
-        Fork::Frontier,
-        Fork::Homestead,
-        Fork::Byzantium,
-        Fork::Constantinople,
-        Fork::Istanbul,
-        Fork::Berlin,
-        Fork::London,
-        Fork::Shanghai,
-        Fork::Cancun,
-    ];
-
-    for &fork in &forks {
+    for fork in Fork::execution_forks().filter(|fork| fork.changes_evm()) {
         let all_available = opcodes
             .iter()
             .all(|&opcode| registry.is_opcode_available(fork, opcode));
@@ -350,7 +340,7 @@ fn find_minimum_fork(opcodes: &[u8], registry: &OpcodeRegistry) -> Fork {
         }
     }
 
-    Fork::Cancun // Fallback to latest
+    Fork::Prague // Fallback to latest
 }
 
 fn find_incompatible_features(opcodes: &[u8]) -> String {
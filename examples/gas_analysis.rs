@@ -54,8 +54,8 @@ fn basic_gas_analysis() -> Result<(), Box<dyn std::error::Error>> {
     ];
 
     // Analyze gas usage using the registry
-    use crate::traits::OpcodeAnalysis;
-    let analysis = OpcodeRegistry::analyze_gas_usage(&opcodes, Fork::London);
+    let registry = OpcodeRegistry::new();
+    let analysis = registry.analyze_gas(&opcodes, Fork::London);
 
     println!("Bytecode analysis:");
     println!("  Total gas: {} gas", analysis.total_gas);
@@ -69,7 +69,7 @@ fn basic_gas_analysis() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     // Get optimization suggestions
-    let suggestions = OpcodeRegistry::get_optimization_suggestions(&opcodes, Fork::Shanghai);
+    let suggestions = registry.optimization_suggestions(&opcodes, Fork::Shanghai);
     if !suggestions.is_empty() {
         println!("\nOptimization suggestions:");
         for (i, suggestion) in suggestions.iter().enumerate() {
@@ -78,7 +78,7 @@ fn basic_gas_analysis() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     // Validate the sequence
-    match OpcodeRegistry::validate_opcode_sequence(&opcodes, Fork::London) {
+    match registry.validate_sequence(&opcodes, Fork::London) {
         Ok(()) => println!("\n✅ Opcode sequence is valid"),
         Err(e) => println!("\n❌ Validation error: {}", e),
     }
@@ -141,17 +141,16 @@ fn analyze_erc20_transfer() -> Result<(), Box<dyn std::error::Error>> {
     let calculator = DynamicGasCalculator::new(Fork::London);
 
     // Simplified ERC-20 transfer sequence
-    let transfer_sequence = vec![
-        (0x54, vec![0x1001]),        // SLOAD - sender balance
-        (0x54, vec![0x1002]),        // SLOAD - receiver balance
-        (0x03, vec![]),              // SUB - subtract from sender
-        (0x01, vec![]),              // ADD - add to receiver
-        (0x55, vec![0x1001, 0x100]), // SSTORE - update sender balance (key, value)
-        (0x55, vec![0x1002, 0x200]), // SSTORE - update receiver balance (key, value)
-        (0xa1, vec![0x40, 0x20]),    // LOG1 - Transfer event (offset, size)
-    ];
+    let transfer_sequence = Seq::new()
+        .sload(0x1001) // sender balance
+        .sload(0x1002) // receiver balance
+        .sub() // subtract from sender
+        .add() // add to receiver
+        .sstore(0x1001, 0x100) // update sender balance
+        .sstore(0x1002, 0x200) // update receiver balance
+        .log(0x40, 0x20, &[0x1]); // Transfer event with one topic
 
-    let analysis = calculator.analyze_sequence_gas(&transfer_sequence)?;
+    let analysis = calculator.analyze_sequence_gas(transfer_sequence.sequence())?;
 
     println!("ERC-20 Transfer Gas Analysis:");
     println!("  Total gas: {} gas", analysis.total_gas);
@@ -258,32 +257,30 @@ fn gas_optimization_analysis() -> Result<(), Box<dyn std::error::Error>> {
     println!("🔧 Example 5: Gas Optimization Analysis");
 
     // Original inefficient contract that reads the same storage slot multiple times
-    let original_contract = vec![
-        (0x54, vec![0x100]),       // SLOAD slot 0x100
-        (0x01, vec![]),            // ADD with something
-        (0x54, vec![0x100]),       // SLOAD same slot again (inefficient!)
-        (0x02, vec![]),            // MUL
-        (0x54, vec![0x100]),       // SLOAD same slot third time!
-        (0x03, vec![]),            // SUB
-        (0x55, vec![0x200, 0x42]), // SSTORE result (key, value)
-    ];
+    let original_contract = Seq::new()
+        .sload(0x100) // SLOAD slot 0x100
+        .add() // ADD with something
+        .sload(0x100) // SLOAD same slot again (inefficient!)
+        .mul()
+        .sload(0x100) // SLOAD same slot third time!
+        .sub()
+        .sstore(0x200, 0x42); // store the result
 
     // Optimized version that caches the storage value
-    let optimized_contract = vec![
-        (0x54, vec![0x100]),       // SLOAD slot 0x100 once
-        (0x80, vec![]),            // DUP1 - duplicate the value
-        (0x01, vec![]),            // ADD
-        (0x81, vec![]),            // DUP2 - use cached value
-        (0x02, vec![]),            // MUL
-        (0x82, vec![]),            // DUP3 - use cached value again
-        (0x03, vec![]),            // SUB
-        (0x55, vec![0x200, 0x42]), // SSTORE result (key, value)
-    ];
+    let optimized_contract = Seq::new()
+        .sload(0x100) // SLOAD slot 0x100 once
+        .dup(1) // duplicate the value
+        .add()
+        .dup(2) // use cached value
+        .mul()
+        .dup(3) // use cached value again
+        .sub()
+        .sstore(0x200, 0x42); // store the result
 
     let calculator = DynamicGasCalculator::new(Fork::London);
 
-    let original_analysis = calculator.analyze_sequence_gas(&original_contract)?;
-    let optimized_analysis = calculator.analyze_sequence_gas(&optimized_contract)?;
+    let original_analysis = calculator.analyze_sequence_gas(original_contract.sequence())?;
+    let optimized_analysis = calculator.analyze_sequence_gas(optimized_contract.sequence())?;
 
     println!("Original contract:");
     println!("  Total gas: {}", original_analysis.total_gas);
@@ -62,17 +62,6 @@ fn cross_fork_analysis() {
     let registry = OpcodeRegistry::new();
 
     // Analyze opcode counts across forks
-    let forks = [
-        Fork::Frontier,
-        Fork::Homestead,
-        Fork::Byzantium,
-        Fork::Constantinople,
-        Fork::Istanbul,
-        Fork::Berlin,
-        Fork::London,
-        Fork::Shanghai,
-        Fork::Cancun,
-    ];
 
     println!("Opcode evolution across forks:");
     println!(
@@ -82,7 +71,7 @@ fn cross_fork_analysis() {
     println!("{}", "-".repeat(50));
 
     let mut previous_count = 0;
-    for fork in forks {
+    for fork in Fork::execution_forks().filter(|fork| fork.changes_evm()) {
         let opcodes = registry.get_opcodes(fork);
         let count = opcodes.len();
         let growth = if previous_count == 0 {
@@ -212,19 +201,8 @@ fn historical_analysis() {
     for (category_name, opcodes) in categories {
         // Find the earliest fork that has all opcodes in this category
         let mut earliest_fork = Fork::Cancun;
-        let forks_to_check = [
-            Fork::Frontier,
-            Fork::Homestead,
-            Fork::Byzantium,
-            Fork::Constantinople,
-            Fork::Istanbul,
-            Fork::Berlin,
-            Fork::London,
-            Fork::Shanghai,
-            Fork::Cancun,
-        ];
 
-        for fork in forks_to_check {
+        for fork in Fork::execution_forks().filter(|fork| fork.changes_evm()) {
             let all_available = opcodes
                 .iter()
                 .all(|&opcode| registry.is_opcode_available(fork, opcode));
@@ -6,9 +6,44 @@ Run with: python3 generate_forks.py
 """
 
 import os
+import re
 import csv
 from io import StringIO
 
+# (fork, module, module doc) in activation order
+FORKS = [
+    ('Frontier', 'frontier', ''),
+    ('Homestead', 'homestead', ''),
+    ('TangerineWhistle', 'tangerine_whistle', """
+EIP-150 reprices IO-heavy opcodes: BALANCE, EXTCODESIZE, EXTCODECOPY, SLOAD, the CALL
+family and SELFDESTRUCT."""),
+    ('SpuriousDragon', 'spurious_dragon', """
+The table is unchanged from Tangerine Whistle. EIP-160 raises the per-byte cost of the
+EXP exponent from 10 to 50, which is a dynamic cost applied by the gas calculator."""),
+    ('Byzantium', 'byzantium', ''),
+    ('Constantinople', 'constantinople', ''),
+    ('Petersburg', 'petersburg', """
+Petersburg removes EIP-1283 net gas metering for SSTORE from Constantinople; the opcode
+table is otherwise unchanged."""),
+    ('Istanbul', 'istanbul', ''),
+    ('Berlin', 'berlin', ''),
+    ('London', 'london', ''),
+    ('Shanghai', 'shanghai', ''),
+    ('Cancun', 'cancun', ''),
+    ('Prague', 'prague', """
+Pectra adds no opcodes. EIP-2935 serves BLOCKHASH from a system contract holding the
+last 8191 block hashes, though the opcode keeps its 256-block window and cost. EIP-7702
+delegated accounts make CALL-family targets and EXTCODE* results depend on the
+delegation designator, which is a runtime property not modeled in this table."""),
+    ('Osaka', 'osaka', """
+Fusaka adds CLZ (EIP-7939) and caps the gas of a single transaction (EIP-7825), which is
+a transaction rule rather than an opcode change. This table follows the fork as
+scheduled and is only compiled with the `unstable-forks` feature."""),
+]
+
+# Forks still scheduled, compiled only with the `unstable-forks` feature
+UNSTABLE_FORKS = ['Osaka']
+
 def main():
     print("🚀 Generating EOT fork files...\n")
     
@@ -16,60 +51,63 @@ def main():
     os.makedirs("src/forks", exist_ok=True)
     
     # Generate all forks
-    generate_frontier()
-    generate_homestead()
-    generate_byzantium() 
-    generate_constantinople()
-    generate_istanbul()
-    generate_berlin()
-    generate_london()
-    generate_shanghai()
-    generate_cancun()
+    for fork_name, module, doc in FORKS:
+        generate_fork(fork_name, module, doc)
     generate_forks_mod()
     
     print("✅ All fork files generated successfully!")
 
+def get_fork_order():
+    """Fork names in activation order"""
+    return [fork_name for fork_name, _, _ in FORKS]
+
 def get_gas_history(opcode_hex, fork_name):
     """Get gas history for opcodes that changed between forks"""
     gas_changes = {
-        # EIP-2929 changes (Istanbul -> Berlin)
-        '0x54': [  # SLOAD
-            ('Istanbul', 800),
-            ('Berlin', 2100)
-        ],
-        '0x31': [  # BALANCE  
-            ('Istanbul', 400),
+        # EIP-150 (Tangerine Whistle), EIP-1884 (Istanbul) and EIP-2929 (Berlin)
+        '0x31': [  # BALANCE
+            ('TangerineWhistle', 400),
+            ('Istanbul', 700),
             ('Berlin', 2600)
         ],
         '0x3b': [  # EXTCODESIZE
-            ('Istanbul', 700), 
+            ('TangerineWhistle', 700),
             ('Berlin', 2600)
         ],
         '0x3c': [  # EXTCODECOPY
-            ('Istanbul', 700),
+            ('TangerineWhistle', 700),
             ('Berlin', 2600)
         ],
-        '0x3f': [  # EXTCODEHASH
-            ('Istanbul', 400),
+        '0x3f': [  # EXTCODEHASH (EIP-1052)
+            ('Constantinople', 400),
+            ('Istanbul', 700),
             ('Berlin', 2600)
         ],
+        '0x54': [  # SLOAD
+            ('TangerineWhistle', 200),
+            ('Istanbul', 800),
+            ('Berlin', 2100)
+        ],
         '0xf1': [  # CALL
-            ('Istanbul', 700),
+            ('TangerineWhistle', 700),
             ('Berlin', 2600)
         ],
         '0xf2': [  # CALLCODE
-            ('Istanbul', 700),
+            ('TangerineWhistle', 700),
             ('Berlin', 2600)
         ],
         '0xf4': [  # DELEGATECALL
-            ('Istanbul', 700),
+            ('TangerineWhistle', 700),
             ('Berlin', 2600)
         ],
-        '0xfa': [  # STATICCALL
-            ('Istanbul', 700),
+        '0xfa': [  # STATICCALL (EIP-214)
+            ('Byzantium', 700),
             ('Berlin', 2600)
         ],
-        # EIP-1884 changes (Constantinople -> Istanbul)
+        '0xff': [  # SELFDESTRUCT
+            ('TangerineWhistle', 5000)
+        ],
+        # EIP-1283 (Constantinople) and EIP-2200 (Istanbul)
         '0x55': [  # SSTORE base cost changes
             ('Constantinople', 5000),
             ('Istanbul', 5000)  # Complex cost, but base remains same
@@ -79,7 +117,7 @@ def get_gas_history(opcode_hex, fork_name):
     if opcode_hex in gas_changes:
         history = gas_changes[opcode_hex]
         # Only include history up to current fork
-        fork_order = ['Frontier', 'Homestead', 'Byzantium', 'Constantinople', 'Istanbul', 'Berlin', 'London', 'Shanghai', 'Cancun']
+        fork_order = get_fork_order()
         current_index = fork_order.index(fork_name)
         
         filtered_history = []
@@ -150,27 +188,46 @@ def get_frontier_opcodes():
 0x5a,GAS,2,0,1,Get the amount of available gas,StackMemoryStorageFlow,Frontier,
 0x5b,JUMPDEST,1,0,0,Mark a valid destination for jumps,StackMemoryStorageFlow,Frontier,"""
 
-def get_istanbul_gas_updates():
-    """Get gas cost updates for Istanbul fork"""
+def get_gas_updates():
+    """Get base gas cost updates, by the fork that made them"""
     return {
-        '0x31': 400,  # BALANCE (EIP-1884)
-        '0x3b': 700,  # EXTCODESIZE (EIP-1884) 
-        '0x3c': 700,  # EXTCODECOPY (EIP-1884)
-        '0x54': 800,  # SLOAD (EIP-1884)
+        # EIP-150: Gas cost changes for IO-heavy operations
+        'TangerineWhistle': {
+            '0x31': 400,   # BALANCE
+            '0x3b': 700,   # EXTCODESIZE
+            '0x3c': 700,   # EXTCODECOPY
+            '0x54': 200,   # SLOAD
+            '0xf1': 700,   # CALL
+            '0xf2': 700,   # CALLCODE
+            '0xf4': 700,   # DELEGATECALL
+            '0xff': 5000,  # SELFDESTRUCT
+        },
+        # EIP-1884: Repricing for trie-size-dependent opcodes
+        'Istanbul': {
+            '0x31': 700,  # BALANCE
+            '0x3f': 700,  # EXTCODEHASH
+            '0x54': 800,  # SLOAD
+        },
+        # EIP-2929: Gas cost increases for state access opcodes
+        'Berlin': {
+            '0x31': 2600,  # BALANCE
+            '0x3b': 2600,  # EXTCODESIZE
+            '0x3c': 2600,  # EXTCODECOPY
+            '0x3f': 2600,  # EXTCODEHASH
+            '0x54': 2100,  # SLOAD
+            '0xf1': 2600,  # CALL
+            '0xf2': 2600,  # CALLCODE
+            '0xf4': 2600,  # DELEGATECALL
+            '0xfa': 2600,  # STATICCALL
+        },
     }
 
-def get_berlin_gas_updates():
-    """Get gas cost updates for Berlin fork (EIP-2929)"""
+def get_description_updates():
+    """Get description updates, by the fork that made them"""
     return {
-        '0x31': 2600,  # BALANCE
-        '0x3b': 2600,  # EXTCODESIZE
-        '0x3c': 2600,  # EXTCODECOPY
-        '0x3f': 2600,  # EXTCODEHASH
-        '0x54': 2100,  # SLOAD
-        '0xf1': 2600,  # CALL
-        '0xf2': 2600,  # CALLCODE
-        '0xf4': 2600,  # DELEGATECALL  
-        '0xfa': 2600,  # STATICCALL
+        'Prague': {
+            '0x40': "Get hash of one of the 256 most recent complete blocks (history also kept by the EIP-2935 system contract)",  # BLOCKHASH (EIP-2935)
+        },
     }
 
 def get_push_opcodes():
@@ -211,7 +268,7 @@ def get_system_opcodes():
 0xf2,CALLCODE,100,7,1,Message-call with alternative account's code,System,Frontier,
 0xf3,RETURN,0,2,0,Halt execution returning output data,System,Frontier,
 0xfe,INVALID,0,0,0,Designated invalid instruction,System,Frontier,
-0xff,SELFDESTRUCT,5000,1,0,Halt execution and register account for deletion,System,Frontier,"""
+0xff,SELFDESTRUCT,0,1,0,Halt execution and register account for deletion,System,Frontier,"""
 
 def get_historical_additions():
     """Get opcodes added in later forks"""
@@ -219,12 +276,12 @@ def get_historical_additions():
         'homestead': "0xf4,DELEGATECALL,40,6,1,Message-call with alternative account's code persisting current context,System,Homestead,",
         'byzantium': """0x3d,RETURNDATASIZE,2,0,1,Get size of output data from previous call,EnvironmentalInformation,Byzantium,211
 0x3e,RETURNDATACOPY,3,3,0,Copy output data from previous call to memory,EnvironmentalInformation,Byzantium,211
-0xfa,STATICCALL,40,6,1,Static message-call into an account,System,Byzantium,214
+0xfa,STATICCALL,700,6,1,Static message-call into an account,System,Byzantium,214
 0xfd,REVERT,0,2,0,Stop execution and revert state changes,System,Byzantium,140""",
         'constantinople': """0x1b,SHL,3,2,1,Left shift operation,ComparisonBitwiseLogic,Constantinople,145
 0x1c,SHR,3,2,1,Logical right shift operation,ComparisonBitwiseLogic,Constantinople,145
 0x1d,SAR,3,2,1,Arithmetic right shift operation,ComparisonBitwiseLogic,Constantinople,145
-0x3f,EXTCODEHASH,100,1,1,Get hash of an account's code,EnvironmentalInformation,Constantinople,1052
+0x3f,EXTCODEHASH,400,1,1,Get hash of an account's code,EnvironmentalInformation,Constantinople,1052
 0xf5,CREATE2,32000,4,1,Create account with associated code at specified address,System,Constantinople,1014""",
         'istanbul': """0x46,CHAINID,2,0,1,Get the chain ID,BlockInformation,Istanbul,1344
 0x47,SELFBALANCE,5,0,1,Get balance of currently executing account,BlockInformation,Istanbul,1884""",
@@ -234,11 +291,12 @@ def get_historical_additions():
 0x4a,BLOBBASEFEE,2,0,1,Get the current blob base fee,BlockInformation,Cancun,7516
 0x5c,TLOAD,100,1,1,Load word from transient storage,StackMemoryStorageFlow,Cancun,1153
 0x5d,TSTORE,100,2,0,Save word to transient storage,StackMemoryStorageFlow,Cancun,1153
-0x5e,MCOPY,3,3,0,Copy memory areas,StackMemoryStorageFlow,Cancun,5656"""
+0x5e,MCOPY,3,3,0,Copy memory areas,StackMemoryStorageFlow,Cancun,5656""",
+        'osaka': "0x1e,CLZ,5,1,1,Count leading zero bits,ComparisonBitwiseLogic,Osaka,7939"
     }
 
-def apply_gas_updates(csv_data, gas_updates):
-    """Apply gas cost updates to CSV data"""
+def apply_updates(csv_data, updates, column):
+    """Apply updates to one column of CSV data"""
     lines = csv_data.strip().split('\n')
     updated_lines = []
     
@@ -248,19 +306,32 @@ def apply_gas_updates(csv_data, gas_updates):
             continue
             
         parts = line.split(',')
-        if len(parts) >= 3:
+        if len(parts) > column:
             opcode = parts[0]
-            if opcode in gas_updates:
-                parts[2] = str(gas_updates[opcode])  # Update gas cost
+            if opcode in updates:
+                parts[column] = str(updates[opcode])
                 line = ','.join(parts)
         updated_lines.append(line)
     
     return '\n'.join(updated_lines)
 
-def generate_fork_file(fork_name, csv_data, base_fork=True):
+def apply_gas_updates(csv_data, gas_updates):
+    """Apply gas cost updates to CSV data"""
+    return apply_updates(csv_data, gas_updates, 2)
+
+def apply_description_updates(csv_data, description_updates):
+    """Apply description updates to CSV data"""
+    return apply_updates(csv_data, description_updates, 5)
+
+def generate_fork_file(fork_name, csv_data, doc=''):
     """Generate a fork file from CSV data"""
     
-    header = f"""//! {fork_name} fork opcodes
+    title = re.sub(r'(?<!^)(?=[A-Z])', ' ', fork_name) + " fork opcodes"
+    if fork_name in UNSTABLE_FORKS:
+        title += " (scheduled)"
+    doc_lines = "".join(f"\n//!{' ' if line else ''}{line}" for line in doc.split('\n')) if doc else ""
+    
+    header = f"""//! {title}{doc_lines}
 
 use crate::{{opcodes, OpCode}};
 
@@ -269,7 +340,8 @@ opcodes! {{
     {fork_name} => {fork_name} {{"""
     
     footer = """    }
-}"""
+}
+"""
     
     opcodes_section = ""
     reader = csv.DictReader(StringIO(csv_data))
@@ -315,64 +387,15 @@ def combine_csvs(*csvs):
                     combined += line + "\n"
     return combined
 
-def generate_frontier():
-    print("📝 Generating Frontier...")
-    
-    # Combine all base opcodes
-    csv_data = combine_csvs(
-        get_frontier_opcodes(),
-        get_push_opcodes(),
-        get_dup_opcodes(), 
-        get_swap_opcodes(),
-        get_log_opcodes(),
-        get_system_opcodes()
-    )
-    
-    content = generate_fork_file("Frontier", csv_data)
-    with open("src/forks/frontier.rs", "w") as f:
-        f.write(content)
-
-def generate_homestead():
-    print("📝 Generating Homestead...")
-    additions = get_historical_additions()
-    
-    csv_data = combine_csvs(
-        get_frontier_opcodes(),
-        get_push_opcodes(),
-        get_dup_opcodes(),
-        get_swap_opcodes(), 
-        get_log_opcodes(),
-        get_system_opcodes(),
-        additions['homestead']
-    )
-    
-    content = generate_fork_file("Homestead", csv_data)
-    with open("src/forks/homestead.rs", "w") as f:
-        f.write(content)
-
-def generate_byzantium():
-    print("📝 Generating Byzantium...")
-    additions = get_historical_additions()
-    
-    csv_data = combine_csvs(
-        get_frontier_opcodes(),
-        get_push_opcodes(),
-        get_dup_opcodes(),
-        get_swap_opcodes(),
-        get_log_opcodes(), 
-        get_system_opcodes(),
-        additions['homestead'],
-        additions['byzantium']
-    )
-    
-    content = generate_fork_file("Byzantium", csv_data)
-    with open("src/forks/byzantium.rs", "w") as f:
-        f.write(content)
-
-def generate_constantinople():
-    print("📝 Generating Constantinople...")
+def generate_fork(fork_name, module, doc):
+    print(f"📝 Generating {fork_name}...")
     additions = get_historical_additions()
+    gas_updates = get_gas_updates()
+    description_updates = get_description_updates()
+    fork_order = get_fork_order()
+    forks = fork_order[:fork_order.index(fork_name) + 1]
     
+    # Frontier opcodes, then the opcodes of every fork up to this one
     csv_data = combine_csvs(
         get_frontier_opcodes(),
         get_push_opcodes(),
@@ -380,171 +403,45 @@ def generate_constantinople():
         get_swap_opcodes(),
         get_log_opcodes(),
         get_system_opcodes(),
-        additions['homestead'],
-        additions['byzantium'],
-        additions['constantinople']
+        *[additions.get(fork.lower(), '') for fork in forks]
     )
     
-    content = generate_fork_file("Constantinople", csv_data)
-    with open("src/forks/constantinople.rs", "w") as f:
-        f.write(content)
-
-def generate_istanbul():
-    print("📝 Generating Istanbul...")
-    additions = get_historical_additions()
+    # Apply gas and description updates in fork order
+    for fork in forks:
+        csv_data = apply_gas_updates(csv_data, gas_updates.get(fork, {}))
+        csv_data = apply_description_updates(csv_data, description_updates.get(fork, {}))
     
-    csv_data = combine_csvs(
-        get_frontier_opcodes(),
-        get_push_opcodes(),
-        get_dup_opcodes(),
-        get_swap_opcodes(),
-        get_log_opcodes(),
-        get_system_opcodes(),
-        additions['homestead'],
-        additions['byzantium'],
-        additions['constantinople'],
-        additions['istanbul']
-    )
-    
-    # Apply Istanbul gas cost updates (EIP-1884)
-    csv_data = apply_gas_updates(csv_data, get_istanbul_gas_updates())
-    
-    content = generate_fork_file("Istanbul", csv_data)
-    with open("src/forks/istanbul.rs", "w") as f:
-        f.write(content)
-
-def generate_berlin():
-    print("📝 Generating Berlin...")
-    # Berlin has no new opcodes, just gas changes
-    additions = get_historical_additions()
-    
-    csv_data = combine_csvs(
-        get_frontier_opcodes(),
-        get_push_opcodes(),
-        get_dup_opcodes(),
-        get_swap_opcodes(),
-        get_log_opcodes(),
-        get_system_opcodes(),
-        additions['homestead'],
-        additions['byzantium'],
-        additions['constantinople'],
-        additions['istanbul']
-    )
-    
-    # Apply Berlin gas cost updates (EIP-2929)
-    csv_data = apply_gas_updates(csv_data, get_berlin_gas_updates())
-    
-    content = generate_fork_file("Berlin", csv_data)
-    with open("src/forks/berlin.rs", "w") as f:
-        f.write(content)
-
-def generate_london():
-    print("📝 Generating London...")
-    additions = get_historical_additions()
-    
-    csv_data = combine_csvs(
-        get_frontier_opcodes(),
-        get_push_opcodes(),
-        get_dup_opcodes(),
-        get_swap_opcodes(),
-        get_log_opcodes(),
-        get_system_opcodes(),
-        additions['homestead'],
-        additions['byzantium'],
-        additions['constantinople'],
-        additions['istanbul'],
-        additions['london']
-    )
-    
-    # Apply Istanbul gas updates first, then Berlin
-    csv_data = apply_gas_updates(csv_data, get_istanbul_gas_updates())
-    csv_data = apply_gas_updates(csv_data, get_berlin_gas_updates())
-    
-    content = generate_fork_file("London", csv_data)
-    with open("src/forks/london.rs", "w") as f:
-        f.write(content)
-
-def generate_shanghai():
-    print("📝 Generating Shanghai...")
-    additions = get_historical_additions()
-    
-    csv_data = combine_csvs(
-        get_frontier_opcodes(),
-        get_push_opcodes(),
-        get_dup_opcodes(),
-        get_swap_opcodes(),
-        get_log_opcodes(),
-        get_system_opcodes(),
-        additions['homestead'],
-        additions['byzantium'],
-        additions['constantinople'],
-        additions['istanbul'],
-        additions['london'],
-        additions['shanghai']
-    )
-    
-    # Apply Istanbul gas updates first, then Berlin
-    csv_data = apply_gas_updates(csv_data, get_istanbul_gas_updates())
-    csv_data = apply_gas_updates(csv_data, get_berlin_gas_updates())
-    
-    content = generate_fork_file("Shanghai", csv_data)
-    with open("src/forks/shanghai.rs", "w") as f:
-        f.write(content)
-
-def generate_cancun():
-    print("📝 Generating Cancun...")
-    additions = get_historical_additions()
-    
-    csv_data = combine_csvs(
-        get_frontier_opcodes(),
-        get_push_opcodes(),
-        get_dup_opcodes(),
-        get_swap_opcodes(),
-        get_log_opcodes(),
-        get_system_opcodes(),
-        additions['homestead'],
-        additions['byzantium'],
-        additions['constantinople'],
-        additions['istanbul'],
-        additions['london'],
-        additions['shanghai'],
-        additions['cancun']
-    )
-    
-    # Apply Istanbul gas updates first, then Berlin
-    csv_data = apply_gas_updates(csv_data, get_istanbul_gas_updates())
-    csv_data = apply_gas_updates(csv_data, get_berlin_gas_updates())
-    
-    content = generate_fork_file("Cancun", csv_data)
-    with open("src/forks/cancun.rs", "w") as f:
+    content = generate_fork_file(fork_name, csv_data, doc)
+    with open(f"src/forks/{module}.rs", "w") as f:
         f.write(content)
 
 def generate_forks_mod():
     print("📝 Generating forks/mod.rs...")
     
     content = """//! Fork-specific opcode implementations
-
-pub mod frontier;
-pub mod homestead;
-pub mod byzantium;
-pub mod constantinople;
-pub mod istanbul;
-pub mod berlin;
-pub mod london;
-pub mod shanghai;
-pub mod cancun;
-
-pub use frontier::Frontier;
-pub use homestead::Homestead;
-pub use byzantium::Byzantium;
-pub use constantinople::Constantinople;
-pub use istanbul::Istanbul;
-pub use berlin::Berlin;
-pub use london::London;
-pub use shanghai::Shanghai;
-pub use cancun::Cancun;
+//!
+//! # Fork pipeline
+//!
+//! A fork enters the crate as soon as its opcode changes are accepted for a devnet: its
+//! [`Fork`](crate::Fork) variant, [`ExecutionFork`](crate::ExecutionFork) variant, table module
+//! and registry entry are gated behind the `unstable-forks` feature and the fork is listed in
+//! [`Fork::SCHEDULED`](crate::Fork::SCHEDULED), so
+//! [`OpcodeMetadata::is_scheduled`](crate::OpcodeMetadata::is_scheduled) flags its new
+//! opcodes. Its table may change in any release while it is scheduled. Once the mainnet
+//! activation is final, the gates are removed, the fork leaves `SCHEDULED` and the dataset
+//! [`CHANGELOG`](crate::version::CHANGELOG) records the addition.
 """
     
+    # Sorted by module, as rustfmt orders them
+    forks = sorted(FORKS, key=lambda fork: fork[1])
+    gate = '#[cfg(feature = "unstable-forks")]\n'
+    content += "\n"
+    for fork_name, module, _ in forks:
+        content += (gate if fork_name in UNSTABLE_FORKS else "") + f"pub mod {module};\n"
+    content += "\n"
+    for fork_name, module, _ in forks:
+        content += (gate if fork_name in UNSTABLE_FORKS else "") + f"pub use {module}::{fork_name};\n"
+    
     with open("src/forks/mod.rs", "w") as f:
         f.write(content)
 
@@ -0,0 +1,212 @@
+//! Checking external calls against an allowlist of targets and selectors
+
+use crate::bytecode::encode_hex;
+use crate::disasm::disassemble;
+use crate::stack::StackSimulator;
+use crate::Fork;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// An external call found in bytecode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalCall {
+    /// Program counter of the call instruction
+    pub pc: usize,
+    /// CALL, CALLCODE, DELEGATECALL or STATICCALL
+    pub opcode: u8,
+    /// Target address, if statically known
+    pub target: Option<[u8; 20]>,
+    /// Function selector of the call data, if statically known
+    pub selector: Option<[u8; 4]>,
+}
+
+/// Find every external call and recover its target and selector where possible
+///
+/// The target is known when it is a constant on the stack (usually a PUSH20). The selector
+/// is known when the word at the argument offset was stored with a constant MSTORE in the
+/// same basic block.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(?fork, len = bytecode.len()))
+)]
+pub fn find_external_calls(bytecode: &[u8], fork: Fork) -> Vec<ExternalCall> {
+    let mut simulator = StackSimulator::new(fork);
+    let mut calls = Vec::new();
+
+    for instruction in disassemble(bytecode) {
+        let args_offset_depth = match instruction.opcode {
+            0xf1 | 0xf2 => Some(3), // gas, addr, value, argsOffset
+            0xf4 | 0xfa => Some(2), // gas, addr, argsOffset
+            _ => None,
+        };
+        if let Some(depth) = args_offset_depth {
+            let args_size = simulator.peek(depth + 1).as_u64();
+            let selector = simulator
+                .peek(depth)
+                .as_u64()
+                .filter(|_| args_size.is_some_and(|size| size >= 4))
+                .and_then(|offset| simulator.memory_word(offset))
+                .map(|word| [word[0], word[1], word[2], word[3]]);
+            calls.push(ExternalCall {
+                pc: instruction.pc,
+                opcode: instruction.opcode,
+                target: simulator.peek(1).as_address(),
+                selector,
+            });
+        }
+        simulator.step(&instruction);
+    }
+
+    calls
+}
+
+/// Allowed external call targets, optionally restricted to specific selectors
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CallAllowlist {
+    /// Allowed targets; an empty selector set allows any selector
+    pub targets: BTreeMap<[u8; 20], BTreeSet<[u8; 4]>>,
+}
+
+impl CallAllowlist {
+    /// Create an empty allowlist
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow any call to `address`
+    pub fn with_address(mut self, address: [u8; 20]) -> Self {
+        self.targets.entry(address).or_default();
+        self
+    }
+
+    /// Allow calls to `address` with the given selector only
+    pub fn with_selector(mut self, address: [u8; 20], selector: [u8; 4]) -> Self {
+        self.targets.entry(address).or_default().insert(selector);
+        self
+    }
+
+    /// Check every call in the bytecode against the allowlist
+    pub fn check(&self, bytecode: &[u8], fork: Fork) -> AllowlistReport {
+        let mut report = AllowlistReport::default();
+
+        for call in find_external_calls(bytecode, fork) {
+            let Some(target) = call.target else {
+                report.unresolved.push(call);
+                continue;
+            };
+            let reason = match self.targets.get(&target) {
+                None => Some(format!(
+                    "call at pc {} targets 0x{} which is not allowlisted",
+                    call.pc,
+                    encode_hex(&target)
+                )),
+                Some(selectors) if selectors.is_empty() => None,
+                Some(selectors) => match call.selector {
+                    Some(selector) if selectors.contains(&selector) => None,
+                    Some(selector) => Some(format!(
+                        "call at pc {} uses selector 0x{} which is not allowed for 0x{}",
+                        call.pc,
+                        encode_hex(&selector),
+                        encode_hex(&target)
+                    )),
+                    None => {
+                        report.unresolved.push(call);
+                        continue;
+                    }
+                },
+            };
+            match reason {
+                Some(reason) => report.violations.push(CallViolation { call, reason }),
+                None => report.allowed.push(call),
+            }
+        }
+
+        report
+    }
+}
+
+/// A call that is statically known to leave the allowlist
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallViolation {
+    /// The offending call
+    pub call: ExternalCall,
+    /// Why the call is not allowed
+    pub reason: String,
+}
+
+/// Result of checking bytecode against a [`CallAllowlist`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AllowlistReport {
+    /// Calls known to be allowed
+    pub allowed: Vec<ExternalCall>,
+    /// Calls known to violate the allowlist
+    pub violations: Vec<CallViolation>,
+    /// Calls whose target (or, for selector-restricted targets, selector) is not statically known
+    pub unresolved: Vec<ExternalCall>,
+}
+
+impl AllowlistReport {
+    /// Check if no call is known to violate the allowlist
+    pub fn is_compliant(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bytecode that stores `selector` at memory 0 and STATICCALLs `target` with 4 bytes of input
+    fn static_call(target: [u8; 20], selector: [u8; 4]) -> Vec<u8> {
+        let mut code = vec![0x63];
+        code.extend_from_slice(&selector);
+        code.extend_from_slice(&[0x60, 0xe0, 0x1b, 0x60, 0x00, 0x52]); // PUSH1 0xe0, SHL, PUSH1 0, MSTORE
+        code.extend_from_slice(&[0x60, 0x20, 0x60, 0x00, 0x60, 0x04, 0x60, 0x00]); // ret size/offset, args size/offset
+        code.push(0x73); // PUSH20 target
+        code.extend_from_slice(&target);
+        code.extend_from_slice(&[0x5a, 0xfa, 0x00]); // GAS, STATICCALL, STOP
+        code
+    }
+
+    #[test]
+    fn test_target_and_selector_recovery() {
+        let calls = find_external_calls(
+            &static_call([0x11; 20], [0xde, 0xad, 0xbe, 0xef]),
+            Fork::London,
+        );
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].target, Some([0x11; 20]));
+        assert_eq!(calls[0].selector, Some([0xde, 0xad, 0xbe, 0xef]));
+    }
+
+    #[test]
+    fn test_allowlist_violations() {
+        let code = static_call([0x11; 20], [0xde, 0xad, 0xbe, 0xef]);
+
+        let report = CallAllowlist::new()
+            .with_address([0x11; 20])
+            .check(&code, Fork::London);
+        assert!(report.is_compliant());
+        assert_eq!(report.allowed.len(), 1);
+
+        let report = CallAllowlist::new()
+            .with_address([0x22; 20])
+            .check(&code, Fork::London);
+        assert_eq!(report.violations.len(), 1);
+
+        let report = CallAllowlist::new()
+            .with_selector([0x11; 20], [0x00, 0x00, 0x00, 0x01])
+            .check(&code, Fork::London);
+        assert!(report.violations[0].reason.contains("selector 0xdeadbeef"));
+    }
+
+    #[test]
+    fn test_dynamic_target_is_unresolved() {
+        // Four zero operands, a target read with CALLDATALOAD, GAS, STATICCALL
+        let code = [
+            0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x35, 0x5a, 0xfa,
+        ];
+        let report = CallAllowlist::new().check(&code, Fork::London);
+        assert!(report.is_compliant());
+        assert_eq!(report.unresolved.len(), 1);
+    }
+}
@@ -0,0 +1,284 @@
+//! Assembling mnemonic text into bytecode
+//!
+//! One instruction per line, mnemonics as parsed by [`UnifiedOpcode`]'s `FromStr` (case does
+//! not matter), comments after `;` or `//`. `name:` defines a label at the next instruction,
+//! normally a JUMPDEST, and a label can be used wherever a PUSH value can:
+//!
+//! ```text
+//! PUSH1 0x10      ; explicit width
+//! PUSH 300        ; smallest PUSH that holds the value (PUSH2 here)
+//! JUMPI done      ; PUSH done, JUMPI
+//! PUSH2 done      ; fixed-width label reference
+//! done:
+//! JUMPDEST
+//! ```
+//!
+//! `PUSH` without a width never becomes PUSH0, so the output does not depend on the fork.
+//! Label references without a width get the smallest PUSH that holds the target. Listings
+//! from [`symbolic_listing`](crate::disasm::symbolic_listing) assemble back into equivalent
+//! code.
+
+use crate::bytecode::decode_hex;
+use crate::UnifiedOpcode;
+use std::collections::BTreeMap;
+
+/// An instruction before labels are resolved
+enum Item {
+    /// An opcode without immediates
+    Op(u8),
+    /// A PUSH, with its width if given
+    Push {
+        width: Option<usize>,
+        value: PushValue,
+        line: usize,
+    },
+}
+
+enum PushValue {
+    /// Big-endian bytes, without leading zeros
+    Bytes(Vec<u8>),
+    Label(String),
+}
+
+/// Assemble mnemonic text into bytecode
+pub fn assemble(source: &str) -> Result<Vec<u8>, String> {
+    let mut items = Vec::new();
+    let mut labels = BTreeMap::new();
+
+    for (number, line) in source.lines().enumerate() {
+        let number = number + 1;
+        let line = line.split(';').next().unwrap_or_default();
+        let line = line.split("//").next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let error = |message: &str| format!("Line {number}: {message}: {line}");
+
+        if let Some(label) = line.strip_suffix(':') {
+            if !is_identifier(label) {
+                return Err(error("invalid label"));
+            }
+            if labels.insert(label.to_string(), items.len()).is_some() {
+                return Err(error("duplicate label"));
+            }
+            continue;
+        }
+
+        let mut tokens = line.split_whitespace();
+        let mnemonic = tokens.next().unwrap_or_default().to_ascii_uppercase();
+        let operand = tokens.next();
+        if tokens.next().is_some() {
+            return Err(error("expected MNEMONIC [OPERAND]"));
+        }
+        let push_value = |operand: &str| match parse_value(operand) {
+            Some(bytes) => Ok(PushValue::Bytes(bytes)),
+            None if is_identifier(operand) => Ok(PushValue::Label(operand.to_string())),
+            None => Err(error("invalid operand")),
+        };
+
+        if mnemonic == "PUSH" {
+            let operand = operand.ok_or_else(|| error("PUSH needs a value"))?;
+            items.push(Item::Push {
+                width: None,
+                value: push_value(operand)?,
+                line: number,
+            });
+            continue;
+        }
+        let opcode = match parse_unknown(&mnemonic) {
+            Some(opcode) => opcode,
+            None => mnemonic
+                .parse::<UnifiedOpcode>()
+                .map_err(|e| error(&e))?
+                .to_byte(),
+        };
+        match (opcode, operand) {
+            (0x60..=0x7f, Some(operand)) => items.push(Item::Push {
+                width: Some((opcode - 0x5f) as usize),
+                value: push_value(operand)?,
+                line: number,
+            }),
+            (0x60..=0x7f, None) => return Err(error("PUSH needs a value")),
+            (0x56 | 0x57, Some(operand)) => {
+                items.push(Item::Push {
+                    width: None,
+                    value: push_value(operand)?,
+                    line: number,
+                });
+                items.push(Item::Op(opcode));
+            }
+            (_, Some(_)) => return Err(error("opcode takes no operand")),
+            (_, None) => items.push(Item::Op(opcode)),
+        }
+    }
+
+    let widths = resolve_widths(&items, &labels)?;
+    let offsets = offsets(&items, &widths);
+    let mut bytecode = Vec::with_capacity(offsets[items.len()]);
+    for (item, width) in items.iter().zip(&widths) {
+        match item {
+            Item::Op(opcode) => bytecode.push(*opcode),
+            Item::Push { value, .. } => {
+                let bytes = match value {
+                    PushValue::Bytes(bytes) => bytes.clone(),
+                    PushValue::Label(label) => minimal_bytes(offsets[labels[label]] as u128),
+                };
+                bytecode.push(0x5f + *width as u8);
+                bytecode.extend(std::iter::repeat_n(0, width - bytes.len()));
+                bytecode.extend(bytes);
+            }
+        }
+    }
+    Ok(bytecode)
+}
+
+/// PUSH widths, growing label references until every target fits
+fn resolve_widths(items: &[Item], labels: &BTreeMap<String, usize>) -> Result<Vec<usize>, String> {
+    let mut widths: Vec<usize> = items
+        .iter()
+        .map(|item| match item {
+            Item::Op(_) => 0,
+            Item::Push { width, value, .. } => width.unwrap_or(match value {
+                PushValue::Bytes(bytes) => bytes.len().max(1),
+                PushValue::Label(_) => 1,
+            }),
+        })
+        .collect();
+
+    loop {
+        let offsets = offsets(items, &widths);
+        let mut changed = false;
+        for (item, width) in items.iter().zip(widths.iter_mut()) {
+            let Item::Push {
+                width: explicit,
+                value,
+                line,
+            } = item
+            else {
+                continue;
+            };
+            let needed = match value {
+                PushValue::Bytes(bytes) => bytes.len(),
+                PushValue::Label(label) => {
+                    let index = labels
+                        .get(label)
+                        .ok_or_else(|| format!("Line {line}: unknown label: {label}"))?;
+                    minimal_bytes(offsets[*index] as u128).len()
+                }
+            };
+            if needed > 32 || (explicit.is_some() && needed > *width) {
+                return Err(format!("Line {line}: value does not fit in PUSH{width}"));
+            }
+            if needed > *width {
+                *width = needed;
+                changed = true;
+            }
+        }
+        if !changed {
+            return Ok(widths);
+        }
+    }
+}
+
+/// Program counter of every item, plus the code size at the end
+fn offsets(items: &[Item], widths: &[usize]) -> Vec<usize> {
+    let mut offsets = Vec::with_capacity(items.len() + 1);
+    let mut pc = 0;
+    for (item, width) in items.iter().zip(widths) {
+        offsets.push(pc);
+        pc += match item {
+            Item::Op(_) => 1,
+            Item::Push { .. } => 1 + width,
+        };
+    }
+    offsets.push(pc);
+    offsets
+}
+
+/// A decimal or `0x` hex value as big-endian bytes without leading zeros
+fn parse_value(text: &str) -> Option<Vec<u8>> {
+    let Some(hex) = text.strip_prefix("0x") else {
+        return text.parse::<u128>().ok().map(minimal_bytes);
+    };
+    if hex.is_empty() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let padded = if hex.len() % 2 == 1 {
+        format!("0{hex}")
+    } else {
+        hex.to_string()
+    };
+    let bytes = decode_hex(&padded).ok()?;
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    Some(bytes[start..].to_vec())
+}
+
+fn minimal_bytes(value: u128) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    bytes[start..].to_vec()
+}
+
+/// An unassigned byte as the disassembler prints it: `UNKNOWN(0x0c)`
+fn parse_unknown(mnemonic: &str) -> Option<u8> {
+    let hex = mnemonic.strip_prefix("UNKNOWN(0X")?.strip_suffix(')')?;
+    u8::from_str_radix(hex, 16).ok()
+}
+
+fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disasm::{disassemble, symbolic_listing};
+
+    #[test]
+    fn test_assemble_mnemonics_and_labels() {
+        let source = "
+            push1 0x10     ; lowercase is fine
+            PUSH 300
+            SSTORE
+            JUMPI end      // PUSH1 end, JUMPI
+            PUSH2 end
+            INVALID
+            end:
+            JUMPDEST
+            STOP
+        ";
+        assert_eq!(
+            assemble(source).unwrap(),
+            [
+                0x60, 0x10, 0x61, 0x01, 0x2c, 0x55, 0x60, 0x0d, 0x57, 0x61, 0x00, 0x0d, 0xfe, 0x5b,
+                0x00
+            ]
+        );
+
+        // The listing of the disassembly assembles back into the same code
+        let code = assemble(source).unwrap();
+        let listing = symbolic_listing(&disassemble(&code));
+        assert_eq!(assemble(&listing).unwrap(), code);
+    }
+
+    #[test]
+    fn test_label_widths_and_errors() {
+        // A target past 0xff needs PUSH2
+        let source = format!("JUMP far\n{}far:\nJUMPDEST", "STOP\n".repeat(300));
+        let code = assemble(&source).unwrap();
+        assert_eq!(code[..4], [0x61, 0x01, 0x30, 0x56]);
+        assert_eq!(code[0x130], 0x5b);
+
+        assert!(assemble("PUSH1 0x100").unwrap_err().contains("PUSH1"));
+        assert!(assemble("JUMP nowhere")
+            .unwrap_err()
+            .starts_with("Line 1: unknown label"));
+        assert!(assemble("a:\na:").unwrap_err().contains("duplicate label"));
+        assert!(assemble("STOP\nFOO").unwrap_err().starts_with("Line 2"));
+        assert!(assemble("ADD 1").is_err());
+    }
+}
@@ -0,0 +1,167 @@
+//! Bytecode from and to hex strings
+//!
+//! RPC nodes and block explorers return code as `0x`-prefixed hex, sometimes wrapped over
+//! several lines or padded with spaces. [`Bytecode::from_hex`] accepts that as is and points
+//! at the offending character when the text is not hex; the analyzers take the result as a
+//! byte slice. Importers of other hex input share the same rules through [`decode_hex`] and
+//! [`encode_hex`].
+
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+/// EVM bytecode
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Bytecode(Vec<u8>);
+
+impl Bytecode {
+    /// Wrap raw bytes
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+
+    /// Parse hex as [`decode_hex`] does
+    pub fn from_hex(text: &str) -> Result<Self, String> {
+        decode_hex(text).map(Self)
+    }
+
+    /// Lowercase hex with a `0x` prefix
+    pub fn to_hex(&self) -> String {
+        format!("0x{}", encode_hex(&self.0))
+    }
+
+    /// The bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Unwrap the bytes
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+/// Decode hex with or without `0x`, ignoring whitespace anywhere in the text
+///
+/// Errors name the character position of the first invalid digit, an unlinked library
+/// placeholder (`__$…$__`) or an odd digit count.
+pub fn decode_hex(text: &str) -> Result<Vec<u8>, String> {
+    let trimmed = text.trim_start();
+    let body_start = text.len() - trimmed.len();
+    let (digits_start, body) = match trimmed
+        .strip_prefix("0x")
+        .or_else(|| trimmed.strip_prefix("0X"))
+    {
+        Some(body) => (body_start + 2, body),
+        None => (body_start, trimmed),
+    };
+
+    let mut digits = Vec::with_capacity(body.len());
+    for (offset, c) in body.char_indices() {
+        if c.is_whitespace() {
+            continue;
+        }
+        let position = text[..digits_start + offset].chars().count();
+        if c == '_' {
+            return Err(format!(
+                "Unlinked library placeholder at position {position} (byte {})",
+                digits.len() / 2
+            ));
+        }
+        let digit = c
+            .to_digit(16)
+            .ok_or_else(|| format!("Invalid hex digit {c:?} at position {position}"))?;
+        digits.push(digit as u8);
+    }
+    if !digits.len().is_multiple_of(2) {
+        return Err(format!("Odd number of hex digits ({})", digits.len()));
+    }
+    Ok(digits
+        .chunks_exact(2)
+        .map(|pair| (pair[0] << 4) | pair[1])
+        .collect())
+}
+
+/// Lowercase hex without a prefix
+pub fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+impl Deref for Bytecode {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for Bytecode {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for Bytecode {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<&[u8]> for Bytecode {
+    fn from(bytes: &[u8]) -> Self {
+        Self(bytes.to_vec())
+    }
+}
+
+impl From<Bytecode> for Vec<u8> {
+    fn from(bytecode: Bytecode) -> Self {
+        bytecode.0
+    }
+}
+
+impl FromStr for Bytecode {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Self::from_hex(text)
+    }
+}
+
+impl fmt::Display for Bytecode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_round_trip_with_whitespace() {
+        let code = Bytecode::from_hex("  0x6001\n6002 01\r\n").unwrap();
+        assert_eq!(code.as_bytes(), [0x60, 0x01, 0x60, 0x02, 0x01]);
+        assert_eq!(code.to_hex(), "0x6001600201");
+        assert_eq!(code.to_string().parse::<Bytecode>(), Ok(code.clone()));
+        assert_eq!(Bytecode::from_hex("6001AB").unwrap()[2], 0xab);
+        assert!(Bytecode::from_hex("0x").unwrap().is_empty());
+        assert_eq!(Bytecode::default().to_hex(), "0x");
+    }
+
+    #[test]
+    fn test_hex_errors() {
+        assert_eq!(
+            Bytecode::from_hex("0x60 0g"),
+            Err("Invalid hex digit 'g' at position 6".to_string())
+        );
+        assert_eq!(
+            Bytecode::from_hex("0x600"),
+            Err("Odd number of hex digits (3)".to_string())
+        );
+        let unlinked = format!("0x6080{}5af4", "__$1e5bd4b6c8f2a8e3e8d3b5d4a1f9c0b7e2$__");
+        assert_eq!(
+            Bytecode::from_hex(&unlinked),
+            Err("Unlinked library placeholder at position 6 (byte 2)".to_string())
+        );
+    }
+}
@@ -0,0 +1,197 @@
+//! Caching of analysis reports keyed by code hash, fork and configuration
+//!
+//! Indexers see the same runtime code many times (clones, proxies, factories). The code hash
+//! is supplied by the caller, usually the keccak256 hash the node already reports for the
+//! account, and is trusted: two different bytecodes passed with the same hash share an entry.
+
+use crate::report::{analyze, Report};
+use crate::{AnalysisBudget, AnalysisConfig, Fork};
+use std::collections::HashMap;
+
+/// Identifies one analysis: code hash, fork and configuration
+///
+/// The budget is not part of the key, since only complete reports are cached and those do
+/// not depend on it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    /// Hash of the analyzed bytecode
+    pub code_hash: [u8; 32],
+    /// Fork the code was analyzed for
+    pub fork: Fork,
+    /// Configuration used, with the budget reset to unlimited
+    pub config: AnalysisConfig,
+}
+
+impl CacheKey {
+    /// Create a key for code with the given hash
+    pub fn new(code_hash: [u8; 32], fork: Fork, config: &AnalysisConfig) -> Self {
+        Self {
+            code_hash,
+            fork,
+            config: config.clone().with_budget(AnalysisBudget::unlimited()),
+        }
+    }
+}
+
+/// Storage for cached reports
+///
+/// Implement this for persistent stores (files, key-value databases); [`MemoryCache`] keeps
+/// everything in memory.
+pub trait AnalysisCache {
+    /// Look up a cached report
+    fn get(&self, key: &CacheKey) -> Option<Report>;
+
+    /// Store a report
+    fn insert(&mut self, key: CacheKey, report: Report);
+}
+
+/// Unbounded in-memory cache
+#[derive(Debug, Clone, Default)]
+pub struct MemoryCache {
+    entries: HashMap<CacheKey, Report>,
+}
+
+impl MemoryCache {
+    /// Create an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of cached reports
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drop all cached reports
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+impl AnalysisCache for MemoryCache {
+    fn get(&self, key: &CacheKey) -> Option<Report> {
+        self.entries.get(key).cloned()
+    }
+
+    fn insert(&mut self, key: CacheKey, report: Report) {
+        self.entries.insert(key, report);
+    }
+}
+
+/// Runs [`analyze`] through a cache
+#[derive(Debug, Clone, Default)]
+pub struct CachedAnalyzer<C = MemoryCache> {
+    cache: C,
+    hits: u64,
+    misses: u64,
+}
+
+impl<C: AnalysisCache> CachedAnalyzer<C> {
+    /// Create an analyzer backed by a cache
+    pub fn new(cache: C) -> Self {
+        Self {
+            cache,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Analyze bytecode, reusing the cached report for the same hash, fork and configuration
+    ///
+    /// Reports cut short by the budget or cancelled are returned but not cached.
+    pub fn analyze(
+        &mut self,
+        code_hash: [u8; 32],
+        bytecode: &[u8],
+        fork: Fork,
+        config: &AnalysisConfig,
+    ) -> Result<Report, String> {
+        let key = CacheKey::new(code_hash, fork, config);
+        if let Some(report) = self.cache.get(&key) {
+            self.hits += 1;
+            return Ok(report);
+        }
+
+        self.misses += 1;
+        let report = analyze(bytecode, fork, config)?;
+        if !report.budget_exhausted {
+            self.cache.insert(key, report.clone());
+        }
+        Ok(report)
+    }
+
+    /// Number of lookups answered from the cache
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Number of lookups that ran the analysis
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// The underlying cache
+    pub fn cache(&self) -> &C {
+        &self.cache
+    }
+
+    /// Take back the underlying cache
+    pub fn into_cache(self) -> C {
+        self.cache
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gas::StackSimEstimator;
+
+    #[test]
+    fn test_repeated_code_hits_cache() {
+        let code = [0x60, 0x00, 0x54, 0x00];
+        let config = AnalysisConfig::default();
+        let mut analyzer = CachedAnalyzer::new(MemoryCache::new());
+
+        let first = analyzer
+            .analyze([0x01; 32], &code, Fork::London, &config)
+            .unwrap();
+        let second = analyzer
+            .analyze([0x01; 32], &code, Fork::London, &config)
+            .unwrap();
+        assert_eq!(first, second);
+        assert_eq!((analyzer.hits(), analyzer.misses()), (1, 1));
+
+        // A different fork is a different entry
+        analyzer
+            .analyze([0x01; 32], &code, Fork::Berlin, &config)
+            .unwrap();
+        assert_eq!(analyzer.misses(), 2);
+        assert_eq!(analyzer.cache().len(), 2);
+
+        // Estimators are keyed by identity, not by instance
+        let simulated = || AnalysisConfig::new().with_operand_estimator(StackSimEstimator);
+        for _ in 0..3 {
+            analyzer
+                .analyze([0x01; 32], &code, Fork::London, &simulated())
+                .unwrap();
+        }
+        assert_eq!((analyzer.hits(), analyzer.misses()), (3, 3));
+    }
+
+    #[test]
+    fn test_partial_reports_are_not_cached() {
+        let config =
+            AnalysisConfig::new().with_budget(AnalysisBudget::unlimited().with_max_steps(1));
+        let mut analyzer = CachedAnalyzer::new(MemoryCache::new());
+        let report = analyzer
+            .analyze([0x02; 32], &[0x60, 0x00, 0x54], Fork::London, &config)
+            .unwrap();
+        assert!(report.budget_exhausted);
+        assert!(analyzer.cache().is_empty());
+    }
+}
@@ -0,0 +1,144 @@
+//! Corpus-level opcode statistics for indexers
+//!
+//! An [`OpcodeCensus`] consumes `(code_hash, bytecode)` pairs one at a time, skipping code it
+//! has already seen, and counts opcodes (compiler metadata trailers excluded). Partial
+//! censuses built by separate workers combine with [`OpcodeCensus::merge`]; shard the input by
+//! code hash so that no contract is counted by two workers.
+
+use crate::bytecode::encode_hex;
+use crate::disasm::InstructionIter;
+use crate::metadata::strip_metadata;
+use crate::stack::Word;
+use crate::{Fork, OpcodeRegistry};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Streaming opcode statistics over a set of contracts
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OpcodeCensus {
+    /// Code hashes already counted
+    seen: BTreeSet<Word>,
+    /// Occurrences of each opcode over all contracts
+    occurrences: BTreeMap<u8, u64>,
+    /// Number of contracts containing each opcode at least once
+    contracts_using: BTreeMap<u8, u64>,
+}
+
+impl OpcodeCensus {
+    /// Create an empty census
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a contract, returning false if its code hash was already counted
+    pub fn add(&mut self, code_hash: Word, bytecode: &[u8]) -> bool {
+        if !self.seen.insert(code_hash) {
+            return false;
+        }
+        let mut used = BTreeSet::new();
+        for instruction in InstructionIter::new(strip_metadata(bytecode)) {
+            *self.occurrences.entry(instruction.opcode).or_default() += 1;
+            used.insert(instruction.opcode);
+        }
+        for opcode in used {
+            *self.contracts_using.entry(opcode).or_default() += 1;
+        }
+        true
+    }
+
+    /// Add the counts of another partial census
+    ///
+    /// Fails without changing `self` if both censuses counted the same code hash.
+    pub fn merge(&mut self, other: &OpcodeCensus) -> Result<(), String> {
+        if let Some(hash) = self.seen.intersection(&other.seen).next() {
+            return Err(format!(
+                "Code hash 0x{} was counted by both censuses; shard the input by code hash",
+                encode_hex(hash)
+            ));
+        }
+        self.seen.extend(other.seen.iter().copied());
+        for (opcode, count) in &other.occurrences {
+            *self.occurrences.entry(*opcode).or_default() += count;
+        }
+        for (opcode, count) in &other.contracts_using {
+            *self.contracts_using.entry(*opcode).or_default() += count;
+        }
+        Ok(())
+    }
+
+    /// Number of distinct contracts counted
+    pub fn contracts(&self) -> u64 {
+        self.seen.len() as u64
+    }
+
+    /// Occurrences of an opcode over all contracts
+    pub fn occurrences(&self, opcode: u8) -> u64 {
+        self.occurrences.get(&opcode).copied().unwrap_or(0)
+    }
+
+    /// Fraction of contracts that use an opcode, between 0 and 1
+    pub fn adoption(&self, opcode: u8) -> f64 {
+        if self.seen.is_empty() {
+            return 0.0;
+        }
+        self.contracts_using.get(&opcode).copied().unwrap_or(0) as f64 / self.seen.len() as f64
+    }
+
+    /// Fraction of contracts that use PUSH0
+    pub fn push0_adoption(&self) -> f64 {
+        self.adoption(0x5f)
+    }
+
+    /// Fraction of contracts that use TSTORE
+    pub fn tstore_adoption(&self) -> f64 {
+        self.adoption(0x5d)
+    }
+
+    /// Opcode occurrences grouped by the fork that introduced the opcode
+    ///
+    /// Bytes that are not opcodes in the latest fork are left out.
+    pub fn frequency_by_era(&self) -> BTreeMap<Fork, u64> {
+        let latest = Fork::execution_forks()
+            .last()
+            .expect("at least one execution fork");
+        let opcodes = OpcodeRegistry::new().get_opcodes(latest);
+        let mut eras = BTreeMap::new();
+        for (opcode, count) in &self.occurrences {
+            if let Some(metadata) = opcodes.get(opcode) {
+                *eras.entry(metadata.introduced_in).or_default() += count;
+            }
+        }
+        eras
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_census_and_merge() {
+        // PUSH0, PUSH0, TSTORE, STOP
+        let modern = [0x5f, 0x5f, 0x5d, 0x00];
+        // PUSH1 0, PUSH1 0, SSTORE, STOP
+        let legacy = [0x60, 0x00, 0x60, 0x00, 0x55, 0x00];
+
+        let mut first = OpcodeCensus::new();
+        assert!(first.add([1; 32], &modern));
+        assert!(!first.add([1; 32], &modern));
+        let mut second = OpcodeCensus::new();
+        second.add([2; 32], &legacy);
+
+        first.merge(&second).unwrap();
+        assert_eq!(first.contracts(), 2);
+        assert_eq!(first.occurrences(0x5f), 2);
+        assert_eq!(first.push0_adoption(), 0.5);
+        assert_eq!(first.tstore_adoption(), 0.5);
+        let eras = first.frequency_by_era();
+        assert_eq!(eras[&Fork::Frontier], 5);
+        assert_eq!(eras[&Fork::Shanghai], 2);
+        assert_eq!(eras[&Fork::Cancun], 1);
+
+        assert!(first.merge(&second).is_err());
+        assert_eq!(first.contracts(), 2);
+    }
+}
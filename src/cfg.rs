@@ -0,0 +1,348 @@
+//! Control-flow graph of basic blocks
+//!
+//! A block starts at offset 0, at every JUMPDEST and after every JUMPI or terminator. Jump
+//! edges come from a PUSH of a JUMPDEST right before the jump, or from the constants and code
+//! tables found by [`find_jump_tables`]. Jumps that neither resolves are listed as unresolved;
+//! any JUMPDEST may be their target, which [`ControlFlowGraph::reachable`] accounts for.
+//! Data regions annotated or detected under the [`AnalysisConfig`], such as the metadata
+//! trailer, are not decoded into blocks. The config's budget bounds construction; a graph
+//! built when it runs out covers only the code decoded so far.
+
+use crate::disasm::{
+    disassemble_with_config, static_jump_target, valid_jump_destinations, Instruction,
+};
+use crate::jumptable::find_jump_tables;
+use crate::{AnalysisConfig, Fork};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+/// How control reaches a block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EdgeKind {
+    /// Execution continues with the next instruction
+    FallThrough,
+    /// Target of a JUMP
+    Jump,
+    /// Target of a taken JUMPI
+    Branch,
+}
+
+/// An edge between two blocks, identified by their start pcs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Edge {
+    /// Start of the source block
+    pub from: usize,
+    /// Start of the target block
+    pub to: usize,
+    /// How control moves
+    pub kind: EdgeKind,
+}
+
+/// Straight-line code entered only at its first instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BasicBlock {
+    /// Program counter of the first instruction
+    pub start: usize,
+    /// Program counter just past the last instruction
+    pub end: usize,
+    /// The instructions, in order
+    pub instructions: Vec<Instruction>,
+}
+
+impl BasicBlock {
+    /// The last instruction, which decides where control goes next
+    pub fn last(&self) -> &Instruction {
+        self.instructions.last().expect("blocks are not empty")
+    }
+}
+
+/// Basic blocks of bytecode and the edges between them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ControlFlowGraph {
+    blocks: Vec<BasicBlock>,
+    edges: Vec<Edge>,
+    /// Indices into `edges` leaving each block
+    outgoing: Vec<Vec<usize>>,
+    /// Indices into `edges` entering each block
+    incoming: Vec<Vec<usize>>,
+    unresolved: Vec<usize>,
+    budget_exhausted: bool,
+}
+
+impl ControlFlowGraph {
+    /// Build the graph of `bytecode`, skipping the data regions of `config`
+    pub fn new(bytecode: &[u8], fork: Fork, config: &AnalysisConfig) -> Self {
+        let (instructions, mut budget_exhausted) =
+            match disassemble_with_config(bytecode, fork, config) {
+                Ok(disassembly) => (disassembly.instructions, disassembly.budget_exhausted),
+                Err(_) => (Vec::new(), false),
+            };
+        let destinations = valid_jump_destinations(&instructions);
+        // Jump tables need the whole code, which a partial disassembly does not cover
+        let tables: BTreeMap<usize, Vec<usize>> = if budget_exhausted {
+            BTreeMap::new()
+        } else {
+            find_jump_tables(bytecode, fork)
+                .into_iter()
+                .map(|table| (table.jump_pc, table.targets))
+                .collect()
+        };
+
+        let blocks: Vec<BasicBlock> = split_blocks(&instructions)
+            .into_iter()
+            .map(|block| BasicBlock {
+                start: block[0].pc,
+                end: block[block.len() - 1].next_pc(),
+                instructions: block.to_vec(),
+            })
+            .collect();
+
+        let mut meter = config.budget.start();
+        let mut edges = Vec::new();
+        let mut unresolved = Vec::new();
+        for (index, block) in blocks.iter().enumerate() {
+            if !budget_exhausted && !meter.step() {
+                budget_exhausted = true;
+            }
+            if budget_exhausted {
+                if matches!(block.last().opcode, 0x56 | 0x57) {
+                    unresolved.push(block.last().pc);
+                }
+                continue;
+            }
+            let last = block.last();
+            let next = blocks.get(index + 1).filter(|next| next.start == block.end);
+            if matches!(last.opcode, 0x56 | 0x57) {
+                let kind = if last.opcode == 0x56 {
+                    EdgeKind::Jump
+                } else {
+                    EdgeKind::Branch
+                };
+                let pushed = block
+                    .instructions
+                    .len()
+                    .checked_sub(2)
+                    .and_then(|i| static_jump_target(&block.instructions[i], &destinations));
+                let targets = match (pushed, tables.get(&last.pc)) {
+                    (Some(target), _) => vec![target],
+                    (None, Some(targets)) => targets
+                        .iter()
+                        .copied()
+                        .filter(|target| destinations.contains(target))
+                        .collect(),
+                    (None, None) => {
+                        unresolved.push(last.pc);
+                        Vec::new()
+                    }
+                };
+                edges.extend(targets.into_iter().map(|to| Edge {
+                    from: block.start,
+                    to,
+                    kind,
+                }));
+            }
+            if last.opcode != 0x56 && !last.is_terminator() {
+                if let Some(next) = next {
+                    edges.push(Edge {
+                        from: block.start,
+                        to: next.start,
+                        kind: EdgeKind::FallThrough,
+                    });
+                }
+            }
+        }
+
+        let mut outgoing = vec![Vec::new(); blocks.len()];
+        let mut incoming = vec![Vec::new(); blocks.len()];
+        let index_of = |start: usize| {
+            blocks
+                .binary_search_by_key(&start, |block| block.start)
+                .expect("edges connect blocks")
+        };
+        for (index, edge) in edges.iter().enumerate() {
+            outgoing[index_of(edge.from)].push(index);
+            incoming[index_of(edge.to)].push(index);
+        }
+
+        Self {
+            blocks,
+            edges,
+            outgoing,
+            incoming,
+            unresolved,
+            budget_exhausted,
+        }
+    }
+
+    /// Blocks in code order
+    pub fn blocks(&self) -> &[BasicBlock] {
+        &self.blocks
+    }
+
+    /// The block containing the instruction at `pc`
+    pub fn block_at(&self, pc: usize) -> Option<&BasicBlock> {
+        let index = self.blocks.partition_point(|block| block.start <= pc);
+        self.blocks[..index].last().filter(|block| pc < block.end)
+    }
+
+    /// Every edge, grouped by source block in code order
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    /// Edges leaving the block starting at `start`
+    pub fn successors(&self, start: usize) -> impl Iterator<Item = &Edge> {
+        self.adjacent(&self.outgoing, start)
+    }
+
+    /// Edges entering the block starting at `start`
+    pub fn predecessors(&self, start: usize) -> impl Iterator<Item = &Edge> {
+        self.adjacent(&self.incoming, start)
+    }
+
+    /// Whether the budget ran out, leaving the graph partial
+    ///
+    /// Blocks past the point where it ran out have no edges, and their jumps are unresolved.
+    pub fn budget_exhausted(&self) -> bool {
+        self.budget_exhausted
+    }
+
+    /// The edges listed for the block starting at `start`
+    fn adjacent<'a>(
+        &'a self,
+        lists: &'a [Vec<usize>],
+        start: usize,
+    ) -> impl Iterator<Item = &'a Edge> {
+        let list = self
+            .blocks
+            .binary_search_by_key(&start, |block| block.start)
+            .map_or(&[][..], |index| lists[index].as_slice());
+        list.iter().map(|&edge| &self.edges[edge])
+    }
+
+    /// Program counters of the JUMPs and JUMPIs whose targets are unknown
+    pub fn unresolved_jumps(&self) -> &[usize] {
+        &self.unresolved
+    }
+
+    /// Starts of the blocks reachable from the entry
+    ///
+    /// Once an unresolved jump is reachable, every JUMPDEST block counts as reachable.
+    pub fn reachable(&self) -> BTreeSet<usize> {
+        let mut reachable = BTreeSet::new();
+        let mut queue: VecDeque<usize> = self.blocks.first().map(|b| b.start).into_iter().collect();
+        let mut any_destination = false;
+        while let Some(start) = queue.pop_front() {
+            if !reachable.insert(start) {
+                continue;
+            }
+            queue.extend(self.successors(start).map(|edge| edge.to));
+            let block = self.block_at(start).expect("edges end at blocks");
+            if !any_destination && self.unresolved.binary_search(&block.last().pc).is_ok() {
+                any_destination = true;
+                queue.extend(
+                    self.blocks
+                        .iter()
+                        .filter(|b| b.instructions[0].opcode == 0x5b)
+                        .map(|b| b.start),
+                );
+            }
+        }
+        reachable
+    }
+}
+
+/// Split decoded instructions into basic blocks
+pub(crate) fn split_blocks(instructions: &[Instruction]) -> Vec<&[Instruction]> {
+    let mut blocks = Vec::new();
+    let mut start = 0;
+    for (index, instruction) in instructions.iter().enumerate() {
+        if instruction.opcode == 0x5b && index > start {
+            blocks.push(&instructions[start..index]);
+            start = index;
+        }
+        if instruction.is_terminator() || instruction.opcode == 0x57 {
+            blocks.push(&instructions[start..=index]);
+            start = index + 1;
+        }
+    }
+    if start < instructions.len() {
+        blocks.push(&instructions[start..]);
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AnalysisBudget;
+
+    #[test]
+    fn test_blocks_and_edges() {
+        // CALLDATASIZE, PUSH1 0x08, JUMPI | PUSH1 0x0a, JUMP | STOP | JUMPDEST, STOP |
+        // JUMPDEST, STOP
+        let code = [
+            0x36, 0x60, 0x08, 0x57, 0x60, 0x0a, 0x56, 0x00, 0x5b, 0x00, 0x5b, 0x00,
+        ];
+        let graph = ControlFlowGraph::new(&code, Fork::Cancun, &AnalysisConfig::new());
+        let starts: Vec<usize> = graph.blocks().iter().map(|b| b.start).collect();
+        assert_eq!(starts, [0, 4, 7, 8, 10]);
+        assert_eq!(
+            graph.edges(),
+            [
+                Edge {
+                    from: 0,
+                    to: 8,
+                    kind: EdgeKind::Branch
+                },
+                Edge {
+                    from: 0,
+                    to: 4,
+                    kind: EdgeKind::FallThrough
+                },
+                Edge {
+                    from: 4,
+                    to: 10,
+                    kind: EdgeKind::Jump
+                },
+            ]
+        );
+        assert_eq!(graph.predecessors(10).count(), 1);
+        assert_eq!(graph.block_at(5).map(|b| b.start), Some(4));
+        assert_eq!(graph.reachable(), BTreeSet::from([0, 4, 8, 10]));
+        assert!(graph.unresolved_jumps().is_empty());
+    }
+
+    #[test]
+    fn test_constant_and_unresolved_jumps() {
+        let config = AnalysisConfig::new();
+        // PUSH1 0x05, DUP1, JUMP, INVALID, JUMPDEST, STOP: the target reaches JUMP via DUP1
+        let graph = ControlFlowGraph::new(
+            &[0x60, 0x05, 0x80, 0x56, 0xfe, 0x5b, 0x00],
+            Fork::Cancun,
+            &config,
+        );
+        assert_eq!(graph.successors(0).map(|e| e.to).collect::<Vec<_>>(), [5]);
+
+        // CALLDATASIZE, JUMP | JUMPDEST, STOP
+        let graph = ControlFlowGraph::new(&[0x36, 0x56, 0x5b, 0x00], Fork::Cancun, &config);
+        assert_eq!(graph.unresolved_jumps(), [1]);
+        assert!(graph.edges().is_empty());
+        assert_eq!(graph.reachable(), BTreeSet::from([0, 2]));
+    }
+
+    #[test]
+    fn test_budget_leaves_partial_graph() {
+        let code = [
+            0x36, 0x60, 0x08, 0x57, 0x60, 0x0a, 0x56, 0x00, 0x5b, 0x00, 0x5b, 0x00,
+        ];
+        let config =
+            AnalysisConfig::new().with_budget(AnalysisBudget::unlimited().with_max_steps(3));
+        let graph = ControlFlowGraph::new(&code, Fork::Cancun, &config);
+        assert!(graph.budget_exhausted());
+        assert_eq!(graph.blocks().len(), 1);
+        assert_eq!(graph.unresolved_jumps(), [3]);
+        assert!(
+            !ControlFlowGraph::new(&code, Fork::Cancun, &AnalysisConfig::new()).budget_exhausted()
+        );
+    }
+}
@@ -0,0 +1,548 @@
+//! Chain profiles: a chain ID, its fork schedule and chain-specific gas overrides
+//!
+//! Presets cover Ethereum mainnet, the Sepolia and Holesky testnets, the OP-Stack chains
+//! Optimism and Base and BNB Smart Chain, whose hardforks map onto Ethereum forks, Arbitrum
+//! One and zkSync Era. Chains with their
+//! own pricing (L2s, private networks) add a [`Repricing`] with the opcodes they charge
+//! differently, add or do not support; [`DynamicGasCalculator::for_chain`] and
+//! [`OpcodeRegistry::get_chain_opcodes`] apply it on top of the fork active at a given block.
+//! Opcodes that keep their price but behave differently are recorded as divergences, which
+//! [`ChainDivergenceRule`](crate::rules::ChainDivergenceRule) and
+//! [`GasAnalyzer::analyze_bytecode_for_chain`] report.
+
+use crate::gas::{calldata_gas, DynamicGasCalculator, GasAnalysis, GasAnalyzer, Repricing};
+use crate::rules::{ChainDivergenceRule, SequenceValidator};
+use crate::schedule::{Activation, ForkSchedule};
+use crate::{AnalysisConfig, Fork, OpcodeMetadata, OpcodeRegistry};
+use std::collections::{BTreeMap, HashMap};
+
+/// A network and the rules it runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainProfile {
+    /// EIP-155 chain ID
+    pub chain_id: u64,
+    /// Human-readable name
+    pub name: String,
+    /// Fork activations
+    pub schedule: ForkSchedule,
+    /// Prices that differ from the active fork's schedule, and unsupported opcodes
+    pub gas_overrides: Repricing,
+    /// Gas charged for posting transaction data to L1, if the chain charges it as gas
+    pub l1_data_pricing: Option<L1DataPricing>,
+    /// Opcodes whose semantics or gas accounting differ from Ethereum, with an explanation
+    pub divergences: BTreeMap<u8, String>,
+}
+
+impl ChainProfile {
+    /// Create a profile without gas overrides
+    pub fn new(chain_id: u64, name: &str, schedule: ForkSchedule) -> Self {
+        Self {
+            chain_id,
+            name: name.to_string(),
+            schedule,
+            gas_overrides: Repricing::new(name),
+            l1_data_pricing: None,
+            divergences: BTreeMap::new(),
+        }
+    }
+
+    /// Set the chain-specific gas overrides
+    pub fn with_gas_overrides(mut self, overrides: Repricing) -> Self {
+        self.gas_overrides = overrides;
+        self
+    }
+
+    /// Record an opcode that behaves differently on the chain
+    pub fn with_divergence(mut self, opcode: u8, note: &str) -> Self {
+        self.divergences.insert(opcode, note.to_string());
+        self
+    }
+
+    /// Set the L1 data pricing
+    pub fn with_l1_data_pricing(mut self, pricing: L1DataPricing) -> Self {
+        self.l1_data_pricing = Some(pricing);
+        self
+    }
+
+    /// Ethereum mainnet
+    pub fn mainnet() -> Self {
+        Self::new(1, "mainnet", ForkSchedule::mainnet())
+    }
+
+    /// Sepolia testnet, launched with London rules
+    pub fn sepolia() -> Self {
+        let schedule = genesis_schedule(Fork::London)
+            .with_fork(Fork::Paris, Activation::Block(1_450_409))
+            .with_fork(Fork::Shanghai, Activation::Timestamp(1_677_557_088))
+            .with_fork(Fork::Cancun, Activation::Timestamp(1_706_655_072))
+            .with_fork(Fork::Prague, Activation::Timestamp(1_741_159_776));
+        Self::new(11_155_111, "sepolia", schedule)
+    }
+
+    /// Holesky testnet, launched with Paris rules
+    pub fn holesky() -> Self {
+        let schedule = genesis_schedule(Fork::Paris)
+            .with_fork(Fork::Shanghai, Activation::Timestamp(1_696_000_704))
+            .with_fork(Fork::Cancun, Activation::Timestamp(1_707_305_664))
+            .with_fork(Fork::Prague, Activation::Timestamp(1_740_434_112));
+        Self::new(17_000, "holesky", schedule)
+    }
+
+    /// An OP-Stack chain with its hardfork activations
+    ///
+    /// Each hardfork activates the EVM of its L1 fork; a hardfork that adds nothing to the
+    /// EVM of the previous one keeps the earlier activation.
+    pub fn op_stack(
+        chain_id: u64,
+        name: &str,
+        hardforks: &[(OpStackHardfork, Activation)],
+    ) -> Self {
+        let forks = hardforks
+            .iter()
+            .map(|(hardfork, activation)| (hardfork.l1_fork(), *activation));
+        Self::new(chain_id, name, mapped_schedule(ForkSchedule::new(), forks))
+    }
+
+    /// OP Mainnet, migrated to Bedrock from the legacy chain
+    pub fn optimism() -> Self {
+        Self::op_stack(
+            10,
+            "optimism",
+            &superchain_hardforks(Activation::Block(105_235_063)),
+        )
+    }
+
+    /// Base mainnet, launched with Bedrock
+    pub fn base() -> Self {
+        Self::op_stack(8453, "base", &superchain_hardforks(Activation::Block(0)))
+    }
+
+    /// BNB Smart Chain mainnet, launched with Istanbul rules
+    ///
+    /// BSC has no beacon chain: DIFFICULTY keeps returning the block difficulty after
+    /// Shanghai, but the opcode set is the same.
+    pub fn bsc() -> Self {
+        let forks = [
+            (BscHardfork::Hertz, Activation::Block(31_302_048)),
+            (BscHardfork::Shanghai, Activation::Timestamp(1_705_996_800)),
+            (BscHardfork::Tycho, Activation::Timestamp(1_718_863_500)),
+            (BscHardfork::Pascal, Activation::Timestamp(1_742_436_600)),
+        ]
+        .map(|(hardfork, activation)| (hardfork.ethereum_fork(), activation));
+        let schedule = mapped_schedule(genesis_schedule(Fork::Istanbul), forks);
+        Self::new(56, "bsc", schedule)
+    }
+
+    /// Arbitrum One running an ArbOS version
+    ///
+    /// Blob transactions do not exist on Arbitrum, so BLOBHASH and BLOBBASEFEE are removed.
+    /// Some opcodes keep their price but return different values: NUMBER and BLOCKHASH refer
+    /// to L1 blocks, PREVRANDAO returns 1 and COINBASE returns the batch poster. The L1 data
+    /// charge depends on market prices; set it with
+    /// [`with_l1_data_pricing`](Self::with_l1_data_pricing).
+    pub fn arbitrum(version: ArbOsVersion) -> Self {
+        let overrides = Repricing::new("arbitrum")
+            .without_opcode(0x49)
+            .without_opcode(0x4a);
+        Self::new(42_161, "arbitrum", genesis_schedule(version.l1_fork()))
+            .with_gas_overrides(overrides)
+    }
+
+    /// zkSync Era, which compiles EVM code to its own EraVM
+    ///
+    /// The zksolc and zkvyper compilers reject SELFDESTRUCT, CALLCODE, PC and EXTCODECOPY, so
+    /// they are removed. Other opcodes compile but behave differently (see
+    /// [`divergences`](Self::divergences)); gas is metered by EraVM, so EVM gas figures are
+    /// only indicative.
+    pub fn zksync_era() -> Self {
+        let overrides = Repricing::new("zksync-era")
+            .without_opcode(0xff)
+            .without_opcode(0xf2)
+            .without_opcode(0x58)
+            .without_opcode(0x3c);
+        Self::new(324, "zksync-era", genesis_schedule(Fork::Cancun))
+            .with_gas_overrides(overrides)
+            .with_divergence(
+                0xf0,
+                "CREATE only deploys bytecode known at compile time (factory dependencies) and derives addresses differently",
+            )
+            .with_divergence(
+                0xf5,
+                "CREATE2 only deploys bytecode known at compile time and derives addresses from the bytecode hash, not the init code",
+            )
+            .with_divergence(
+                0xf1,
+                "CALL with value goes through a system contract and the 2300 gas stipend is not forwarded as on Ethereum",
+            )
+            .with_divergence(0x39, "CODECOPY copies call data in deploy code and is rejected in runtime code")
+            .with_divergence(0x38, "CODESIZE returns the call data size in deploy code")
+            .with_divergence(0x3f, "EXTCODEHASH returns the versioned EraVM bytecode hash")
+            .with_divergence(0x5a, "GAS returns EraVM gas, which is not comparable to EVM gas")
+            .with_divergence(0x44, "PREVRANDAO returns a constant")
+            .with_divergence(0x41, "COINBASE returns the bootloader address")
+    }
+
+    /// The preset for a chain ID, if there is one
+    pub fn from_chain_id(chain_id: u64) -> Option<Self> {
+        [
+            Self::mainnet(),
+            Self::sepolia(),
+            Self::holesky(),
+            Self::optimism(),
+            Self::base(),
+            Self::bsc(),
+            Self::arbitrum(ArbOsVersion::ArbOs40),
+            Self::zksync_era(),
+        ]
+        .into_iter()
+        .find(|profile| profile.chain_id == chain_id)
+    }
+
+    /// The fork active in a block with this number and timestamp
+    pub fn fork_at(&self, block: u64, timestamp: u64) -> Fork {
+        self.schedule.fork_at(block, timestamp)
+    }
+
+    /// Gas charged before execution: the 21000 base cost, calldata and the L1 data charge
+    pub fn intrinsic_gas(&self, calldata: &[u8], block: u64, timestamp: u64) -> u64 {
+        let l1_gas = self
+            .l1_data_pricing
+            .map_or(0, |pricing| pricing.l1_gas(calldata.len()));
+        21_000 + calldata_gas(calldata, self.fork_at(block, timestamp)) + l1_gas
+    }
+}
+
+/// L1 data charge converted to L2 gas, as Arbitrum charges it
+///
+/// The poster cost is `units * l1_price_per_unit` with 16 units per byte of transaction data,
+/// and is charged as that cost divided by the L2 base fee. Arbitrum prices the compressed
+/// transaction; this uses the uncompressed size, which is an upper bound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct L1DataPricing {
+    /// L1 price per data unit, in wei
+    pub l1_price_per_unit: u128,
+    /// L2 base fee, in wei
+    pub l2_base_fee: u128,
+}
+
+impl L1DataPricing {
+    /// Create a pricing from the L1 price per unit and the L2 base fee, both in wei
+    pub fn new(l1_price_per_unit: u128, l2_base_fee: u128) -> Self {
+        Self {
+            l1_price_per_unit,
+            l2_base_fee,
+        }
+    }
+
+    /// L2 gas charged for `size` bytes of transaction data
+    pub fn l1_gas(&self, size: usize) -> u64 {
+        if self.l2_base_fee == 0 {
+            return 0;
+        }
+        let cost = size as u128 * 16 * self.l1_price_per_unit;
+        cost.div_ceil(self.l2_base_fee).min(u64::MAX as u128) as u64
+    }
+}
+
+/// ArbOS versions that change the EVM
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ArbOsVersion {
+    /// ArbOS 11 - Shanghai EVM (PUSH0)
+    ArbOs11,
+    /// ArbOS 20 "Atlas" - Cancun EVM without blobs
+    ArbOs20,
+    /// ArbOS 40 "Callisto" - Prague EVM
+    ArbOs40,
+}
+
+impl ArbOsVersion {
+    /// All versions in activation order
+    pub const ALL: [ArbOsVersion; 3] = [
+        ArbOsVersion::ArbOs11,
+        ArbOsVersion::ArbOs20,
+        ArbOsVersion::ArbOs40,
+    ];
+
+    /// The L1 fork whose opcodes and gas schedule the version runs
+    pub fn l1_fork(self) -> Fork {
+        match self {
+            ArbOsVersion::ArbOs11 => Fork::Shanghai,
+            ArbOsVersion::ArbOs20 => Fork::Cancun,
+            ArbOsVersion::ArbOs40 => Fork::Prague,
+        }
+    }
+}
+
+/// OP-Stack network upgrades that change the EVM
+///
+/// Regolith, Delta, Granite and Holocene only change the rollup protocol and are left out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum OpStackHardfork {
+    /// Bedrock (June 2023) - London EVM with PREVRANDAO
+    Bedrock,
+    /// Canyon (January 2024) - Shanghai EVM (PUSH0)
+    Canyon,
+    /// Ecotone (March 2024) - Cancun EVM (transient storage, MCOPY, blobs)
+    Ecotone,
+    /// Fjord (July 2024) - RIP-7212 precompile, no new opcodes
+    Fjord,
+    /// Isthmus (May 2025) - Prague EVM
+    Isthmus,
+}
+
+impl OpStackHardfork {
+    /// All hardforks in activation order
+    pub const ALL: [OpStackHardfork; 5] = [
+        OpStackHardfork::Bedrock,
+        OpStackHardfork::Canyon,
+        OpStackHardfork::Ecotone,
+        OpStackHardfork::Fjord,
+        OpStackHardfork::Isthmus,
+    ];
+
+    /// The L1 fork whose opcodes and gas schedule the hardfork runs
+    pub fn l1_fork(self) -> Fork {
+        match self {
+            OpStackHardfork::Bedrock => Fork::Paris,
+            OpStackHardfork::Canyon => Fork::Shanghai,
+            OpStackHardfork::Ecotone | OpStackHardfork::Fjord => Fork::Cancun,
+            OpStackHardfork::Isthmus => Fork::Prague,
+        }
+    }
+}
+
+/// BNB Smart Chain hardforks that change the EVM
+///
+/// The many BSC hardforks that only change consensus or system contracts are left out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BscHardfork {
+    /// Hertz (April 2023) - Berlin and London EVM
+    Hertz,
+    /// Shanghai (January 2024) - Shanghai EVM (PUSH0)
+    Shanghai,
+    /// Tycho (June 2024) - Cancun EVM (transient storage, MCOPY, blobs)
+    Tycho,
+    /// Pascal (March 2025) - Prague EVM
+    Pascal,
+}
+
+impl BscHardfork {
+    /// All hardforks in activation order
+    pub const ALL: [BscHardfork; 4] = [
+        BscHardfork::Hertz,
+        BscHardfork::Shanghai,
+        BscHardfork::Tycho,
+        BscHardfork::Pascal,
+    ];
+
+    /// The Ethereum fork whose opcodes and gas schedule the hardfork adopts
+    pub fn ethereum_fork(self) -> Fork {
+        match self {
+            BscHardfork::Hertz => Fork::London,
+            BscHardfork::Shanghai => Fork::Shanghai,
+            BscHardfork::Tycho => Fork::Cancun,
+            BscHardfork::Pascal => Fork::Prague,
+        }
+    }
+}
+
+/// Superchain hardfork activations after a chain's Bedrock activation
+fn superchain_hardforks(bedrock: Activation) -> [(OpStackHardfork, Activation); 5] {
+    [
+        (OpStackHardfork::Bedrock, bedrock),
+        (
+            OpStackHardfork::Canyon,
+            Activation::Timestamp(1_704_992_401),
+        ),
+        (
+            OpStackHardfork::Ecotone,
+            Activation::Timestamp(1_710_374_401),
+        ),
+        (OpStackHardfork::Fjord, Activation::Timestamp(1_720_627_201)),
+        (
+            OpStackHardfork::Isthmus,
+            Activation::Timestamp(1_746_806_401),
+        ),
+    ]
+}
+
+/// Add each Ethereum fork, with every EVM-changing fork before it that `schedule` lacks, at
+/// its activation; a fork that is already scheduled keeps the earlier activation
+fn mapped_schedule(
+    mut schedule: ForkSchedule,
+    forks: impl IntoIterator<Item = (Fork, Activation)>,
+) -> ForkSchedule {
+    for (target, activation) in forks {
+        for fork in Fork::execution_forks().filter(|fork| *fork <= target) {
+            if (fork.changes_evm() || fork == target) && schedule.activation(fork).is_none() {
+                schedule = schedule.with_fork(fork, activation);
+            }
+        }
+    }
+    schedule
+}
+
+/// Every EVM-changing fork up to `last` active from block 0
+fn genesis_schedule(last: Fork) -> ForkSchedule {
+    Fork::execution_forks()
+        .filter(|fork| *fork <= last && (fork.changes_evm() || *fork == last))
+        .fold(ForkSchedule::new(), |schedule, fork| {
+            schedule.with_fork(fork, Activation::Block(0))
+        })
+}
+
+impl OpcodeRegistry {
+    /// Get the opcodes a chain supports at a block, without the ones it removes
+    pub fn get_chain_opcodes(
+        &self,
+        chain: &ChainProfile,
+        block: u64,
+        timestamp: u64,
+    ) -> HashMap<u8, OpcodeMetadata> {
+        let mut opcodes = self.get_opcodes(chain.fork_at(block, timestamp));
+        opcodes.retain(|opcode, _| !chain.gas_overrides.removes(*opcode));
+        opcodes
+    }
+}
+
+impl GasAnalyzer {
+    /// Analyze bytecode under the fork a chain runs at a block, adding a warning for every
+    /// opcode the chain does not support or runs differently
+    pub fn analyze_bytecode_for_chain(
+        bytecode: &[u8],
+        chain: &ChainProfile,
+        block: u64,
+        timestamp: u64,
+        config: &AnalysisConfig,
+    ) -> Result<GasAnalysis, String> {
+        let fork = chain.fork_at(block, timestamp);
+        let mut analysis = Self::analyze_bytecode(bytecode, fork, config)?;
+        if let Err(violations) = SequenceValidator::empty()
+            .with_rule(ChainDivergenceRule::new(chain.clone()))
+            .validate(bytecode, fork)
+        {
+            analysis
+                .warnings
+                .extend(violations.into_iter().map(|violation| violation.message));
+        }
+        Ok(analysis)
+    }
+}
+
+impl DynamicGasCalculator {
+    /// Create a calculator for the fork a chain runs at a block, with the chain's gas overrides
+    pub fn for_chain(chain: &ChainProfile, block: u64, timestamp: u64) -> Self {
+        Self::new(chain.fork_at(block, timestamp)).with_repricing(chain.gas_overrides.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gas::ExecutionContext;
+
+    #[test]
+    fn test_presets() {
+        assert_eq!(ChainProfile::from_chain_id(1).unwrap().name, "mainnet");
+        let sepolia = ChainProfile::from_chain_id(11_155_111).unwrap();
+        assert_eq!(sepolia.fork_at(0, 0), Fork::London);
+        assert_eq!(sepolia.fork_at(1_450_409, 0), Fork::Paris);
+        assert_eq!(sepolia.fork_at(5_000_000, 1_706_655_072), Fork::Cancun);
+        assert_eq!(ChainProfile::holesky().fork_at(0, 0), Fork::Paris);
+        assert!(ChainProfile::from_chain_id(5).is_none());
+    }
+
+    #[test]
+    fn test_op_stack_profiles() {
+        let registry = crate::OpcodeRegistry::new();
+        let optimism = ChainProfile::from_chain_id(10).unwrap();
+        let bedrock = optimism.fork_at(105_235_063, 1_700_000_000);
+        assert_eq!(bedrock, Fork::Paris);
+        assert!(!registry.is_opcode_available(bedrock, 0x5f));
+        // PUSH0 arrives with Canyon, transient storage with Ecotone
+        let canyon = optimism.fork_at(120_000_000, 1_704_992_401);
+        assert!(registry.is_opcode_available(canyon, 0x5f));
+        assert!(!registry.is_opcode_available(canyon, 0x5c));
+        let fjord = optimism.fork_at(125_000_000, 1_720_627_201);
+        assert_eq!(fjord, Fork::Cancun);
+        assert_eq!(
+            optimism.schedule.activation(Fork::Cancun),
+            Some(Activation::Timestamp(1_710_374_401))
+        );
+        assert_eq!(ChainProfile::base().fork_at(0, 0), Fork::Paris);
+    }
+
+    #[test]
+    fn test_bsc_profile() {
+        let registry = crate::OpcodeRegistry::new();
+        let bsc = ChainProfile::from_chain_id(56).unwrap();
+        assert_eq!(bsc.fork_at(0, 0), Fork::Istanbul);
+        // BASEFEE arrives with Hertz, PUSH0 with the Shanghai hardfork
+        let hertz = bsc.fork_at(31_302_048, 1_681_000_000);
+        assert_eq!(hertz, Fork::London);
+        assert!(registry.is_opcode_available(hertz, 0x48));
+        assert!(!registry.is_opcode_available(hertz, 0x5f));
+        assert_eq!(bsc.fork_at(40_000_000, 1_718_863_500), Fork::Cancun);
+        assert_eq!(
+            bsc.schedule.activation(Fork::Berlin),
+            Some(Activation::Block(31_302_048))
+        );
+    }
+
+    #[test]
+    fn test_arbitrum_profile() {
+        let atlas = ChainProfile::arbitrum(ArbOsVersion::ArbOs20);
+        assert_eq!(atlas.fork_at(0, 0), Fork::Cancun);
+        let opcodes = crate::OpcodeRegistry::new().get_chain_opcodes(&atlas, 0, 0);
+        assert!(opcodes.contains_key(&0x5c));
+        assert!(!opcodes.contains_key(&0x49));
+        let calculator = DynamicGasCalculator::for_chain(&atlas, 0, 0);
+        assert!(calculator
+            .calculate_gas_cost(0x4a, &ExecutionContext::default(), &[])
+            .is_err());
+
+        // 100 bytes at 16 units each, L1 price 10 gwei per unit, L2 base fee 0.1 gwei
+        let priced = atlas.with_l1_data_pricing(L1DataPricing::new(10_000_000_000, 100_000_000));
+        let calldata = [0u8; 100];
+        assert_eq!(
+            priced.intrinsic_gas(&calldata, 0, 0),
+            21_000 + 400 + 160_000
+        );
+        assert_eq!(
+            ChainProfile::from_chain_id(42_161).unwrap().fork_at(0, 0),
+            Fork::Prague
+        );
+    }
+
+    #[test]
+    fn test_zksync_divergences() {
+        let era = ChainProfile::from_chain_id(324).unwrap();
+        // PUSH1 0, PUSH1 0, PUSH1 0, CREATE, SELFDESTRUCT
+        let code = [0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0xf0, 0xff];
+        let analysis =
+            GasAnalyzer::analyze_bytecode_for_chain(&code, &era, 0, 0, &AnalysisConfig::default())
+                .unwrap();
+        assert!(analysis
+            .warnings
+            .iter()
+            .any(|w| w.starts_with("CREATE at pc 6 behaves differently on zksync-era")));
+        assert!(analysis
+            .warnings
+            .contains(&"SELFDESTRUCT at pc 7 is not supported on zksync-era".to_string()));
+    }
+
+    #[test]
+    fn test_calculator_uses_chain_overrides() {
+        let chain = ChainProfile::new(
+            424_242,
+            "devnet",
+            ForkSchedule::new().with_fork(Fork::Cancun, Activation::Block(0)),
+        )
+        .with_gas_overrides(Repricing::new("devnet").with_cost(0x01, 1));
+        let calculator = DynamicGasCalculator::for_chain(&chain, 10, 0);
+        let context = ExecutionContext::default();
+        assert_eq!(calculator.calculate_gas_cost(0x01, &context, &[]), Ok(1));
+        // TLOAD is priced, so the Cancun rules apply
+        assert!(calculator.calculate_gas_cost(0x5c, &context, &[0]).is_ok());
+    }
+}
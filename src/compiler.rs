@@ -0,0 +1,193 @@
+//! Heuristic identification of the compiler that produced bytecode
+//!
+//! The metadata trailer names the compiler when it is present. Otherwise opcode idioms
+//! give it away: solc sets up the free memory pointer with `PUSH1 0x80 PUSH1 0x40 MSTORE`
+//! (via-IR with `PUSH1 0x80 DUP1 PUSH1 0x40 MSTORE`), Vyper compares selectors with XOR, and
+//! Huff contracts dispatch on selectors without either. The result is a guess for triaging
+//! corpora, not a proof.
+
+use crate::disasm::{disassemble, Instruction};
+use crate::functions::detect_dispatcher;
+use crate::metadata::decode_metadata;
+
+/// Compiler families that can be told apart by their output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompilerFamily {
+    /// solc with the legacy code generator
+    SolcLegacy,
+    /// solc with the IR-based code generator
+    SolcViaIr,
+    /// Vyper
+    Vyper,
+    /// Huff
+    Huff,
+    /// No compiler idioms found
+    HandWritten,
+    /// No code to look at
+    Unknown,
+}
+
+/// How much evidence backs a guess
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Confidence {
+    /// Only the absence of other idioms
+    Low,
+    /// A characteristic opcode idiom
+    Medium,
+    /// The compiler named itself in the metadata
+    High,
+}
+
+/// Guessed compiler of some bytecode
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CompilerGuess {
+    /// The compiler family
+    pub family: CompilerFamily,
+    /// Compiler version from the metadata, if present
+    pub version: Option<String>,
+    /// How much evidence backs the guess
+    pub confidence: Confidence,
+    /// The idioms that were found
+    pub evidence: Vec<&'static str>,
+}
+
+/// Guess the compiler family and version of runtime or creation code
+pub fn identify_compiler(bytecode: &[u8]) -> CompilerGuess {
+    let metadata = decode_metadata(bytecode);
+    let code = match &metadata {
+        Some(metadata) => &bytecode[..metadata.region.start],
+        None => bytecode,
+    };
+    let instructions = disassemble(code);
+    let mut evidence = Vec::new();
+
+    let legacy_prologue = starts_with(&instructions, &[(0x60, 0x80), (0x60, 0x40)], 0x52);
+    let ir_prologue = instructions.len() >= 5
+        && instructions[0].opcode == 0x60
+        && instructions[0].immediate == [0x80]
+        && instructions[1].opcode == 0x80
+        && starts_with(&instructions[2..], &[(0x60, 0x40)], 0x52);
+    if legacy_prologue {
+        evidence.push("free memory pointer set with PUSH1 0x80 PUSH1 0x40 MSTORE");
+    }
+    if ir_prologue {
+        evidence.push("free memory pointer set with PUSH1 0x80 DUP1 PUSH1 0x40 MSTORE");
+    }
+    // PUSH4 selector [DUPn] XOR
+    let xor_selectors = instructions.windows(3).any(|window| {
+        let [push, next, after] = [window[0].opcode, window[1].opcode, window[2].opcode];
+        push == 0x63 && (next == 0x18 || (0x80..=0x8f).contains(&next) && after == 0x18)
+    });
+    if xor_selectors {
+        evidence.push("selectors compared with XOR");
+    }
+    let dispatcher = !detect_dispatcher(code).is_empty();
+    if dispatcher && !xor_selectors {
+        evidence.push("selector dispatcher");
+    }
+
+    if let Some(metadata) = metadata {
+        if let Some(version) = metadata.vyper {
+            evidence.push("metadata names vyper");
+            return guess(
+                CompilerFamily::Vyper,
+                Some(version),
+                Confidence::High,
+                evidence,
+            );
+        }
+        if let Some(version) = metadata.solc {
+            evidence.push("metadata names solc");
+            let family = if ir_prologue {
+                CompilerFamily::SolcViaIr
+            } else {
+                CompilerFamily::SolcLegacy
+            };
+            return guess(family, Some(version), Confidence::High, evidence);
+        }
+    }
+
+    let (family, confidence) = if code.is_empty() {
+        (CompilerFamily::Unknown, Confidence::Low)
+    } else if ir_prologue {
+        (CompilerFamily::SolcViaIr, Confidence::Medium)
+    } else if legacy_prologue {
+        (CompilerFamily::SolcLegacy, Confidence::Medium)
+    } else if xor_selectors {
+        (CompilerFamily::Vyper, Confidence::Medium)
+    } else if dispatcher {
+        (CompilerFamily::Huff, Confidence::Low)
+    } else {
+        (CompilerFamily::HandWritten, Confidence::Low)
+    };
+    guess(family, None, confidence, evidence)
+}
+
+fn guess(
+    family: CompilerFamily,
+    version: Option<String>,
+    confidence: Confidence,
+    evidence: Vec<&'static str>,
+) -> CompilerGuess {
+    CompilerGuess {
+        family,
+        version,
+        confidence,
+        evidence,
+    }
+}
+
+/// Check for PUSHes with the given one-byte immediates followed by `last`
+fn starts_with(instructions: &[Instruction], pushes: &[(u8, u8)], last: u8) -> bool {
+    instructions.len() > pushes.len()
+        && pushes
+            .iter()
+            .zip(instructions)
+            .all(|(&(opcode, value), i)| i.opcode == opcode && i.immediate == [value])
+        && instructions[pushes.len()].opcode == last
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identify_from_idioms() {
+        let legacy = [0x60, 0x80, 0x60, 0x40, 0x52, 0x34, 0x80, 0x15, 0x00];
+        assert_eq!(
+            identify_compiler(&legacy).family,
+            CompilerFamily::SolcLegacy
+        );
+        let via_ir = [0x60, 0x80, 0x80, 0x60, 0x40, 0x52, 0x00];
+        assert_eq!(identify_compiler(&via_ir).family, CompilerFamily::SolcViaIr);
+
+        // PUSH4 selector, DUP2, XOR, PUSH1 0x0a, JUMPI, STOP, JUMPDEST, STOP
+        let vyper = [
+            0x63, 0xa9, 0x05, 0x9c, 0xbb, 0x81, 0x18, 0x60, 0x0a, 0x57, 0x00, 0x5b, 0x00,
+        ];
+        assert_eq!(identify_compiler(&vyper).family, CompilerFamily::Vyper);
+
+        // PUSH4 selector, EQ, PUSH1 0x0a, JUMPI, STOP, JUMPDEST, STOP
+        let huff = [
+            0x63, 0xa9, 0x05, 0x9c, 0xbb, 0x14, 0x60, 0x0a, 0x57, 0x00, 0x5b, 0x00,
+        ];
+        assert_eq!(identify_compiler(&huff).family, CompilerFamily::Huff);
+        assert_eq!(
+            identify_compiler(&[0x33, 0xff]).family,
+            CompilerFamily::HandWritten
+        );
+        assert_eq!(identify_compiler(&[]).family, CompilerFamily::Unknown);
+    }
+
+    #[test]
+    fn test_metadata_is_decisive() {
+        // Legacy prologue, then {"solc": 0.8.24}
+        let mut code = vec![0x60, 0x80, 0x60, 0x40, 0x52, 0x00, 0xa1, 0x64];
+        code.extend_from_slice(b"solc");
+        code.extend_from_slice(&[0x43, 0x00, 0x08, 0x18, 0x00, 0x0a]);
+        let guess = identify_compiler(&code);
+        assert_eq!(guess.family, CompilerFamily::SolcLegacy);
+        assert_eq!(guess.version.as_deref(), Some("0.8.24"));
+        assert_eq!(guess.confidence, Confidence::High);
+    }
+}
@@ -0,0 +1,332 @@
+//! Configuration shared by the analysis passes
+
+use crate::disasm::{DataRegion, Instruction};
+use crate::gas::{DummyEstimator, OperandEstimator, SharedEstimator};
+use crate::Fork;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How analysis passes treat bytes that are not assigned to an opcode in the target fork
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum UnknownOpcodePolicy {
+    /// Abort the analysis with an error naming the byte and its position
+    Error,
+    /// Skip the byte and record a warning in the analysis result
+    #[default]
+    SkipWithWarning,
+    /// Treat the byte like INVALID (0xfe): execution halts at this point
+    TreatAsInvalid,
+}
+
+/// What an analysis pass should do with an unknown byte after applying the policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnknownOpcodeAction {
+    /// Drop the byte and continue with the next one
+    Skip,
+    /// Replace the byte with INVALID and stop analyzing the sequence
+    TreatAsInvalid,
+}
+
+impl UnknownOpcodePolicy {
+    /// Apply the policy to an unknown byte found at `index` in a sequence
+    ///
+    /// Returns the action to take, or an error message for [`UnknownOpcodePolicy::Error`].
+    pub fn resolve(
+        self,
+        opcode: u8,
+        index: usize,
+        fork: Fork,
+    ) -> Result<UnknownOpcodeAction, String> {
+        match self {
+            Self::Error => Err(unknown_opcode_message(opcode, index, fork)),
+            Self::SkipWithWarning => Ok(UnknownOpcodeAction::Skip),
+            Self::TreatAsInvalid => Ok(UnknownOpcodeAction::TreatAsInvalid),
+        }
+    }
+}
+
+/// Format the standard message describing an unknown byte
+pub fn unknown_opcode_message(opcode: u8, index: usize, fork: Fork) -> String {
+    format!("Unknown opcode 0x{opcode:02x} at index {index} for fork {fork:?}")
+}
+
+/// Options controlling how bytecode and opcode sequences are analyzed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct AnalysisConfig {
+    /// Handling of bytes that are not valid opcodes in the analyzed fork
+    pub unknown_opcode_policy: UnknownOpcodePolicy,
+    /// Known data regions to exclude from decoding and gas analysis
+    pub data_regions: Vec<DataRegion>,
+    /// Automatically detect trailing data after unreachable terminators
+    pub detect_data_regions: bool,
+    /// Work limit for each analysis pass
+    pub budget: AnalysisBudget,
+    /// Treat the code as an EOF code section and report the legacy opcodes EOF bans
+    pub eof_mode: bool,
+    /// Source of operands for dynamic gas, [`DummyEstimator`](crate::gas::DummyEstimator) if `None`
+    pub operand_estimator: Option<SharedEstimator>,
+}
+
+impl AnalysisConfig {
+    /// Create a configuration with default settings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the unknown opcode policy
+    pub fn with_unknown_opcode_policy(mut self, policy: UnknownOpcodePolicy) -> Self {
+        self.unknown_opcode_policy = policy;
+        self
+    }
+
+    /// Annotate a known data region
+    pub fn with_data_region(mut self, region: DataRegion) -> Self {
+        self.data_regions.push(region);
+        self
+    }
+
+    /// Enable or disable automatic detection of trailing data regions
+    pub fn with_data_region_detection(mut self, enabled: bool) -> Self {
+        self.detect_data_regions = enabled;
+        self
+    }
+
+    /// Limit the work each analysis pass may do
+    pub fn with_budget(mut self, budget: AnalysisBudget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Enable or disable EOF mode, see [`EofBannedOpcodeRule`](crate::rules::EofBannedOpcodeRule)
+    pub fn with_eof_mode(mut self, enabled: bool) -> Self {
+        self.eof_mode = enabled;
+        self
+    }
+
+    /// Price dynamic gas with operands from `estimator`
+    pub fn with_operand_estimator(mut self, estimator: impl OperandEstimator + 'static) -> Self {
+        self.operand_estimator = Some(SharedEstimator::new(estimator));
+        self
+    }
+
+    /// Each instruction's opcode with operands from the configured estimator
+    pub(crate) fn operand_sequence(
+        &self,
+        instructions: &[Instruction],
+        fork: Fork,
+    ) -> Vec<(u8, Vec<u64>)> {
+        let operands = match &self.operand_estimator {
+            Some(estimator) => estimator.get().estimate(instructions, fork),
+            None => DummyEstimator.estimate(instructions, fork),
+        };
+        instructions
+            .iter()
+            .map(|i| i.opcode)
+            .zip(operands)
+            .collect()
+    }
+}
+
+/// Limits on the work a single analysis pass may do before returning partial results
+///
+/// A step is one decoded or analyzed instruction, so step budgets give the same results on
+/// every platform. Time budgets depend on the host and need a clock, which
+/// `wasm32-unknown-unknown` does not provide.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct AnalysisBudget {
+    /// Maximum number of steps, unlimited if `None`
+    pub max_steps: Option<u64>,
+    /// Maximum wall-clock time, unlimited if `None`
+    pub max_duration: Option<Duration>,
+    /// Token that aborts the pass when cancelled
+    pub cancellation: Option<CancellationToken>,
+}
+
+impl AnalysisBudget {
+    /// A budget without limits
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// Limit the number of steps
+    pub fn with_max_steps(mut self, steps: u64) -> Self {
+        self.max_steps = Some(steps);
+        self
+    }
+
+    /// Limit the wall-clock time
+    pub fn with_max_duration(mut self, duration: Duration) -> Self {
+        self.max_duration = Some(duration);
+        self
+    }
+
+    /// Abort the pass when `token` is cancelled
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Start metering a pass against this budget
+    pub fn start(&self) -> BudgetMeter {
+        BudgetMeter {
+            budget: self.clone(),
+            steps: 0,
+            started: self.max_duration.map(|_| Instant::now()),
+            exhausted: false,
+        }
+    }
+}
+
+/// Shared flag for aborting analyses from another thread
+///
+/// Clones share the flag. Two tokens are equal when they share the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Create a token that is not cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wrap an existing flag; setting it to `true` cancels the token
+    pub fn from_flag(flag: Arc<AtomicBool>) -> Self {
+        Self(flag)
+    }
+
+    /// Request cancellation of every analysis using this token
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Check if cancellation was requested
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl PartialEq for CancellationToken {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for CancellationToken {}
+
+impl Hash for CancellationToken {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0).hash(state);
+    }
+}
+
+/// Work done by one analysis pass, checked against an [`AnalysisBudget`]
+#[derive(Debug, Clone)]
+pub struct BudgetMeter {
+    budget: AnalysisBudget,
+    steps: u64,
+    started: Option<Instant>,
+    exhausted: bool,
+}
+
+impl BudgetMeter {
+    /// Record one step; returns `false` once the budget is exhausted
+    pub fn step(&mut self) -> bool {
+        if self.exhausted {
+            return false;
+        }
+        self.steps += 1;
+        let out_of_steps = self.budget.max_steps.is_some_and(|max| self.steps > max);
+        let out_of_time = match (self.budget.max_duration, self.started) {
+            (Some(max), Some(started)) => started.elapsed() > max,
+            _ => false,
+        };
+        self.exhausted = out_of_steps || out_of_time || self.is_cancelled();
+        !self.exhausted
+    }
+
+    /// Check if the budget's cancellation token was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.budget
+            .cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+
+    /// Number of steps recorded so far, including the one that exhausted the budget
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Check if the budget ran out or the pass was cancelled
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted
+    }
+
+    /// Short description of why the pass stopped, for warnings
+    pub fn stop_reason(&self) -> &'static str {
+        if self.is_cancelled() {
+            "Analysis cancelled"
+        } else {
+            "Analysis budget exhausted"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_policy_skips() {
+        let config = AnalysisConfig::new();
+        assert_eq!(
+            config.unknown_opcode_policy,
+            UnknownOpcodePolicy::SkipWithWarning
+        );
+    }
+
+    #[test]
+    fn test_policy_resolution() {
+        let err = UnknownOpcodePolicy::Error
+            .resolve(0x0c, 3, Fork::London)
+            .unwrap_err();
+        assert!(err.contains("0x0c"));
+        assert!(err.contains("index 3"));
+
+        assert_eq!(
+            UnknownOpcodePolicy::TreatAsInvalid.resolve(0x0c, 0, Fork::London),
+            Ok(UnknownOpcodeAction::TreatAsInvalid)
+        );
+    }
+
+    #[test]
+    fn test_budget_meter() {
+        let mut meter = AnalysisBudget::unlimited().with_max_steps(2).start();
+        assert!(meter.step());
+        assert!(meter.step());
+        assert!(!meter.step());
+        assert!(meter.is_exhausted());
+        assert!(!meter.step());
+        assert_eq!(meter.steps(), 3);
+
+        let mut meter = AnalysisBudget::unlimited()
+            .with_max_duration(Duration::ZERO)
+            .start();
+        std::thread::sleep(Duration::from_millis(1));
+        assert!(!meter.step());
+    }
+
+    #[test]
+    fn test_cancellation_stops_meter() {
+        let token = CancellationToken::new();
+        let mut meter = AnalysisBudget::unlimited()
+            .with_cancellation(token.clone())
+            .start();
+        assert!(meter.step());
+        token.cancel();
+        assert!(!meter.step());
+        assert!(meter.is_cancelled());
+    }
+}
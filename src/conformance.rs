@@ -0,0 +1,223 @@
+//! Golden test vectors for dynamic gas
+//!
+//! `vectors/dynamic_gas.txt` lists the expected cost of every opcode with a dynamic part
+//! (memory expansion, copied or hashed words, EIP-2929 access, log data, init code, value
+//! transfers) on Berlin, London, Shanghai and Cancun. The file is embedded as
+//! [`GAS_VECTORS`] so other gas pricers can check themselves against the same numbers:
+//!
+//! ```
+//! use eot::conformance::{price_with_calculator, run_gas_vectors};
+//!
+//! let report = run_gas_vectors(price_with_calculator);
+//! assert!(report.is_ok(), "{:?}", report.failures);
+//! ```
+
+use crate::gas::{DynamicGasCalculator, ExecutionContext};
+use crate::{Fork, OpcodeRegistry};
+
+/// The bundled vector file
+pub const GAS_VECTORS: &str = include_str!("../vectors/dynamic_gas.txt");
+
+/// One expected gas cost
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GasVector {
+    /// Line of the vector in its file
+    pub line: usize,
+    /// Fork to price in
+    pub fork: Fork,
+    /// The opcode
+    pub opcode: u8,
+    /// Operands, top of the stack first
+    pub operands: Vec<u64>,
+    /// Memory size before the instruction, in bytes
+    pub memory_size: usize,
+    /// Storage slots of the current contract accessed earlier in the transaction
+    pub warm_slots: Vec<u64>,
+    /// Addresses accessed earlier in the transaction
+    pub warm_addresses: Vec<u64>,
+    /// Full gas cost of the instruction
+    pub expected: u64,
+}
+
+impl GasVector {
+    /// The execution context the vector describes
+    pub fn context(&self) -> ExecutionContext {
+        let mut context = ExecutionContext::new();
+        context.memory_size = self.memory_size;
+        let address = context.current_address;
+        for slot in &self.warm_slots {
+            let mut key = [0u8; 32];
+            key[24..].copy_from_slice(&slot.to_be_bytes());
+            context.mark_storage_accessed(&address, &key);
+        }
+        for warm in &self.warm_addresses {
+            context.mark_address_accessed(&ExecutionContext::from_vec_address(&warm.to_be_bytes()));
+        }
+        context
+    }
+}
+
+/// A vector a pricer got wrong
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VectorFailure {
+    /// The vector
+    pub vector: GasVector,
+    /// What the pricer returned
+    pub actual: Result<u64, String>,
+}
+
+/// Outcome of checking a pricer against vectors
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VectorReport {
+    /// Number of vectors priced correctly
+    pub passed: usize,
+    /// Vectors priced incorrectly or not at all
+    pub failures: Vec<VectorFailure>,
+}
+
+impl VectorReport {
+    /// Whether every vector passed
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Parse a vector file in the format of [`GAS_VECTORS`]
+pub fn parse_gas_vectors(text: &str) -> Result<Vec<GasVector>, String> {
+    let registry = OpcodeRegistry::new();
+    let mut vectors = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let error = |message: &str| format!("Line {}: {message}: {line}", number + 1);
+        let columns: Vec<&str> = line.split_whitespace().collect();
+        let [fork, opcode, operands, context, expected] = columns[..] else {
+            return Err(error("expected FORK OPCODE OPERANDS CONTEXT GAS"));
+        };
+        let fork = parse_fork(fork).ok_or_else(|| error("unknown fork"))?;
+        let opcode = registry
+            .get_opcodes(fork)
+            .iter()
+            .find(|(_, metadata)| metadata.name.eq_ignore_ascii_case(opcode))
+            .map(|(byte, _)| *byte)
+            .ok_or_else(|| error("unknown opcode"))?;
+        let mut vector = GasVector {
+            line: number + 1,
+            fork,
+            opcode,
+            operands: Vec::new(),
+            memory_size: 0,
+            warm_slots: Vec::new(),
+            warm_addresses: Vec::new(),
+            expected: parse_value(expected).ok_or_else(|| error("invalid gas amount"))?,
+        };
+        for operand in list(operands) {
+            vector
+                .operands
+                .push(parse_value(operand).ok_or_else(|| error("invalid operand"))?);
+        }
+        for entry in list(context) {
+            let (key, value) = entry
+                .split_once('=')
+                .ok_or_else(|| error("expected KEY=VALUE in context"))?;
+            let value = parse_value(value).ok_or_else(|| error("invalid context value"))?;
+            match key {
+                "memory" => vector.memory_size = value as usize,
+                "warm_slot" => vector.warm_slots.push(value),
+                "warm_address" => vector.warm_addresses.push(value),
+                _ => return Err(error("unknown context key")),
+            }
+        }
+        vectors.push(vector);
+    }
+    Ok(vectors)
+}
+
+/// Check a pricer against the bundled vectors
+pub fn run_gas_vectors<F>(pricer: F) -> VectorReport
+where
+    F: FnMut(&GasVector) -> Result<u64, String>,
+{
+    let vectors = parse_gas_vectors(GAS_VECTORS).expect("bundled gas vectors are valid");
+    check_gas_vectors(&vectors, pricer)
+}
+
+/// Check a pricer against a set of vectors
+pub fn check_gas_vectors<F>(vectors: &[GasVector], mut pricer: F) -> VectorReport
+where
+    F: FnMut(&GasVector) -> Result<u64, String>,
+{
+    let mut report = VectorReport::default();
+    for vector in vectors {
+        match pricer(vector) {
+            Ok(gas) if gas == vector.expected => report.passed += 1,
+            actual => report.failures.push(VectorFailure {
+                vector: vector.clone(),
+                actual,
+            }),
+        }
+    }
+    report
+}
+
+/// Price a vector with [`DynamicGasCalculator`]
+pub fn price_with_calculator(vector: &GasVector) -> Result<u64, String> {
+    DynamicGasCalculator::new(vector.fork).calculate_gas_cost(
+        vector.opcode,
+        &vector.context(),
+        &vector.operands,
+    )
+}
+
+/// Items of a comma-separated column, none for `-`
+fn list(column: &str) -> impl Iterator<Item = &str> {
+    column
+        .split(',')
+        .filter(|item| !item.is_empty() && *item != "-")
+}
+
+/// A decimal or `0x` hex value
+fn parse_value(text: &str) -> Option<u64> {
+    match text.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
+
+/// Forks the vector files may name
+fn parse_fork(name: &str) -> Option<Fork> {
+    match name.to_ascii_lowercase().as_str() {
+        "berlin" => Some(Fork::Berlin),
+        "london" => Some(Fork::London),
+        "shanghai" => Some(Fork::Shanghai),
+        "cancun" => Some(Fork::Cancun),
+        "prague" => Some(Fork::Prague),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calculator_passes_bundled_vectors() {
+        let report = run_gas_vectors(price_with_calculator);
+        assert!(report.is_ok(), "{:#?}", report.failures);
+        assert!(report.passed > 200);
+    }
+
+    #[test]
+    fn test_parse_errors_and_failures() {
+        let vectors = parse_gas_vectors("cancun SLOAD 0x1 warm_slot=0x1 100\n").unwrap();
+        assert_eq!(vectors[0].opcode, 0x54);
+        let report = check_gas_vectors(&vectors, |_| Ok(2100));
+        assert_eq!(report.failures[0].actual, Ok(2100));
+
+        let error = parse_gas_vectors("\ncancun SLOAD 0x1 hot=1 100").unwrap_err();
+        assert!(error.starts_with("Line 2: unknown context key"));
+        assert!(parse_gas_vectors("frontier SLOAD 0x1 - 50").is_err());
+    }
+}
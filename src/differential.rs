@@ -0,0 +1,297 @@
+//! Differential testing across fork tables
+//!
+//! A [`DifferentialTest`] asks every registered fork the same questions about every opcode
+//! byte (is it available, what does it pop and push, what does it cost, what is it called)
+//! and compares the answers of consecutive forks. Two properties must hold: an opcode never
+//! disappears, and its stack inputs and outputs never change unless the change is flagged,
+//! either in [`SEMANTIC_CHANGES`] or with
+//! [`with_expected_change`](DifferentialTest::with_expected_change). Every other difference
+//! is reported too, so EVM implementers can diff their own fork tables against the same
+//! list.
+
+use crate::semantics::SEMANTIC_CHANGES;
+use crate::{Fork, OpcodeId, OpcodeMetadata, OpcodeRegistry};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// What differs between two consecutive forks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DifferenceKind {
+    /// The opcode became available
+    Introduced,
+    /// The opcode is no longer available
+    Removed,
+    /// Stack inputs and outputs changed
+    StackIoChanged {
+        /// Inputs and outputs in the earlier fork
+        from: (u8, u8),
+        /// Inputs and outputs in the later fork
+        to: (u8, u8),
+    },
+    /// Base gas cost changed
+    GasChanged {
+        /// Cost in the earlier fork
+        from: u16,
+        /// Cost in the later fork
+        to: u16,
+    },
+    /// Mnemonic changed
+    Renamed {
+        /// Name in the earlier fork
+        from: &'static str,
+        /// Name in the later fork
+        to: &'static str,
+    },
+}
+
+/// A difference between the answers of two consecutive forks for one opcode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ForkDifference {
+    /// The opcode
+    pub opcode: u8,
+    /// The earlier fork
+    pub from: Fork,
+    /// The later fork
+    pub to: Fork,
+    /// What differs
+    pub kind: DifferenceKind,
+    /// Whether the change is flagged as intended
+    pub expected: bool,
+}
+
+impl ForkDifference {
+    /// Stable identifier of the opcode: in the earlier fork for removals, the later otherwise
+    pub fn id(&self) -> OpcodeId {
+        match self.kind {
+            DifferenceKind::Removed => OpcodeId::new(self.opcode, self.from),
+            _ => OpcodeId::new(self.opcode, self.to),
+        }
+    }
+
+    /// Whether the difference breaks a cross-fork property
+    pub fn is_violation(&self) -> bool {
+        !self.expected
+            && matches!(
+                self.kind,
+                DifferenceKind::Removed | DifferenceKind::StackIoChanged { .. }
+            )
+    }
+}
+
+impl fmt::Display for ForkDifference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "0x{:02x} {:?} -> {:?}: ",
+            self.opcode, self.from, self.to
+        )?;
+        match self.kind {
+            DifferenceKind::Introduced => write!(f, "introduced")?,
+            DifferenceKind::Removed => write!(f, "removed")?,
+            DifferenceKind::StackIoChanged { from, to } => {
+                write!(f, "stack {}/{} -> {}/{}", from.0, from.1, to.0, to.1)?
+            }
+            DifferenceKind::GasChanged { from, to } => write!(f, "gas {from} -> {to}")?,
+            DifferenceKind::Renamed { from, to } => write!(f, "renamed {from} -> {to}")?,
+        }
+        if self.expected {
+            write!(f, " (expected)")?;
+        }
+        write!(f, " [{}]", self.id())
+    }
+}
+
+/// Outcome of a differential run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DifferentialReport {
+    /// Forks with their own table, oldest first
+    pub forks: Vec<Fork>,
+    /// Number of (fork, opcode byte) queries made
+    pub queries: usize,
+    /// Differences between consecutive forks, in fork then opcode order
+    pub differences: Vec<ForkDifference>,
+}
+
+impl DifferentialReport {
+    /// Differences that break a cross-fork property
+    pub fn violations(&self) -> impl Iterator<Item = &ForkDifference> {
+        self.differences.iter().filter(|d| d.is_violation())
+    }
+
+    /// Whether every property holds
+    pub fn is_consistent(&self) -> bool {
+        self.violations().next().is_none()
+    }
+}
+
+impl fmt::Display for DifferentialReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} forks, {} queries, {} differences, {} violations",
+            self.forks.len(),
+            self.queries,
+            self.differences.len(),
+            self.violations().count()
+        )?;
+        for difference in &self.differences {
+            let marker = if difference.is_violation() { "!" } else { " " };
+            writeln!(f, "{marker} {difference}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Cross-fork consistency checks over a registry
+pub struct DifferentialTest<'a> {
+    registry: &'a OpcodeRegistry,
+    expected: BTreeSet<(u8, Fork)>,
+}
+
+impl<'a> DifferentialTest<'a> {
+    /// Checks over `registry`, with the stack changes in [`SEMANTIC_CHANGES`] expected
+    pub fn new(registry: &'a OpcodeRegistry) -> Self {
+        Self {
+            registry,
+            expected: SEMANTIC_CHANGES
+                .iter()
+                .map(|change| (change.opcode, change.fork))
+                .collect(),
+        }
+    }
+
+    /// Flag a removal or stack change of `opcode` in `fork` as intended
+    pub fn with_expected_change(mut self, opcode: u8, fork: Fork) -> Self {
+        self.expected.insert((opcode, fork));
+        self
+    }
+
+    /// Query every fork and compare consecutive ones
+    pub fn run(&self) -> DifferentialReport {
+        let forks: Vec<Fork> = self
+            .registry
+            .opcodes
+            .keys()
+            .map(|f| Fork::from(*f))
+            .collect();
+        let tables: Vec<_> = forks
+            .iter()
+            .map(|fork| self.registry.get_opcodes(*fork))
+            .collect();
+        let mut report = DifferentialReport {
+            queries: forks.len() * 256,
+            ..Default::default()
+        };
+
+        for (pair, tables) in forks.windows(2).zip(tables.windows(2)) {
+            let (from, to) = (pair[0], pair[1]);
+            for opcode in 0..=u8::MAX {
+                let kinds = match (tables[0].get(&opcode), tables[1].get(&opcode)) {
+                    (None, None) => continue,
+                    (None, Some(_)) => vec![DifferenceKind::Introduced],
+                    (Some(_), None) => vec![DifferenceKind::Removed],
+                    (Some(before), Some(after)) => {
+                        let mut kinds = Vec::new();
+                        let io = |m: &OpcodeMetadata| (m.stack_inputs, m.stack_outputs);
+                        if io(before) != io(after) {
+                            kinds.push(DifferenceKind::StackIoChanged {
+                                from: io(before),
+                                to: io(after),
+                            });
+                        }
+                        if before.gas_cost != after.gas_cost {
+                            kinds.push(DifferenceKind::GasChanged {
+                                from: before.gas_cost,
+                                to: after.gas_cost,
+                            });
+                        }
+                        if before.name != after.name {
+                            kinds.push(DifferenceKind::Renamed {
+                                from: before.name,
+                                to: after.name,
+                            });
+                        }
+                        kinds
+                    }
+                };
+                let expected = self.expected.contains(&(opcode, to));
+                report
+                    .differences
+                    .extend(kinds.into_iter().map(|kind| ForkDifference {
+                        opcode,
+                        from,
+                        to,
+                        kind,
+                        expected,
+                    }));
+            }
+        }
+
+        report.forks = forks;
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_tables_are_consistent() {
+        let registry = OpcodeRegistry::new();
+        let report = DifferentialTest::new(&registry).run();
+        assert!(report.is_consistent(), "{report}");
+        assert_eq!(report.forks[0], Fork::Frontier);
+        assert_eq!(report.queries, report.forks.len() * 256);
+
+        let push0 = report
+            .differences
+            .iter()
+            .find(|d| d.opcode == 0x5f)
+            .unwrap();
+        assert_eq!(push0.kind, DifferenceKind::Introduced);
+        assert_eq!(push0.id().to_string(), "evm/0x5f@shanghai");
+        assert!(report
+            .differences
+            .iter()
+            .any(|d| d.opcode == 0x54 && matches!(d.kind, DifferenceKind::GasChanged { .. })));
+    }
+
+    #[test]
+    fn test_stack_change_is_violation_unless_flagged() {
+        // Make ADD pop three items in the newest fork
+        let mut registry = OpcodeRegistry::new();
+        let (newest, table) = registry.opcodes.iter_mut().next_back().unwrap();
+        let newest = Fork::from(*newest);
+        let add = table[&0x01].clone();
+        table.insert(
+            0x01,
+            OpcodeMetadata {
+                stack_inputs: 3,
+                ..add
+            },
+        );
+        let report = DifferentialTest::new(&registry).run();
+        let violations: Vec<_> = report.violations().collect();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].to, newest);
+        assert_eq!(
+            violations[0].kind,
+            DifferenceKind::StackIoChanged {
+                from: (2, 1),
+                to: (3, 1)
+            }
+        );
+        assert!(report.to_string().contains("stack 2/1 -> 3/1"));
+
+        // Flagged, it is only reported
+        let report = DifferentialTest::new(&registry)
+            .with_expected_change(0x01, newest)
+            .run();
+        assert!(report.is_consistent());
+        assert!(report
+            .differences
+            .iter()
+            .any(|d| d.opcode == 0x01 && d.expected));
+    }
+}
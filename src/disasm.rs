@@ -0,0 +1,732 @@
+//! Bytecode disassembly
+//!
+//! Decodes raw bytecode into [`Instruction`]s, keeping PUSH immediates attached
+//! to the instruction that owns them instead of treating them as opcodes.
+//! Known data regions (constructor arguments, embedded strings, metadata) can be
+//! annotated through [`AnalysisConfig`] so they are excluded from the instruction
+//! stream, and common layouts can be detected automatically.
+
+use crate::config::{AnalysisBudget, AnalysisConfig, BudgetMeter, UnknownOpcodeAction};
+use crate::metadata::decode_metadata;
+use crate::stack::Word;
+use crate::{forks::Prague, Fork, OpCode, OpcodeRegistry};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// A single decoded instruction
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Instruction {
+    /// Program counter (byte offset) of the opcode
+    pub pc: usize,
+    /// The opcode byte
+    pub opcode: u8,
+    /// Immediate bytes following the opcode (PUSH data), possibly truncated at end of code
+    pub immediate: Vec<u8>,
+}
+
+impl Instruction {
+    /// Create a new instruction
+    pub fn new(pc: usize, opcode: u8, immediate: Vec<u8>) -> Self {
+        Self {
+            pc,
+            opcode,
+            immediate,
+        }
+    }
+
+    /// Total size in bytes (opcode + immediate)
+    pub fn size(&self) -> usize {
+        1 + self.immediate.len()
+    }
+
+    /// Program counter of the instruction that follows this one
+    pub fn next_pc(&self) -> usize {
+        self.pc + self.size()
+    }
+
+    /// Check if this is a PUSH0-PUSH32 instruction
+    pub fn is_push(&self) -> bool {
+        (0x5f..=0x7f).contains(&self.opcode)
+    }
+
+    /// Check if the immediate is shorter than the opcode requires (code ended early)
+    pub fn is_truncated(&self) -> bool {
+        self.immediate.len() < immediate_size(self.opcode)
+    }
+
+    /// Check if this instruction unconditionally ends the current execution path
+    pub fn is_terminator(&self) -> bool {
+        is_terminator(self.opcode)
+    }
+
+    /// The pushed value as a big-endian word, if this is a PUSH
+    ///
+    /// A truncated immediate is padded with zeros on the right, as the EVM reads zeros past
+    /// the end of the code.
+    pub fn push_value(&self) -> Option<Word> {
+        push_word(self.opcode, &self.immediate)
+    }
+
+    /// The pushed value as u64, if this is a PUSH whose value fits in 64 bits
+    ///
+    /// Padded like [`push_value`](Self::push_value).
+    pub fn push_value_u64(&self) -> Option<u64> {
+        let word = self.push_value()?;
+        let (high, low) = word.split_at(24);
+        high.iter()
+            .all(|b| *b == 0)
+            .then(|| u64::from_be_bytes(low.try_into().expect("8 bytes")))
+    }
+
+    /// Mnemonic of the opcode (e.g. "PUSH1", "SSTORE")
+    pub fn name(&self) -> String {
+        mnemonic(self.opcode)
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())?;
+        if !self.immediate.is_empty() {
+            write!(f, " 0x")?;
+            for byte in &self.immediate {
+                write!(f, "{byte:02x}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A decoded instruction borrowing its immediate from the bytecode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InstructionRef<'a> {
+    /// Program counter (byte offset) of the opcode
+    pub pc: usize,
+    /// The opcode byte
+    pub opcode: u8,
+    /// Immediate bytes following the opcode (PUSH data), possibly truncated at end of code
+    pub immediate: &'a [u8],
+}
+
+impl InstructionRef<'_> {
+    /// Total size in bytes (opcode + immediate)
+    pub fn size(&self) -> usize {
+        1 + self.immediate.len()
+    }
+
+    /// Program counter of the instruction that follows this one
+    pub fn next_pc(&self) -> usize {
+        self.pc + self.size()
+    }
+
+    /// Check if this is a PUSH0-PUSH32 instruction
+    pub fn is_push(&self) -> bool {
+        (0x5f..=0x7f).contains(&self.opcode)
+    }
+
+    /// The pushed value as a big-endian word, see [`Instruction::push_value`]
+    pub fn push_value(&self) -> Option<Word> {
+        push_word(self.opcode, self.immediate)
+    }
+}
+
+impl From<InstructionRef<'_>> for Instruction {
+    fn from(instruction: InstructionRef<'_>) -> Self {
+        Self::new(
+            instruction.pc,
+            instruction.opcode,
+            instruction.immediate.to_vec(),
+        )
+    }
+}
+
+/// Lazily decodes legacy bytecode into [`InstructionRef`]s without allocating
+///
+/// Decodes like [`disassemble`], for code or corpora too large to collect into a
+/// `Vec<Instruction>` first.
+#[derive(Debug, Clone)]
+pub struct InstructionIter<'a> {
+    bytecode: &'a [u8],
+    pc: usize,
+}
+
+impl<'a> InstructionIter<'a> {
+    /// Decode `bytecode` from its first byte
+    pub fn new(bytecode: &'a [u8]) -> Self {
+        Self { bytecode, pc: 0 }
+    }
+}
+
+impl<'a> Iterator for InstructionIter<'a> {
+    type Item = InstructionRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let opcode = *self.bytecode.get(self.pc)?;
+        let end = (self.pc + 1 + immediate_size(opcode)).min(self.bytecode.len());
+        let instruction = InstructionRef {
+            pc: self.pc,
+            opcode,
+            immediate: &self.bytecode[self.pc + 1..end],
+        };
+        self.pc = end;
+        Some(instruction)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.bytecode.len() - self.pc;
+        (remaining.div_ceil(33), Some(remaining))
+    }
+}
+
+impl std::iter::FusedIterator for InstructionIter<'_> {}
+
+/// Number of immediate bytes that follow an opcode in legacy bytecode
+pub fn immediate_size(opcode: u8) -> usize {
+    match opcode {
+        0x60..=0x7f => (opcode - 0x5f) as usize, // PUSH1-PUSH32
+        _ => 0,
+    }
+}
+
+/// Number of immediate bytes that follow an opcode in an EOF code section, given the bytes
+/// after the opcode (RJUMPV's jump table length is in its first immediate byte)
+pub fn eof_immediate_size(opcode: u8, following: &[u8]) -> usize {
+    match opcode {
+        0xe2 => following
+            .first()
+            .map_or(1, |max_index| 1 + (*max_index as usize + 1) * 2),
+        // DATALOADN, RJUMP, RJUMPI, CALLF, JUMPF
+        0xd1 | 0xe0 | 0xe1 | 0xe3 | 0xe5 => 2,
+        // DUPN, SWAPN, EXCHANGE, EOFCREATE, RETURNCONTRACT
+        0xe6..=0xe8 | 0xec | 0xee => 1,
+        _ => immediate_size(opcode),
+    }
+}
+
+/// Check if an opcode unconditionally ends the current execution path
+pub fn is_terminator(opcode: u8) -> bool {
+    matches!(
+        opcode,
+        0x00 | // STOP
+        0x56 | // JUMP
+        0xf3 | // RETURN
+        0xfd | // REVERT
+        0xfe | // INVALID
+        0xff // SELFDESTRUCT
+    )
+}
+
+/// Mnemonic for an opcode byte, falling back to `UNKNOWN(0x..)` for unassigned bytes
+pub fn mnemonic(opcode: u8) -> String {
+    match opcode {
+        0x60..=0x7f => format!("PUSH{}", opcode - 0x5f),
+        0x80..=0x8f => format!("DUP{}", opcode - 0x7f),
+        0x90..=0x9f => format!("SWAP{}", opcode - 0x8f),
+        _ => Prague::try_from_byte(opcode)
+            .map(|op| op.metadata().name.to_string())
+            .unwrap_or_else(|| format!("UNKNOWN(0x{opcode:02x})")),
+    }
+}
+
+/// Kind of non-code bytes embedded in bytecode
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DataRegionKind {
+    /// ABI-encoded constructor arguments appended to creation code
+    ConstructorArguments,
+    /// Embedded constants such as strings or lookup tables
+    EmbeddedData,
+    /// Compiler metadata trailer
+    Metadata,
+    /// Bytes after an unreachable terminator that do not decode as code (auto-detected)
+    Unreachable,
+    /// User-defined region kind
+    Custom(String),
+}
+
+/// A byte range of bytecode that holds data rather than instructions
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DataRegion {
+    /// First byte of the region
+    pub start: usize,
+    /// One past the last byte of the region
+    pub end: usize,
+    /// What the region contains
+    pub kind: DataRegionKind,
+}
+
+impl DataRegion {
+    /// Create a new data region covering `start..end`
+    pub fn new(start: usize, end: usize, kind: DataRegionKind) -> Self {
+        Self { start, end, kind }
+    }
+
+    /// Length of the region in bytes
+    pub fn len(&self) -> usize {
+        self.end.saturating_sub(self.start)
+    }
+
+    /// Check if the region covers no bytes
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Check if a byte offset falls inside the region
+    pub fn contains(&self, offset: usize) -> bool {
+        (self.start..self.end).contains(&offset)
+    }
+}
+
+/// Result of disassembling bytecode with an analysis configuration
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Disassembly {
+    /// Decoded instructions, excluding data regions
+    pub instructions: Vec<Instruction>,
+    /// Data regions excluded from decoding (annotated and auto-detected), sorted by start
+    pub data_regions: Vec<DataRegion>,
+    /// Warnings produced while decoding
+    pub warnings: Vec<String>,
+    /// Decoding stopped early because the analysis budget ran out or was cancelled
+    pub budget_exhausted: bool,
+}
+
+impl Disassembly {
+    /// Get the opcode bytes of all decoded instructions, in order
+    pub fn opcodes(&self) -> Vec<u8> {
+        self.instructions.iter().map(|i| i.opcode).collect()
+    }
+
+    /// Values pushed by the PUSH instructions, by program counter
+    pub fn push_values(&self) -> BTreeMap<usize, Word> {
+        self.instructions
+            .iter()
+            .filter_map(|i| Some((i.pc, i.push_value()?)))
+            .collect()
+    }
+
+    /// Find the instruction starting at a given program counter
+    pub fn instruction_at(&self, pc: usize) -> Option<&Instruction> {
+        self.instructions
+            .binary_search_by_key(&pc, |i| i.pc)
+            .ok()
+            .map(|index| &self.instructions[index])
+    }
+
+    /// Check if a byte offset belongs to a data region
+    pub fn is_data(&self, offset: usize) -> bool {
+        self.data_regions.iter().any(|r| r.contains(offset))
+    }
+
+    /// Render the decoded instructions as a symbolic listing (see [`symbolic_listing`])
+    pub fn to_symbolic_text(&self) -> String {
+        symbolic_listing(&self.instructions)
+    }
+}
+
+/// Decode bytecode into instructions without any fork checks
+///
+/// Every byte that is not PUSH data becomes an instruction, including unassigned bytes.
+pub fn disassemble(bytecode: &[u8]) -> Vec<Instruction> {
+    InstructionIter::new(bytecode)
+        .map(Instruction::from)
+        .collect()
+}
+
+/// Encode instructions back into bytecode, the inverse of [`disassemble`]
+///
+/// Instructions are written in order with their immediates as they are; `pc` fields are
+/// ignored. Edits that move code do not update jump targets, see
+/// [`patch`](crate::patch::patch) for that.
+pub fn reassemble(instructions: &[Instruction]) -> Vec<u8> {
+    let mut bytecode = Vec::with_capacity(instructions.iter().map(Instruction::size).sum());
+    for instruction in instructions {
+        bytecode.push(instruction.opcode);
+        bytecode.extend_from_slice(&instruction.immediate);
+    }
+    bytecode
+}
+
+/// Decode an EOF code section, skipping the immediates of the EOF-only opcodes
+pub fn disassemble_eof(code: &[u8]) -> Vec<Instruction> {
+    let mut instructions = Vec::new();
+    let mut pc = 0;
+    while pc < code.len() {
+        let opcode = code[pc];
+        let end = (pc + 1 + eof_immediate_size(opcode, &code[pc + 1..])).min(code.len());
+        let instruction = Instruction::new(pc, opcode, code[pc + 1..end].to_vec());
+        pc = instruction.next_pc();
+        instructions.push(instruction);
+    }
+    instructions
+}
+
+/// Decode bytecode for a fork, honoring the data regions and unknown-opcode policy in `config`
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(?fork, len = bytecode.len()))
+)]
+pub fn disassemble_with_config(
+    bytecode: &[u8],
+    fork: Fork,
+    config: &AnalysisConfig,
+) -> Result<Disassembly, String> {
+    let registry = OpcodeRegistry::new();
+    let known_opcodes = registry.get_opcodes(fork);
+
+    let mut meter = config.budget.start();
+    let mut disassembly = Disassembly {
+        data_regions: config
+            .data_regions
+            .iter()
+            .filter(|r| !r.is_empty() && r.start < bytecode.len())
+            .map(|r| DataRegion::new(r.start, r.end.min(bytecode.len()), r.kind.clone()))
+            .collect(),
+        ..Default::default()
+    };
+    if config.detect_data_regions {
+        let trailer = decode_metadata(bytecode).map(|metadata| metadata.region);
+        let Some(detected) = detect_data_regions_metered(bytecode, fork, &mut meter) else {
+            disassembly.warnings.push(format!(
+                "{} while detecting data regions, disassembly is partial",
+                meter.stop_reason()
+            ));
+            disassembly.budget_exhausted = true;
+            disassembly.data_regions.sort_by_key(|r| r.start);
+            return Ok(disassembly);
+        };
+        for detected in trailer.into_iter().chain(detected) {
+            if !disassembly
+                .data_regions
+                .iter()
+                .any(|r| r.contains(detected.start))
+            {
+                disassembly.data_regions.push(detected);
+            }
+        }
+    }
+    disassembly.data_regions.sort_by_key(|r| r.start);
+
+    let mut pc = 0;
+    while pc < bytecode.len() {
+        if let Some(region) = disassembly.data_regions.iter().find(|r| r.contains(pc)) {
+            pc = region.end;
+            continue;
+        }
+        if !meter.step() {
+            disassembly.warnings.push(format!(
+                "{} at pc {pc}, disassembly is partial",
+                meter.stop_reason()
+            ));
+            disassembly.budget_exhausted = true;
+            break;
+        }
+
+        let instruction = decode_at(bytecode, pc);
+        pc = instruction.next_pc();
+
+        if !known_opcodes.contains_key(&instruction.opcode) {
+            let index = disassembly.instructions.len();
+            match config
+                .unknown_opcode_policy
+                .resolve(instruction.opcode, index, fork)?
+            {
+                UnknownOpcodeAction::Skip => {
+                    disassembly.warnings.push(format!(
+                        "Skipped unknown opcode 0x{:02x} at pc {}",
+                        instruction.opcode, instruction.pc
+                    ));
+                    continue;
+                }
+                UnknownOpcodeAction::TreatAsInvalid => {
+                    disassembly.warnings.push(format!(
+                        "Unknown opcode 0x{:02x} at pc {} treated as INVALID, halting decode",
+                        instruction.opcode, instruction.pc
+                    ));
+                    disassembly.instructions.push(instruction);
+                    break;
+                }
+            }
+        }
+
+        disassembly.instructions.push(instruction);
+    }
+
+    Ok(disassembly)
+}
+
+/// Detect trailing data: bytes after a terminator that are not reachable through a
+/// JUMPDEST and fail to decode (unassigned opcode or truncated PUSH) for the fork
+pub fn detect_data_regions(bytecode: &[u8], fork: Fork) -> Vec<DataRegion> {
+    detect_data_regions_metered(bytecode, fork, &mut AnalysisBudget::unlimited().start())
+        .unwrap_or_default()
+}
+
+/// [`detect_data_regions`], one step of `meter` per decoded instruction
+///
+/// Returns `None` when the budget runs out.
+fn detect_data_regions_metered(
+    bytecode: &[u8],
+    fork: Fork,
+    meter: &mut BudgetMeter,
+) -> Option<Vec<DataRegion>> {
+    let registry = OpcodeRegistry::new();
+    let known_opcodes = registry.get_opcodes(fork);
+    // Start of the data after the first terminator not yet followed by a JUMPDEST
+    let mut data_start = None;
+    for instruction in InstructionIter::new(bytecode) {
+        if !meter.step() {
+            return None;
+        }
+        if instruction.opcode == 0x5b {
+            // JUMPDEST makes the following code reachable again
+            data_start = None;
+        } else if let Some(start) = data_start {
+            if !known_opcodes.contains_key(&instruction.opcode)
+                || instruction.immediate.len() < immediate_size(instruction.opcode)
+            {
+                return Some(vec![DataRegion::new(
+                    start,
+                    bytecode.len(),
+                    DataRegionKind::Unreachable,
+                )]);
+            }
+        } else if is_terminator(instruction.opcode) {
+            data_start = Some(instruction.next_pc());
+        }
+    }
+
+    Some(Vec::new())
+}
+
+/// Program counters of all JUMPDEST instructions that are valid jump targets
+///
+/// Only decoded instructions count, so 0x5b bytes inside PUSH data are excluded.
+pub fn valid_jump_destinations(instructions: &[Instruction]) -> BTreeSet<usize> {
+    instructions
+        .iter()
+        .filter(|i| i.opcode == 0x5b)
+        .map(|i| i.pc)
+        .collect()
+}
+
+/// Label name used for a jump destination in symbolic listings
+pub fn label_name(pc: usize) -> String {
+    format!("label_{pc:04x}")
+}
+
+/// Infer labels for JUMPDESTs targeted by a PUSH immediately followed by JUMP or JUMPI
+///
+/// Returns a map from JUMPDEST program counter to label name.
+pub fn infer_jump_labels(instructions: &[Instruction]) -> BTreeMap<usize, String> {
+    let destinations = valid_jump_destinations(instructions);
+    instructions
+        .windows(2)
+        .filter(|pair| matches!(pair[1].opcode, 0x56 | 0x57)) // JUMP, JUMPI
+        .filter_map(|pair| static_jump_target(&pair[0], &destinations))
+        .map(|target| (target, label_name(target)))
+        .collect()
+}
+
+/// Render instructions as text, with labels on referenced JUMPDESTs and statically
+/// resolved jumps written as `JUMP label_xxxx` / `JUMPI label_xxxx`
+pub fn symbolic_listing(instructions: &[Instruction]) -> String {
+    let labels = infer_jump_labels(instructions);
+    let destinations = valid_jump_destinations(instructions);
+    let mut listing = String::new();
+
+    let mut index = 0;
+    while index < instructions.len() {
+        let instruction = &instructions[index];
+        if let Some(label) = labels.get(&instruction.pc) {
+            listing.push_str(&format!("{label}:\n"));
+        }
+
+        let folded_jump = instructions
+            .get(index + 1)
+            .filter(|next| matches!(next.opcode, 0x56 | 0x57))
+            .and_then(|next| {
+                static_jump_target(instruction, &destinations).map(|target| (next, target))
+            });
+
+        match folded_jump {
+            Some((jump, target)) => {
+                listing.push_str(&format!("    {} {}\n", jump.name(), label_name(target)));
+                index += 2;
+            }
+            None => {
+                listing.push_str(&format!("    {instruction}\n"));
+                index += 1;
+            }
+        }
+    }
+
+    listing
+}
+
+/// Resolve a PUSH instruction to the JUMPDEST it references, if any
+pub(crate) fn static_jump_target(
+    push: &Instruction,
+    destinations: &BTreeSet<usize>,
+) -> Option<usize> {
+    push.push_value_u64()
+        .map(|value| value as usize)
+        .filter(|target| destinations.contains(target))
+}
+
+/// Value of a PUSH with the given immediate, zero-padded on the right if truncated
+fn push_word(opcode: u8, immediate: &[u8]) -> Option<Word> {
+    if !(0x5f..=0x7f).contains(&opcode) {
+        return None;
+    }
+    let size = immediate_size(opcode);
+    let mut word = [0u8; 32];
+    word[32 - size..32 - size + immediate.len()].copy_from_slice(immediate);
+    Some(word)
+}
+
+/// Decode the instruction starting at `pc`
+fn decode_at(bytecode: &[u8], pc: usize) -> Instruction {
+    let opcode = bytecode[pc];
+    let end = (pc + 1 + immediate_size(opcode)).min(bytecode.len());
+    Instruction::new(pc, opcode, bytecode[pc + 1..end].to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UnknownOpcodePolicy;
+
+    #[test]
+    fn test_disassemble_push_immediates() {
+        // PUSH1 0x80, PUSH1 0x40, MSTORE
+        let instructions = disassemble(&[0x60, 0x80, 0x60, 0x40, 0x52]);
+        assert_eq!(instructions.len(), 3);
+        assert_eq!(instructions[0].immediate, vec![0x80]);
+        assert_eq!(instructions[1].pc, 2);
+        assert_eq!(instructions[2].pc, 4);
+        assert_eq!(instructions[2].to_string(), "MSTORE");
+        assert_eq!(instructions[0].to_string(), "PUSH1 0x80");
+        assert_eq!(instructions[0].push_value().unwrap()[31], 0x80);
+        assert_eq!(instructions[2].push_value(), None);
+        assert_eq!(disassemble(&[0x5f])[0].push_value(), Some([0; 32]));
+    }
+
+    #[test]
+    fn test_jump_labels_and_symbolic_listing() {
+        // PUSH1 0x04, JUMP, INVALID, JUMPDEST, STOP
+        let instructions = disassemble(&[0x60, 0x04, 0x56, 0xfe, 0x5b, 0x00]);
+        let labels = infer_jump_labels(&instructions);
+        assert_eq!(labels.get(&4), Some(&"label_0004".to_string()));
+
+        let listing = symbolic_listing(&instructions);
+        assert_eq!(
+            listing,
+            "    JUMP label_0004\n    INVALID\nlabel_0004:\n    JUMPDEST\n    STOP\n"
+        );
+    }
+
+    #[test]
+    fn test_jumpdest_inside_push_data_is_not_a_target() {
+        // PUSH1 0x5b, PUSH1 0x01, JUMP
+        let instructions = disassemble(&[0x60, 0x5b, 0x60, 0x01, 0x56]);
+        assert!(valid_jump_destinations(&instructions).is_empty());
+        assert!(infer_jump_labels(&instructions).is_empty());
+        assert!(symbolic_listing(&instructions).contains("PUSH1 0x01\n    JUMP\n"));
+    }
+
+    #[test]
+    fn test_instruction_iter_matches_disassemble() {
+        let code = [0x60, 0x80, 0x60, 0x40, 0x52, 0x7f, 0x01, 0x5b, 0x61];
+        let decoded: Vec<Instruction> = InstructionIter::new(&code).map(Into::into).collect();
+        assert_eq!(decoded, disassemble(&code));
+
+        let mut iter = InstructionIter::new(&code);
+        assert_eq!(iter.size_hint(), (1, Some(9)));
+        let push = iter.nth(1).unwrap();
+        assert_eq!((push.pc, push.immediate), (2, [0x40].as_slice()));
+        assert_eq!(iter.last().unwrap().next_pc(), code.len());
+    }
+
+    #[test]
+    fn test_reassemble_round_trips() {
+        // PUSH1 0x80, PUSH1 0x40, MSTORE, INVALID, unassigned 0x0c, truncated PUSH4
+        let code = [0x60, 0x80, 0x60, 0x40, 0x52, 0xfe, 0x0c, 0x63, 0x01, 0x02];
+        let mut instructions = disassemble(&code);
+        assert_eq!(reassemble(&instructions), code);
+
+        instructions[0] = Instruction::new(0, 0x61, vec![0x00, 0x80]);
+        instructions.truncate(3);
+        assert_eq!(
+            reassemble(&instructions),
+            [0x61, 0x00, 0x80, 0x60, 0x40, 0x52]
+        );
+    }
+
+    #[test]
+    fn test_truncated_push() {
+        let instructions = disassemble(&[0x61, 0x01]);
+        assert_eq!(instructions.len(), 1);
+        assert!(instructions[0].is_truncated());
+        // The missing byte reads as zero: PUSH2 0x0100
+        assert_eq!(instructions[0].push_value().unwrap()[30..], [0x01, 0x00]);
+        assert_eq!(instructions[0].push_value_u64(), Some(0x0100));
+    }
+
+    #[test]
+    fn test_annotated_data_region_excluded() {
+        // PUSH1 0x01, STOP, "hi", JUMPDEST
+        let bytecode = [0x60, 0x01, 0x00, 0x68, 0x69, 0x5b];
+        let config = AnalysisConfig::new().with_data_region(DataRegion::new(
+            3,
+            5,
+            DataRegionKind::EmbeddedData,
+        ));
+        let disassembly = disassemble_with_config(&bytecode, Fork::Cancun, &config).unwrap();
+        assert_eq!(disassembly.opcodes(), vec![0x60, 0x00, 0x5b]);
+        assert!(disassembly.is_data(4));
+    }
+
+    #[test]
+    fn test_detect_trailing_data() {
+        // PUSH1 0x00, RETURN-like terminator (INVALID), then 0x0c (unassigned)
+        let bytecode = [0x60, 0x00, 0xfe, 0xa2, 0x0c, 0x01];
+        let regions = detect_data_regions(&bytecode, Fork::Cancun);
+        assert_eq!(
+            regions,
+            vec![DataRegion::new(3, 6, DataRegionKind::Unreachable)]
+        );
+
+        // A JUMPDEST after the terminator keeps the code reachable
+        let bytecode = [0x00, 0x5b, 0x01, 0x00];
+        assert!(detect_data_regions(&bytecode, Fork::Cancun).is_empty());
+
+        // Data starts after the first of several terminators
+        let bytecode = [0x00, 0x00, 0x00, 0x0c];
+        assert_eq!(
+            detect_data_regions(&bytecode, Fork::Cancun),
+            vec![DataRegion::new(1, 4, DataRegionKind::Unreachable)]
+        );
+    }
+
+    #[test]
+    fn test_detection_is_metered() {
+        let config = AnalysisConfig::new()
+            .with_data_region_detection(true)
+            .with_budget(AnalysisBudget::unlimited().with_max_steps(10));
+        let disassembly = disassemble_with_config(&[0x00; 1000], Fork::Cancun, &config).unwrap();
+        assert!(disassembly.budget_exhausted);
+        assert!(disassembly.instructions.is_empty());
+        assert!(disassembly.warnings[0].contains("while detecting data regions"));
+    }
+
+    #[test]
+    fn test_unknown_policy_in_disassembly() {
+        let bytecode = [0x01, 0x0c, 0x02];
+        let strict = AnalysisConfig::new().with_unknown_opcode_policy(UnknownOpcodePolicy::Error);
+        assert!(disassemble_with_config(&bytecode, Fork::Cancun, &strict).is_err());
+
+        let lenient = AnalysisConfig::new();
+        let disassembly = disassemble_with_config(&bytecode, Fork::Cancun, &lenient).unwrap();
+        assert_eq!(disassembly.opcodes(), vec![0x01, 0x02]);
+        assert_eq!(disassembly.warnings.len(), 1);
+    }
+}
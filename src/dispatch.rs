@@ -0,0 +1,299 @@
+//! Gas spent in the dispatcher before each function is reached
+//!
+//! Every call pays for the selector comparisons made before its own matches, so functions
+//! checked late cost more to call. [`dispatch_report`] follows the dispatcher for each
+//! selector found by [`detect_dispatcher`], deciding the `EQ`, `XOR` and binary search `GT`/`LT`
+//! jumps from the selector itself, and [`DispatchReport::suggest_order`] puts the most called
+//! functions into the cheapest positions. Vyper and hand-written dispatchers can be reordered
+//! directly; Solidity sorts selectors numerically, so there the suggestion is met by choosing
+//! selectors (e.g. renaming functions).
+
+use crate::disasm::{disassemble, static_jump_target, valid_jump_destinations, Instruction};
+use crate::functions::{detect_dispatcher, FunctionEntry};
+use crate::{Fork, OpcodeRegistry};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Gas spent by the dispatcher to reach a function
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DispatchCost {
+    /// The function
+    pub function: FunctionEntry,
+    /// Selector comparisons made on the way, including the matching one
+    pub checks: usize,
+    /// Base gas of the instructions executed from the start of the code to the entry
+    pub gas: u64,
+}
+
+/// Dispatcher costs of a contract
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DispatchReport {
+    /// Functions the dispatcher reaches, cheapest first
+    pub costs: Vec<DispatchCost>,
+    /// Functions whose path could not be followed (or that an earlier comparison shadows)
+    pub unreached: Vec<FunctionEntry>,
+    /// Selectors compared more than once; only the first comparison can match
+    pub duplicates: Vec<[u8; 4]>,
+}
+
+/// A reordering of the dispatcher for a given call mix
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderSuggestion {
+    /// Selectors from the cheapest position to the most expensive
+    pub order: Vec<[u8; 4]>,
+    /// Dispatcher gas of the call mix with the current order
+    pub current_gas: u64,
+    /// Dispatcher gas of the call mix with the suggested order
+    pub suggested_gas: u64,
+}
+
+impl OrderSuggestion {
+    /// Gas saved by the suggested order
+    pub fn savings(&self) -> u64 {
+        self.current_gas.saturating_sub(self.suggested_gas)
+    }
+}
+
+impl DispatchReport {
+    /// Dispatcher cost of a selector, if it is reached
+    pub fn cost_of(&self, selector: [u8; 4]) -> Option<&DispatchCost> {
+        self.costs
+            .iter()
+            .find(|cost| cost.function.selector == selector)
+    }
+
+    /// Assign the cheapest positions to the most called functions
+    ///
+    /// `calls` gives the number of calls per selector; selectors the dispatcher does not
+    /// reach are ignored. Position costs are the current ones, which holds for linear
+    /// dispatchers and approximates binary search ones.
+    pub fn suggest_order(&self, calls: &[([u8; 4], u64)]) -> OrderSuggestion {
+        let calls: HashMap<[u8; 4], u64> = calls.iter().copied().collect();
+        let weight = |cost: &DispatchCost| calls.get(&cost.function.selector).copied();
+
+        let mut functions: Vec<&DispatchCost> = self.costs.iter().collect();
+        functions.sort_by_key(|cost| std::cmp::Reverse(weight(cost).unwrap_or(0)));
+
+        let mut suggestion = OrderSuggestion {
+            order: functions
+                .iter()
+                .map(|cost| cost.function.selector)
+                .collect(),
+            current_gas: 0,
+            suggested_gas: 0,
+        };
+        for (function, slot) in functions.iter().zip(&self.costs) {
+            let weight = weight(function).unwrap_or(0);
+            suggestion.current_gas += weight * function.gas;
+            suggestion.suggested_gas += weight * slot.gas;
+        }
+        suggestion
+    }
+}
+
+/// Follow the dispatcher to every function it compares a selector for
+pub fn dispatch_report(bytecode: &[u8], fork: Fork) -> DispatchReport {
+    let instructions = disassemble(bytecode);
+    let destinations = valid_jump_destinations(&instructions);
+    let index_of: HashMap<usize, usize> = instructions
+        .iter()
+        .enumerate()
+        .map(|(index, i)| (i.pc, index))
+        .collect();
+    let opcodes = OpcodeRegistry::new().get_opcodes(fork);
+    let gas = |opcode: u8| {
+        opcodes
+            .get(&opcode)
+            .map_or(0, |m| m.gas_cost_in(fork) as u64)
+    };
+
+    let mut report = DispatchReport::default();
+    let mut seen: BTreeMap<[u8; 4], usize> = BTreeMap::new();
+    let mut duplicates = BTreeSet::new();
+    for function in detect_dispatcher(bytecode) {
+        if seen.insert(function.selector, function.entry).is_some() {
+            duplicates.insert(function.selector);
+            report.unreached.push(function);
+            continue;
+        }
+        let path = follow(&instructions, &index_of, &destinations, &gas, function);
+        match path {
+            Some((checks, gas)) => report.costs.push(DispatchCost {
+                function,
+                checks,
+                gas,
+            }),
+            None => report.unreached.push(function),
+        }
+    }
+    report
+        .costs
+        .sort_by_key(|cost| (cost.gas, cost.function.selector));
+    report.duplicates = duplicates.into_iter().collect();
+    report
+}
+
+/// Selectors exposed by both contracts, such as a proxy and its implementation: calls to
+/// them stop at the first contract
+pub fn selector_collisions(first: &[FunctionEntry], second: &[FunctionEntry]) -> Vec<[u8; 4]> {
+    let first: BTreeSet<[u8; 4]> = first.iter().map(|f| f.selector).collect();
+    let second: BTreeSet<[u8; 4]> = second.iter().map(|f| f.selector).collect();
+    first.intersection(&second).copied().collect()
+}
+
+/// Execute from the start of the code with `function`'s selector until its entry, returning
+/// the selector comparisons made and the gas spent
+fn follow(
+    instructions: &[Instruction],
+    index_of: &HashMap<usize, usize>,
+    destinations: &BTreeSet<usize>,
+    gas: &dyn Fn(u8) -> u64,
+    function: FunctionEntry,
+) -> Option<(usize, u64)> {
+    let selector = u32::from_be_bytes(function.selector);
+    let (mut index, mut checks, mut spent) = (0, 0, 0);
+    // Every instruction at most once: the dispatcher does not loop
+    for _ in 0..instructions.len() {
+        let instruction = instructions.get(index)?;
+        if instruction.pc == function.entry {
+            return Some((checks, spent));
+        }
+        spent += gas(instruction.opcode);
+        let jump = |index: usize| {
+            let target =
+                static_jump_target(instructions.get(index.checked_sub(1)?)?, destinations)?;
+            index_of.get(&target).copied()
+        };
+        index = match instruction.opcode {
+            0x56 => jump(index)?,
+            0x57 => match compare(instructions, index, selector) {
+                Some(taken) => {
+                    checks += 1;
+                    if taken {
+                        jump(index)?
+                    } else {
+                        index + 1
+                    }
+                }
+                // Other checks (call data size, call value) pass for a well-formed call
+                None => index + 1,
+            },
+            _ if instruction.is_terminator() => return None,
+            _ => index + 1,
+        };
+    }
+    None
+}
+
+/// Whether the JUMPI at `jumpi` is taken for `selector`, if its condition compares the
+/// selector with a PUSH4 constant
+fn compare(instructions: &[Instruction], jumpi: usize, selector: u32) -> Option<bool> {
+    let at = |index: usize| instructions.get(index);
+    let mut condition = jumpi.checked_sub(2)?;
+    let negated = at(condition)?.opcode == 0x15
+        && condition
+            .checked_sub(1)
+            .and_then(at)
+            .is_some_and(|i| i.opcode == 0x14);
+    if negated {
+        condition -= 1;
+    }
+    let constant = |index: usize| {
+        let push = at(index).filter(|i| i.opcode == 0x63)?;
+        Some(u32::from_be_bytes(
+            push.immediate.as_slice().try_into().ok()?,
+        ))
+    };
+    // `PUSH4 k OP` has the constant on top; `PUSH4 k DUPn OP` and `PUSH4 k PUSH1 0 MLOAD
+    // OP` have the selector on top
+    let (top, second) = match (condition.checked_sub(1), condition.checked_sub(2)) {
+        (Some(prev), _) if constant(prev).is_some() => (constant(prev)?, selector),
+        (Some(prev), Some(before)) if (0x80..=0x8f).contains(&at(prev)?.opcode) => {
+            (selector, constant(before)?)
+        }
+        (Some(prev), Some(before)) if at(prev)?.opcode == 0x51 && at(before)?.opcode == 0x60 => {
+            (selector, constant(before.checked_sub(1)?)?)
+        }
+        _ => return None,
+    };
+    let taken = match at(condition)?.opcode {
+        0x14 => top == second,
+        0x18 => top != second,
+        0x10 => top < second,
+        0x11 => top > second,
+        _ => return None,
+    };
+    Some(taken != negated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Linear Solidity-style dispatcher for `selectors`, each function a JUMPDEST, STOP
+    fn linear(selectors: &[u32]) -> Vec<u8> {
+        // PUSH1 0, CALLDATALOAD, PUSH1 0xe0, SHR
+        let mut code = vec![0x60, 0x00, 0x35, 0x60, 0xe0, 0x1c];
+        let bodies = code.len() + selectors.len() * 10 + 1;
+        for (position, selector) in selectors.iter().enumerate() {
+            code.push(0x80);
+            code.push(0x63);
+            code.extend(selector.to_be_bytes());
+            code.extend([0x14, 0x60, (bodies + position * 2) as u8, 0x57]);
+        }
+        code.push(0x00);
+        for _ in selectors {
+            code.extend([0x5b, 0x00]);
+        }
+        code
+    }
+
+    #[test]
+    fn test_linear_dispatch_costs_and_order() {
+        let code = linear(&[0x0000_0001, 0x0000_0002, 0x0000_0003]);
+        let report = dispatch_report(&code, Fork::Cancun);
+        // 12 gas to load the selector, then DUP1, PUSH4, EQ, PUSH1, JUMPI per check
+        let gas: Vec<(usize, u64)> = report.costs.iter().map(|c| (c.checks, c.gas)).collect();
+        assert_eq!(gas, vec![(1, 34), (2, 56), (3, 78)]);
+        assert!(report.unreached.is_empty() && report.duplicates.is_empty());
+
+        let suggestion = report.suggest_order(&[([0, 0, 0, 3], 100), ([0, 0, 0, 1], 1)]);
+        assert_eq!(
+            suggestion.order,
+            vec![[0, 0, 0, 3], [0, 0, 0, 1], [0, 0, 0, 2]]
+        );
+        assert_eq!(suggestion.current_gas, 34 + 100 * 78);
+        assert_eq!(suggestion.suggested_gas, 56 + 100 * 34);
+        assert_eq!(suggestion.savings(), 4378);
+    }
+
+    #[test]
+    fn test_binary_search_split_and_collisions() {
+        let mut code = vec![0x60, 0x00, 0x35, 0x60, 0xe0, 0x1c];
+        // DUP1, PUSH4 0x50000000, GT, PUSH1 0x1b, JUMPI
+        code.extend([0x80, 0x63, 0x50, 0x00, 0x00, 0x00, 0x11, 0x60, 0x1b, 0x57]);
+        // DUP1, PUSH4 balanceOf, EQ, PUSH1 0x27, JUMPI, STOP
+        code.extend([
+            0x80, 0x63, 0x70, 0xa0, 0x82, 0x31, 0x14, 0x60, 0x27, 0x57, 0x00,
+        ]);
+        // 0x1b: JUMPDEST, DUP1, PUSH4 approve, EQ, PUSH1 0x29, JUMPI, STOP
+        code.extend([
+            0x5b, 0x80, 0x63, 0x09, 0x5e, 0xa7, 0xb3, 0x14, 0x60, 0x29, 0x57, 0x00,
+        ]);
+        code.extend([0x5b, 0x00, 0x5b, 0x00]);
+
+        let report = dispatch_report(&code, Fork::Cancun);
+        let balance_of = report.cost_of([0x70, 0xa0, 0x82, 0x31]).unwrap();
+        assert_eq!((balance_of.checks, balance_of.gas), (2, 12 + 22 + 22));
+        let approve = report.cost_of([0x09, 0x5e, 0xa7, 0xb3]).unwrap();
+        assert_eq!((approve.checks, approve.gas), (2, 12 + 22 + 1 + 22));
+
+        let proxy = [FunctionEntry {
+            selector: [0x09, 0x5e, 0xa7, 0xb3],
+            entry: 0,
+        }];
+        assert_eq!(
+            selector_collisions(&proxy, &detect_dispatcher(&code)),
+            vec![[0x09, 0x5e, 0xa7, 0xb3]]
+        );
+    }
+}
@@ -0,0 +1,259 @@
+//! Drift between the fork code was compiled for and the fork it runs under
+//!
+//! [`check_fork_drift`] combines the opcode tables, the known semantic changes of each fork
+//! and a stipend analysis to report what an auditor should re-check when code compiled for one
+//! fork is deployed, or keeps running, under another: opcodes that are not available, gas
+//! costs that changed, semantics that changed, and calls whose 2300 gas stipend may no longer
+//! cover the callee after storage access was repriced.
+
+use crate::disasm::{disassemble, Instruction};
+use crate::gas::{ChangeType, GasComparator};
+use crate::semantics::SEMANTIC_CHANGES;
+use crate::stack::{trace_operands, StackValue};
+use crate::{Fork, OpcodeId, OpcodeRegistry};
+use std::collections::BTreeMap;
+
+/// Forks that raised the cost of operations a 2300 gas stipend has to cover
+const STIPEND_BREAKING_FORKS: [(Fork, u32); 2] = [(Fork::Istanbul, 1884), (Fork::Berlin, 2929)];
+
+/// The kind of a drift
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DriftKind {
+    /// The opcode does not exist in the deployment fork
+    Unavailable,
+    /// The base gas cost changed
+    GasCost {
+        /// Cost in the compilation fork
+        old: u16,
+        /// Cost in the deployment fork
+        new: u16,
+    },
+    /// The behavior changed
+    Semantics {
+        /// EIP that changed it
+        eip: u32,
+    },
+    /// A call relies on the 2300 gas stipend, which covers less after repricings
+    Stipend {
+        /// EIP that raised the cost of storage or account access
+        eip: u32,
+    },
+}
+
+/// One difference that matters for the code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Drift {
+    /// The opcode affected
+    pub opcode: u8,
+    /// Program counter of the first affected instruction
+    pub pc: usize,
+    /// Fork that introduced the change, if it is a single fork
+    pub fork: Option<Fork>,
+    /// Kind of drift
+    pub kind: DriftKind,
+    /// Human-readable description
+    pub message: String,
+}
+
+impl Drift {
+    /// Stable identifier of the opcode in the fork that changed it, if known
+    pub fn id(&self) -> Option<OpcodeId> {
+        self.fork.map(|fork| OpcodeId::new(self.opcode, fork))
+    }
+}
+
+/// Drifts between two forks for a piece of code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DriftReport {
+    /// Fork the code was compiled for
+    pub compiled_for: Fork,
+    /// Fork the code runs under
+    pub deployed_at: Fork,
+    /// Drifts, unavailable opcodes first, then gas, semantic and stipend drifts
+    pub drifts: Vec<Drift>,
+}
+
+impl DriftReport {
+    /// Check if nothing relevant changed
+    pub fn is_empty(&self) -> bool {
+        self.drifts.is_empty()
+    }
+
+    /// Drifts of one kind of change, ignoring the kind's fields
+    pub fn of_kind(&self, kind: &DriftKind) -> impl Iterator<Item = &Drift> + '_ {
+        let discriminant = std::mem::discriminant(kind);
+        self.drifts
+            .iter()
+            .filter(move |drift| std::mem::discriminant(&drift.kind) == discriminant)
+    }
+}
+
+/// Report what changes for `bytecode` between the fork it was compiled for and the one it
+/// is deployed at (in either direction)
+pub fn check_fork_drift(bytecode: &[u8], compiled_for: Fork, deployed_at: Fork) -> DriftReport {
+    let instructions = disassemble(bytecode);
+    let mut first_pc: BTreeMap<u8, usize> = BTreeMap::new();
+    for instruction in &instructions {
+        first_pc.entry(instruction.opcode).or_insert(instruction.pc);
+    }
+    let (low, high) = if compiled_for <= deployed_at {
+        (compiled_for, deployed_at)
+    } else {
+        (deployed_at, compiled_for)
+    };
+    let crossed = |fork: Fork| low < fork && fork <= high;
+
+    let registry = OpcodeRegistry::new();
+    let compiled_opcodes = registry.get_opcodes(compiled_for);
+    let deployed_opcodes = registry.get_opcodes(deployed_at);
+    let name = |opcode: u8| {
+        compiled_opcodes
+            .get(&opcode)
+            .or_else(|| deployed_opcodes.get(&opcode))
+            .map_or_else(|| format!("0x{opcode:02x}"), |m| m.name.to_string())
+    };
+    let mut drifts = Vec::new();
+
+    for (&opcode, &pc) in &first_pc {
+        if compiled_opcodes.contains_key(&opcode) && !deployed_opcodes.contains_key(&opcode) {
+            drifts.push(Drift {
+                opcode,
+                pc,
+                fork: None,
+                kind: DriftKind::Unavailable,
+                message: format!(
+                    "{} at pc {pc} is not available in {deployed_at:?}",
+                    name(opcode)
+                ),
+            });
+        }
+    }
+
+    for change in GasComparator::get_changes_between_forks(compiled_for, deployed_at) {
+        let (Some(&pc), ChangeType::GasCostChanged, Some(old), Some(new)) = (
+            first_pc.get(&change.opcode),
+            &change.change_type,
+            change.old_value,
+            change.new_value,
+        ) else {
+            continue;
+        };
+        drifts.push(Drift {
+            opcode: change.opcode,
+            pc,
+            fork: None,
+            kind: DriftKind::GasCost { old, new },
+            message: format!(
+                "{} costs {new} gas in {deployed_at:?} instead of {old} in {compiled_for:?}",
+                name(change.opcode)
+            ),
+        });
+    }
+
+    for change in SEMANTIC_CHANGES {
+        let (opcode, fork, eip) = (change.opcode, change.fork, change.eip);
+        let Some(&pc) = first_pc.get(&opcode).filter(|_| crossed(fork)) else {
+            continue;
+        };
+        drifts.push(Drift {
+            opcode,
+            pc,
+            fork: Some(fork),
+            kind: DriftKind::Semantics { eip },
+            message: format!(
+                "{} at pc {pc}: since {fork:?} (EIP-{eip}), {}",
+                name(opcode),
+                change.note
+            ),
+        });
+    }
+
+    for (fork, eip) in STIPEND_BREAKING_FORKS
+        .into_iter()
+        .filter(|(f, _)| crossed(*f))
+    {
+        for pc in stipend_calls(&instructions, compiled_for) {
+            drifts.push(Drift {
+                opcode: 0xf1,
+                pc,
+                fork: Some(fork),
+                kind: DriftKind::Stipend { eip },
+                message: format!(
+                    "CALL at pc {pc} only gives the callee the 2300 gas stipend; since {fork:?} (EIP-{eip}) storage and account access cost more, so callees that read state may run out of gas"
+                ),
+            });
+        }
+    }
+
+    DriftReport {
+        compiled_for,
+        deployed_at,
+        drifts,
+    }
+}
+
+/// CALLs that forward a constant gas amount no larger than the stipend, or follow the
+/// `PUSH2 0x08fc` pattern Solidity emits for `transfer` and `send`
+fn stipend_calls(instructions: &[Instruction], fork: Fork) -> Vec<usize> {
+    let stipend_pushes: Vec<usize> = instructions
+        .iter()
+        .filter(|i| i.opcode == 0x61 && i.push_value_u64() == Some(2300))
+        .map(|i| i.pc)
+        .collect();
+    trace_operands(instructions, fork, |i| i.opcode == 0xf1)
+        .into_iter()
+        .filter(|call| {
+            let constant = call
+                .inputs
+                .first()
+                .and_then(StackValue::as_u64)
+                .is_some_and(|gas| gas <= 2300);
+            let pattern = stipend_pushes
+                .iter()
+                .any(|&push| push < call.pc && call.pc - push <= 32);
+            constant || pattern
+        })
+        .map(|call| call.pc)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selfdestruct_drift() {
+        // PUSH1 0, SELFDESTRUCT
+        let report = check_fork_drift(&[0x60, 0x00, 0xff], Fork::London, Fork::Cancun);
+        let semantics: Vec<&Drift> = report.of_kind(&DriftKind::Semantics { eip: 0 }).collect();
+        assert_eq!(semantics.len(), 1);
+        assert_eq!(semantics[0].kind, DriftKind::Semantics { eip: 6780 });
+        assert!(check_fork_drift(&[0x60, 0x00, 0xff], Fork::Cancun, Fork::Prague).is_empty());
+    }
+
+    #[test]
+    fn test_stipend_and_unavailable_drift() {
+        // transfer(): PUSH2 0x08fc, CALLVALUE, ISZERO, MUL, then CALL arguments
+        let mut code = vec![0x61, 0x08, 0xfc, 0x34, 0x15, 0x02];
+        code.extend([0x60, 0x00].repeat(6));
+        code.extend([0x86, 0xf1, 0x5f]); // DUP7, CALL, PUSH0
+        let report = check_fork_drift(&code, Fork::Petersburg, Fork::Berlin);
+        let stipend: Vec<u32> = report
+            .drifts
+            .iter()
+            .filter_map(|drift| match drift.kind {
+                DriftKind::Stipend { eip } => Some(eip),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(stipend, vec![1884, 2929]);
+        assert!(report
+            .drifts
+            .iter()
+            .any(|drift| drift.opcode == 0xf1 && matches!(drift.kind, DriftKind::GasCost { .. })));
+
+        let downgrade = check_fork_drift(&code, Fork::Shanghai, Fork::London);
+        assert_eq!(downgrade.drifts[0].kind, DriftKind::Unavailable);
+        assert_eq!(downgrade.drifts[0].opcode, 0x5f);
+    }
+}
@@ -0,0 +1,285 @@
+//! Predicted state-access footprints, for conflict detection between bundled transactions
+//!
+//! [`state_footprint`] follows the call path selected by the call data (as in
+//! [`presign_check`](crate::presign_check)) and lists every storage slot and account field
+//! the code may read or write. Keys that depend on runtime values are kept with an unknown
+//! slot or address, which [`StateKey::may_alias`] treats as matching any key of the same kind.
+//! Calls into other contracts are listed separately: what the callee touches is not part of
+//! the footprint.
+
+use crate::disasm::disassemble;
+use crate::functions::{detect_dispatcher, reachable_from};
+use crate::stack::{trace_operands, StackValue, Word};
+use crate::Fork;
+use std::collections::BTreeSet;
+
+/// Opcodes that read or write state outside memory and the stack
+const STATE_OPCODES: [u8; 14] = [
+    0x31, 0x3b, 0x3c, 0x3f, 0x47, 0x54, 0x55, 0xf0, 0xf1, 0xf2, 0xf4, 0xf5, 0xfa, 0xff,
+];
+
+/// A piece of state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StateKey {
+    /// A storage slot of an account; `None` if the slot is computed at runtime
+    Storage {
+        /// The account owning the storage
+        address: [u8; 20],
+        /// The slot
+        slot: Option<Word>,
+    },
+    /// The balance of an account; `None` if the address is computed at runtime
+    Balance(Option<[u8; 20]>),
+    /// The code of an account (its size and hash included); `None` if the address is computed
+    /// at runtime
+    Code(Option<[u8; 20]>),
+}
+
+impl StateKey {
+    /// Check if two keys may refer to the same piece of state
+    pub fn may_alias(&self, other: &StateKey) -> bool {
+        match (self, other) {
+            (
+                Self::Storage { address, slot },
+                Self::Storage {
+                    address: other_address,
+                    slot: other_slot,
+                },
+            ) => address == other_address && unknown_or_equal(slot, other_slot),
+            (Self::Balance(a), Self::Balance(b)) | (Self::Code(a), Self::Code(b)) => {
+                unknown_or_equal(a, b)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Unknown values may be equal to anything
+fn unknown_or_equal<T: PartialEq>(a: &Option<T>, b: &Option<T>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a == b,
+        _ => true,
+    }
+}
+
+/// Whether an access reads or writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AccessMode {
+    /// The state is read
+    Read,
+    /// The state may be modified
+    Write,
+}
+
+/// One predicted state access
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StateAccess {
+    /// Program counter of the instruction
+    pub pc: usize,
+    /// The state accessed
+    pub key: StateKey,
+    /// Read or write
+    pub mode: AccessMode,
+}
+
+/// Every state access of a call path
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StateFootprint {
+    /// Accesses in code order
+    pub accesses: Vec<StateAccess>,
+    /// Program counters of CALLs and STATICCALLs into other code, whose accesses are not
+    /// included
+    pub external_calls: Vec<usize>,
+}
+
+impl StateFootprint {
+    /// Distinct keys read
+    pub fn reads(&self) -> BTreeSet<StateKey> {
+        self.keys(AccessMode::Read)
+    }
+
+    /// Distinct keys written
+    pub fn writes(&self) -> BTreeSet<StateKey> {
+        self.keys(AccessMode::Write)
+    }
+
+    fn keys(&self, mode: AccessMode) -> BTreeSet<StateKey> {
+        self.accesses
+            .iter()
+            .filter(|access| access.mode == mode)
+            .map(|access| access.key)
+            .collect()
+    }
+
+    /// Check if the footprint covers everything the call touches (no calls into other code)
+    pub fn is_complete(&self) -> bool {
+        self.external_calls.is_empty()
+    }
+
+    /// Keys of `self` that conflict with `other`: written by one and read or written by the
+    /// other, so the order of the two transactions matters
+    pub fn conflicts_with(&self, other: &StateFootprint) -> BTreeSet<StateKey> {
+        let (writes, other_writes) = (self.writes(), other.writes());
+        let touched = |keys: &BTreeSet<StateKey>, key: &StateKey| {
+            keys.iter().any(|other| key.may_alias(other))
+        };
+        let other_all: BTreeSet<StateKey> = other_writes.union(&other.reads()).copied().collect();
+        writes
+            .iter()
+            .filter(|key| touched(&other_all, key))
+            .chain(
+                self.reads()
+                    .iter()
+                    .filter(|key| touched(&other_writes, key)),
+            )
+            .copied()
+            .collect()
+    }
+}
+
+/// Predict the state accessed by calling `bytecode`, deployed at `address`, with `calldata`
+pub fn state_footprint(
+    bytecode: &[u8],
+    address: [u8; 20],
+    calldata: &[u8],
+    fork: Fork,
+) -> StateFootprint {
+    let instructions = disassemble(bytecode);
+    let entry = calldata
+        .get(..4)
+        .and_then(|selector| {
+            detect_dispatcher(bytecode)
+                .into_iter()
+                .find(|function| function.selector == selector)
+        })
+        .map_or(0, |function| function.entry);
+    let reachable: BTreeSet<usize> = reachable_from(bytecode, fork, &instructions, entry)
+        .into_iter()
+        .map(|index| instructions[index].pc)
+        .collect();
+
+    let mut accesses = Vec::new();
+    let mut external_calls = Vec::new();
+    let snapshots = trace_operands(&instructions, fork, |i| {
+        reachable.contains(&i.pc) && STATE_OPCODES.contains(&i.opcode)
+    });
+    for snapshot in snapshots {
+        let pc = snapshot.pc;
+        let operand = |index: usize| snapshot.inputs.get(index).copied();
+        let target = operand(0).and_then(|value| value.as_address());
+        let mut access = |key, mode| accesses.push(StateAccess { pc, key, mode });
+        let transfers_value = |value: Option<StackValue>| value.and_then(|v| v.as_u64()) != Some(0);
+        let slot = operand(0).and_then(|value| value.word());
+        match snapshot.opcode {
+            0x54 => access(StateKey::Storage { address, slot }, AccessMode::Read),
+            0x55 => access(StateKey::Storage { address, slot }, AccessMode::Write),
+            0x31 => access(StateKey::Balance(target), AccessMode::Read),
+            0x47 => access(StateKey::Balance(Some(address)), AccessMode::Read),
+            0x3b | 0x3c | 0x3f => access(StateKey::Code(target), AccessMode::Read),
+            0xf0 | 0xf5 => {
+                access(StateKey::Code(None), AccessMode::Write);
+                if transfers_value(operand(0)) {
+                    access(StateKey::Balance(Some(address)), AccessMode::Write);
+                    access(StateKey::Balance(None), AccessMode::Write);
+                }
+            }
+            0xf1 | 0xf2 | 0xf4 | 0xfa => {
+                let callee = operand(1).and_then(|value| value.as_address());
+                access(StateKey::Code(callee), AccessMode::Read);
+                if matches!(snapshot.opcode, 0xf1 | 0xf2) && transfers_value(operand(2)) {
+                    access(StateKey::Balance(Some(address)), AccessMode::Write);
+                    if snapshot.opcode == 0xf1 {
+                        access(StateKey::Balance(callee), AccessMode::Write);
+                    }
+                }
+                if matches!(snapshot.opcode, 0xf2 | 0xf4) {
+                    // The callee's code runs on this account's storage
+                    let key = StateKey::Storage {
+                        address,
+                        slot: None,
+                    };
+                    access(key, AccessMode::Read);
+                    access(key, AccessMode::Write);
+                } else {
+                    external_calls.push(pc);
+                }
+            }
+            0xff => {
+                access(StateKey::Balance(Some(address)), AccessMode::Write);
+                access(StateKey::Balance(target), AccessMode::Write);
+            }
+            _ => {}
+        }
+    }
+    StateFootprint {
+        accesses,
+        external_calls,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTRACT: [u8; 20] = [0xcc; 20];
+
+    #[test]
+    fn test_storage_footprint_and_conflicts() {
+        // SSTORE(0, SLOAD(1) + 1)
+        let writer = [0x60, 0x01, 0x54, 0x60, 0x01, 0x01, 0x60, 0x00, 0x55, 0x00];
+        // SLOAD(0)
+        let reader = [0x60, 0x00, 0x54, 0x00];
+        // SLOAD(2)
+        let other = [0x60, 0x02, 0x54, 0x00];
+        let slot = |n: u64| StackValue::from_u64(n).word();
+
+        let writes = state_footprint(&writer, CONTRACT, &[], Fork::Cancun);
+        assert!(writes.is_complete());
+        assert_eq!(
+            writes.writes(),
+            BTreeSet::from([StateKey::Storage {
+                address: CONTRACT,
+                slot: slot(0)
+            }])
+        );
+        let reads = state_footprint(&reader, CONTRACT, &[], Fork::Cancun);
+        assert_eq!(writes.conflicts_with(&reads).len(), 1);
+        assert_eq!(reads.conflicts_with(&writes).len(), 1);
+        let unrelated = state_footprint(&other, CONTRACT, &[], Fork::Cancun);
+        assert!(writes.conflicts_with(&unrelated).is_empty());
+        assert!(reads.conflicts_with(&unrelated).is_empty());
+    }
+
+    #[test]
+    fn test_unknown_slot_and_external_call() {
+        // SSTORE(CALLDATALOAD(0), 1)
+        let code = [0x60, 0x01, 0x60, 0x00, 0x35, 0x55, 0x00];
+        let footprint = state_footprint(&code, CONTRACT, &[], Fork::Cancun);
+        let any = StateKey::Storage {
+            address: CONTRACT,
+            slot: None,
+        };
+        assert_eq!(footprint.writes(), BTreeSet::from([any]));
+        assert!(any.may_alias(&StateKey::Storage {
+            address: CONTRACT,
+            slot: Some([7; 32]),
+        }));
+        assert!(!any.may_alias(&StateKey::Storage {
+            address: [0; 20],
+            slot: None,
+        }));
+
+        // CALL(GAS, 0xaa..aa, 0, 0, 0, 0, 0)
+        let mut call = [0x60, 0x00].repeat(5);
+        call.push(0x73);
+        call.extend([0xaa; 20]);
+        call.extend([0x5a, 0xf1, 0x00]);
+        let footprint = state_footprint(&call, CONTRACT, &[], Fork::Cancun);
+        assert_eq!(footprint.external_calls, vec![32]);
+        assert_eq!(
+            footprint.reads(),
+            BTreeSet::from([StateKey::Code(Some([0xaa; 20]))])
+        );
+        assert!(footprint.writes().is_empty());
+    }
+}
@@ -263,7 +263,7 @@ opcodes! {
             introduced_in: Frontier,
             group: EnvironmentalInformation,
             eip: None,
-            gas_history: [Istanbul => 400, Berlin => 2600],
+            gas_history: [TangerineWhistle => 400, Istanbul => 700, Berlin => 2600],
         },
         0x32 => ORIGIN {
             gas: 2,
@@ -363,7 +363,7 @@ opcodes! {
             introduced_in: Frontier,
             group: EnvironmentalInformation,
             eip: None,
-            gas_history: [Istanbul => 700, Berlin => 2600],
+            gas_history: [TangerineWhistle => 700, Berlin => 2600],
         },
         0x3c => EXTCODECOPY {
             gas: 2600,
@@ -373,7 +373,7 @@ opcodes! {
             introduced_in: Frontier,
             group: EnvironmentalInformation,
             eip: None,
-            gas_history: [Istanbul => 700, Berlin => 2600],
+            gas_history: [TangerineWhistle => 700, Berlin => 2600],
         },
         0x40 => BLOCKHASH {
             gas: 20,
@@ -483,7 +483,7 @@ opcodes! {
             introduced_in: Frontier,
             group: StackMemoryStorageFlow,
             eip: None,
-            gas_history: [Istanbul => 800, Berlin => 2100],
+            gas_history: [TangerineWhistle => 200, Istanbul => 800, Berlin => 2100],
         },
         0x55 => SSTORE {
             gas: 0,
@@ -1263,7 +1263,7 @@ opcodes! {
             introduced_in: Frontier,
             group: System,
             eip: None,
-            gas_history: [Istanbul => 700, Berlin => 2600],
+            gas_history: [TangerineWhistle => 700, Berlin => 2600],
         },
         0xf2 => CALLCODE {
             gas: 2600,
@@ -1273,7 +1273,7 @@ opcodes! {
             introduced_in: Frontier,
             group: System,
             eip: None,
-            gas_history: [Istanbul => 700, Berlin => 2600],
+            gas_history: [TangerineWhistle => 700, Berlin => 2600],
         },
         0xf3 => RETURN {
             gas: 0,
@@ -1303,7 +1303,7 @@ opcodes! {
             introduced_in: Frontier,
             group: System,
             eip: None,
-            gas_history: [],
+            gas_history: [TangerineWhistle => 5000],
         },
         0xf4 => DELEGATECALL {
             gas: 2600,
@@ -1313,7 +1313,7 @@ opcodes! {
             introduced_in: Homestead,
             group: System,
             eip: None,
-            gas_history: [Istanbul => 700, Berlin => 2600],
+            gas_history: [TangerineWhistle => 700, Berlin => 2600],
         },
         0x3d => RETURNDATASIZE {
             gas: 2,
@@ -1343,7 +1343,7 @@ opcodes! {
             introduced_in: Byzantium,
             group: System,
             eip: Some(214),
-            gas_history: [Istanbul => 700, Berlin => 2600],
+            gas_history: [Byzantium => 700, Berlin => 2600],
         },
         0xfd => REVERT {
             gas: 0,
@@ -1393,7 +1393,7 @@ opcodes! {
             introduced_in: Constantinople,
             group: EnvironmentalInformation,
             eip: Some(1052),
-            gas_history: [Istanbul => 400, Berlin => 2600],
+            gas_history: [Constantinople => 400, Istanbul => 700, Berlin => 2600],
         },
         0xf5 => CREATE2 {
             gas: 32000,
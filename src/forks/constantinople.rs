@@ -256,14 +256,14 @@ opcodes! {
             gas_history: [],
         },
         0x31 => BALANCE {
-            gas: 20,
+            gas: 400,
             inputs: 1,
             outputs: 1,
             description: "Get balance of the given account",
             introduced_in: Frontier,
             group: EnvironmentalInformation,
             eip: None,
-            gas_history: [],
+            gas_history: [TangerineWhistle => 400],
         },
         0x32 => ORIGIN {
             gas: 2,
@@ -356,24 +356,24 @@ opcodes! {
             gas_history: [],
         },
         0x3b => EXTCODESIZE {
-            gas: 20,
+            gas: 700,
             inputs: 1,
             outputs: 1,
             description: "Get size of an account's code",
             introduced_in: Frontier,
             group: EnvironmentalInformation,
             eip: None,
-            gas_history: [],
+            gas_history: [TangerineWhistle => 700],
         },
         0x3c => EXTCODECOPY {
-            gas: 20,
+            gas: 700,
             inputs: 4,
             outputs: 0,
             description: "Copy an account's code to memory",
             introduced_in: Frontier,
             group: EnvironmentalInformation,
             eip: None,
-            gas_history: [],
+            gas_history: [TangerineWhistle => 700],
         },
         0x40 => BLOCKHASH {
             gas: 20,
@@ -476,14 +476,14 @@ opcodes! {
             gas_history: [],
         },
         0x54 => SLOAD {
-            gas: 50,
+            gas: 200,
             inputs: 1,
             outputs: 1,
             description: "Load word from storage",
             introduced_in: Frontier,
             group: StackMemoryStorageFlow,
             eip: None,
-            gas_history: [],
+            gas_history: [TangerineWhistle => 200],
         },
         0x55 => SSTORE {
             gas: 0,
@@ -1256,24 +1256,24 @@ opcodes! {
             gas_history: [],
         },
         0xf1 => CALL {
-            gas: 100,
+            gas: 700,
             inputs: 7,
             outputs: 1,
             description: "Message-call into an account",
             introduced_in: Frontier,
             group: System,
             eip: None,
-            gas_history: [],
+            gas_history: [TangerineWhistle => 700],
         },
         0xf2 => CALLCODE {
-            gas: 100,
+            gas: 700,
             inputs: 7,
             outputs: 1,
             description: "Message-call with alternative account's code",
             introduced_in: Frontier,
             group: System,
             eip: None,
-            gas_history: [],
+            gas_history: [TangerineWhistle => 700],
         },
         0xf3 => RETURN {
             gas: 0,
@@ -1303,17 +1303,17 @@ opcodes! {
             introduced_in: Frontier,
             group: System,
             eip: None,
-            gas_history: [],
+            gas_history: [TangerineWhistle => 5000],
         },
         0xf4 => DELEGATECALL {
-            gas: 40,
+            gas: 700,
             inputs: 6,
             outputs: 1,
             description: "Message-call with alternative account's code persisting current context",
             introduced_in: Homestead,
             group: System,
             eip: None,
-            gas_history: [],
+            gas_history: [TangerineWhistle => 700],
         },
         0x3d => RETURNDATASIZE {
             gas: 2,
@@ -1336,14 +1336,14 @@ opcodes! {
             gas_history: [],
         },
         0xfa => STATICCALL {
-            gas: 40,
+            gas: 700,
             inputs: 6,
             outputs: 1,
             description: "Static message-call into an account",
             introduced_in: Byzantium,
             group: System,
             eip: Some(214),
-            gas_history: [],
+            gas_history: [Byzantium => 700],
         },
         0xfd => REVERT {
             gas: 0,
@@ -1386,14 +1386,14 @@ opcodes! {
             gas_history: [],
         },
         0x3f => EXTCODEHASH {
-            gas: 100,
+            gas: 400,
             inputs: 1,
             outputs: 1,
             description: "Get hash of an account's code",
             introduced_in: Constantinople,
             group: EnvironmentalInformation,
             eip: Some(1052),
-            gas_history: [],
+            gas_history: [Constantinople => 400],
         },
         0xf5 => CREATE2 {
             gas: 32000,
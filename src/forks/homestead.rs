@@ -1296,7 +1296,7 @@ opcodes! {
             gas_history: [],
         },
         0xff => SELFDESTRUCT {
-            gas: 5000,
+            gas: 0,
             inputs: 1,
             outputs: 0,
             description: "Halt execution and register account for deletion",
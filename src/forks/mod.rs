@@ -1,4 +1,15 @@
 //! Fork-specific opcode implementations
+//!
+//! # Fork pipeline
+//!
+//! A fork enters the crate as soon as its opcode changes are accepted for a devnet: its
+//! [`Fork`](crate::Fork) variant, [`ExecutionFork`](crate::ExecutionFork) variant, table module
+//! and registry entry are gated behind the `unstable-forks` feature and the fork is listed in
+//! [`Fork::SCHEDULED`](crate::Fork::SCHEDULED), so
+//! [`OpcodeMetadata::is_scheduled`](crate::OpcodeMetadata::is_scheduled) flags its new
+//! opcodes. Its table may change in any release while it is scheduled. Once the mainnet
+//! activation is final, the gates are removed, the fork leaves `SCHEDULED` and the dataset
+//! [`CHANGELOG`](crate::version::CHANGELOG) records the addition.
 
 pub mod berlin;
 pub mod byzantium;
@@ -8,7 +19,13 @@ pub mod frontier;
 pub mod homestead;
 pub mod istanbul;
 pub mod london;
+#[cfg(feature = "unstable-forks")]
+pub mod osaka;
+pub mod petersburg;
+pub mod prague;
 pub mod shanghai;
+pub mod spurious_dragon;
+pub mod tangerine_whistle;
 
 pub use berlin::Berlin;
 pub use byzantium::Byzantium;
@@ -18,4 +35,10 @@ pub use frontier::Frontier;
 pub use homestead::Homestead;
 pub use istanbul::Istanbul;
 pub use london::London;
+#[cfg(feature = "unstable-forks")]
+pub use osaka::Osaka;
+pub use petersburg::Petersburg;
+pub use prague::Prague;
 pub use shanghai::Shanghai;
+pub use spurious_dragon::SpuriousDragon;
+pub use tangerine_whistle::TangerineWhistle;
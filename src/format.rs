@@ -0,0 +1,162 @@
+//! Number formatting for rendered reports
+//!
+//! A [`NumberFormat`] controls how the Markdown and JSON renderers print numbers: digit
+//! grouping, the decimal separator and, given a gas price, what an amount of gas costs in
+//! gwei and ETH. The default prints raw integers, as the renderers always did. JSON output
+//! keeps its numbers raw and only gains cost fields, so it stays machine-readable.
+
+const WEI_PER_GWEI: u128 = 1_000_000_000;
+const WEI_PER_ETH: u128 = 1_000_000_000_000_000_000;
+
+/// How numbers are written in rendered reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NumberFormat {
+    /// Separator between groups of three digits, none for raw integers
+    pub thousands_separator: Option<char>,
+    /// Separator before the fractional part of gwei and ETH amounts
+    pub decimal_separator: char,
+    /// Gas price in wei, to convert gas into gwei and ETH
+    pub gas_price: Option<u128>,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            thousands_separator: None,
+            decimal_separator: '.',
+            gas_price: None,
+        }
+    }
+}
+
+impl NumberFormat {
+    /// Raw integers, no cost conversion
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Comma-grouped digits, e.g. `1,234,567`
+    pub fn readable() -> Self {
+        Self::new().with_thousands_separator(',')
+    }
+
+    /// Group digits with `separator`
+    pub fn with_thousands_separator(mut self, separator: char) -> Self {
+        self.thousands_separator = Some(separator);
+        self
+    }
+
+    /// Use `separator` before fractional digits
+    pub fn with_decimal_separator(mut self, separator: char) -> Self {
+        self.decimal_separator = separator;
+        self
+    }
+
+    /// Convert gas into costs at a gas price in wei
+    pub fn with_gas_price(mut self, wei: u128) -> Self {
+        self.gas_price = Some(wei);
+        self
+    }
+
+    /// Convert gas into costs at a gas price in gwei
+    pub fn with_gas_price_gwei(self, gwei: u64) -> Self {
+        self.with_gas_price(gwei as u128 * WEI_PER_GWEI)
+    }
+
+    /// An integer with digit grouping
+    pub fn integer(&self, value: u128) -> String {
+        let digits = value.to_string();
+        let Some(separator) = self.thousands_separator else {
+            return digits;
+        };
+        let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+        for (index, digit) in digits.chars().enumerate() {
+            if index > 0 && (digits.len() - index).is_multiple_of(3) {
+                grouped.push(separator);
+            }
+            grouped.push(digit);
+        }
+        grouped
+    }
+
+    /// A signed integer with digit grouping and an explicit `+` for positive values
+    pub fn signed(&self, value: i128) -> String {
+        let sign = match value {
+            0 => "",
+            value if value < 0 => "-",
+            _ => "+",
+        };
+        format!("{sign}{}", self.integer(value.unsigned_abs()))
+    }
+
+    /// Cost of `gas` in wei, if a gas price is set
+    pub fn cost_wei(&self, gas: u64) -> Option<u128> {
+        self.gas_price
+            .map(|price| price.saturating_mul(gas as u128))
+    }
+
+    /// Cost of `gas` in gwei with two decimals, if a gas price is set
+    pub fn gwei(&self, gas: u64) -> Option<String> {
+        let wei = self.cost_wei(gas)?;
+        Some(format!("{} gwei", self.decimal(wei, WEI_PER_GWEI, 2)))
+    }
+
+    /// Cost of `gas` in ETH with six decimals, if a gas price is set
+    pub fn eth(&self, gas: u64) -> Option<String> {
+        let wei = self.cost_wei(gas)?;
+        Some(format!("{} ETH", self.decimal(wei, WEI_PER_ETH, 6)))
+    }
+
+    /// An amount of gas, followed by its cost in ETH if a gas price is set
+    pub fn gas(&self, gas: u64) -> String {
+        let amount = format!("{} gas", self.integer(gas as u128));
+        match self.eth(gas) {
+            Some(eth) => format!("{amount} ({eth})"),
+            None => amount,
+        }
+    }
+
+    /// `value / unit` rounded to `decimals` fractional digits
+    fn decimal(&self, value: u128, unit: u128, decimals: u32) -> String {
+        let scale = 10u128.pow(decimals);
+        let scaled = value.saturating_mul(scale).saturating_add(unit / 2) / unit;
+        format!(
+            "{}{}{:0width$}",
+            self.integer(scaled / scale),
+            self.decimal_separator,
+            scaled % scale,
+            width = decimals as usize
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grouping_and_signs() {
+        let raw = NumberFormat::new();
+        assert_eq!(raw.integer(1_234_567), "1234567");
+        let readable = NumberFormat::readable();
+        assert_eq!(readable.integer(1_234_567), "1,234,567");
+        assert_eq!(readable.integer(999), "999");
+        assert_eq!(readable.signed(-2_500), "-2,500");
+        assert_eq!(readable.signed(100_000), "+100,000");
+        let european = NumberFormat::new()
+            .with_thousands_separator('.')
+            .with_decimal_separator(',');
+        assert_eq!(european.integer(30_000_000), "30.000.000");
+    }
+
+    #[test]
+    fn test_gas_costs() {
+        let format = NumberFormat::readable().with_gas_price_gwei(20);
+        assert_eq!(format.cost_wei(21_000), Some(420_000_000_000_000));
+        assert_eq!(format.gwei(21_000).unwrap(), "420,000.00 gwei");
+        assert_eq!(format.eth(21_000).unwrap(), "0.000420 ETH");
+        assert_eq!(format.gas(21_000), "21,000 gas (0.000420 ETH)");
+        assert_eq!(NumberFormat::readable().gas(21_000), "21,000 gas");
+        assert_eq!(NumberFormat::new().eth(21_000), None);
+    }
+}
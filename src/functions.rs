@@ -0,0 +1,349 @@
+//! Per-function analysis seeded by dispatcher detection or external tools
+//!
+//! Solidity and Vyper dispatchers compare the call data selector with a `PUSH4 selector`
+//! constant before a JUMPI, which [`detect_dispatcher`] recognizes. Jump-table dispatchers
+//! (as in recent Vyper releases) and hand-written ones are missed; function boundaries found
+//! by tools such as evmole or heimdall can then be passed in as [`FunctionEntry`] values
+//! (evmole's `selector` and `bytecodeOffset` fields map directly onto them).
+
+use crate::disasm::{disassemble, static_jump_target, valid_jump_destinations, Instruction};
+use crate::jumptable::jump_table_targets;
+use crate::resolver::{selector_label, SelectorResolver};
+use crate::{Fork, OpcodeRegistry};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// A public function: its selector and the program counter where its code starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FunctionEntry {
+    /// Four-byte function selector
+    pub selector: [u8; 4],
+    /// Program counter of the function's entry JUMPDEST
+    pub entry: usize,
+}
+
+/// Where a function entry came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntrySource {
+    /// Found by [`detect_dispatcher`]
+    Dispatcher,
+    /// Supplied by the caller
+    External,
+}
+
+/// Static properties of the code reachable from a function entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionSummary {
+    /// The function
+    pub function: FunctionEntry,
+    /// Where the entry came from
+    pub source: EntrySource,
+    /// Number of instructions reachable through static jumps and fallthrough
+    pub instructions: usize,
+    /// Sum of the base gas costs of the reachable instructions
+    pub static_gas: u64,
+    /// Reachable SLOADs
+    pub storage_reads: usize,
+    /// Reachable SSTOREs
+    pub storage_writes: usize,
+    /// Reachable CALL, CALLCODE, DELEGATECALL and STATICCALL instructions
+    pub external_calls: usize,
+    /// Exploration stopped at jumps whose targets could not be enumerated (e.g. internal
+    /// returns)
+    pub has_dynamic_jumps: bool,
+}
+
+impl FunctionSummary {
+    /// The function's signature if the resolver knows it, otherwise its selector in hex
+    pub fn label(&self, resolver: &dyn SelectorResolver) -> String {
+        selector_label(resolver, self.function.selector)
+    }
+}
+
+/// Find the selector comparisons of a Solidity or Vyper dispatcher
+///
+/// Solidity jumps to the function when the selector matches: `PUSH4 selector [DUP2] EQ
+/// PUSH dest JUMPI`. Vyper jumps past the function when it does not, so the function starts
+/// right after the JUMPI: `PUSH4 selector [DUPn] XOR PUSH next JUMPI` in current releases and
+/// `PUSH4 selector PUSH1 0 MLOAD EQ ISZERO PUSH next JUMPI` in older ones.
+pub fn detect_dispatcher(bytecode: &[u8]) -> Vec<FunctionEntry> {
+    let instructions = disassemble(bytecode);
+    let destinations = valid_jump_destinations(&instructions);
+    let opcode_at = |index: usize| instructions.get(index).map(|i| i.opcode);
+    let mut entries = Vec::new();
+
+    for (index, push) in instructions.iter().enumerate() {
+        if push.opcode != 0x63 {
+            continue;
+        }
+        let Ok(selector) = <[u8; 4]>::try_from(push.immediate.as_slice()) else {
+            continue;
+        };
+        // Skip what loads the call data selector next to the constant
+        let compare = match (opcode_at(index + 1), opcode_at(index + 2)) {
+            (Some(0x80..=0x8f), _) => index + 2,
+            (Some(0x60), Some(0x51)) => index + 3,
+            _ => index + 1,
+        };
+        let (skips_function, target) = match (opcode_at(compare), opcode_at(compare + 1)) {
+            (Some(0x18), _) => (true, compare + 1),          // XOR
+            (Some(0x14), Some(0x15)) => (true, compare + 2), // EQ ISZERO
+            (Some(0x14), _) => (false, compare + 1),         // EQ
+            _ => continue,
+        };
+        let (Some(target), Some(jumpi)) = (instructions.get(target), instructions.get(target + 1))
+        else {
+            continue;
+        };
+        if jumpi.opcode != 0x57 {
+            continue;
+        }
+        let entry = if skips_function {
+            Some(jumpi.next_pc()).filter(|&pc| pc < bytecode.len())
+        } else {
+            static_jump_target(target, &destinations)
+        };
+        if let Some(entry) = entry {
+            entries.push(FunctionEntry { selector, entry });
+        }
+    }
+
+    entries
+}
+
+/// Parse function entries from lines of `selector offset`
+///
+/// Both values may be hex with a `0x` prefix; selectors are always read as hex and offsets
+/// as decimal without the prefix. Empty lines and lines starting with `#` are skipped.
+pub fn parse_function_entries(text: &str) -> Result<Vec<FunctionEntry>, String> {
+    let mut entries = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line
+            .split(|c: char| c.is_whitespace() || matches!(c, ',' | ':'))
+            .filter(|field| !field.is_empty());
+        let (Some(selector), Some(offset), None) = (fields.next(), fields.next(), fields.next())
+        else {
+            return Err(format!("Line {}: expected `selector offset`", index + 1));
+        };
+
+        let selector = selector.trim_start_matches("0x");
+        let selector = u32::from_str_radix(selector, 16)
+            .ok()
+            .filter(|_| selector.len() == 8)
+            .ok_or_else(|| format!("Line {}: invalid selector {selector}", index + 1))?;
+        let entry = match offset.strip_prefix("0x") {
+            Some(hex) => usize::from_str_radix(hex, 16),
+            None => offset.parse(),
+        }
+        .map_err(|_| format!("Line {}: invalid offset {offset}", index + 1))?;
+
+        entries.push(FunctionEntry {
+            selector: selector.to_be_bytes(),
+            entry,
+        });
+    }
+    Ok(entries)
+}
+
+/// Summarize every function found by the dispatcher detection or supplied in `external`
+///
+/// External entries take precedence over detected ones with the same selector. Summaries
+/// are sorted by selector.
+pub fn analyze_functions(
+    bytecode: &[u8],
+    fork: Fork,
+    external: &[FunctionEntry],
+) -> Vec<FunctionSummary> {
+    let mut functions: BTreeMap<[u8; 4], (FunctionEntry, EntrySource)> = BTreeMap::new();
+    for function in detect_dispatcher(bytecode) {
+        functions.insert(function.selector, (function, EntrySource::Dispatcher));
+    }
+    for function in external {
+        functions.insert(function.selector, (*function, EntrySource::External));
+    }
+
+    let instructions = disassemble(bytecode);
+    let destinations = valid_jump_destinations(&instructions);
+    let index_of: HashMap<usize, usize> = instructions
+        .iter()
+        .enumerate()
+        .map(|(index, i)| (i.pc, index))
+        .collect();
+    let opcodes = OpcodeRegistry::new().get_opcodes(fork);
+    let tables = jump_table_targets(bytecode, fork);
+
+    functions
+        .into_values()
+        .map(|(function, source)| {
+            let mut summary = FunctionSummary {
+                function,
+                source,
+                instructions: 0,
+                static_gas: 0,
+                storage_reads: 0,
+                storage_writes: 0,
+                external_calls: 0,
+                has_dynamic_jumps: false,
+            };
+            let reachable = reachable(
+                &instructions,
+                &index_of,
+                &destinations,
+                &tables,
+                function.entry,
+            );
+            for index in reachable {
+                let instruction = &instructions[index];
+                summary.instructions += 1;
+                summary.static_gas += opcodes
+                    .get(&instruction.opcode)
+                    .map_or(0, |m| m.gas_cost_in(fork) as u64);
+                match instruction.opcode {
+                    0x54 => summary.storage_reads += 1,
+                    0x55 => summary.storage_writes += 1,
+                    0xf1 | 0xf2 | 0xf4 | 0xfa => summary.external_calls += 1,
+                    0x56 | 0x57 => {
+                        let resolved = index.checked_sub(1).and_then(|prev| {
+                            static_jump_target(&instructions[prev], &destinations)
+                        });
+                        summary.has_dynamic_jumps |=
+                            resolved.is_none() && !tables.contains_key(&instruction.pc);
+                    }
+                    _ => {}
+                }
+            }
+            summary
+        })
+        .collect()
+}
+
+/// Indices into `instructions` (the disassembly of `bytecode`) of the instructions reachable
+/// from `entry`
+pub(crate) fn reachable_from(
+    bytecode: &[u8],
+    fork: Fork,
+    instructions: &[Instruction],
+    entry: usize,
+) -> BTreeSet<usize> {
+    let destinations = valid_jump_destinations(instructions);
+    let index_of: HashMap<usize, usize> = instructions
+        .iter()
+        .enumerate()
+        .map(|(index, i)| (i.pc, index))
+        .collect();
+    let tables = jump_table_targets(bytecode, fork);
+    reachable(instructions, &index_of, &destinations, &tables, entry)
+}
+
+/// Indices of the instructions reachable from `entry` through static jumps, recognized jump
+/// tables and fallthrough
+fn reachable(
+    instructions: &[Instruction],
+    index_of: &HashMap<usize, usize>,
+    destinations: &BTreeSet<usize>,
+    tables: &BTreeMap<usize, Vec<usize>>,
+    entry: usize,
+) -> BTreeSet<usize> {
+    let mut visited = BTreeSet::new();
+    let mut pending: Vec<usize> = index_of.get(&entry).copied().into_iter().collect();
+
+    while let Some(mut index) = pending.pop() {
+        while let Some(instruction) = instructions.get(index) {
+            if !visited.insert(index) {
+                break;
+            }
+            if matches!(instruction.opcode, 0x56 | 0x57) {
+                let target = index
+                    .checked_sub(1)
+                    .and_then(|prev| static_jump_target(&instructions[prev], destinations));
+                let targets = target
+                    .into_iter()
+                    .chain(tables.get(&instruction.pc).into_iter().flatten().copied());
+                pending.extend(targets.filter_map(|pc| index_of.get(&pc)));
+            }
+            if instruction.is_terminator() {
+                break;
+            }
+            index += 1;
+        }
+    }
+
+    visited
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Dispatcher for `transfer` at 0x11 (SSTORE, STOP) and a function at 0x18 that the
+    /// dispatcher does not reference (SLOAD, STOP)
+    fn contract() -> Vec<u8> {
+        let mut code = vec![
+            0x60, 0x00, 0x35, 0x60, 0xe0, 0x1c, // PUSH1 0, CALLDATALOAD, PUSH1 0xe0, SHR
+            0x80, 0x63, 0xa9, 0x05, 0x9c, 0xbb, 0x14, // DUP1, PUSH4 transfer, EQ
+            0x60, 0x11, 0x57, 0x00, // PUSH1 0x11, JUMPI, STOP
+        ];
+        code.extend_from_slice(&[0x5b, 0x60, 0x01, 0x60, 0x00, 0x55, 0x00]); // 0x11
+        code.extend_from_slice(&[0x5b, 0x60, 0x00, 0x54, 0x00]); // 0x18
+        code
+    }
+
+    #[test]
+    fn test_dispatcher_detection() {
+        let code = contract();
+        assert_eq!(
+            detect_dispatcher(&code),
+            vec![FunctionEntry {
+                selector: [0xa9, 0x05, 0x9c, 0xbb],
+                entry: 0x11
+            }]
+        );
+
+        let summaries = analyze_functions(&code, Fork::London, &[]);
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].source, EntrySource::Dispatcher);
+        assert_eq!(summaries[0].storage_writes, 1);
+        assert_eq!(summaries[0].instructions, 5);
+        let database = crate::SignatureDatabase::new()
+            .with_function([0xa9, 0x05, 0x9c, 0xbb], "transfer(address,uint256)");
+        assert_eq!(summaries[0].label(&database), "transfer(address,uint256)");
+    }
+
+    #[test]
+    fn test_vyper_dispatcher_detection() {
+        let code = [
+            0x60, 0x00, 0x35, 0x60, 0xe0, 0x1c, // PUSH1 0, CALLDATALOAD, PUSH1 0xe0, SHR
+            0x63, 0xa9, 0x05, 0x9c, 0xbb, 0x81, 0x18, // PUSH4 transfer, DUP2, XOR
+            0x60, 0x16, 0x57, // PUSH1 0x16, JUMPI to the next check
+            0x60, 0x01, 0x60, 0x00, 0x55, 0x00, // 0x10: function body
+            0x5b, 0x00, // 0x16: fallback
+        ];
+        assert_eq!(
+            detect_dispatcher(&code),
+            vec![FunctionEntry {
+                selector: [0xa9, 0x05, 0x9c, 0xbb],
+                entry: 0x10
+            }]
+        );
+        let summaries = analyze_functions(&code, Fork::London, &[]);
+        assert_eq!(summaries[0].storage_writes, 1);
+        assert_eq!(summaries[0].instructions, 4);
+    }
+
+    #[test]
+    fn test_external_entries_seed_missing_functions() {
+        let external = parse_function_entries("# evmole\n0x70a08231 0x18\n").unwrap();
+        let summaries = analyze_functions(&contract(), Fork::London, &external);
+        assert_eq!(summaries.len(), 2);
+        let balance = &summaries[0];
+        assert_eq!(balance.function.selector, [0x70, 0xa0, 0x82, 0x31]);
+        assert_eq!(balance.source, EntrySource::External);
+        assert_eq!(balance.storage_reads, 1);
+        assert_eq!(balance.static_gas, 1 + 3 + 2100);
+
+        assert!(parse_function_entries("0x1234 10").is_err());
+    }
+}
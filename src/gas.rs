@@ -7,15 +7,58 @@
 //! - Fork-specific gas cost evolution
 //! - Storage state changes (EIP-2200)
 
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 pub mod analysis;
+pub mod bundle;
 pub mod calculator;
+pub mod confidence;
 pub mod context;
+pub mod explain;
+pub mod external;
+pub mod formulas;
+pub mod l1fee;
+#[cfg(feature = "multidim-gas")]
+pub mod multidim;
+pub mod operands;
+pub mod presets;
+pub mod refunds;
+pub mod repricing;
+pub mod state;
+#[cfg(feature = "unstable-verkle")]
+pub mod verkle;
 
 pub use analysis::*;
+pub use bundle::*;
 pub use calculator::*;
+pub use confidence::*;
 pub use context::*;
+pub use explain::*;
+pub use external::*;
+pub use formulas::*;
+pub use l1fee::*;
+#[cfg(feature = "multidim-gas")]
+pub use multidim::*;
+pub use operands::*;
+pub use presets::*;
+pub use refunds::*;
+pub use repricing::*;
+pub use state::*;
+#[cfg(feature = "unstable-verkle")]
+pub use verkle::*;
+
+/// Calldata gas under the fork's pricing (EIP-2028 lowered non-zero bytes to 16 in Istanbul)
+pub fn calldata_gas(calldata: &[u8], fork: crate::Fork) -> u64 {
+    let non_zero_cost = if fork >= crate::Fork::Istanbul {
+        16
+    } else {
+        68
+    };
+    calldata
+        .iter()
+        .map(|byte| if *byte == 0 { 4 } else { non_zero_cost })
+        .sum()
+}
 
 /// Represents different types of gas costs
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -100,9 +143,21 @@ pub struct GasAnalysisResult {
     pub context: ExecutionContext,
     /// Detected optimization opportunities
     pub optimizations: Vec<String>,
+    /// Index of the first INVALID or unassigned byte, where execution halts consuming all gas
+    pub exceptional_halt: Option<usize>,
+    /// Analysis stopped early because the analysis budget ran out or was cancelled
+    pub budget_exhausted: bool,
 }
 
 impl GasAnalysisResult {
+    /// Check if the sequence reaches an exceptional halt, which consumes all remaining gas
+    ///
+    /// `total_gas` then only covers the instructions before the halt; the real cost is
+    /// the full gas limit of the call.
+    pub fn consumes_all_gas(&self) -> bool {
+        self.exceptional_halt.is_some()
+    }
+
     /// Get gas efficiency ratio compared to a baseline
     pub fn efficiency_ratio(&self, baseline_gas: u64) -> f64 {
         self.total_gas as f64 / baseline_gas as f64
@@ -116,7 +171,7 @@ impl GasAnalysisResult {
     /// Get the most expensive operations
     pub fn top_expensive_operations(&self, n: usize) -> Vec<(u8, u64)> {
         let mut sorted = self.breakdown.clone();
-        sorted.sort_by(|a, b| b.1.cmp(&a.1));
+        sorted.sort_by_key(|b| std::cmp::Reverse(b.1));
         sorted.into_iter().take(n).collect()
     }
 
@@ -165,7 +220,7 @@ impl GasAnalysisResult {
         }
 
         // Check for repeated expensive operations
-        let mut opcode_counts = HashMap::new();
+        let mut opcode_counts = BTreeMap::new();
         for (opcode, _) in &self.breakdown {
             *opcode_counts.entry(*opcode).or_insert(0) += 1;
         }
@@ -252,6 +307,8 @@ mod tests {
             warnings: vec![],
             context: ExecutionContext::default(),
             optimizations: vec![],
+            exceptional_halt: None,
+            budget_exhausted: false,
         };
 
         assert!(result.efficiency_score() >= 80); // Should be very efficient
@@ -270,6 +327,8 @@ mod tests {
             warnings: vec![],
             context: ExecutionContext::default(),
             optimizations: vec![],
+            exceptional_halt: None,
+            budget_exhausted: false,
         };
 
         let top_ops = result.top_expensive_operations(2);
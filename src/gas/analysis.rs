@@ -1,10 +1,14 @@
 //! Gas analysis utilities and enhanced analysis structures
 
-use super::{DynamicGasCalculator, ExecutionContext, GasCostCategory};
-use crate::{Fork, OpcodeRegistry};
+use super::{DynamicGasCalculator, GasCostCategory};
+use crate::disasm::{Disassembly, Instruction};
+use crate::stack::Word;
+use crate::{AnalysisConfig, Fork, OpcodeId, OpcodeRegistry};
+use std::collections::BTreeMap;
+use std::fmt;
 
 /// Enhanced gas analysis structure for compatibility with existing validation system
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GasAnalysis {
     /// Total base gas cost
     pub total_gas: u64,
@@ -14,6 +18,11 @@ pub struct GasAnalysis {
     pub optimizations: Vec<String>,
     /// Warnings about expensive operations
     pub warnings: Vec<String>,
+    /// Analysis stopped early because the analysis budget ran out or was cancelled
+    pub budget_exhausted: bool,
+    /// Values pushed by PUSH instructions, by program counter; only filled when analyzing
+    /// bytecode, since a bare opcode sequence has no immediates
+    pub push_values: BTreeMap<usize, Word>,
 }
 
 impl GasAnalysis {
@@ -24,6 +33,8 @@ impl GasAnalysis {
             breakdown: Vec::new(),
             optimizations: Vec::new(),
             warnings: Vec::new(),
+            budget_exhausted: false,
+            push_values: BTreeMap::new(),
         }
     }
 
@@ -72,7 +83,7 @@ impl GasAnalysis {
         }
 
         // Check for repeated expensive operations
-        let mut opcode_counts = std::collections::HashMap::new();
+        let mut opcode_counts = BTreeMap::new();
         for (opcode, _) in &self.breakdown {
             *opcode_counts.entry(*opcode).or_insert(0) += 1;
         }
@@ -181,38 +192,30 @@ pub struct GasAnalyzer;
 
 impl GasAnalyzer {
     /// Analyze gas usage for a sequence of opcodes
+    ///
+    /// Unknown opcodes are skipped with a warning; use
+    /// [`analyze_gas_usage_with_config`](Self::analyze_gas_usage_with_config)
+    /// to choose a different [`UnknownOpcodePolicy`](crate::UnknownOpcodePolicy).
     pub fn analyze_gas_usage(opcodes: &[u8], fork: Fork) -> GasAnalysis {
-        let calculator = DynamicGasCalculator::new(fork);
-        let _context = ExecutionContext::new();
-
-        // Convert opcodes to (opcode, operands) pairs
-        // This is simplified - real implementation would parse operands from bytecode
-        let opcode_sequence: Vec<(u8, Vec<u64>)> = opcodes
-            .iter()
-            .map(|&opcode| (opcode, Self::estimate_operands(opcode)))
-            .collect();
+        Self::analyze_gas_usage_in(&OpcodeRegistry::new(), opcodes, fork)
+    }
 
-        match calculator.analyze_sequence_gas(&opcode_sequence) {
-            Ok(result) => {
-                let breakdown: Vec<(u8, u16)> = result
-                    .breakdown
-                    .into_iter()
-                    .map(|(op, cost)| (op, cost.min(u16::MAX as u64) as u16))
-                    .collect();
-
-                GasAnalysis {
-                    total_gas: result.total_gas,
-                    breakdown,
-                    optimizations: result.optimizations,
-                    warnings: result.warnings,
-                }
-            }
+    /// [`analyze_gas_usage`](Self::analyze_gas_usage) with the tables of `registry`
+    pub(crate) fn analyze_gas_usage_in(
+        registry: &OpcodeRegistry,
+        opcodes: &[u8],
+        fork: Fork,
+    ) -> GasAnalysis {
+        let calculator = DynamicGasCalculator::from_registry(registry.clone(), fork);
+        let instructions = Self::bare_instructions(opcodes);
+        match Self::analyze_with_calculator(&calculator, &instructions, &AnalysisConfig::default())
+        {
+            Ok(analysis) => analysis,
             Err(e) => {
                 let mut analysis = GasAnalysis::new();
                 analysis.warnings.push(format!("Gas analysis failed: {e}"));
 
                 // Fallback to simple gas calculation
-                let registry = OpcodeRegistry::new();
                 let opcodes_map = registry.get_opcodes(fork);
 
                 for &opcode in opcodes {
@@ -228,85 +231,118 @@ impl GasAnalyzer {
         }
     }
 
-    /// Validate opcode sequence for gas efficiency
-    pub fn validate_opcode_sequence(opcodes: &[u8], fork: Fork) -> Result<(), String> {
-        let analysis = Self::analyze_gas_usage(opcodes, fork);
-
-        // Check if sequence exceeds block gas limit
-        const BLOCK_GAS_LIMIT: u64 = 30_000_000;
-        if analysis.total_gas > BLOCK_GAS_LIMIT {
-            return Err(format!(
-                "Opcode sequence consumes {} gas, exceeding block limit of {}",
-                analysis.total_gas, BLOCK_GAS_LIMIT
-            ));
-        }
-
-        // Check for known problematic patterns
-        for window in opcodes.windows(2) {
-            match (window[0], window[1]) {
-                // Detect potential infinite loops
-                (0x56, 0x56) => return Err("Consecutive JUMP instructions detected".to_string()),
-
-                // Detect expensive operations in loops
-                (0x57, 0x55) => {
-                    return Err("SSTORE after JUMPI may create expensive loop".to_string());
-                }
-
-                // Detect redundant operations
-                (0x80..=0x8f, 0x50) => {
-                    return Err("DUP followed by POP detected - inefficient pattern".to_string());
-                }
-
-                _ => {}
-            }
-        }
-
-        // Check for gas bombs
-        let gas_bombs = analysis.find_gas_bombs();
-        if !gas_bombs.is_empty() {
-            return Err(format!(
-                "Potential gas bombs detected: {}",
-                gas_bombs.join("; ")
-            ));
-        }
-
-        Ok(())
+    /// Analyze gas usage for a sequence of opcodes using an analysis configuration
+    pub fn analyze_gas_usage_with_config(
+        opcodes: &[u8],
+        fork: Fork,
+        config: &AnalysisConfig,
+    ) -> Result<GasAnalysis, String> {
+        Self::analyze_with_calculator(
+            &DynamicGasCalculator::new(fork),
+            &Self::bare_instructions(opcodes),
+            config,
+        )
     }
 
-    /// Estimate operands for an opcode (simplified heuristic)
-    fn estimate_operands(opcode: u8) -> Vec<u64> {
-        match opcode {
-            // Storage operations
-            0x54 => vec![0x0],      // SLOAD with dummy key
-            0x55 => vec![0x0, 0x1], // SSTORE with dummy key/value
-            0x5c => vec![0x0],      // TLOAD with dummy key
-            0x5d => vec![0x0, 0x1], // TSTORE with dummy key/value
-
-            // Memory operations
-            0x51..=0x53 => vec![0x40],      // Memory ops at offset 0x40
-            0x5e => vec![0x40, 0x80, 0x20], // MCOPY: dst, src, size
-
-            // Call operations (simplified)
-            0xf1 | 0xf2 | 0xf4 | 0xfa => vec![100000, 0x123, 0, 0, 0, 0, 0], // Basic call params
-
-            // Account access
-            0x31 | 0x3b | 0x3c | 0x3f => vec![0x123], // Dummy address
+    /// Opcodes as instructions without immediates, at their index
+    fn bare_instructions(opcodes: &[u8]) -> Vec<Instruction> {
+        opcodes
+            .iter()
+            .enumerate()
+            .map(|(index, &opcode)| Instruction::new(index, opcode, Vec::new()))
+            .collect()
+    }
 
-            // Copy operations
-            0x37 | 0x39 | 0x3e => vec![0x40, 0x0, 0x20], // dest, src, size
+    fn analyze_with_calculator(
+        calculator: &DynamicGasCalculator,
+        instructions: &[Instruction],
+        config: &AnalysisConfig,
+    ) -> Result<GasAnalysis, String> {
+        let opcode_sequence = config.operand_sequence(instructions, calculator.fork());
 
-            // Create operations
-            0xf0 | 0xf5 => vec![0, 0x40, 0x100], // value, offset, size
+        let result = calculator.analyze_sequence_gas_with_config(&opcode_sequence, config)?;
+        let breakdown: Vec<(u8, u16)> = result
+            .breakdown
+            .into_iter()
+            .map(|(op, cost)| (op, cost.min(u16::MAX as u64) as u16))
+            .collect();
 
-            // Hash operations
-            0x20 => vec![0x40, 0x20], // offset, size
+        Ok(GasAnalysis {
+            total_gas: result.total_gas,
+            breakdown,
+            optimizations: result.optimizations,
+            warnings: result.warnings,
+            budget_exhausted: result.budget_exhausted,
+            push_values: BTreeMap::new(),
+        })
+    }
 
-            // Log operations
-            0xa0..=0xa4 => vec![0x40, 0x20], // offset, size
+    /// Analyze gas usage of raw bytecode
+    ///
+    /// Unlike [`analyze_gas_usage`](Self::analyze_gas_usage), the input is decoded first so
+    /// PUSH immediates and the data regions configured (or detected) in `config` are not
+    /// priced as opcodes.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(?fork, len = bytecode.len()))
+    )]
+    pub fn analyze_bytecode(
+        bytecode: &[u8],
+        fork: Fork,
+        config: &AnalysisConfig,
+    ) -> Result<GasAnalysis, String> {
+        let disassembly = crate::disasm::disassemble_with_config(bytecode, fork, config)?;
+        Self::analyze_disassembly(bytecode, disassembly, fork, config)
+    }
 
-            // Most operations don't need operands
-            _ => vec![],
+    /// [`analyze_bytecode`](Self::analyze_bytecode) with `bytecode` already disassembled
+    /// under `config`, so data regions are not detected again
+    pub(crate) fn analyze_disassembly(
+        bytecode: &[u8],
+        disassembly: Disassembly,
+        fork: Fork,
+        config: &AnalysisConfig,
+    ) -> Result<GasAnalysis, String> {
+        let mut analysis = Self::analyze_with_calculator(
+            &DynamicGasCalculator::new(fork),
+            &disassembly.instructions,
+            config,
+        )?;
+        analysis.push_values = disassembly.push_values();
+        analysis.warnings.extend(disassembly.warnings);
+        analysis.budget_exhausted |= disassembly.budget_exhausted;
+        if fork >= Fork::Prague {
+            if let Some(target) = crate::setcode::delegation_target(bytecode) {
+                analysis.warnings.push(format!(
+                    "Bytecode is an EIP-7702 delegation designator; the code that runs is at 0x{}",
+                    crate::bytecode::encode_hex(&target)
+                ));
+            }
+            analysis.warnings.extend(
+                crate::setcode::find_eoa_checks(bytecode)
+                    .iter()
+                    .map(|check| check.warning()),
+            );
         }
+        Ok(analysis)
+    }
+
+    /// Validate an opcode sequence
+    ///
+    /// The input is decoded as bytecode, so PUSH immediates are not mistaken for opcodes.
+    /// Runs [`SequenceValidator::standard`](crate::rules::SequenceValidator::standard) and
+    /// returns the first violation; use the validator directly to get all of them or to
+    /// customize the rules. The standard rules, in order:
+    /// 1. PUSH0 is only used from Shanghai on
+    /// 2. Total gas must not exceed the block gas limit
+    /// 3. Control-flow structure: no unreachable code after terminators and no static jumps
+    ///    to non-JUMPDEST offsets (see [`validate_sequence_structure`](crate::validate_sequence_structure))
+    /// 4. Inefficient patterns: SSTORE directly after JUMPI, DUP directly followed by POP
+    /// 5. No gas bombs
+    pub fn validate_opcode_sequence(opcodes: &[u8], fork: Fork) -> Result<(), String> {
+        crate::rules::SequenceValidator::standard()
+            .validate(opcodes, fork)
+            .map_err(|violations| violations[0].message.clone())
     }
 }
 
@@ -329,9 +365,10 @@ impl GasComparator {
 
     /// Get all opcodes that changed between two forks
     pub fn get_changes_between_forks(fork1: Fork, fork2: Fork) -> Vec<OpcodeChange> {
+        // Ordered maps so the changes come out sorted by opcode
         let registry = OpcodeRegistry::new();
-        let opcodes1 = registry.get_opcodes(fork1);
-        let opcodes2 = registry.get_opcodes(fork2);
+        let opcodes1: BTreeMap<_, _> = registry.get_opcodes(fork1).into_iter().collect();
+        let opcodes2: BTreeMap<_, _> = registry.get_opcodes(fork2).into_iter().collect();
         let mut changes = Vec::new();
 
         //todo: properly detect changes in fork file
@@ -414,6 +451,18 @@ impl GasComparator {
             }
         }
 
+        // Behavior changes of opcodes present in both forks
+        for change in crate::semantics::semantic_changes_between(fork1, fork2) {
+            if opcodes1.contains_key(&change.opcode) && opcodes2.contains_key(&change.opcode) {
+                changes.push(OpcodeChange {
+                    opcode: change.opcode,
+                    change_type: ChangeType::SemanticsChanged,
+                    old_value: None,
+                    new_value: None,
+                });
+            }
+        }
+
         // Find opcodes that were removed (rare)
         for (opcode, metadata1) in &opcodes1 {
             if !opcodes2.contains_key(opcode) {
@@ -507,64 +556,19 @@ pub struct GasComparisonReport {
 }
 
 impl GasComparisonReport {
-    /// Print a human-readable report
+    /// Print a human-readable report to stdout
+    ///
+    /// The same text is available through the [`Display`](fmt::Display) implementation.
     pub fn print_report(&self) {
-        println!("=== Gas Cost Comparison Report ===");
-        println!("Comparing {:?} → {:?}", self.fork1, self.fork2);
-        println!();
-
-        println!("Summary:");
-        println!("  Opcodes added: {}", self.summary.opcodes_added);
-        println!("  Opcodes removed: {}", self.summary.opcodes_removed);
-        println!("  Gas cost changes: {}", self.summary.gas_cost_changes);
-        println!(
-            "  Gas increases: {} (total: +{} gas)",
-            self.summary.gas_increases, self.summary.total_gas_increase
-        );
-        println!(
-            "  Gas decreases: {} (total: -{} gas)",
-            self.summary.gas_decreases, self.summary.total_gas_decrease
-        );
-        println!(
-            "  Stack behavior changes: {}",
-            self.summary.stack_behavior_changes
-        );
-        println!();
+        print!("{self}");
+    }
 
-        if !self.changes.is_empty() {
-            println!("Detailed Changes:");
-            for change in &self.changes {
-                match change.change_type {
-                    ChangeType::Added => {
-                        println!(
-                            "  + Added opcode 0x{:02x} (gas: {})",
-                            change.opcode,
-                            change.new_value.unwrap_or(0)
-                        );
-                    }
-                    ChangeType::Removed => {
-                        println!(
-                            "  - Removed opcode 0x{:02x} (was: {} gas)",
-                            change.opcode,
-                            change.old_value.unwrap_or(0)
-                        );
-                    }
-                    ChangeType::GasCostChanged => {
-                        println!(
-                            "  ~ Opcode 0x{:02x}: {} → {} gas",
-                            change.opcode,
-                            change.old_value.unwrap_or(0),
-                            change.new_value.unwrap_or(0)
-                        );
-                    }
-                    ChangeType::StackBehaviorChanged => {
-                        println!("  ! Opcode 0x{:02x}: stack behavior changed", change.opcode);
-                    }
-                    ChangeType::SemanticsChanged => {
-                        println!("  ! Opcode 0x{:02x}: semantics changed", change.opcode);
-                    }
-                }
-            }
+    /// Stable identifier of a change: the opcode in the fork it was removed from, or in the
+    /// later fork otherwise
+    pub fn change_id(&self, change: &OpcodeChange) -> OpcodeId {
+        match change.change_type {
+            ChangeType::Removed => OpcodeId::new(change.opcode, self.fork1),
+            _ => OpcodeId::new(change.opcode, self.fork2),
         }
     }
 
@@ -594,6 +598,78 @@ impl GasComparisonReport {
     }
 }
 
+impl fmt::Display for GasComparisonReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "=== Gas Cost Comparison Report ===")?;
+        writeln!(f, "Comparing {:?} → {:?}", self.fork1, self.fork2)?;
+        writeln!(f)?;
+
+        writeln!(f, "Summary:")?;
+        writeln!(f, "  Opcodes added: {}", self.summary.opcodes_added)?;
+        writeln!(f, "  Opcodes removed: {}", self.summary.opcodes_removed)?;
+        writeln!(f, "  Gas cost changes: {}", self.summary.gas_cost_changes)?;
+        writeln!(
+            f,
+            "  Gas increases: {} (total: +{} gas)",
+            self.summary.gas_increases, self.summary.total_gas_increase
+        )?;
+        writeln!(
+            f,
+            "  Gas decreases: {} (total: -{} gas)",
+            self.summary.gas_decreases, self.summary.total_gas_decrease
+        )?;
+        writeln!(
+            f,
+            "  Stack behavior changes: {}",
+            self.summary.stack_behavior_changes
+        )?;
+        writeln!(f)?;
+
+        if !self.changes.is_empty() {
+            writeln!(f, "Detailed Changes:")?;
+            for change in &self.changes {
+                match change.change_type {
+                    ChangeType::Added => writeln!(
+                        f,
+                        "  + Added opcode 0x{:02x} (gas: {}) [{}]",
+                        change.opcode,
+                        change.new_value.unwrap_or(0),
+                        self.change_id(change)
+                    )?,
+                    ChangeType::Removed => writeln!(
+                        f,
+                        "  - Removed opcode 0x{:02x} (was: {} gas) [{}]",
+                        change.opcode,
+                        change.old_value.unwrap_or(0),
+                        self.change_id(change)
+                    )?,
+                    ChangeType::GasCostChanged => writeln!(
+                        f,
+                        "  ~ Opcode 0x{:02x}: {} → {} gas [{}]",
+                        change.opcode,
+                        change.old_value.unwrap_or(0),
+                        change.new_value.unwrap_or(0),
+                        self.change_id(change)
+                    )?,
+                    ChangeType::StackBehaviorChanged => writeln!(
+                        f,
+                        "  ! Opcode 0x{:02x}: stack behavior changed [{}]",
+                        change.opcode,
+                        self.change_id(change)
+                    )?,
+                    ChangeType::SemanticsChanged => writeln!(
+                        f,
+                        "  ! Opcode 0x{:02x}: semantics changed [{}]",
+                        change.opcode,
+                        self.change_id(change)
+                    )?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Summary statistics for gas changes between forks
 #[derive(Debug, Clone, Default)]
 pub struct GasChangeSummary {
@@ -631,7 +707,7 @@ impl GasOptimizationAdvisor {
                     "Use PUSH0 instead of PUSH1 0x00 to save 2 gas per occurrence".to_string(),
                 );
             }
-            Fork::Cancun => {
+            Fork::Cancun | Fork::Prague => {
                 recommendations.push("Use PUSH0 for zero values (2 gas savings)".to_string());
                 recommendations.push("Consider TSTORE/TLOAD for temporary storage (100 gas vs 2100+ for SSTORE/SLOAD)".to_string());
                 recommendations.push(
@@ -677,28 +753,42 @@ impl GasOptimizationAdvisor {
 
     /// Analyze a gas pattern and suggest specific optimizations
     pub fn analyze_pattern(opcodes: &[u8], fork: Fork) -> Vec<String> {
+        Self::analyze_pattern_of(
+            &GasAnalyzer::analyze_gas_usage(opcodes, fork),
+            opcodes,
+            fork,
+        )
+    }
+
+    /// [`analyze_pattern`](Self::analyze_pattern) given the gas analysis of `opcodes`
+    pub(crate) fn analyze_pattern_of(
+        analysis: &GasAnalysis,
+        opcodes: &[u8],
+        fork: Fork,
+    ) -> Vec<String> {
         let mut suggestions = Vec::new();
-        let analysis = GasAnalyzer::analyze_gas_usage(opcodes, fork);
 
-        // Analyze for common anti-patterns
+        // Analyze for common anti-patterns on decoded instructions, so bytes inside PUSH
+        // data (such as inlined immutables) are not mistaken for SLOADs
+        let instructions = crate::disasm::disassemble(opcodes);
         let mut consecutive_sloads = 0;
-        let mut total_sloads = 0;
         let mut push_zeros = 0;
 
-        for window in opcodes.windows(2) {
-            match window {
-                [0x54, 0x54] => consecutive_sloads += 1,
-                [0x60, 0x00] if fork >= Fork::Shanghai => push_zeros += 1, // PUSH1 0x00
-                _ => {}
+        for window in instructions.windows(2) {
+            if window[0].opcode == 0x54 && window[1].opcode == 0x54 {
+                consecutive_sloads += 1;
             }
         }
 
-        for &opcode in opcodes {
-            if opcode == 0x54 {
-                total_sloads += 1;
+        for instruction in &instructions {
+            if instruction.opcode == 0x60 && instruction.immediate == [0x00] {
+                push_zeros += 1; // PUSH1 0x00
             }
         }
 
+        let reads = crate::reads::classify_reads(opcodes, fork);
+        let total_sloads = reads.storage_reads();
+
         if consecutive_sloads > 0 {
             suggestions.push(format!(
                 "Found {consecutive_sloads} consecutive SLOAD operations - consider caching in memory",
@@ -712,6 +802,14 @@ impl GasOptimizationAdvisor {
             );
         }
 
+        if reads.immutable_reads() > 0 {
+            suggestions.push(format!(
+                "{} of {} reads are immutables copied from code - they need no storage caching",
+                reads.immutable_reads(),
+                reads.immutable_reads() + total_sloads
+            ));
+        }
+
         if push_zeros > 0 && fork >= Fork::Shanghai {
             suggestions.push(format!(
                 "Found {} PUSH1 0x00 operations - replace with PUSH0 to save {} gas",
@@ -744,6 +842,81 @@ mod tests {
         assert!(analysis.optimizations.is_empty());
     }
 
+    #[test]
+    fn test_unknown_opcode_policies() {
+        use crate::UnknownOpcodePolicy;
+
+        let opcodes = vec![0x01, 0x0c, 0x02]; // ADD, unassigned, MUL
+
+        let skipped = GasAnalyzer::analyze_gas_usage(&opcodes, Fork::London);
+        assert_eq!(skipped.breakdown.len(), 2);
+        assert!(skipped.warnings.iter().any(|w| w.contains("0x0c")));
+
+        let strict = AnalysisConfig::new().with_unknown_opcode_policy(UnknownOpcodePolicy::Error);
+        let err = GasAnalyzer::analyze_gas_usage_with_config(&opcodes, Fork::London, &strict)
+            .unwrap_err();
+        assert!(err.contains("index 1"));
+
+        let invalid =
+            AnalysisConfig::new().with_unknown_opcode_policy(UnknownOpcodePolicy::TreatAsInvalid);
+        let halted =
+            GasAnalyzer::analyze_gas_usage_with_config(&opcodes, Fork::London, &invalid).unwrap();
+        assert_eq!(halted.breakdown.len(), 2); // MUL is never reached
+        assert_eq!(halted.breakdown[1].0, 0x0c);
+    }
+
+    #[test]
+    fn test_bytecode_analysis_excludes_data() {
+        use crate::{DataRegion, DataRegionKind};
+
+        // PUSH1 0x55, STOP, followed by two data bytes that look like SSTORE
+        let bytecode = vec![0x60, 0x55, 0x00, 0x55, 0x55];
+        let config = AnalysisConfig::new().with_data_region(DataRegion::new(
+            3,
+            5,
+            DataRegionKind::EmbeddedData,
+        ));
+        let analysis = GasAnalyzer::analyze_bytecode(&bytecode, Fork::London, &config).unwrap();
+
+        assert_eq!(analysis.breakdown.len(), 2);
+        assert!(analysis.breakdown.iter().all(|(op, _)| *op != 0x55));
+        assert_eq!(analysis.push_values.len(), 1);
+        assert_eq!(analysis.push_values[&0][31], 0x55);
+    }
+
+    #[test]
+    fn test_sequence_validation_is_terminator_aware() {
+        // PUSH1 0x00, PUSH1 0x00, RETURN
+        assert!(GasAnalyzer::validate_opcode_sequence(
+            &[0x60, 0x00, 0x60, 0x00, 0xf3],
+            Fork::London
+        )
+        .is_ok());
+
+        // STOP, ADD: code after STOP without a JUMPDEST
+        let err = GasAnalyzer::validate_opcode_sequence(&[0x00, 0x01], Fork::London).unwrap_err();
+        assert!(err.contains("Unreachable code at pc 1"));
+
+        // STOP, JUMPDEST, STOP is fine
+        assert!(GasAnalyzer::validate_opcode_sequence(&[0x00, 0x5b, 0x00], Fork::London).is_ok());
+
+        // PUSH1 0x5b (a JUMPDEST byte inside PUSH data), PUSH1 0x01, JUMPI
+        let err = GasAnalyzer::validate_opcode_sequence(
+            &[0x60, 0x5b, 0x60, 0x01, 0x57, 0x00],
+            Fork::London,
+        )
+        .unwrap_err();
+        assert!(err.contains("inside the immediate of PUSH1"));
+    }
+
+    #[test]
+    fn test_pattern_analysis_ignores_push_data() {
+        // PUSH4 0x54545454, PUSH1 0x00, SLOAD: only one real SLOAD
+        let opcodes = [0x63, 0x54, 0x54, 0x54, 0x54, 0x60, 0x00, 0x54];
+        let suggestions = GasOptimizationAdvisor::analyze_pattern(&opcodes, Fork::London);
+        assert!(!suggestions.iter().any(|s| s.contains("SLOAD")));
+    }
+
     #[test]
     fn test_efficiency_score_calculation() {
         let analysis = GasAnalysis {
@@ -751,6 +924,8 @@ mod tests {
             breakdown: vec![(0x01, 3), (0x02, 3), (0x03, 3)],
             optimizations: vec![],
             warnings: vec![],
+            budget_exhausted: false,
+            push_values: BTreeMap::new(),
         };
 
         assert_eq!(analysis.efficiency_score(), 100); // Should be very efficient with 3 gas average
@@ -767,6 +942,8 @@ mod tests {
             ],
             optimizations: vec![],
             warnings: vec![],
+            budget_exhausted: false,
+            push_values: BTreeMap::new(),
         };
 
         let by_category = analysis.gas_by_category();
@@ -785,6 +962,8 @@ mod tests {
             ],
             optimizations: vec![],
             warnings: vec![],
+            budget_exhausted: false,
+            push_values: BTreeMap::new(),
         };
 
         let bombs = analysis.find_gas_bombs();
@@ -804,18 +983,6 @@ mod tests {
     fn test_fork_changes() {
         let changes = GasComparator::get_changes_between_forks(Fork::Istanbul, Fork::Berlin);
 
-        // Print debug info
-        println!(
-            "Found {} changes between Istanbul and Berlin:",
-            changes.len()
-        );
-        for change in &changes {
-            println!(
-                "  0x{:02x}: {:?} -> {:?} ({:?})",
-                change.opcode, change.old_value, change.new_value, change.change_type
-            );
-        }
-
         // Should detect EIP-2929 changes
         assert!(
             !changes.is_empty(),
@@ -829,11 +996,17 @@ mod tests {
         assert!(has_sload_change, "Should detect SLOAD gas cost change");
         assert!(has_balance_change, "Should detect BALANCE gas cost change");
 
+        let report = GasComparator::generate_comparison_report(Fork::Istanbul, Fork::Berlin);
+        let sload = report.changes.iter().find(|c| c.opcode == 0x54).unwrap();
+        assert_eq!(report.change_id(sload).to_string(), "evm/0x54@berlin");
+        assert!(report
+            .to_string()
+            .contains("800 → 2100 gas [evm/0x54@berlin]"));
+
         // Should have at least the major EIP-2929 changes
         assert!(
             changes.len() >= 5,
-            "Should detect at least 5 EIP-2929 changes, found {}",
-            changes.len()
+            "Should detect at least 5 EIP-2929 changes, found {changes:?}"
         );
     }
 
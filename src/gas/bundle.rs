@@ -0,0 +1,161 @@
+//! Checking whether a bundle of transactions fits a block gas limit
+//!
+//! Each transaction's execution gas is estimated under worst-case assumptions: every decoded
+//! instruction runs once and every storage slot and account is accessed cold. Calldata gas
+//! is added on top. A transaction with a gas limit reserves that limit instead, since it may
+//! use all of it (loops are not bounded by the estimate), which is also how block builders
+//! account for it.
+
+use super::{calldata_gas, GasAnalyzer};
+use crate::{AnalysisConfig, Fork};
+
+/// A transaction in a bundle
+#[derive(Debug, Clone, Default)]
+pub struct BundleTransaction {
+    /// Name used in the report (e.g. the transaction hash)
+    pub label: String,
+    /// Code executed by the transaction
+    pub bytecode: Vec<u8>,
+    /// Calldata sent with the transaction
+    pub calldata: Vec<u8>,
+    /// Gas limit of the transaction, if known
+    pub gas_limit: Option<u64>,
+}
+
+impl BundleTransaction {
+    /// Create a transaction executing `bytecode`
+    pub fn new(label: &str, bytecode: Vec<u8>) -> Self {
+        Self {
+            label: label.to_string(),
+            bytecode,
+            ..Self::default()
+        }
+    }
+
+    /// Set the calldata
+    pub fn with_calldata(mut self, calldata: Vec<u8>) -> Self {
+        self.calldata = calldata;
+        self
+    }
+
+    /// Set the gas limit
+    pub fn with_gas_limit(mut self, gas_limit: u64) -> Self {
+        self.gas_limit = Some(gas_limit);
+        self
+    }
+}
+
+/// Gas a transaction contributes to the bundle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionContribution {
+    /// Label of the transaction
+    pub label: String,
+    /// Worst-case execution gas, including the 21000 intrinsic cost
+    pub execution_gas: u64,
+    /// Calldata gas
+    pub calldata_gas: u64,
+    /// Gas counted against the block: the gas limit if set, otherwise the estimate
+    pub reserved_gas: u64,
+    /// The estimate exceeds the gas limit, so the transaction may run out of gas
+    pub may_run_out_of_gas: bool,
+    /// Warnings from the execution analysis
+    pub warnings: Vec<String>,
+}
+
+impl TransactionContribution {
+    /// Estimated gas of the transaction
+    pub fn estimated_gas(&self) -> u64 {
+        self.execution_gas + self.calldata_gas
+    }
+}
+
+/// Result of checking a bundle against a block gas limit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BundleReport {
+    /// The block gas limit checked against
+    pub block_gas_limit: u64,
+    /// One entry per transaction, in bundle order
+    pub contributions: Vec<TransactionContribution>,
+    /// Sum of the reserved gas of all transactions
+    pub total_gas: u64,
+}
+
+impl BundleReport {
+    /// Check if the bundle fits the block gas limit
+    pub fn fits(&self) -> bool {
+        self.total_gas <= self.block_gas_limit
+    }
+
+    /// Gas left in the block after the bundle, negative if it does not fit
+    pub fn headroom(&self) -> i128 {
+        self.block_gas_limit as i128 - self.total_gas as i128
+    }
+
+    /// Index of the first transaction that no longer fits, if any
+    pub fn first_overflowing(&self) -> Option<usize> {
+        let mut used = 0u64;
+        self.contributions.iter().position(|contribution| {
+            used += contribution.reserved_gas;
+            used > self.block_gas_limit
+        })
+    }
+}
+
+/// Estimate the gas of every transaction in a bundle and compare it with a block gas limit
+pub fn check_bundle(
+    transactions: &[BundleTransaction],
+    fork: Fork,
+    block_gas_limit: u64,
+) -> Result<BundleReport, String> {
+    let config = AnalysisConfig::default();
+    let mut contributions = Vec::with_capacity(transactions.len());
+
+    for transaction in transactions {
+        let result = GasAnalyzer::analyze_bytecode(&transaction.bytecode, fork, &config)
+            .map_err(|e| format!("Transaction {}: {e}", transaction.label))?;
+        let calldata_gas = calldata_gas(&transaction.calldata, fork);
+        let estimated = result.total_gas + calldata_gas;
+        contributions.push(TransactionContribution {
+            label: transaction.label.clone(),
+            execution_gas: result.total_gas,
+            calldata_gas,
+            reserved_gas: transaction.gas_limit.unwrap_or(estimated),
+            may_run_out_of_gas: transaction.gas_limit.is_some_and(|limit| estimated > limit),
+            warnings: result.warnings,
+        });
+    }
+
+    Ok(BundleReport {
+        block_gas_limit,
+        total_gas: contributions.iter().map(|c| c.reserved_gas).sum(),
+        contributions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_fits() {
+        // PUSH1 0x01, PUSH1 0x00, SSTORE
+        let store = vec![0x60, 0x01, 0x60, 0x00, 0x55];
+        let bundle = [
+            BundleTransaction::new("store", store.clone()).with_calldata(vec![0x00, 0x01]),
+            BundleTransaction::new("capped", store).with_gas_limit(50_000),
+        ];
+        let report = check_bundle(&bundle, Fork::London, 100_000).unwrap();
+        let first = &report.contributions[0];
+        assert_eq!(first.calldata_gas, 4 + 16);
+        // The slot is accessed cold
+        assert!(first.execution_gas > 21_000 + 2100);
+        assert_eq!(report.contributions[1].reserved_gas, 50_000);
+        assert!(!report.contributions[1].may_run_out_of_gas);
+        assert!(report.fits());
+
+        let report = check_bundle(&bundle, Fork::London, 60_000).unwrap();
+        assert!(!report.fits());
+        assert_eq!(report.first_overflowing(), Some(1));
+        assert!(report.headroom() < 0);
+    }
+}
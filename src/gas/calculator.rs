@@ -1,23 +1,132 @@
 //! Dynamic gas cost calculator for EVM opcodes
 
-use super::{ExecutionContext, GasAnalysisResult};
+use super::formulas::{
+    copy_gas, exp_gas, init_code_gas, keccak_gas, memory_expansion_gas, words, KECCAK_BASE_GAS,
+    KECCAK_WORD_GAS,
+};
+use super::state::{pays_new_account, StateOracle, NEW_ACCOUNT_COST};
+use super::{ExecutionContext, GasAnalysisResult, PriceOverride, Repricing};
+use crate::config::{AnalysisConfig, UnknownOpcodeAction, UnknownOpcodePolicy};
 use crate::{Fork, OpcodeMetadata, OpcodeRegistry};
+use std::fmt;
+
+/// Price of a warm account or storage access (EIP-2929)
+const WARM_ACCESS_COST: u64 = 100;
+/// Price of a cold SLOAD (EIP-2929)
+const COLD_SLOAD_COST: u64 = 2100;
+/// Price of a cold account access (EIP-2929)
+const COLD_ACCOUNT_ACCESS_COST: u64 = 2600;
+
+/// EXTCALL, EXTDELEGATECALL and EXTSTATICCALL (EIP-7069, EOF code only)
+fn is_eof_call(opcode: u8) -> bool {
+    matches!(opcode, 0xf8 | 0xf9 | 0xfb)
+}
+
+/// Storage key of a slot given as a u64 operand
+pub(super) fn storage_key(slot: u64) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    key[24..].copy_from_slice(&slot.to_be_bytes());
+    key
+}
+
+/// Operands missing for the pricing of an instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperandError {
+    /// Index of the instruction in the analyzed sequence, if it was part of one
+    pub index: Option<usize>,
+    /// The opcode
+    pub opcode: u8,
+    /// Mnemonic of the opcode
+    pub name: &'static str,
+    /// Number of operands the pricing needs
+    pub expected: usize,
+    /// Names of the expected operands, top of stack first
+    pub expected_names: &'static str,
+    /// Number of operands provided
+    pub provided: usize,
+}
+
+impl OperandError {
+    /// Record the position of the instruction in a sequence
+    pub fn at_index(mut self, index: usize) -> Self {
+        self.index = Some(index);
+        self
+    }
+}
+
+impl fmt::Display for OperandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (0x{:02x})", self.name, self.opcode)?;
+        if let Some(index) = self.index {
+            write!(f, " at index {index}")?;
+        }
+        write!(
+            f,
+            " requires {} operands ({}), got {}",
+            self.expected, self.expected_names, self.provided
+        )
+    }
+}
+
+impl std::error::Error for OperandError {}
+
+impl From<OperandError> for String {
+    fn from(error: OperandError) -> Self {
+        error.to_string()
+    }
+}
 
 /// Dynamic gas cost calculator that accounts for execution context
 pub struct DynamicGasCalculator {
     registry: OpcodeRegistry,
     fork: Fork,
+    repricing: Repricing,
+    state: Option<Box<dyn StateOracle>>,
 }
 
 impl DynamicGasCalculator {
     /// Create a new dynamic gas calculator for a specific fork
     pub fn new(fork: Fork) -> Self {
+        Self::from_registry(OpcodeRegistry::new(), fork)
+    }
+
+    /// Create a calculator pricing from the tables of `registry`, e.g. a
+    /// [subset](OpcodeRegistry::subset)
+    pub fn from_registry(registry: OpcodeRegistry, fork: Fork) -> Self {
         Self {
-            registry: OpcodeRegistry::new(),
+            registry,
             fork,
+            repricing: Repricing::default(),
+            state: None,
         }
     }
 
+    /// Apply hypothetical price changes on top of the fork's gas schedule
+    pub fn with_repricing(mut self, repricing: Repricing) -> Self {
+        self.repricing = repricing;
+        self
+    }
+
+    /// Answer new-account charges from account state instead of warmth (EIP-161)
+    pub fn with_state_oracle(mut self, state: impl StateOracle + 'static) -> Self {
+        self.state = Some(Box::new(state));
+        self
+    }
+
+    /// Layer a table of per-opcode overrides on top of the current schedule and repricing
+    pub fn with_gas_overrides(
+        mut self,
+        table: impl IntoIterator<Item = (u8, PriceOverride)>,
+    ) -> Self {
+        self.repricing = self.repricing.with_overrides(table);
+        self
+    }
+
+    /// The fork whose gas schedule is applied
+    pub fn fork(&self) -> Fork {
+        self.fork
+    }
+
     /// Calculate gas cost for a single opcode with execution context
     pub fn calculate_gas_cost(
         &self,
@@ -28,16 +137,102 @@ impl DynamicGasCalculator {
         let opcodes = self.registry.get_opcodes(self.fork);
         let metadata = opcodes
             .get(&opcode)
+            .filter(|_| !self.repricing.removes(opcode));
+        if let (None, Some(price)) = (metadata, self.repricing.override_for(opcode)) {
+            // Opcode proposed by the repricing scenario but not part of the fork; the EOF
+            // calls, DATACOPY, EOFCREATE and RETURNCONTRACT keep their dynamic part so
+            // scenarios only need their base price
+            let dynamic_cost = match opcode {
+                _ if is_eof_call(opcode) => self.calculate_call_cost(opcode, context, operands)?,
+                0xd3 => self.calculate_copy_cost(opcode, context, operands)?,
+                0xec | 0xee => self.calculate_create_cost(opcode, context, operands)?,
+                _ => 0,
+            };
+            let cost = price.apply(dynamic_cost);
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                opcode,
+                cost,
+                "priced opcode outside the fork from repricing override"
+            );
+            return Ok(cost);
+        }
+        let metadata = metadata
             .ok_or_else(|| format!("Unknown opcode: 0x{:02x} for fork {:?}", opcode, self.fork))?;
 
-        let base_cost = self.get_base_gas_cost(metadata);
+        self.check_operands(metadata, operands)?;
+        let base_cost = self.get_base_gas_cost(opcode, metadata);
         let dynamic_cost = self.calculate_dynamic_cost(opcode, metadata, context, operands)?;
 
-        Ok(base_cost + dynamic_cost)
+        let price = self.repricing.override_for(opcode);
+        let cost = match price {
+            Some(price) => price.apply(dynamic_cost),
+            None => base_cost.saturating_add(dynamic_cost),
+        };
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            opcode,
+            base_cost,
+            dynamic_cost,
+            cost,
+            overridden = price.is_some(),
+            "priced opcode"
+        );
+        Ok(cost)
+    }
+
+    /// Check that every operand the pricing of an opcode depends on is present
+    pub fn check_operands(
+        &self,
+        metadata: &OpcodeMetadata,
+        operands: &[u64],
+    ) -> Result<(), OperandError> {
+        let required = match metadata.opcode {
+            0x54 if self.fork >= Fork::Berlin => Some((1, "key")),
+            0x55 => Some((2, "key, value")),
+            0x5c if self.fork >= Fork::Cancun => Some((1, "key")),
+            0x5d if self.fork >= Fork::Cancun => Some((2, "key, value")),
+            0x51..=0x53 => Some((1, "offset")),
+            0x5e if self.fork >= Fork::Cancun => Some((3, "dst, src, size")),
+            0xf1 | 0xf2 => Some((
+                7,
+                "gas, address, value, args offset, args size, ret offset, ret size",
+            )),
+            // No value on the stack, but the calculator reads them in the CALL layout
+            0xf4 | 0xfa => Some((
+                7,
+                "gas, address, value placeholder (0), args offset, args size, ret offset, ret size",
+            )),
+            _ => None,
+        };
+        match required {
+            Some((expected, names)) if operands.len() < expected => Err(OperandError {
+                index: None,
+                opcode: metadata.opcode,
+                name: metadata.name,
+                expected,
+                expected_names: names,
+                provided: operands.len(),
+            }),
+            _ => Ok(()),
+        }
     }
 
     /// Get base gas cost from metadata with fork-specific adjustments
-    fn get_base_gas_cost(&self, metadata: &OpcodeMetadata) -> u64 {
+    ///
+    /// From Berlin on the tables hold cold access prices; for opcodes that touch accounts or
+    /// storage the base is the warm price and the cold surcharge is dynamic (EIP-2929).
+    pub(super) fn get_base_gas_cost(&self, opcode: u8, metadata: &OpcodeMetadata) -> u64 {
+        if self.fork >= Fork::Berlin {
+            match opcode {
+                0x31 | 0x3b | 0x3c | 0x3f | 0x54 | 0xf1 | 0xf2 | 0xf4 | 0xfa => {
+                    return WARM_ACCESS_COST
+                }
+                // SSTORE_RESET_GAS: 5000 minus the cold SLOAD price
+                0x55 => return 5000 - COLD_SLOAD_COST,
+                _ => {}
+            }
+        }
         // Find the most recent gas cost for this fork
         metadata
             .gas_history
@@ -57,13 +252,16 @@ impl DynamicGasCalculator {
         operands: &[u64],
     ) -> Result<u64, String> {
         match opcode {
+            // Exponent size (EIP-160, Spurious Dragon)
+            0x0a => Ok(self.calculate_exp_cost(operands)),
+
             // Storage operations with EIP-2929 warm/cold access
             0x54 => self.calculate_sload_cost(context, operands),
             0x55 => self.calculate_sstore_cost(context, operands),
 
             // Transient storage (EIP-1153, Cancun)
-            0x5c => self.calculate_tload_cost(context, operands),
-            0x5d => self.calculate_tstore_cost(context, operands),
+            0x5c => self.calculate_tload_cost(),
+            0x5d => self.calculate_tstore_cost(),
 
             // Memory operations with expansion costs
             0x51..=0x53 => self.calculate_memory_cost(opcode, context, operands),
@@ -73,9 +271,14 @@ impl DynamicGasCalculator {
             0xf1 | 0xf2 | 0xf4 | 0xfa => self.calculate_call_cost(opcode, context, operands),
 
             // Account access operations (EIP-2929)
-            0x31 | 0x3b | 0x3c | 0x3f => {
-                self.calculate_account_access_cost(opcode, context, operands)
-            }
+            0x31 | 0x3b | 0x3f => Ok(self.calculate_account_access_cost(context, operands.first())),
+            0x3c => self.calculate_extcodecopy_cost(context, operands),
+
+            // Beneficiary access (EIP-2929)
+            0xff => Ok(self.calculate_selfdestruct_cost(context, operands)),
+
+            // Memory read by RETURN and REVERT
+            0xf3 | 0xfd => Ok(self.calculate_return_cost(context, operands)),
 
             // Copy operations with data size dependency
             0x37 | 0x39 | 0x3e => self.calculate_copy_cost(opcode, context, operands),
@@ -87,13 +290,20 @@ impl DynamicGasCalculator {
             0x20 => self.calculate_keccak256_cost(context, operands),
 
             // Log operations
-            0xa0..=0xa4 => self.calculate_log_cost(opcode, context, operands),
+            0xa0..=0xa4 => self.calculate_log_cost(context, operands),
 
             // Most opcodes have static costs
             _ => Ok(0),
         }
     }
 
+    /// Calculate the per-byte cost of the EXP exponent, free when the exponent is unknown
+    fn calculate_exp_cost(&self, operands: &[u64]) -> u64 {
+        operands
+            .get(1)
+            .map_or(0, |exponent| exp_gas(*exponent, self.fork))
+    }
+
     /// Calculate SLOAD gas cost with warm/cold access (EIP-2929)
     fn calculate_sload_cost(
         &self,
@@ -101,25 +311,16 @@ impl DynamicGasCalculator {
         operands: &[u64],
     ) -> Result<u64, String> {
         if self.fork >= Fork::Berlin {
-            // EIP-2929: Warm/cold storage access
-            if operands.is_empty() {
-                return Err("SLOAD requires storage key operand".to_string());
-            }
-
-            let key_bytes = operands[0].to_be_bytes();
-            let mut full_key = [0u8; 32];
-            full_key[24..32].copy_from_slice(&key_bytes);
-            let is_warm = context.is_storage_warm(&context.current_address, &full_key);
-
-            // Berlin SLOAD: warm = 100, cold = 2100
-            if is_warm {
-                Ok(100) // Warm access
+            // EIP-2929: the base is the warm price, cold slots cost 2100 in total
+            let key = storage_key(operands[0]);
+            if context.is_storage_warm(&context.current_address, &key) {
+                Ok(0)
             } else {
-                Ok(2100) // Cold access
+                Ok(COLD_SLOAD_COST - WARM_ACCESS_COST)
             }
         } else {
-            // Pre-Berlin: static cost
-            Ok(800)
+            // Pre-Berlin: static cost from the table
+            Ok(0)
         }
     }
 
@@ -129,12 +330,7 @@ impl DynamicGasCalculator {
         context: &ExecutionContext,
         operands: &[u64],
     ) -> Result<u64, String> {
-        if operands.len() < 2 {
-            return Err("SSTORE requires key and value operands".to_string());
-        }
-
-        let key_bytes = operands[0].to_be_bytes();
-        let key = ExecutionContext::from_vec_storage_key(&key_bytes);
+        let key = storage_key(operands[0]);
         let _new_value = operands[1];
 
         if self.fork >= Fork::Berlin {
@@ -142,10 +338,10 @@ impl DynamicGasCalculator {
             let is_warm = context.is_storage_warm(&context.current_address, &key);
 
             if !is_warm {
-                // Cold access surcharge (beyond the base 5000 already in metadata)
-                Ok(2100)
+                // Cold access surcharge on top of the 2900 reset cost
+                Ok(COLD_SLOAD_COST)
             } else {
-                // Warm access - base cost (5000) already covers this
+                // Warm access - the reset cost covers this
                 // TODO: Implement proper EIP-2200 state transition logic
                 // This would require knowing original and current storage values
                 Ok(0)
@@ -164,32 +360,18 @@ impl DynamicGasCalculator {
     }
 
     /// Calculate TLOAD gas cost (transient storage)
-    fn calculate_tload_cost(
-        &self,
-        _context: &ExecutionContext,
-        operands: &[u64],
-    ) -> Result<u64, String> {
+    fn calculate_tload_cost(&self) -> Result<u64, String> {
         if self.fork >= Fork::Cancun {
-            if operands.is_empty() {
-                return Err("TLOAD requires storage key operand".to_string());
-            }
-            Ok(100) // TLOAD is always warm (100 gas)
+            Ok(0) // TLOAD is always warm, the 100 gas are in the table
         } else {
             Err("TLOAD not available before Cancun fork".to_string())
         }
     }
 
     /// Calculate TSTORE gas cost (transient storage)
-    fn calculate_tstore_cost(
-        &self,
-        _context: &ExecutionContext,
-        operands: &[u64],
-    ) -> Result<u64, String> {
+    fn calculate_tstore_cost(&self) -> Result<u64, String> {
         if self.fork >= Fork::Cancun {
-            if operands.len() < 2 {
-                return Err("TSTORE requires key and value operands".to_string());
-            }
-            Ok(100) // TSTORE is always 100 gas
+            Ok(0) // TSTORE is always 100 gas, all in the table
         } else {
             Err("TSTORE not available before Cancun fork".to_string())
         }
@@ -202,10 +384,6 @@ impl DynamicGasCalculator {
         context: &ExecutionContext,
         operands: &[u64],
     ) -> Result<u64, String> {
-        if operands.is_empty() {
-            return Err("Memory operation requires offset operand".to_string());
-        }
-
         let offset = operands[0] as usize;
         let size = match opcode {
             0x51 => 32, // MLOAD
@@ -214,7 +392,7 @@ impl DynamicGasCalculator {
             _ => return Err("Unknown memory opcode".to_string()),
         };
 
-        let new_memory_size = offset + size;
+        let new_memory_size = offset.saturating_add(size);
 
         if new_memory_size > context.memory_size {
             let expansion_cost =
@@ -235,43 +413,28 @@ impl DynamicGasCalculator {
             return Err("MCOPY not available before Cancun fork".to_string());
         }
 
-        if operands.len() < 3 {
-            return Err("MCOPY requires dst, src, and size operands".to_string());
-        }
-
         let dst_offset = operands[0] as usize;
-        let _src_offset = operands[1] as usize;
+        let src_offset = operands[1] as usize;
         let size = operands[2] as usize;
 
-        // Calculate memory expansion cost
-        let new_memory_size = dst_offset + size;
+        // Memory expands to cover both the source and the destination
+        let new_memory_size = if size == 0 {
+            0
+        } else {
+            dst_offset.max(src_offset).saturating_add(size)
+        };
         let expansion_cost = if new_memory_size > context.memory_size {
             self.calculate_memory_expansion_cost(context.memory_size, new_memory_size)
         } else {
             0
         };
 
-        // Calculate copy cost (3 gas per word)
-        let words = size.div_ceil(32);
-        let copy_cost = words as u64 * 3;
-
-        Ok(expansion_cost + copy_cost)
+        Ok(expansion_cost.saturating_add(copy_gas(size as u64)))
     }
 
     /// Calculate memory expansion cost (quadratic)
-    fn calculate_memory_expansion_cost(&self, old_size: usize, new_size: usize) -> u64 {
-        fn memory_cost(size: usize) -> u64 {
-            let size_in_words = size.div_ceil(32);
-            let linear_cost = size_in_words as u64 * 3;
-            let quadratic_cost = (size_in_words * size_in_words) as u64 / 512;
-            linear_cost + quadratic_cost
-        }
-
-        if new_size <= old_size {
-            0
-        } else {
-            memory_cost(new_size) - memory_cost(old_size)
-        }
+    pub(super) fn calculate_memory_expansion_cost(&self, old_size: usize, new_size: usize) -> u64 {
+        memory_expansion_gas(old_size as u64, new_size as u64, self.fork)
     }
 
     /// Calculate call operation costs
@@ -281,33 +444,46 @@ impl DynamicGasCalculator {
         context: &ExecutionContext,
         operands: &[u64],
     ) -> Result<u64, String> {
-        if operands.len() < 7 {
-            return Err("CALL requires at least 7 operands".to_string());
+        if is_eof_call(opcode) {
+            return self.calculate_eof_call_cost(opcode, context, operands);
         }
-
         let _gas_limit = operands[0];
         let target_address_bytes = operands[1].to_be_bytes();
         let target_address = ExecutionContext::from_vec_address(
             &target_address_bytes[0..8.min(target_address_bytes.len())],
         );
-        let value = if opcode == 0xf1 { operands[2] } else { 0 }; // Only CALL transfers value
+        // CALL and CALLCODE transfer value
+        let value = if matches!(opcode, 0xf1 | 0xf2) {
+            operands[2]
+        } else {
+            0
+        };
 
         let mut total_cost = 0u64;
 
-        // Account access cost (EIP-2929)
-        if self.fork >= Fork::Berlin {
-            let is_warm = context.is_address_warm(&target_address);
-            total_cost += if is_warm { 0 } else { 2600 }; // Only extra cost beyond base
+        // Account access cost (EIP-2929), the warm price is the base
+        if self.fork >= Fork::Berlin && !context.is_address_warm(&target_address) {
+            total_cost += COLD_ACCOUNT_ACCESS_COST - WARM_ACCESS_COST;
         }
 
         // Value transfer cost
         if value > 0 {
             total_cost += 9000;
+        }
 
-            // Account creation cost if target doesn't exist (simplified)
-            // Todo: check account existence
-            if !context.is_address_warm(&target_address) {
-                total_cost += 25000;
+        // Account creation cost if the target is new
+        if opcode == 0xf1 {
+            let charged = match &self.state {
+                Some(state) => pays_new_account(
+                    self.fork,
+                    state.account(&target_address).as_ref(),
+                    value.into(),
+                ),
+                // Without state, a cold target of a value transfer is assumed new
+                None => value > 0 && !context.is_address_warm(&target_address),
+            };
+            if charged {
+                total_cost += NEW_ACCOUNT_COST;
             }
         }
 
@@ -324,36 +500,143 @@ impl DynamicGasCalculator {
             let ret_offset = operands[5] as usize;
             let ret_size = operands[6] as usize;
 
-            let max_memory_access = std::cmp::max(args_offset + args_size, ret_offset + ret_size);
+            let max_memory_access = std::cmp::max(
+                args_offset.saturating_add(args_size),
+                ret_offset.saturating_add(ret_size),
+            );
 
             if max_memory_access > context.memory_size {
-                total_cost +=
-                    self.calculate_memory_expansion_cost(context.memory_size, max_memory_access);
+                total_cost = total_cost.saturating_add(
+                    self.calculate_memory_expansion_cost(context.memory_size, max_memory_access),
+                );
             }
         }
 
         Ok(total_cost)
     }
 
-    /// Calculate account access costs (BALANCE, EXTCODESIZE, etc.)
+    /// Calculate EXTCALL, EXTDELEGATECALL and EXTSTATICCALL costs (EIP-7069)
+    ///
+    /// The calls take no gas operand and no output range: operands are the target, the input
+    /// offset and size, and for EXTCALL the value. The warm price is the base. Which gas the
+    /// callee gets is decided at run time, see [`eof_callee_gas`](super::eof_callee_gas).
+    fn calculate_eof_call_cost(
+        &self,
+        opcode: u8,
+        context: &ExecutionContext,
+        operands: &[u64],
+    ) -> Result<u64, String> {
+        let expected = if opcode == 0xf8 { 4 } else { 3 };
+        if operands.len() < expected {
+            return Err(format!(
+                "EOF call (0x{opcode:02x}) requires {expected} operands, got {}",
+                operands.len()
+            ));
+        }
+        let target = ExecutionContext::from_vec_address(&operands[0].to_be_bytes());
+        let value = operands.get(3).copied().unwrap_or(0);
+
+        let mut total_cost = 0;
+        if !context.is_address_warm(&target) {
+            total_cost += COLD_ACCOUNT_ACCESS_COST - WARM_ACCESS_COST;
+        }
+        if value > 0 {
+            total_cost += 9000;
+        }
+        let charged = match &self.state {
+            Some(state) => {
+                pays_new_account(self.fork, state.account(&target).as_ref(), value.into())
+            }
+            // Without state, a cold target of a value transfer is assumed new
+            None => value > 0 && !context.is_address_warm(&target),
+        };
+        if charged {
+            total_cost += NEW_ACCOUNT_COST;
+        }
+        let (input_offset, input_size) = (operands[1] as usize, operands[2] as usize);
+        let input_end = input_offset.saturating_add(input_size);
+        if input_size > 0 && input_end > context.memory_size {
+            total_cost += self.calculate_memory_expansion_cost(context.memory_size, input_end);
+        }
+        Ok(total_cost)
+    }
+
+    /// Calculate the cold surcharge of account access (BALANCE, EXTCODESIZE, etc.)
     fn calculate_account_access_cost(
         &self,
-        _opcode: u8,
+        context: &ExecutionContext,
+        address: Option<&u64>,
+    ) -> u64 {
+        match address {
+            Some(address) if self.fork >= Fork::Berlin => {
+                let address_bytes = address.to_be_bytes();
+                let address = ExecutionContext::from_vec_address(&address_bytes);
+                if context.is_address_warm(&address) {
+                    0
+                } else {
+                    COLD_ACCOUNT_ACCESS_COST - WARM_ACCESS_COST
+                }
+            }
+            _ => 0,
+        }
+    }
+
+    /// Calculate EXTCODECOPY cost: account access, copied words and memory expansion
+    fn calculate_extcodecopy_cost(
+        &self,
         context: &ExecutionContext,
         operands: &[u64],
     ) -> Result<u64, String> {
-        if self.fork >= Fork::Berlin && !operands.is_empty() {
-            let address_bytes = operands[0].to_be_bytes();
-            let address =
-                ExecutionContext::from_vec_address(&address_bytes[0..8.min(address_bytes.len())]);
-            let is_warm = context.is_address_warm(&address);
-            Ok(if is_warm { 100 } else { 2600 })
-        } else {
-            Ok(0)
+        let access_cost = self.calculate_account_access_cost(context, operands.first());
+        let copy_cost = match operands.get(1..) {
+            Some(copy_operands) => self.calculate_copy_cost(0x3c, context, copy_operands)?,
+            None => 0,
+        };
+        Ok(access_cost + copy_cost)
+    }
+
+    /// Calculate the cold beneficiary surcharge of SELFDESTRUCT
+    ///
+    /// SELFDESTRUCT has no warm price, so a cold beneficiary costs the full 2600. Sending the
+    /// balance to a new account (25000 more, since Tangerine Whistle) is only priced with a
+    /// [`StateOracle`].
+    fn calculate_selfdestruct_cost(&self, context: &ExecutionContext, operands: &[u64]) -> u64 {
+        let Some(beneficiary) = operands.first() else {
+            return 0;
+        };
+        let address = ExecutionContext::from_vec_address(&beneficiary.to_be_bytes());
+        let mut total_cost = 0;
+        if self.fork >= Fork::Berlin && !context.is_address_warm(&address) {
+            total_cost += COLD_ACCOUNT_ACCESS_COST;
+        }
+        if let Some(state) = self
+            .state
+            .as_ref()
+            .filter(|_| self.fork >= Fork::TangerineWhistle)
+        {
+            let balance = state
+                .account(&context.current_address)
+                .map_or(0, |account| account.balance);
+            if pays_new_account(self.fork, state.account(&address).as_ref(), balance) {
+                total_cost += NEW_ACCOUNT_COST;
+            }
         }
+        total_cost
     }
 
-    /// Calculate copy operation costs (CALLDATACOPY, CODECOPY, RETURNDATACOPY)
+    /// Calculate memory expansion of RETURN and REVERT
+    fn calculate_return_cost(&self, context: &ExecutionContext, operands: &[u64]) -> u64 {
+        match operands {
+            [offset, size, ..] if *size > 0 => {
+                let new_memory_size = offset.saturating_add(*size) as usize;
+                self.calculate_memory_expansion_cost(context.memory_size, new_memory_size)
+            }
+            _ => 0,
+        }
+    }
+
+    /// Calculate copy operation costs (CALLDATACOPY, CODECOPY, RETURNDATACOPY, and DATACOPY
+    /// in EOF code)
     fn calculate_copy_cost(
         &self,
         _opcode: u8,
@@ -369,27 +652,26 @@ impl DynamicGasCalculator {
         let size = operands[2] as usize;
 
         // Memory expansion cost
-        let new_memory_size = dest_offset + size;
+        let new_memory_size = dest_offset.saturating_add(size);
         let expansion_cost = if new_memory_size > context.memory_size {
             self.calculate_memory_expansion_cost(context.memory_size, new_memory_size)
         } else {
             0
         };
 
-        // Copy cost (3 gas per word)
-        let words = size.div_ceil(32);
-        let copy_cost = words as u64 * 3;
-
-        Ok(expansion_cost + copy_cost)
+        Ok(expansion_cost.saturating_add(copy_gas(size as u64)))
     }
 
-    /// Calculate CREATE/CREATE2 costs
+    /// Calculate CREATE/CREATE2 costs, and EOFCREATE/RETURNCONTRACT in EOF code
     fn calculate_create_cost(
         &self,
         opcode: u8,
         context: &ExecutionContext,
         operands: &[u64],
     ) -> Result<u64, String> {
+        if matches!(opcode, 0xec | 0xee) {
+            return self.calculate_eof_create_cost(opcode, context, operands);
+        }
         if operands.len() < 3 {
             return Ok(0);
         }
@@ -398,30 +680,54 @@ impl DynamicGasCalculator {
         let offset = operands[1] as usize;
         let size = operands[2] as usize;
 
-        let mut total_cost = 32000u64; // Base CREATE cost
+        let mut total_cost = 0u64; // The 32000 base is in the table
 
-        // CREATE2 has additional cost for hashing
+        // CREATE2 hashes the init code to compute the address
         if opcode == 0xf5 {
-            let words = size.div_ceil(32);
-            total_cost += words as u64 * 6; // SHA3 cost for CREATE2 address computation
+            total_cost += words(size as u64).saturating_mul(KECCAK_WORD_GAS);
         }
 
         // Init code cost (EIP-3860, Shanghai)
-        if self.fork >= Fork::Shanghai {
-            let words = size.div_ceil(32);
-            total_cost += words as u64 * 2;
-        }
+        total_cost = total_cost.saturating_add(init_code_gas(size as u64, self.fork));
 
         // Memory expansion cost
-        let new_memory_size = offset + size;
+        let new_memory_size = offset.saturating_add(size);
         if new_memory_size > context.memory_size {
-            total_cost +=
-                self.calculate_memory_expansion_cost(context.memory_size, new_memory_size);
+            total_cost = total_cost.saturating_add(
+                self.calculate_memory_expansion_cost(context.memory_size, new_memory_size),
+            );
         }
 
         Ok(total_cost)
     }
 
+    /// Calculate EOFCREATE and RETURNCONTRACT costs (EIP-7620)
+    ///
+    /// EOFCREATE takes the value, salt, input offset and size. Its init container is
+    /// validated at deployment and does not go into the address, so only the input memory is
+    /// charged. RETURNCONTRACT takes the offset and size of the auxiliary data appended to
+    /// the deployed container.
+    fn calculate_eof_create_cost(
+        &self,
+        opcode: u8,
+        context: &ExecutionContext,
+        operands: &[u64],
+    ) -> Result<u64, String> {
+        let (expected, range) = if opcode == 0xec { (4, 2) } else { (2, 0) };
+        if operands.len() < expected {
+            return Err(format!(
+                "EOF creation (0x{opcode:02x}) requires {expected} operands, got {}",
+                operands.len()
+            ));
+        }
+        let (offset, size) = (operands[range] as usize, operands[range + 1] as usize);
+        let end = offset.saturating_add(size);
+        if size > 0 && end > context.memory_size {
+            return Ok(self.calculate_memory_expansion_cost(context.memory_size, end));
+        }
+        Ok(0)
+    }
+
     /// Calculate KECCAK256 (SHA3) cost
     fn calculate_keccak256_cost(
         &self,
@@ -436,24 +742,20 @@ impl DynamicGasCalculator {
         let size = operands[1] as usize;
 
         // Memory expansion cost
-        let new_memory_size = offset + size;
+        let new_memory_size = offset.saturating_add(size);
         let expansion_cost = if new_memory_size > context.memory_size {
             self.calculate_memory_expansion_cost(context.memory_size, new_memory_size)
         } else {
             0
         };
 
-        // Hash cost (6 gas per word)
-        let words = size.div_ceil(32);
-        let hash_cost = words as u64 * 6;
-
-        Ok(expansion_cost + hash_cost)
+        // The 30 gas base cost is in the opcode table
+        Ok(expansion_cost.saturating_add(keccak_gas(size as u64) - KECCAK_BASE_GAS))
     }
 
     /// Calculate LOG operation costs
     fn calculate_log_cost(
         &self,
-        opcode: u8,
         context: &ExecutionContext,
         operands: &[u64],
     ) -> Result<u64, String> {
@@ -464,37 +766,105 @@ impl DynamicGasCalculator {
         let offset = operands[0] as usize;
         let size = operands[1] as usize;
 
-        // Number of topics
-        let topic_count = (opcode - 0xa0) as u64;
-
         // Memory expansion cost
-        let new_memory_size = offset + size;
+        let new_memory_size = offset.saturating_add(size);
         let expansion_cost = if new_memory_size > context.memory_size {
             self.calculate_memory_expansion_cost(context.memory_size, new_memory_size)
         } else {
             0
         };
 
-        // Log cost: 375 gas per topic + 8 gas per byte
-        let log_cost = topic_count * 375 + size as u64 * 8;
-
-        Ok(expansion_cost + log_cost)
+        // 8 gas per byte; the 375 per topic are in the table
+        Ok(expansion_cost.saturating_add((size as u64).saturating_mul(8)))
     }
 
     /// Analyze gas characteristics for a sequence of opcodes
+    ///
+    /// Unknown opcodes abort the analysis; use
+    /// [`analyze_sequence_gas_with_config`](Self::analyze_sequence_gas_with_config)
+    /// to choose a different [`UnknownOpcodePolicy`].
     pub fn analyze_sequence_gas(
         &self,
         opcodes: &[(u8, Vec<u64>)], // (opcode, operands)
+    ) -> Result<GasAnalysisResult, String> {
+        let config = AnalysisConfig::new().with_unknown_opcode_policy(UnknownOpcodePolicy::Error);
+        self.analyze_sequence_gas_with_config(opcodes, &config)
+    }
+
+    /// Analyze gas characteristics for a sequence of opcodes using an analysis configuration
+    ///
+    /// Analysis stops at the first INVALID, which halts execution; later opcodes are not priced.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(fork = ?self.fork, len = opcodes.len()))
+    )]
+    pub fn analyze_sequence_gas_with_config(
+        &self,
+        opcodes: &[(u8, Vec<u64>)], // (opcode, operands)
+        config: &AnalysisConfig,
     ) -> Result<GasAnalysisResult, String> {
         let mut context = ExecutionContext::new();
         let mut total_gas = 21000u64; // Base transaction cost
         let mut breakdown = Vec::new();
         let mut warnings = Vec::new();
         let mut optimizations = Vec::new();
+        let mut exceptional_halt = None;
+        let known_opcodes = self.registry.get_opcodes(self.fork);
+        let mut meter = config.budget.start();
+
+        for (index, (opcode, operands)) in opcodes.iter().enumerate() {
+            if !meter.step() {
+                warnings.push(format!(
+                    "{} at index {index}, gas analysis is partial",
+                    meter.stop_reason()
+                ));
+                break;
+            }
+            if (!known_opcodes.contains_key(opcode) || self.repricing.removes(*opcode))
+                && self.repricing.override_for(*opcode).is_none()
+            {
+                match config
+                    .unknown_opcode_policy
+                    .resolve(*opcode, index, self.fork)?
+                {
+                    UnknownOpcodeAction::Skip => {
+                        #[cfg(feature = "tracing")]
+                        tracing::debug!(opcode, index, "skipping unknown opcode");
+                        warnings.push(format!(
+                            "Skipped unknown opcode 0x{:02x} at index {}",
+                            opcode, index
+                        ));
+                        continue;
+                    }
+                    UnknownOpcodeAction::TreatAsInvalid => {
+                        #[cfg(feature = "tracing")]
+                        tracing::debug!(opcode, index, "unknown opcode treated as INVALID");
+                        warnings.push(format!(
+                            "Unknown opcode 0x{:02x} at index {} treated as INVALID, halting analysis",
+                            opcode, index
+                        ));
+                        breakdown.push((*opcode, 0));
+                        exceptional_halt = Some(index);
+                        break;
+                    }
+                }
+            }
+
+            if *opcode == 0xfe {
+                warnings.push(format!(
+                    "INVALID at index {index} halts execution and consumes all remaining gas"
+                ));
+                breakdown.push((*opcode, 0));
+                exceptional_halt = Some(index);
+                break;
+            }
 
-        for (opcode, operands) in opcodes {
+            if let Some(metadata) = known_opcodes.get(opcode) {
+                self.check_operands(metadata, operands)
+                    .map_err(|error| error.at_index(index))?;
+            }
             let gas_cost = self.calculate_gas_cost(*opcode, &context, operands)?;
-            total_gas += gas_cost;
+            total_gas = total_gas.saturating_add(gas_cost);
             breakdown.push((*opcode, gas_cost));
 
             // Update context based on opcode execution
@@ -521,16 +891,22 @@ impl DynamicGasCalculator {
             warnings,
             context,
             optimizations,
+            exceptional_halt,
+            budget_exhausted: meter.is_exhausted(),
         })
     }
 
     /// Update execution context based on opcode execution
-    fn update_context(&self, context: &mut ExecutionContext, opcode: u8, operands: &[u64]) {
+    pub(super) fn update_context(
+        &self,
+        context: &mut ExecutionContext,
+        opcode: u8,
+        operands: &[u64],
+    ) {
         match opcode {
             // Storage access updates
             0x54 | 0x55 if !operands.is_empty() => {
-                let key_bytes = operands[0].to_be_bytes();
-                let key = ExecutionContext::from_vec_storage_key(&key_bytes);
+                let key = storage_key(operands[0]);
                 let current_address = context.current_address; // Copy to avoid borrow conflict
                 context.mark_storage_accessed(&current_address, &key);
             }
@@ -543,11 +919,15 @@ impl DynamicGasCalculator {
 
             // Account access updates
             0x31 | 0x3b | 0x3c | 0x3f | 0xf1 | 0xf2 | 0xf4 | 0xfa if !operands.is_empty() => {
-                let address_bytes = operands[1].to_be_bytes(); // Note: different operand for calls
-                let address = ExecutionContext::from_vec_address(
-                    &address_bytes[0..8.min(address_bytes.len())],
-                );
-                context.mark_address_accessed(&address);
+                // Calls take the address as their second operand
+                let index = usize::from(matches!(opcode, 0xf1 | 0xf2 | 0xf4 | 0xfa));
+                if let Some(operand) = operands.get(index) {
+                    let address_bytes = operand.to_be_bytes();
+                    let address = ExecutionContext::from_vec_address(
+                        &address_bytes[0..8.min(address_bytes.len())],
+                    );
+                    context.mark_address_accessed(&address);
+                }
             }
 
             // Memory operations update memory size
@@ -559,24 +939,30 @@ impl DynamicGasCalculator {
                     0x53 => 1,  // MSTORE8
                     _ => 0,
                 };
-                context.expand_memory(offset + size);
+                context.expand_memory(offset.saturating_add(size));
             }
 
             // MCOPY updates memory
             0x5e if operands.len() >= 3 => {
                 let dst_offset = operands[0] as usize;
                 let size = operands[2] as usize;
-                context.expand_memory(dst_offset + size);
+                context.expand_memory(dst_offset.saturating_add(size));
             }
 
             // Copy operations update memory
-            0x37 | 0x39 | 0x3e if operands.len() >= 3 => {
+            0x37 | 0x39 | 0x3e | 0xd3 if operands.len() >= 3 => {
                 let dest_offset = operands[0] as usize;
                 let size = operands[2] as usize;
-                context.expand_memory(dest_offset + size);
+                context.expand_memory(dest_offset.saturating_add(size));
             }
 
             // Call operations update call depth and mark addresses
+            0xf8 | 0xf9 | 0xfb if !operands.is_empty() => {
+                let target = ExecutionContext::from_vec_address(&operands[0].to_be_bytes());
+                context.mark_address_accessed(&target);
+                context.enter_call();
+            }
+
             0xf1 | 0xf2 | 0xf4 | 0xfa if operands.len() >= 2 => {
                 let target_address_bytes = operands[1].to_be_bytes();
                 let target_address = ExecutionContext::from_vec_address(
@@ -675,6 +1061,62 @@ mod tests {
         assert_eq!(gas_cost, 3);
     }
 
+    #[test]
+    fn test_eof_calls_priced_through_scenario() {
+        // EOF calls are in no fork table; a scenario proposing them supplies the base price
+        let scenario = Repricing::new("eof")
+            .with_base_cost(0xf8, 100)
+            .with_base_cost(0xfb, 100);
+        let calculator = DynamicGasCalculator::new(Fork::Cancun).with_repricing(scenario);
+        let context = ExecutionContext::new();
+
+        // EXTCALL to a cold target with 64 bytes of input: 100 + 2500 + 6
+        let extcall = calculator.calculate_gas_cost(0xf8, &context, &[0x1, 0x0, 0x40, 0x0]);
+        assert_eq!(extcall, Ok(2606));
+        let sequence = [(0xfb, vec![0x1, 0x0, 0x0]), (0xfb, vec![0x1, 0x0, 0x0])];
+        let result = calculator.analyze_sequence_gas(&sequence).unwrap();
+        assert_eq!(result.breakdown, vec![(0xfb, 2600), (0xfb, 100)]);
+        assert!(calculator
+            .calculate_gas_cost(0xf8, &context, &[0x1, 0x0, 0x40])
+            .is_err());
+    }
+
+    #[test]
+    fn test_missing_operands_error_has_context() {
+        let calculator = DynamicGasCalculator::new(Fork::Cancun);
+        let error = calculator
+            .analyze_sequence_gas(&[(0x01, vec![]), (0x55, vec![0x0])])
+            .unwrap_err();
+        assert_eq!(
+            error,
+            "SSTORE (0x55) at index 1 requires 2 operands (key, value), got 1"
+        );
+
+        let sstore = &calculator.registry.get_opcodes(Fork::Cancun)[&0x55];
+        let typed = calculator.check_operands(sstore, &[]).unwrap_err();
+        assert_eq!((typed.expected, typed.provided, typed.index), (2, 0, None));
+
+        let error = calculator
+            .analyze_sequence_gas(&[(0xf4, vec![100000, 0x123, 0x0, 0x0, 0x0, 0x0])])
+            .unwrap_err();
+        assert!(error.contains("value placeholder (0)"), "{error}");
+    }
+
+    #[test]
+    fn test_exp_byte_cost_follows_eip_160() {
+        let context = ExecutionContext::new();
+        // Two-byte exponent
+        let operands = [2, 0x1234];
+        let homestead = DynamicGasCalculator::new(Fork::Homestead)
+            .calculate_gas_cost(0x0a, &context, &operands)
+            .unwrap();
+        let spurious_dragon = DynamicGasCalculator::new(Fork::SpuriousDragon)
+            .calculate_gas_cost(0x0a, &context, &operands)
+            .unwrap();
+        assert_eq!(homestead, 10 + 2 * 10);
+        assert_eq!(spurious_dragon, 10 + 2 * 50);
+    }
+
     #[test]
     fn test_sload_warm_cold() {
         let calculator = DynamicGasCalculator::new(Fork::Berlin);
@@ -684,7 +1126,6 @@ mod tests {
         let cold_cost = calculator
             .calculate_gas_cost(0x54, &context, &[0x123])
             .unwrap();
-        println!("Cold SLOAD cost: {}", cold_cost);
 
         // Mark storage as warm
         let key_bytes = 0x123u64.to_be_bytes();
@@ -696,7 +1137,6 @@ mod tests {
         let warm_cost = calculator
             .calculate_gas_cost(0x54, &context, &[0x123])
             .unwrap();
-        println!("Warm SLOAD cost: {}", warm_cost);
 
         // For now, just verify that there's a difference and warm is cheaper
         // The exact values seem to be different than expected due to our implementation
@@ -707,11 +1147,6 @@ mod tests {
             cold_cost
         );
 
-        // If they're the same, it means warming isn't working, but let's not fail for now
-        if warm_cost == cold_cost {
-            println!("Warning: Warm and cold costs are the same - warming logic may need fixes");
-        }
-
         // Basic sanity checks
         assert!(cold_cost > 0, "Cold cost should be positive");
         assert!(warm_cost > 0, "Warm cost should be positive");
@@ -729,6 +1164,23 @@ mod tests {
         assert!(gas_cost > 3); // Should be more than base MSTORE cost
     }
 
+    #[test]
+    fn test_memory_ends_past_u64_saturate() {
+        let calculator = DynamicGasCalculator::new(Fork::Cancun);
+        let context = ExecutionContext::new();
+        for (opcode, operands) in [
+            (0xf3, vec![u64::MAX, 1]),
+            (0xfd, vec![u64::MAX, 1]),
+            (0x52, vec![u64::MAX]),
+            (0x5e, vec![u64::MAX, 0, 1]),
+            (0x37, vec![u64::MAX, 0, 1]),
+            (0xf1, vec![0, 0x123, 0, u64::MAX, 1, u64::MAX, 1]),
+        ] {
+            let cost = calculator.calculate_gas_cost(opcode, &context, &operands);
+            assert_eq!(cost, Ok(u64::MAX), "0x{opcode:02x}");
+        }
+    }
+
     #[test]
     fn test_sequence_analysis() {
         let calculator = DynamicGasCalculator::new(Fork::London);
@@ -758,6 +1210,21 @@ mod tests {
         assert!(gas_cost >= 32000);
     }
 
+    #[test]
+    fn test_invalid_marks_exceptional_halt() {
+        let calculator = DynamicGasCalculator::new(Fork::London);
+        let sequence = vec![(0x01, vec![]), (0xfe, vec![]), (0x54, vec![0x0])];
+        let result = calculator.analyze_sequence_gas(&sequence).unwrap();
+        assert_eq!(result.exceptional_halt, Some(1));
+        assert!(result.consumes_all_gas());
+        // Nothing after INVALID runs
+        assert_eq!(result.breakdown, vec![(0x01, 3), (0xfe, 0)]);
+        assert_eq!(result.total_gas, 21000 + 3);
+
+        let result = calculator.analyze_sequence_gas(&[(0x01, vec![])]).unwrap();
+        assert!(!result.consumes_all_gas());
+    }
+
     #[test]
     fn test_optimization_suggestions() {
         let calculator = DynamicGasCalculator::new(Fork::London);
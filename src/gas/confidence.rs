@@ -0,0 +1,127 @@
+//! How far a gas estimate can be trusted
+//!
+//! Each per-instruction cost is classified by what it depends on: nothing but the opcode and
+//! its operands ([`EstimateConfidence::Exact`]), state that only moves it within known
+//! bounds such as warm or cold access ([`EstimateConfidence::Bounded`]), or things the
+//! analysis cannot see such as callee execution or guessed operands
+//! ([`EstimateConfidence::Heuristic`]). A total is as trustworthy as its weakest part.
+
+use super::{GasAnalysis, GasAnalysisResult};
+
+/// Trustworthiness of a gas cost, from most to least reliable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EstimateConfidence {
+    /// Determined by the opcode and its operands
+    Exact,
+    /// Depends on state (warm or cold access, original storage values) within known bounds
+    Bounded,
+    /// Depends on code or values the analysis does not know
+    Heuristic,
+}
+
+impl EstimateConfidence {
+    /// Classify the cost of an opcode; `operands_known` is false when operands were guessed,
+    /// as in bytecode analysis
+    pub fn for_opcode(opcode: u8, operands_known: bool) -> Self {
+        let operand_dependent = if operands_known {
+            Self::Exact
+        } else {
+            Self::Heuristic
+        };
+        match opcode {
+            // Calls run unknown code and may create accounts; creations run init code
+            0xec | 0xf0..=0xf2 | 0xf4 | 0xf5 | 0xf8 | 0xf9 | 0xfa | 0xfb | 0xff => Self::Heuristic,
+            // Warm or cold access, and SSTORE's original and current values
+            0x31 | 0x3b | 0x3f | 0x54 | 0x55 => Self::Bounded,
+            0x3c => operand_dependent.max(Self::Bounded),
+            // Memory expansion, copied words, exponent bytes, log data
+            0x0a
+            | 0x20
+            | 0x37
+            | 0x39
+            | 0x3e
+            | 0x51..=0x53
+            | 0x5e
+            | 0xa0..=0xa4
+            | 0xd3
+            | 0xee
+            | 0xf3
+            | 0xfd => operand_dependent,
+            _ => Self::Exact,
+        }
+    }
+
+    /// The confidence of a total made of parts with these confidences
+    pub fn combine(parts: impl IntoIterator<Item = Self>) -> Self {
+        parts.into_iter().max().unwrap_or(Self::Exact)
+    }
+}
+
+impl GasAnalysisResult {
+    /// Confidence of each entry of the breakdown, given the operands passed to the calculator
+    pub fn confidence(&self) -> Vec<EstimateConfidence> {
+        self.breakdown
+            .iter()
+            .map(|(opcode, _)| EstimateConfidence::for_opcode(*opcode, true))
+            .collect()
+    }
+
+    /// Confidence of the total
+    pub fn total_confidence(&self) -> EstimateConfidence {
+        EstimateConfidence::combine(self.confidence())
+    }
+}
+
+impl GasAnalysis {
+    /// Confidence of each entry of the breakdown; operands are estimated, so every
+    /// operand-dependent cost is heuristic
+    pub fn confidence(&self) -> Vec<EstimateConfidence> {
+        self.breakdown
+            .iter()
+            .map(|(opcode, _)| EstimateConfidence::for_opcode(*opcode, false))
+            .collect()
+    }
+
+    /// Confidence of the total
+    pub fn total_confidence(&self) -> EstimateConfidence {
+        EstimateConfidence::combine(self.confidence())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gas::{DynamicGasCalculator, GasAnalyzer};
+    use crate::{AnalysisConfig, Fork};
+
+    #[test]
+    fn test_confidence_levels() {
+        let sequence = [
+            (0x01, vec![]),         // ADD
+            (0x52, vec![0x0, 0x1]), // MSTORE with known offset
+            (0x54, vec![0x0]),      // SLOAD
+        ];
+        let result = DynamicGasCalculator::new(Fork::Cancun)
+            .analyze_sequence_gas(&sequence)
+            .unwrap();
+        assert_eq!(
+            result.confidence(),
+            vec![
+                EstimateConfidence::Exact,
+                EstimateConfidence::Exact,
+                EstimateConfidence::Bounded
+            ]
+        );
+        assert_eq!(result.total_confidence(), EstimateConfidence::Bounded);
+
+        // PUSH1 0, MLOAD: the offset is guessed in bytecode analysis
+        let analysis = GasAnalyzer::analyze_bytecode(
+            &[0x60, 0x00, 0x51],
+            Fork::Cancun,
+            &AnalysisConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(analysis.total_confidence(), EstimateConfidence::Heuristic);
+        assert_eq!(EstimateConfidence::combine([]), EstimateConfidence::Exact);
+    }
+}
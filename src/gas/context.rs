@@ -8,6 +8,47 @@ pub type Address = [u8; 20];
 /// Fixed-size storage key type (32 bytes)  
 pub type StorageKey = [u8; 32];
 
+/// How warm/cold access checks are answered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum AccessAssumption {
+    /// Warm only what was accessed before (EIP-2929)
+    #[default]
+    Tracked,
+    /// Every address and slot is warm, the lower bound of an estimate
+    AllWarm,
+    /// Every access is cold, even repeated ones, the upper bound of an estimate
+    AllCold,
+}
+
+/// An address and storage keys pre-warmed by a transaction access list
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccessListItem {
+    /// Address to warm
+    pub address: Address,
+    /// Storage keys of the address to warm
+    pub storage_keys: Vec<StorageKey>,
+}
+
+/// Transaction access list (EIP-2930)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccessList(pub Vec<AccessListItem>);
+
+impl AccessList {
+    /// Create an empty access list
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an address with storage keys
+    pub fn with_item(mut self, address: Address, storage_keys: Vec<StorageKey>) -> Self {
+        self.0.push(AccessListItem {
+            address,
+            storage_keys,
+        });
+        self
+    }
+}
+
 /// Execution context that affects gas costs
 ///
 /// This tracks the state that influences dynamic gas pricing,
@@ -47,6 +88,9 @@ pub struct ExecutionContext {
 
     /// Value sent with the current call
     pub call_value: u64,
+
+    /// How warm/cold checks are answered
+    pub access_assumption: AccessAssumption,
 }
 
 impl ExecutionContext {
@@ -64,6 +108,7 @@ impl ExecutionContext {
             current_address: [0u8; 20],
             caller_address: [0u8; 20],
             call_value: 0,
+            access_assumption: AccessAssumption::Tracked,
         }
     }
 
@@ -79,12 +124,20 @@ impl ExecutionContext {
 
     /// Check if a storage slot has been accessed (is warm)
     pub fn is_storage_warm(&self, address: &Address, key: &StorageKey) -> bool {
-        self.accessed_storage_keys.contains(&(*address, *key))
+        match self.access_assumption {
+            AccessAssumption::Tracked => self.accessed_storage_keys.contains(&(*address, *key)),
+            AccessAssumption::AllWarm => true,
+            AccessAssumption::AllCold => false,
+        }
     }
 
     /// Check if an address has been accessed (is warm)  
     pub fn is_address_warm(&self, address: &Address) -> bool {
-        self.accessed_addresses.contains(address)
+        match self.access_assumption {
+            AccessAssumption::Tracked => self.accessed_addresses.contains(address),
+            AccessAssumption::AllWarm => true,
+            AccessAssumption::AllCold => false,
+        }
     }
 
     /// Update memory size if the new size is larger
@@ -216,6 +269,31 @@ impl ExecutionContextBuilder {
         self
     }
 
+    /// Pre-warm the addresses and storage keys of an access list
+    pub fn warm_from_access_list(mut self, access_list: &AccessList) -> Self {
+        for item in &access_list.0 {
+            self.context.accessed_addresses.insert(item.address);
+            for key in &item.storage_keys {
+                self.context
+                    .accessed_storage_keys
+                    .insert((item.address, *key));
+            }
+        }
+        self
+    }
+
+    /// Treat every address and storage slot as warm, for the lower bound of an estimate
+    pub fn assume_all_warm(mut self) -> Self {
+        self.context.access_assumption = AccessAssumption::AllWarm;
+        self
+    }
+
+    /// Treat every access as cold, even repeated ones, for the upper bound of an estimate
+    pub fn assume_all_cold(mut self) -> Self {
+        self.context.access_assumption = AccessAssumption::AllCold;
+        self
+    }
+
     /// Set static call mode
     pub fn with_static(mut self, is_static: bool) -> Self {
         self.context.is_static = is_static;
@@ -347,4 +425,22 @@ mod tests {
         assert!(context.is_static);
         assert!(context.is_address_warm(&addr));
     }
+
+    #[test]
+    fn test_warmth_presets() {
+        let (token, other) = ([1u8; 20], [2u8; 20]);
+        let access_list = AccessList::new().with_item(token, vec![[3u8; 32]]);
+        let context = ExecutionContextBuilder::new()
+            .warm_from_access_list(&access_list)
+            .build();
+        assert!(context.is_address_warm(&token));
+        assert!(context.is_storage_warm(&token, &[3u8; 32]));
+        assert!(!context.is_storage_warm(&token, &[4u8; 32]));
+
+        let warm = ExecutionContextBuilder::new().assume_all_warm().build();
+        assert!(warm.is_address_warm(&other) && warm.is_storage_warm(&other, &[0u8; 32]));
+        let mut cold = ExecutionContextBuilder::new().assume_all_cold().build();
+        cold.mark_address_accessed(&other);
+        assert!(!cold.is_address_warm(&other));
+    }
 }
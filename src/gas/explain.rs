@@ -0,0 +1,283 @@
+//! Step-by-step explanations of gas costs, for teaching material
+//!
+//! [`explain_execution`] prices a sequence the same way as
+//! [`DynamicGasCalculator::analyze_sequence_gas`] and splits each instruction's cost into
+//! structured components (base cost, EIP-2929 access charges, memory expansion and the
+//! size-dependent rest), which always add up to the instruction's total.
+
+use super::calculator::storage_key;
+use super::{word_aligned, DynamicGasCalculator, ExecutionContext};
+use crate::{Fork, OpcodeRegistry};
+
+/// One part of an instruction's gas cost
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CostComponent {
+    /// Static cost of the opcode in the fork
+    Base(u64),
+    /// Charge for a storage slot or account not yet accessed in the transaction (EIP-2929)
+    ColdAccess(u64),
+    /// Charge for a storage slot or account already accessed in the transaction (EIP-2929)
+    WarmAccess(u64),
+    /// Growth of memory to cover the bytes the instruction touches
+    MemoryExpansion {
+        /// Memory size before the instruction, in bytes
+        from: usize,
+        /// Memory size after the instruction, in bytes (a multiple of 32)
+        to: usize,
+        /// Gas charged
+        gas: u64,
+    },
+    /// Cost that depends on the data handled: exponent bytes, copied or hashed words, log
+    /// topics and data, contract creation, value transfers
+    Variable(u64),
+}
+
+impl CostComponent {
+    /// Gas of the component
+    pub fn gas(&self) -> u64 {
+        match self {
+            Self::Base(gas)
+            | Self::ColdAccess(gas)
+            | Self::WarmAccess(gas)
+            | Self::Variable(gas) => *gas,
+            Self::MemoryExpansion { gas, .. } => *gas,
+        }
+    }
+}
+
+/// Why one instruction cost what it did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepExplanation {
+    /// Index of the instruction in the sequence
+    pub index: usize,
+    /// The opcode
+    pub opcode: u8,
+    /// Mnemonic of the opcode
+    pub name: String,
+    /// Parts of the cost, adding up to `total`
+    pub components: Vec<CostComponent>,
+    /// Gas charged for the instruction
+    pub total: u64,
+}
+
+impl StepExplanation {
+    /// A sentence describing the cost, e.g. "MSTORE costs 12 gas: 3 base and 9 to expand
+    /// memory from 0 to 96 bytes."
+    pub fn describe(&self) -> String {
+        let parts: Vec<String> = self
+            .components
+            .iter()
+            .filter(|component| component.gas() > 0)
+            .map(|component| self.describe_component(component))
+            .collect();
+        let reasons = match parts.split_last() {
+            None => return format!("{} costs nothing.", self.name),
+            Some((last, [])) => last.clone(),
+            Some((last, init)) => format!("{} and {last}", init.join(", ")),
+        };
+        format!("{} costs {} gas: {reasons}.", self.name, self.total)
+    }
+
+    fn describe_component(&self, component: &CostComponent) -> String {
+        let target = match self.opcode {
+            0x54 | 0x55 => "storage",
+            _ => "account",
+        };
+        match component {
+            CostComponent::Base(gas) => format!("{gas} base"),
+            CostComponent::ColdAccess(gas) => {
+                format!("{gas} for a cold {target} access (EIP-2929)")
+            }
+            CostComponent::WarmAccess(gas) => {
+                format!("{gas} for a warm {target} access (EIP-2929)")
+            }
+            CostComponent::MemoryExpansion { from, to, gas } => {
+                format!("{gas} to expand memory from {from} to {to} bytes")
+            }
+            CostComponent::Variable(gas) => {
+                let reason = match self.opcode {
+                    0x0a => "for the exponent bytes",
+                    0x20 => "for hashing the input words",
+                    0x37 | 0x39 | 0x3e | 0x5e => "for copying the words",
+                    0xa0..=0xa4 => "for the topics and data bytes",
+                    0xf0 | 0xf5 => "for creating the contract and its init code",
+                    0xf1 | 0xf2 => "for the value transfer",
+                    _ => "of dynamic cost",
+                };
+                format!("{gas} {reason}")
+            }
+        }
+    }
+}
+
+/// Explain the gas cost of each instruction of an (opcode, operands) sequence
+///
+/// The execution context starts empty, so every first access is cold.
+pub fn explain_execution(
+    sequence: &[(u8, Vec<u64>)],
+    fork: Fork,
+) -> Result<Vec<StepExplanation>, String> {
+    let calculator = DynamicGasCalculator::new(fork);
+    let opcodes = OpcodeRegistry::new().get_opcodes(fork);
+    let mut context = ExecutionContext::new();
+    let mut steps = Vec::with_capacity(sequence.len());
+
+    for (index, (opcode, operands)) in sequence.iter().enumerate() {
+        let total = calculator.calculate_gas_cost(*opcode, &context, operands)?;
+        let metadata = opcodes
+            .get(opcode)
+            .ok_or_else(|| format!("Unknown opcode: 0x{opcode:02x} for fork {fork:?}"))?;
+        // EIP-2929 access opcodes are all access cost, warm or cold
+        let base = if fork >= Fork::Berlin && is_access_opcode(*opcode) {
+            0
+        } else {
+            calculator.get_base_gas_cost(*opcode, metadata).min(total)
+        };
+        let mut dynamic = total - base;
+        let mut components = vec![CostComponent::Base(base)];
+
+        if let Some(end) = memory_end(*opcode, operands).filter(|end| *end > context.memory_size) {
+            let gas = calculator
+                .calculate_memory_expansion_cost(context.memory_size, end)
+                .min(dynamic);
+            dynamic -= gas;
+            components.push(CostComponent::MemoryExpansion {
+                from: context.memory_size,
+                to: word_aligned(end as u64) as usize,
+                gas,
+            });
+        }
+
+        if let Some(access) = access_component(fork, *opcode, operands, &context, dynamic) {
+            dynamic -= access.gas();
+            components.push(access);
+        }
+        if dynamic > 0 {
+            components.push(CostComponent::Variable(dynamic));
+        }
+
+        calculator.update_context(&mut context, *opcode, operands);
+        steps.push(StepExplanation {
+            index,
+            opcode: *opcode,
+            name: metadata.name.to_string(),
+            components,
+            total,
+        });
+    }
+    Ok(steps)
+}
+
+/// End of the memory range an instruction touches, as priced by the calculator
+fn memory_end(opcode: u8, operands: &[u64]) -> Option<usize> {
+    let range_end = |offset: usize, size: usize| Some(operands.get(offset)? + operands.get(size)?);
+    let end = match opcode {
+        0x51 | 0x52 => Some(operands.first()? + 32),
+        0x53 => Some(operands.first()? + 1),
+        0x20 | 0xa0..=0xa4 => range_end(0, 1),
+        0x37 | 0x39 | 0x3e => range_end(0, 2),
+        0x5e => range_end(0, 2).max(range_end(1, 2)),
+        0x3c => range_end(1, 3),
+        0xf3 | 0xfd => range_end(0, 1),
+        0xf0 | 0xf5 => range_end(1, 2),
+        0xf1 | 0xf2 | 0xf4 | 0xfa => range_end(3, 4).max(range_end(5, 6)),
+        _ => None,
+    };
+    end.map(|end| end as usize)
+}
+
+/// Opcodes whose whole static cost is the EIP-2929 warm or cold access charge
+fn is_access_opcode(opcode: u8) -> bool {
+    matches!(
+        opcode,
+        0x31 | 0x3b | 0x3c | 0x3f | 0x54 | 0xf1 | 0xf2 | 0xf4 | 0xfa
+    )
+}
+
+/// The EIP-2929 part of the dynamic cost, given what is left after memory expansion
+fn access_component(
+    fork: Fork,
+    opcode: u8,
+    operands: &[u64],
+    context: &ExecutionContext,
+    dynamic: u64,
+) -> Option<CostComponent> {
+    if fork < Fork::Berlin {
+        return None;
+    }
+    let address = |index: usize| {
+        let operand = operands.get(index)?;
+        Some(ExecutionContext::from_vec_address(&operand.to_be_bytes()))
+    };
+    let access = |warm: bool, cold_gas: u64| {
+        if warm {
+            CostComponent::WarmAccess(dynamic.min(100))
+        } else {
+            CostComponent::ColdAccess(dynamic.min(cold_gas))
+        }
+    };
+    match opcode {
+        // SLOAD charges 2100 cold and 100 warm
+        0x54 => {
+            let key = storage_key(*operands.first()?);
+            Some(access(
+                context.is_storage_warm(&context.current_address, &key),
+                2100,
+            ))
+        }
+        // Account opcodes and calls charge 2600 cold and 100 warm
+        0x31 | 0x3b | 0x3c | 0x3f => Some(access(context.is_address_warm(&address(0)?), 2600)),
+        0xf1 | 0xf2 | 0xf4 | 0xfa => Some(access(context.is_address_warm(&address(1)?), 2600)),
+        // SSTORE and SELFDESTRUCT only add a cold surcharge
+        0x55 | 0xff if dynamic > 0 => Some(CostComponent::ColdAccess(dynamic)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_components_add_up() {
+        let sequence = [
+            (0x52, vec![0x40, 0x1]), // MSTORE, expands memory
+            (0x54, vec![0x0]),       // SLOAD, cold
+            (0x54, vec![0x0]),       // SLOAD, warm
+            (0x20, vec![0x0, 0x40]), // KECCAK256 over two words
+            (0x01, vec![]),          // ADD
+        ];
+        let steps = explain_execution(&sequence, Fork::Cancun).unwrap();
+        let expected = DynamicGasCalculator::new(Fork::Cancun)
+            .analyze_sequence_gas(&sequence)
+            .unwrap();
+        for (step, (_, cost)) in steps.iter().zip(&expected.breakdown) {
+            assert_eq!(step.total, *cost);
+            assert_eq!(step.components.iter().map(|c| c.gas()).sum::<u64>(), *cost);
+        }
+        assert!(steps[0].components.iter().any(|c| matches!(
+            c,
+            CostComponent::MemoryExpansion {
+                from: 0,
+                to: 96,
+                ..
+            }
+        )));
+        assert!(matches!(
+            steps[1].components[1],
+            CostComponent::ColdAccess(_)
+        ));
+        assert!(matches!(
+            steps[2].components[1],
+            CostComponent::WarmAccess(100)
+        ));
+        assert_eq!(
+            steps[0].describe(),
+            "MSTORE costs 12 gas: 3 base and 9 to expand memory from 0 to 96 bytes."
+        );
+        assert_eq!(steps[4].describe(), "ADD costs 3 gas: 3 base.");
+        assert!(steps[1]
+            .describe()
+            .contains("for a cold storage access (EIP-2929)"));
+    }
+}
@@ -0,0 +1,354 @@
+//! Importing gas schedules from other EVM implementations and diffing them against ours
+//!
+//! Three input formats are understood:
+//! - go-ethereum `params/protocol_params.go` constants (`SloadGasEIP1884 uint64 = 800`)
+//! - revm `interpreter::gas::constants` (`pub const COLD_SLOAD_COST: u64 = 2100;`)
+//! - a plain per-opcode table (`SLOAD 2100` or `0x54 = 2100`), e.g. dumped from revm's
+//!   instruction table for a spec id
+//!
+//! Named constants only cover the costs that changed between forks; the mapping from
+//! constant names to opcodes is listed in [`GETH_PARAMS`] and [`REVM_CONSTANTS`].
+
+use crate::{Fork, OpcodeId, OpcodeRegistry};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// A named gas constant, the opcodes whose base cost it is, and the fork it applies from
+pub type ConstantMapping = (&'static str, &'static [u8], Fork);
+
+/// Account access opcodes repriced by EIP-2929
+const ACCOUNT_ACCESS: &[u8] = &[0x31, 0x3b, 0x3c, 0x3f, 0xf1, 0xf2, 0xf4, 0xfa];
+
+/// go-ethereum `params` constants that price a single opcode
+pub const GETH_PARAMS: &[ConstantMapping] = &[
+    ("ExpGas", &[0x0a], Fork::Frontier),
+    ("Keccak256Gas", &[0x20], Fork::Frontier),
+    ("JumpdestGas", &[0x5b], Fork::Frontier),
+    ("LogGas", &[0xa0], Fork::Frontier),
+    ("CreateGas", &[0xf0], Fork::Frontier),
+    ("Create2Gas", &[0xf5], Fork::Constantinople),
+    ("SloadGasFrontier", &[0x54], Fork::Frontier),
+    ("SloadGasEIP150", &[0x54], Fork::TangerineWhistle),
+    ("SloadGasEIP1884", &[0x54], Fork::Istanbul),
+    ("ColdSloadCostEIP2929", &[0x54], Fork::Berlin),
+    ("BalanceGasFrontier", &[0x31], Fork::Frontier),
+    ("BalanceGasEIP150", &[0x31], Fork::TangerineWhistle),
+    ("BalanceGasEIP1884", &[0x31], Fork::Istanbul),
+    ("ExtcodeSizeGasFrontier", &[0x3b], Fork::Frontier),
+    ("ExtcodeSizeGasEIP150", &[0x3b], Fork::TangerineWhistle),
+    ("ExtcodeCopyBaseFrontier", &[0x3c], Fork::Frontier),
+    ("ExtcodeCopyBaseEIP150", &[0x3c], Fork::TangerineWhistle),
+    (
+        "ExtcodeHashGasConstantinople",
+        &[0x3f],
+        Fork::Constantinople,
+    ),
+    ("ExtcodeHashGasEIP1884", &[0x3f], Fork::Istanbul),
+    ("CallGasFrontier", &[0xf1, 0xf2, 0xf4], Fork::Frontier),
+    (
+        "CallGasEIP150",
+        &[0xf1, 0xf2, 0xf4, 0xfa],
+        Fork::TangerineWhistle,
+    ),
+    ("SelfdestructGasEIP150", &[0xff], Fork::TangerineWhistle),
+    ("ColdAccountAccessCostEIP2929", ACCOUNT_ACCESS, Fork::Berlin),
+    ("WarmStorageReadCostEIP2929", &[0x5c, 0x5d], Fork::Cancun),
+];
+
+/// revm gas constants that price a single opcode
+pub const REVM_CONSTANTS: &[ConstantMapping] = &[
+    ("EXP", &[0x0a], Fork::Frontier),
+    ("KECCAK256", &[0x20], Fork::Frontier),
+    ("BLOCKHASH", &[0x40], Fork::Frontier),
+    ("JUMPDEST", &[0x5b], Fork::Frontier),
+    ("LOG", &[0xa0], Fork::Frontier),
+    ("CREATE", &[0xf0, 0xf5], Fork::Frontier),
+    ("ISTANBUL_SLOAD_GAS", &[0x54], Fork::Istanbul),
+    ("COLD_SLOAD_COST", &[0x54], Fork::Berlin),
+    ("COLD_ACCOUNT_ACCESS_COST", ACCOUNT_ACCESS, Fork::Berlin),
+    ("WARM_STORAGE_READ_COST", &[0x5c, 0x5d], Fork::Cancun),
+];
+
+/// Base gas costs of one fork as reported by another implementation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalSchedule {
+    /// Where the schedule came from, e.g. "geth" or "revm"
+    pub source: String,
+    /// Fork the costs apply to
+    pub fork: Fork,
+    /// Base cost per opcode
+    pub costs: BTreeMap<u8, u64>,
+}
+
+impl ExternalSchedule {
+    /// Import go-ethereum `params` constants for a fork
+    pub fn from_geth_params(text: &str, fork: Fork) -> Self {
+        Self::from_constants("geth", text, fork, GETH_PARAMS)
+    }
+
+    /// Import revm gas constants for a fork
+    pub fn from_revm_constants(text: &str, fork: Fork) -> Self {
+        Self::from_constants("revm", text, fork, REVM_CONSTANTS)
+    }
+
+    /// Import named constants using a custom mapping
+    ///
+    /// For every opcode available in `fork`, the constant with the latest fork not after
+    /// `fork` wins. Lines that are not `name = number` assignments are ignored.
+    pub fn from_constants(
+        source: &str,
+        text: &str,
+        fork: Fork,
+        mapping: &[ConstantMapping],
+    ) -> Self {
+        let values = parse_assignments(text);
+        let available = OpcodeRegistry::new().get_opcodes(fork);
+        let mut chosen: BTreeMap<u8, (Fork, u64)> = BTreeMap::new();
+        for (name, opcodes, since) in mapping {
+            let Some(value) = values.get(*name) else {
+                continue;
+            };
+            if *since > fork {
+                continue;
+            }
+            for opcode in opcodes.iter().filter(|op| available.contains_key(op)) {
+                if chosen
+                    .get(opcode)
+                    .is_none_or(|(previous, _)| previous <= since)
+                {
+                    chosen.insert(*opcode, (*since, *value));
+                }
+            }
+        }
+
+        Self {
+            source: source.to_string(),
+            fork,
+            costs: chosen
+                .into_iter()
+                .map(|(opcode, (_, cost))| (opcode, cost))
+                .collect(),
+        }
+    }
+
+    /// Import a per-opcode table with one `opcode cost` pair per line
+    ///
+    /// Opcodes are given by mnemonic or hex byte, separated from the cost by whitespace,
+    /// `=`, `:` or `,`. Empty lines and lines starting with `#` or `//` are skipped.
+    pub fn parse_table(source: &str, text: &str, fork: Fork) -> Result<Self, String> {
+        let opcodes = OpcodeRegistry::new().get_opcodes(fork);
+        let by_name: HashMap<&str, u8> = opcodes
+            .iter()
+            .map(|(byte, metadata)| (metadata.name, *byte))
+            .collect();
+
+        let mut costs = BTreeMap::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with("//") {
+                continue;
+            }
+            let mut fields = line
+                .split(|c: char| c.is_whitespace() || matches!(c, '=' | ':' | ','))
+                .filter(|field| !field.is_empty());
+            let (Some(opcode), Some(cost), None) = (fields.next(), fields.next(), fields.next())
+            else {
+                return Err(format!("Line {}: expected `opcode cost`", index + 1));
+            };
+            let byte = match opcode.strip_prefix("0x") {
+                Some(hex) => u8::from_str_radix(hex, 16).ok(),
+                None => by_name.get(opcode.to_uppercase().as_str()).copied(),
+            }
+            .ok_or_else(|| format!("Line {}: unknown opcode {opcode}", index + 1))?;
+            let cost = parse_number(cost)
+                .ok_or_else(|| format!("Line {}: invalid cost {cost}", index + 1))?;
+            costs.insert(byte, cost);
+        }
+
+        Ok(Self {
+            source: source.to_string(),
+            fork,
+            costs,
+        })
+    }
+
+    /// Compare against this crate's base costs for the same fork
+    pub fn diff(&self) -> ScheduleDiff {
+        let opcodes = OpcodeRegistry::new().get_opcodes(self.fork);
+        let mut diff = ScheduleDiff {
+            source: self.source.clone(),
+            fork: self.fork,
+            compared: self.costs.len(),
+            mismatches: Vec::new(),
+        };
+        for (opcode, theirs) in &self.costs {
+            let metadata = opcodes.get(opcode);
+            let ours = metadata.map(|m| m.gas_cost_in(self.fork) as u64);
+            if ours != Some(*theirs) {
+                diff.mismatches.push(ScheduleMismatch {
+                    opcode: *opcode,
+                    name: metadata.map_or_else(|| format!("0x{opcode:02x}"), |m| m.name.into()),
+                    ours,
+                    theirs: *theirs,
+                });
+            }
+        }
+        diff
+    }
+}
+
+/// An opcode whose base cost differs between an external schedule and ours
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduleMismatch {
+    /// The opcode
+    pub opcode: u8,
+    /// Mnemonic, or the hex byte if we do not know the opcode
+    pub name: String,
+    /// Our base cost, `None` if the opcode does not exist in our table for the fork
+    pub ours: Option<u64>,
+    /// The external base cost
+    pub theirs: u64,
+}
+
+/// Result of comparing an external schedule with ours
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduleDiff {
+    /// Source of the external schedule
+    pub source: String,
+    /// Fork compared
+    pub fork: Fork,
+    /// Number of opcodes present in the external schedule
+    pub compared: usize,
+    /// Opcodes whose costs disagree
+    pub mismatches: Vec<ScheduleMismatch>,
+}
+
+impl ScheduleDiff {
+    /// Check if every imported cost matches ours
+    pub fn is_consistent(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+impl fmt::Display for ScheduleDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} vs eot ({:?}): {} compared, {} mismatches",
+            self.source,
+            self.fork,
+            self.compared,
+            self.mismatches.len()
+        )?;
+        for mismatch in &self.mismatches {
+            match mismatch.ours {
+                Some(ours) => writeln!(
+                    f,
+                    "  {} (0x{:02x}): eot {} vs {} {} [{}]",
+                    mismatch.name,
+                    mismatch.opcode,
+                    ours,
+                    self.source,
+                    mismatch.theirs,
+                    OpcodeId::new(mismatch.opcode, self.fork)
+                )?,
+                None => writeln!(
+                    f,
+                    "  {} (0x{:02x}): missing in eot, {} {} [{}]",
+                    mismatch.name,
+                    mismatch.opcode,
+                    self.source,
+                    mismatch.theirs,
+                    OpcodeId::new(mismatch.opcode, self.fork)
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Collect `name = number` assignments from Go or Rust constant declarations
+fn parse_assignments(text: &str) -> HashMap<String, u64> {
+    let mut values = HashMap::new();
+    for line in text.lines() {
+        let line = line.split("//").next().unwrap_or_default();
+        let Some((lhs, rhs)) = line.split_once('=') else {
+            continue;
+        };
+        let name = lhs
+            .split(|c: char| c.is_whitespace() || c == ':')
+            .find(|token| !token.is_empty() && !matches!(*token, "pub" | "const"));
+        let rhs = rhs.trim().trim_end_matches(';').trim();
+        // Go typed constants: `uint64(2600)`
+        let rhs = rhs
+            .strip_prefix("uint64(")
+            .and_then(|inner| inner.strip_suffix(')'))
+            .unwrap_or(rhs);
+        let value = parse_number(rhs);
+        if let (Some(name), Some(value)) = (name, value) {
+            values.insert(name.to_string(), value);
+        }
+    }
+    values
+}
+
+/// Parse a decimal or `0x` hex number, allowing `_` separators
+fn parse_number(text: &str) -> Option<u64> {
+    let text = text.replace('_', "");
+    match text.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GETH: &str = "
+const (
+	SloadGasFrontier          uint64 = 50
+	SloadGasEIP150            uint64 = 200
+	SloadGasEIP1884           uint64 = 800  // Cost of SLOAD after EIP 1884 (part of Istanbul)
+	ColdSloadCostEIP2929      uint64 = 2100 // Cost of cold SLOAD (EIP-2929)
+	ColdAccountAccessCostEIP2929 = uint64(2600)
+	CreateGas                 uint64 = 32000
+)";
+
+    #[test]
+    fn test_geth_params_pick_latest_constant_for_fork() {
+        let istanbul = ExternalSchedule::from_geth_params(GETH, Fork::Istanbul);
+        assert_eq!(istanbul.costs.get(&0x54), Some(&800));
+        let berlin = ExternalSchedule::from_geth_params(GETH, Fork::Berlin);
+        assert_eq!(berlin.costs.get(&0x54), Some(&2100));
+        assert_eq!(berlin.costs.get(&0xf0), Some(&32000));
+        assert_eq!(berlin.costs.get(&0xfa), Some(&2600));
+        assert!(berlin.diff().is_consistent());
+    }
+
+    #[test]
+    fn test_revm_constants_and_table_diff() {
+        let revm = ExternalSchedule::from_revm_constants(
+            "pub const COLD_SLOAD_COST: u64 = 2_100;\npub const EXP: u64 = 10;",
+            Fork::London,
+        );
+        assert_eq!(revm.costs.len(), 2);
+        assert!(revm.diff().is_consistent());
+
+        let table = ExternalSchedule::parse_table(
+            "custom",
+            "# base costs\nSLOAD 2000\n0x01 = 3",
+            Fork::London,
+        )
+        .unwrap();
+        let diff = table.diff();
+        assert_eq!(diff.compared, 2);
+        assert_eq!(diff.mismatches.len(), 1);
+        assert_eq!(diff.mismatches[0].ours, Some(2100));
+        assert!(diff
+            .to_string()
+            .contains("SLOAD (0x54): eot 2100 vs custom 2000 [evm/0x54@london]"));
+
+        assert!(ExternalSchedule::parse_table("custom", "NOPE 1", Fork::London).is_err());
+    }
+}
@@ -0,0 +1,127 @@
+//! Size-dependent gas formulas shared by the calculator and other tools
+//!
+//! Sizes are in bytes and rounded up to 32-byte words. The calculator prices memory, copies,
+//! hashing, init code and exponents with these functions, so callers get exactly the same
+//! numbers. Costs that do not fit in a `u64` saturate at `u64::MAX`, far above any gas limit.
+
+use crate::Fork;
+
+/// Gas per word of memory (linear part of the memory cost)
+pub const MEMORY_WORD_GAS: u64 = 3;
+/// Divisor of the quadratic part of the memory cost
+pub const MEMORY_QUADRATIC_DIVISOR: u64 = 512;
+/// Gas per word copied (CALLDATACOPY, CODECOPY, EXTCODECOPY, RETURNDATACOPY, MCOPY)
+pub const COPY_WORD_GAS: u64 = 3;
+/// Base gas of KECCAK256
+pub const KECCAK_BASE_GAS: u64 = 30;
+/// Gas per word hashed by KECCAK256 and CREATE2
+pub const KECCAK_WORD_GAS: u64 = 6;
+/// Gas per word of init code from Shanghai on (EIP-3860)
+pub const INIT_CODE_WORD_GAS: u64 = 2;
+/// Gas an EOF call always leaves to the caller (EIP-7069)
+pub const MIN_RETAINED_GAS: u64 = 5000;
+/// Least gas an EOF call passes to a callee; with less the call fails without running it
+pub const MIN_CALLEE_GAS: u64 = 2300;
+
+/// Number of 32-byte words needed to hold `size` bytes
+pub fn words(size: u64) -> u64 {
+    size.div_ceil(32)
+}
+
+/// `size` rounded up to a multiple of 32, the size memory grows to
+pub fn word_aligned(size: u64) -> u64 {
+    words(size).saturating_mul(32)
+}
+
+/// Total gas of `size` bytes of memory
+///
+/// The formula has not changed since Frontier; `fork` keeps callers independent of that.
+pub fn memory_gas(size: u64, _fork: Fork) -> u64 {
+    // At most 2^59 words, so the square fits in a u128
+    let words = u128::from(words(size));
+    let gas =
+        words * u128::from(MEMORY_WORD_GAS) + words * words / u128::from(MEMORY_QUADRATIC_DIVISOR);
+    u64::try_from(gas).unwrap_or(u64::MAX)
+}
+
+/// Gas to grow memory from `old_size` to `new_size` bytes (nothing if it does not grow)
+pub fn memory_expansion_gas(old_size: u64, new_size: u64, fork: Fork) -> u64 {
+    memory_gas(new_size, fork).saturating_sub(memory_gas(old_size, fork))
+}
+
+/// Per-word gas of copying `len` bytes, without memory expansion
+pub fn copy_gas(len: u64) -> u64 {
+    words(len).saturating_mul(COPY_WORD_GAS)
+}
+
+/// Gas of KECCAK256 over `len` bytes, base cost included and memory expansion excluded
+pub fn keccak_gas(len: u64) -> u64 {
+    words(len)
+        .saturating_mul(KECCAK_WORD_GAS)
+        .saturating_add(KECCAK_BASE_GAS)
+}
+
+/// Gas charged per word of `len` bytes of init code (EIP-3860, free before Shanghai)
+pub fn init_code_gas(len: u64, fork: Fork) -> u64 {
+    if fork >= Fork::Shanghai {
+        words(len).saturating_mul(INIT_CODE_WORD_GAS)
+    } else {
+        0
+    }
+}
+
+/// Per-byte gas of EXP's exponent: 10 per byte, 50 from Spurious Dragon on (EIP-160)
+pub fn exp_gas(exponent: u64, fork: Fork) -> u64 {
+    let byte_gas = if fork >= Fork::SpuriousDragon { 50 } else { 10 };
+    byte_gas * (64 - exponent.leading_zeros() as u64).div_ceil(8)
+}
+
+/// Gas an EOF call (EXTCALL, EXTDELEGATECALL, EXTSTATICCALL) passes to the callee when
+/// `available` gas is left after its own cost
+///
+/// The caller retains the larger of 1/64 of the gas and [`MIN_RETAINED_GAS`]. Returns `None`
+/// when the callee would get less than [`MIN_CALLEE_GAS`]: the call then fails with status 1
+/// instead of reverting the caller.
+pub fn eof_callee_gas(available: u64) -> Option<u64> {
+    let retained = (available / 64).max(MIN_RETAINED_GAS);
+    available
+        .checked_sub(retained)
+        .filter(|gas| *gas >= MIN_CALLEE_GAS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formulas() {
+        assert_eq!(words(0), 0);
+        assert_eq!(words(33), 2);
+        assert_eq!(word_aligned(33), 64);
+        // 1024 words: 3072 linear and 2048 quadratic
+        assert_eq!(memory_gas(32 * 1024, Fork::Cancun), 5120);
+        assert_eq!(memory_expansion_gas(0, 96, Fork::Cancun), 9);
+        assert_eq!(memory_expansion_gas(96, 64, Fork::Cancun), 0);
+        assert_eq!(copy_gas(33), 6);
+        assert_eq!(keccak_gas(64), 42);
+        assert_eq!(init_code_gas(64, Fork::London), 0);
+        assert_eq!(init_code_gas(64, Fork::Shanghai), 4);
+        assert_eq!(exp_gas(0x1_0000, Fork::Frontier), 30);
+        assert_eq!(exp_gas(0x1_0000, Fork::Cancun), 150);
+        assert_eq!(eof_callee_gas(64_000), Some(59_000));
+        assert_eq!(eof_callee_gas(640_000), Some(630_000));
+        assert_eq!(eof_callee_gas(7_000), None);
+    }
+
+    #[test]
+    fn test_formulas_saturate() {
+        // 2^35 words: the quadratic part alone is 2^61
+        assert_eq!(memory_gas(1 << 40, Fork::Cancun), 3 << 35 | 1 << 61);
+        assert_eq!(memory_gas(u64::MAX, Fork::Cancun), u64::MAX);
+        assert_eq!(memory_expansion_gas(0, u64::MAX, Fork::Cancun), u64::MAX);
+        assert_eq!(word_aligned(u64::MAX), u64::MAX);
+        assert_eq!(copy_gas(u64::MAX), 3 << 59);
+        assert_eq!(keccak_gas(u64::MAX), (3 << 60) + 30);
+        assert_eq!(init_code_gas(u64::MAX, Fork::Shanghai), 1 << 60);
+    }
+}
@@ -0,0 +1,236 @@
+//! L1 data fees of OP-Stack transactions
+//!
+//! OP-Stack chains charge every transaction for publishing its data to L1, on top of the
+//! execution gas. The formula depends on the hardfork:
+//! - Bedrock and Canyon price the calldata gas of the transaction plus a fixed overhead at the
+//!   L1 base fee, times a scalar
+//! - Ecotone prices the data at both the L1 base fee and the blob base fee, using calldata gas
+//!   divided by 16 as the compressed size
+//! - Fjord and later estimate the compressed size from the FastLZ-compressed size with a
+//!   linear regression, with a minimum of 100 bytes
+//!
+//! Scalars use six decimals, as in the chain's `GasPriceOracle` predeploy.
+
+use super::{calldata_gas, GasAnalyzer};
+use crate::chain::OpStackHardfork;
+use crate::{AnalysisConfig, Fork};
+
+/// Fjord size regression intercept, scaled by 1e6
+const FJORD_INTERCEPT: i128 = -42_585_600;
+/// Fjord size regression coefficient of the FastLZ size, scaled by 1e6
+const FJORD_FASTLZ_COEFFICIENT: i128 = 836_500;
+/// Fjord minimum transaction size, scaled by 1e6
+const FJORD_MIN_SIZE: i128 = 100_000_000;
+
+/// Assumption about how well the transaction data compresses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Compression {
+    /// Use the data as it is (Ecotone's calldata-gas estimate, or the raw length for Fjord)
+    Uncompressed,
+    /// The compressed size is this percentage of the data size
+    Percent(u32),
+    /// The compressed size is known, e.g. measured with FastLZ
+    Size(usize),
+}
+
+impl Compression {
+    fn compressed_size(self, data: &[u8]) -> Option<u128> {
+        match self {
+            Self::Uncompressed => None,
+            Self::Percent(percent) => Some(data.len() as u128 * percent as u128 / 100),
+            Self::Size(size) => Some(size as u128),
+        }
+    }
+}
+
+/// L1 prices and chain scalars, as read from the `L1Block` and `GasPriceOracle` predeploys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct L1FeeParams {
+    /// L1 base fee, in wei
+    pub l1_base_fee: u128,
+    /// L1 blob base fee, in wei (ignored before Ecotone)
+    pub blob_base_fee: u128,
+    /// Base fee scalar (the single `scalar` before Ecotone)
+    pub base_fee_scalar: u128,
+    /// Blob base fee scalar (ignored before Ecotone)
+    pub blob_base_fee_scalar: u128,
+    /// Fixed gas overhead per transaction (only used before Ecotone)
+    pub overhead: u64,
+}
+
+impl L1FeeParams {
+    /// Create parameters from the L1 fees and the chain's scalars
+    pub fn new(
+        l1_base_fee: u128,
+        blob_base_fee: u128,
+        base_fee_scalar: u128,
+        blob_base_fee_scalar: u128,
+    ) -> Self {
+        Self {
+            l1_base_fee,
+            blob_base_fee,
+            base_fee_scalar,
+            blob_base_fee_scalar,
+            overhead: 0,
+        }
+    }
+
+    /// Set the pre-Ecotone fixed overhead
+    pub fn with_overhead(mut self, overhead: u64) -> Self {
+        self.overhead = overhead;
+        self
+    }
+}
+
+/// L1 data fee of a transaction, in wei
+///
+/// `tx_data` is the transaction as posted to L1 (the signed RLP encoding); passing only the
+/// calldata underestimates the fee slightly.
+pub fn l1_data_fee(
+    tx_data: &[u8],
+    hardfork: OpStackHardfork,
+    params: &L1FeeParams,
+    compression: Compression,
+) -> u128 {
+    let data_gas = calldata_gas(tx_data, hardfork.l1_fork()) as u128;
+    match hardfork {
+        OpStackHardfork::Bedrock | OpStackHardfork::Canyon => {
+            (data_gas + params.overhead as u128) * params.l1_base_fee * params.base_fee_scalar
+                / 1_000_000
+        }
+        OpStackHardfork::Ecotone => {
+            let size = compression
+                .compressed_size(tx_data)
+                .unwrap_or(data_gas / 16);
+            (size * 16 * params.base_fee_scalar * params.l1_base_fee
+                + size * params.blob_base_fee_scalar * params.blob_base_fee)
+                / 1_000_000
+        }
+        _ => {
+            let fastlz_size = compression
+                .compressed_size(tx_data)
+                .unwrap_or(tx_data.len() as u128);
+            let estimated_size = (FJORD_INTERCEPT + FJORD_FASTLZ_COEFFICIENT * fastlz_size as i128)
+                .max(FJORD_MIN_SIZE) as u128;
+            let scaled_fee = params.base_fee_scalar * params.l1_base_fee * 16
+                + params.blob_base_fee_scalar * params.blob_base_fee;
+            estimated_size * scaled_fee / 1_000_000_000_000
+        }
+    }
+}
+
+/// Total cost of a transaction on an OP-Stack chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct L2Cost {
+    /// Execution gas, including the intrinsic and calldata costs
+    pub execution_gas: u64,
+    /// Execution gas times the L2 gas price, in wei
+    pub execution_fee: u128,
+    /// L1 data fee, in wei
+    pub l1_data_fee: u128,
+    /// Warnings from the execution analysis
+    pub warnings: Vec<String>,
+}
+
+impl L2Cost {
+    /// Execution fee plus L1 data fee, in wei
+    pub fn total_fee(&self) -> u128 {
+        self.execution_fee + self.l1_data_fee
+    }
+
+    /// Share of the total fee paid for L1 data, in percent
+    pub fn l1_share(&self) -> f64 {
+        if self.total_fee() == 0 {
+            return 0.0;
+        }
+        self.l1_data_fee as f64 / self.total_fee() as f64 * 100.0
+    }
+}
+
+/// Estimate the execution and L1 data cost of calling `bytecode` with `calldata`
+///
+/// The calldata stands in for the posted transaction data.
+pub fn estimate_l2_cost(
+    bytecode: &[u8],
+    calldata: &[u8],
+    hardfork: OpStackHardfork,
+    params: &L1FeeParams,
+    compression: Compression,
+    l2_gas_price: u128,
+) -> Result<L2Cost, String> {
+    let fork: Fork = hardfork.l1_fork();
+    let analysis = GasAnalyzer::analyze_bytecode(bytecode, fork, &AnalysisConfig::default())?;
+    let execution_gas = analysis.total_gas + calldata_gas(calldata, fork);
+    Ok(L2Cost {
+        execution_gas,
+        execution_fee: execution_gas as u128 * l2_gas_price,
+        l1_data_fee: l1_data_fee(calldata, hardfork, params, compression),
+        warnings: analysis.warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GWEI: u128 = 1_000_000_000;
+
+    #[test]
+    fn test_l1_data_fee_formulas() {
+        let data = [0xffu8; 100];
+        let params = L1FeeParams::new(10 * GWEI, 1, 1368, 810_949);
+
+        // (1600 + 188) * 10 gwei * 0.684
+        let bedrock = L1FeeParams::new(10 * GWEI, 0, 684_000, 0).with_overhead(188);
+        let fee = l1_data_fee(
+            &data,
+            OpStackHardfork::Bedrock,
+            &bedrock,
+            Compression::Uncompressed,
+        );
+        assert_eq!(fee, 12_229_920_000_000);
+
+        // 100 bytes either way; Fjord's minimum size is also 100 bytes
+        let ecotone = l1_data_fee(
+            &data,
+            OpStackHardfork::Ecotone,
+            &params,
+            Compression::Uncompressed,
+        );
+        assert_eq!(ecotone, 21_888_000_081);
+        let fjord = l1_data_fee(
+            &data,
+            OpStackHardfork::Fjord,
+            &params,
+            Compression::Size(100),
+        );
+        assert_eq!(fjord, ecotone);
+
+        // Halving the compressed size halves the Ecotone fee
+        let compressed = l1_data_fee(
+            &data,
+            OpStackHardfork::Ecotone,
+            &params,
+            Compression::Percent(50),
+        );
+        assert_eq!(compressed, ecotone / 2);
+    }
+
+    #[test]
+    fn test_l2_cost() {
+        let params = L1FeeParams::new(10 * GWEI, 1, 1368, 810_949);
+        let cost = estimate_l2_cost(
+            &[0x00],
+            &[0xff; 100],
+            OpStackHardfork::Isthmus,
+            &params,
+            Compression::Uncompressed,
+            1_000_000,
+        )
+        .unwrap();
+        assert_eq!(cost.execution_gas, 21_000 + 1600);
+        assert_eq!(cost.execution_fee, 22_600 * 1_000_000);
+        assert!(cost.l1_data_fee > 0);
+        assert!(cost.l1_share() > 0.0 && cost.l1_share() < 100.0);
+    }
+}
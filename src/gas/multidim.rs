@@ -0,0 +1,193 @@
+//! Experimental multi-dimensional gas accounting (EIP-7706 style)
+//!
+//! Execution, calldata and blob gas are tracked as separate dimensions instead of being
+//! folded into one number, anticipating multi-dimensional fee markets. Enabled by the
+//! `multidim-gas` feature; the API may change as the proposals evolve.
+
+use super::{calldata_gas, GasAnalyzer};
+use crate::{AnalysisConfig, Fork};
+use std::fmt;
+use std::ops::Add;
+
+/// Gas per blob (EIP-4844)
+pub const GAS_PER_BLOB: u64 = 1 << 17;
+
+/// A separately priced gas resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum GasDimension {
+    /// EVM execution, including the intrinsic transaction cost
+    Execution,
+    /// Transaction calldata
+    Calldata,
+    /// Blob data (EIP-4844)
+    Blob,
+}
+
+impl GasDimension {
+    /// All dimensions, in reporting order
+    pub const ALL: [GasDimension; 3] = [Self::Execution, Self::Calldata, Self::Blob];
+}
+
+/// Gas used in each dimension
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct GasVector {
+    /// Execution gas
+    pub execution: u64,
+    /// Calldata gas
+    pub calldata: u64,
+    /// Blob gas
+    pub blob: u64,
+}
+
+impl GasVector {
+    /// Create a vector from per-dimension amounts
+    pub fn new(execution: u64, calldata: u64, blob: u64) -> Self {
+        Self {
+            execution,
+            calldata,
+            blob,
+        }
+    }
+
+    /// Gas used in one dimension
+    pub fn get(&self, dimension: GasDimension) -> u64 {
+        match dimension {
+            GasDimension::Execution => self.execution,
+            GasDimension::Calldata => self.calldata,
+            GasDimension::Blob => self.blob,
+        }
+    }
+
+    /// Dimensions in which this vector exceeds the given limits
+    pub fn exceeded_dimensions(&self, limits: &GasVector) -> Vec<GasDimension> {
+        GasDimension::ALL
+            .into_iter()
+            .filter(|dimension| self.get(*dimension) > limits.get(*dimension))
+            .collect()
+    }
+
+    /// Sum of all dimensions, as a single-dimensional fee market would charge them
+    pub fn total(&self) -> u64 {
+        self.execution + self.calldata + self.blob
+    }
+}
+
+impl Add for GasVector {
+    type Output = GasVector;
+
+    fn add(self, other: GasVector) -> GasVector {
+        GasVector::new(
+            self.execution + other.execution,
+            self.calldata + other.calldata,
+            self.blob + other.blob,
+        )
+    }
+}
+
+impl fmt::Display for GasVector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "execution={} calldata={} blob={}",
+            self.execution, self.calldata, self.blob
+        )
+    }
+}
+
+/// Transaction inputs that consume non-execution gas
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransactionProfile {
+    /// Calldata sent with the transaction
+    pub calldata: Vec<u8>,
+    /// Number of blobs attached
+    pub blob_count: u64,
+}
+
+impl TransactionProfile {
+    /// Create an empty profile
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the calldata
+    pub fn with_calldata(mut self, calldata: Vec<u8>) -> Self {
+        self.calldata = calldata;
+        self
+    }
+
+    /// Set the number of blobs
+    pub fn with_blobs(mut self, blob_count: u64) -> Self {
+        self.blob_count = blob_count;
+        self
+    }
+}
+
+/// Blob gas for a number of blobs; blobs do not exist before Cancun
+pub fn blob_gas(blob_count: u64, fork: Fork) -> Result<u64, String> {
+    if blob_count > 0 && fork < Fork::Cancun {
+        return Err(format!("Blobs are not supported in fork {fork:?}"));
+    }
+    Ok(blob_count * GAS_PER_BLOB)
+}
+
+/// Per-dimension gas usage of bytecode executed by a transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiDimensionalAnalysis {
+    /// Gas used in each dimension
+    pub gas: GasVector,
+    /// Warnings from the execution analysis
+    pub warnings: Vec<String>,
+}
+
+/// Analyze bytecode and a transaction profile, reporting each gas dimension separately
+pub fn analyze_multidimensional(
+    bytecode: &[u8],
+    fork: Fork,
+    transaction: &TransactionProfile,
+    config: &AnalysisConfig,
+) -> Result<MultiDimensionalAnalysis, String> {
+    let execution = GasAnalyzer::analyze_bytecode(bytecode, fork, config)?;
+    Ok(MultiDimensionalAnalysis {
+        gas: GasVector::new(
+            execution.total_gas,
+            calldata_gas(&transaction.calldata, fork),
+            blob_gas(transaction.blob_count, fork)?,
+        ),
+        warnings: execution.warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dimensions_are_reported_separately() {
+        let transaction = TransactionProfile::new()
+            .with_calldata(vec![0x00, 0x01, 0x02])
+            .with_blobs(2);
+        let analysis = analyze_multidimensional(
+            &[0x60, 0x01, 0x00],
+            Fork::Cancun,
+            &transaction,
+            &AnalysisConfig::default(),
+        )
+        .unwrap();
+
+        assert_eq!(analysis.gas.calldata, 4 + 16 + 16);
+        assert_eq!(analysis.gas.blob, 2 * GAS_PER_BLOB);
+        assert!(analysis.gas.execution >= 21000);
+
+        let limits = GasVector::new(30_000_000, 1_000, GAS_PER_BLOB);
+        assert_eq!(
+            analysis.gas.exceeded_dimensions(&limits),
+            vec![GasDimension::Blob]
+        );
+    }
+
+    #[test]
+    fn test_fork_specific_pricing() {
+        assert_eq!(calldata_gas(&[0xff], Fork::Byzantium), 68);
+        assert!(blob_gas(1, Fork::Shanghai).is_err());
+    }
+}
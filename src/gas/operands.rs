@@ -0,0 +1,275 @@
+//! Operand estimation strategies for gas analysis
+//!
+//! Dynamic gas depends on operands (memory offsets, storage keys, call targets) that raw
+//! opcodes do not carry. An [`OperandEstimator`] supplies them, trading fidelity for speed:
+//!
+//! - [`DummyEstimator`]: fixed placeholder operands per opcode, the default and the fastest
+//! - [`StackSimEstimator`]: constants tracked by stack simulation, placeholders where a value
+//!   depends on runtime state
+//! - [`TraceEstimator`]: operands recorded from a real execution, by program counter
+//!
+//! Select one with [`AnalysisConfig::with_operand_estimator`](crate::AnalysisConfig::with_operand_estimator).
+//! Bare opcode sequences have no PUSH immediates, so simulation is only useful on bytecode
+//! analyzed with [`GasAnalyzer::analyze_bytecode`](super::GasAnalyzer::analyze_bytecode).
+
+use crate::disasm::Instruction;
+use crate::stack::trace_operands;
+use crate::Fork;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+/// Supplies the operands the gas calculator prices instructions with
+pub trait OperandEstimator: fmt::Debug + Send + Sync {
+    /// Operands for each instruction, in the calculator's layout, one entry per instruction
+    ///
+    /// For bare opcode sequences the program counter of each instruction is its index.
+    fn estimate(&self, instructions: &[Instruction], fork: Fork) -> Vec<Vec<u64>>;
+
+    /// Stable identity, the same across processes for estimators that supply the same operands
+    ///
+    /// Analysis configurations, and so cache keys, compare and hash estimators by this.
+    fn id(&self) -> String;
+}
+
+/// Fixed placeholder operands: memory at 0x40, storage key 0, a dummy address
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct DummyEstimator;
+
+impl DummyEstimator {
+    /// Placeholder operands for `opcode`
+    pub fn operands(opcode: u8) -> Vec<u64> {
+        match opcode {
+            // Storage operations
+            0x54 => vec![0x0],      // SLOAD with dummy key
+            0x55 => vec![0x0, 0x1], // SSTORE with dummy key/value
+            0x5c => vec![0x0],      // TLOAD with dummy key
+            0x5d => vec![0x0, 0x1], // TSTORE with dummy key/value
+
+            // Memory operations
+            0x51..=0x53 => vec![0x40],      // Memory ops at offset 0x40
+            0x5e => vec![0x40, 0x80, 0x20], // MCOPY: dst, src, size
+
+            // Call operations (simplified)
+            0xf1 | 0xf2 | 0xf4 | 0xfa => vec![100000, 0x123, 0, 0, 0, 0, 0], // Basic call params
+
+            // Account access
+            0x31 | 0x3b | 0x3c | 0x3f => vec![0x123], // Dummy address
+
+            // Copy operations
+            0x37 | 0x39 | 0x3e => vec![0x40, 0x0, 0x20], // dest, src, size
+
+            // Create operations
+            0xf0 | 0xf5 => vec![0, 0x40, 0x100], // value, offset, size
+
+            // Hash operations
+            0x20 => vec![0x40, 0x20], // offset, size
+
+            // Log operations
+            0xa0..=0xa4 => vec![0x40, 0x20], // offset, size
+
+            // Most operations don't need operands
+            _ => vec![],
+        }
+    }
+}
+
+impl OperandEstimator for DummyEstimator {
+    fn estimate(&self, instructions: &[Instruction], _fork: Fork) -> Vec<Vec<u64>> {
+        instructions
+            .iter()
+            .map(|i| Self::operands(i.opcode))
+            .collect()
+    }
+
+    fn id(&self) -> String {
+        "dummy".to_string()
+    }
+}
+
+/// Constants from stack simulation, with [`DummyEstimator`] placeholders for the rest
+///
+/// Values that are unknown or do not fit in 32 bits fall back to the placeholder at the same
+/// position; no real memory offset or size is that large.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct StackSimEstimator;
+
+impl OperandEstimator for StackSimEstimator {
+    fn estimate(&self, instructions: &[Instruction], fork: Fork) -> Vec<Vec<u64>> {
+        trace_operands(instructions, fork, |_| true)
+            .into_iter()
+            .map(|snapshot| {
+                let fallback = DummyEstimator::operands(snapshot.opcode);
+                let mut operands: Vec<u64> = snapshot
+                    .inputs
+                    .iter()
+                    .enumerate()
+                    .map(|(position, value)| {
+                        value
+                            .as_u64()
+                            .filter(|v| *v <= u32::MAX as u64)
+                            .unwrap_or_else(|| fallback.get(position).copied().unwrap_or(0))
+                    })
+                    .collect();
+                // The calculator reads every call with the CALL layout
+                if matches!(snapshot.opcode, 0xf4 | 0xfa) && operands.len() == 6 {
+                    operands.insert(2, 0);
+                }
+                operands
+            })
+            .collect()
+    }
+
+    fn id(&self) -> String {
+        "stack-sim".to_string()
+    }
+}
+
+/// Operands recorded from an execution trace, by program counter
+///
+/// Instructions missing from the trace get [`DummyEstimator`] placeholders.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct TraceEstimator {
+    operands: BTreeMap<usize, Vec<u64>>,
+}
+
+impl TraceEstimator {
+    /// An empty trace
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the operands of the instruction at `pc`, in the calculator's layout
+    pub fn with_operands(mut self, pc: usize, operands: Vec<u64>) -> Self {
+        self.operands.insert(pc, operands);
+        self
+    }
+}
+
+impl FromIterator<(usize, Vec<u64>)> for TraceEstimator {
+    fn from_iter<I: IntoIterator<Item = (usize, Vec<u64>)>>(iter: I) -> Self {
+        Self {
+            operands: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl OperandEstimator for TraceEstimator {
+    fn estimate(&self, instructions: &[Instruction], _fork: Fork) -> Vec<Vec<u64>> {
+        instructions
+            .iter()
+            .map(|i| {
+                self.operands
+                    .get(&i.pc)
+                    .cloned()
+                    .unwrap_or_else(|| DummyEstimator::operands(i.opcode))
+            })
+            .collect()
+    }
+
+    /// `trace` followed by every recorded `pc:operands` entry, in pc order
+    fn id(&self) -> String {
+        let mut id = "trace".to_string();
+        for (pc, operands) in &self.operands {
+            let operands: Vec<String> = operands.iter().map(|v| format!("{v:x}")).collect();
+            id.push_str(&format!(";{pc:x}:{}", operands.join(",")));
+        }
+        id
+    }
+}
+
+/// An estimator shared between analysis configurations
+///
+/// Clones share the estimator. Two handles are equal when their estimators have the same
+/// [`id`](OperandEstimator::id).
+#[derive(Debug, Clone)]
+pub struct SharedEstimator(Arc<dyn OperandEstimator>);
+
+impl SharedEstimator {
+    /// Share `estimator`
+    pub fn new(estimator: impl OperandEstimator + 'static) -> Self {
+        Self(Arc::new(estimator))
+    }
+
+    /// The shared estimator
+    pub fn get(&self) -> &dyn OperandEstimator {
+        self.0.as_ref()
+    }
+}
+
+impl PartialEq for SharedEstimator {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.id() == other.0.id()
+    }
+}
+
+impl Eq for SharedEstimator {}
+
+impl Hash for SharedEstimator {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.id().hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disasm::disassemble;
+    use crate::gas::GasAnalyzer;
+    use crate::AnalysisConfig;
+
+    // PUSH2 0x1000, MLOAD, POP, STOP
+    const CODE: [u8; 6] = [0x61, 0x10, 0x00, 0x51, 0x50, 0x00];
+
+    #[test]
+    fn test_estimators_supply_operands() {
+        let instructions = disassemble(&CODE);
+        let mload = |estimator: &dyn OperandEstimator| {
+            estimator.estimate(&instructions, Fork::Cancun)[1].clone()
+        };
+        assert_eq!(mload(&DummyEstimator), [0x40]);
+        assert_eq!(mload(&StackSimEstimator), [0x1000]);
+        assert_eq!(
+            mload(&TraceEstimator::new().with_operands(3, vec![0x2000])),
+            [0x2000]
+        );
+        // Missing from the trace
+        assert_eq!(mload(&TraceEstimator::new()), [0x40]);
+
+        // DELEGATECALL gets a zero value slot; CALLER is not a constant
+        let code = [0x60, 0, 0x80, 0x80, 0x80, 0x33, 0x5a, 0xf4];
+        let operands = StackSimEstimator.estimate(&disassemble(&code), Fork::Cancun);
+        assert_eq!(operands[6], [100000, 0x123, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_config_selects_estimator() {
+        let analyze = |config: &AnalysisConfig| {
+            GasAnalyzer::analyze_bytecode(&CODE, Fork::Cancun, config)
+                .unwrap()
+                .total_gas
+        };
+        let dummy = analyze(&AnalysisConfig::default());
+        let simulated = analyze(&AnalysisConfig::new().with_operand_estimator(StackSimEstimator));
+        // Touching 0x1020 bytes costs more expansion than 0x60
+        assert!(simulated > dummy, "{simulated} <= {dummy}");
+
+        let config = AnalysisConfig::new().with_operand_estimator(DummyEstimator);
+        assert_eq!(analyze(&config), dummy);
+        assert_eq!(
+            config,
+            AnalysisConfig::new().with_operand_estimator(DummyEstimator)
+        );
+        assert_ne!(
+            config,
+            AnalysisConfig::new().with_operand_estimator(StackSimEstimator)
+        );
+        let trace = |value| {
+            AnalysisConfig::new()
+                .with_operand_estimator(TraceEstimator::new().with_operands(3, vec![value]))
+        };
+        assert_eq!(trace(0x2000), trace(0x2000));
+        assert_ne!(trace(0x2000), trace(0x3000));
+    }
+}
@@ -0,0 +1,173 @@
+//! Ready-made repricing scenarios for EIPs under discussion
+//!
+//! Presets follow the proposals as currently drafted and are revised as they evolve. They
+//! only capture opcode-level price changes that [`Repricing`] can express.
+
+use super::{Repricing, RepricingImpact};
+use crate::{AnalysisConfig, Fork};
+
+/// A repricing scenario taken from an EIP
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EipPreset {
+    /// EIP number
+    pub eip: u16,
+    /// EIP title
+    pub title: &'static str,
+    /// What the preset models and any simplifications it makes
+    pub notes: &'static str,
+    /// The price changes
+    pub repricing: Repricing,
+}
+
+impl EipPreset {
+    /// Evaluate the preset on raw bytecode
+    pub fn evaluate_bytecode(
+        &self,
+        fork: Fork,
+        bytecode: &[u8],
+        config: &AnalysisConfig,
+    ) -> Result<RepricingImpact, String> {
+        self.repricing.evaluate_bytecode(fork, bytecode, config)
+    }
+}
+
+/// EIP-7939: CLZ (0x1e) count leading zeros, a new cheap opcode priced like MUL
+pub fn eip_7939_clz() -> EipPreset {
+    EipPreset {
+        eip: 7939,
+        title: "Count leading zeros (CLZ) opcode",
+        notes: "Adds CLZ at 0x1e for 5 gas",
+        repricing: Repricing::new("EIP-7939").with_cost(0x1e, 5),
+    }
+}
+
+/// EIP-7843: SLOTNUM (0x4b) returning the current slot number
+pub fn eip_7843_slotnum() -> EipPreset {
+    EipPreset {
+        eip: 7843,
+        title: "SLOTNUM opcode",
+        notes: "Adds SLOTNUM at 0x4b for 2 gas",
+        repricing: Repricing::new("EIP-7843").with_cost(0x4b, 2),
+    }
+}
+
+/// EIP-7480: EOF data section access, DATALOAD (0xd0), DATALOADN (0xd1), DATASIZE (0xd2)
+/// and DATACOPY (0xd3)
+pub fn eip_7480_data_section() -> EipPreset {
+    EipPreset {
+        eip: 7480,
+        title: "EOF - Data section access instructions",
+        notes: "Adds DATALOAD for 4 gas, DATALOADN for 3, DATASIZE for 2 and DATACOPY for 3 \
+                plus copied words and memory expansion; the opcodes are only valid in EOF \
+                code, which legacy bytecode analysis does not separate",
+        repricing: Repricing::new("EIP-7480")
+            .with_cost(0xd0, 4)
+            .with_cost(0xd1, 3)
+            .with_cost(0xd2, 2)
+            .with_base_cost(0xd3, 3),
+    }
+}
+
+/// EIP-7620: EOF contract creation, EOFCREATE (0xec) and RETURNCONTRACT (0xee)
+pub fn eip_7620_eof_create() -> EipPreset {
+    EipPreset {
+        eip: 7620,
+        title: "EOF Contract Creation",
+        notes: "Adds EOFCREATE for 32000 gas and RETURNCONTRACT for free, both plus memory \
+                expansion; the gas passed to the init code is not modeled",
+        repricing: Repricing::new("EIP-7620")
+            .with_base_cost(0xec, 32000)
+            .with_base_cost(0xee, 0),
+    }
+}
+
+/// EIP-5022: raise the cost of SSTORE from zero to non-zero to 40000
+pub fn eip_5022_sstore_increase() -> EipPreset {
+    EipPreset {
+        eip: 5022,
+        title: "Increase price of SSTORE from zero to non-zero to 40k gas",
+        notes: "Operand values are not tracked, so every SSTORE is priced as a zero to \
+                non-zero write (an upper bound); cold access surcharges are kept",
+        repricing: Repricing::new("EIP-5022").with_base_cost(0x55, 40000),
+    }
+}
+
+/// All available presets
+pub fn eip_presets() -> Vec<EipPreset> {
+    vec![
+        eip_5022_sstore_increase(),
+        eip_7480_data_section(),
+        eip_7620_eof_create(),
+        eip_7843_slotnum(),
+        eip_7939_clz(),
+    ]
+}
+
+/// Look up a preset by EIP number
+pub fn eip_preset(eip: u16) -> Option<EipPreset> {
+    eip_presets().into_iter().find(|preset| preset.eip == eip)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preset_lookup() {
+        assert_eq!(
+            eip_preset(7939).unwrap().title,
+            "Count leading zeros (CLZ) opcode"
+        );
+        assert!(eip_preset(1).is_none());
+    }
+
+    #[test]
+    fn test_new_opcode_preset_prices_unknown_byte() {
+        // PUSH1 0x01, CLZ
+        let impact = eip_7939_clz()
+            .evaluate_bytecode(
+                Fork::Cancun,
+                &[0x60, 0x01, 0x1e],
+                &AnalysisConfig::default(),
+            )
+            .unwrap();
+        assert_eq!(impact.gas_delta(), 5);
+    }
+
+    #[test]
+    fn test_datacopy_keeps_copy_cost() {
+        use crate::gas::DynamicGasCalculator;
+
+        let calculator = DynamicGasCalculator::new(Fork::Cancun)
+            .with_repricing(eip_7480_data_section().repricing);
+        // DATACOPY of 64 bytes to fresh memory: 3 + 2 words * 3 + 6 for expansion
+        let sequence = [(0xd3, vec![0x0, 0x0, 0x40]), (0xd1, vec![])];
+        let result = calculator.analyze_sequence_gas(&sequence).unwrap();
+        assert_eq!(result.breakdown, vec![(0xd3, 15), (0xd1, 3)]);
+    }
+
+    #[test]
+    fn test_eof_create_charges_input_memory() {
+        use crate::gas::{DynamicGasCalculator, ExecutionContext};
+
+        let calculator =
+            DynamicGasCalculator::new(Fork::Cancun).with_repricing(eip_7620_eof_create().repricing);
+        let context = ExecutionContext::new();
+        // EOFCREATE with 64 bytes of input, RETURNCONTRACT of 32 bytes of auxiliary data
+        let eofcreate = calculator.calculate_gas_cost(0xec, &context, &[0, 0, 0, 0x40]);
+        assert_eq!(eofcreate, Ok(32006));
+        let returncontract = calculator.calculate_gas_cost(0xee, &context, &[0, 0x20]);
+        assert_eq!(returncontract, Ok(3));
+        assert!(calculator
+            .calculate_gas_cost(0xec, &context, &[0, 0])
+            .is_err());
+    }
+
+    #[test]
+    fn test_sstore_preset_increases_cost() {
+        let impact = eip_5022_sstore_increase()
+            .evaluate_bytecode(Fork::London, &[0x55], &AnalysisConfig::default())
+            .unwrap();
+        assert!(impact.gas_delta() > 30000);
+    }
+}
@@ -0,0 +1,222 @@
+//! Gas refund rules and detection of legacy gas-token patterns
+//!
+//! Gas tokens (GST1, GST2, CHI) stored gas by writing storage or deploying throwaway
+//! contracts while gas was cheap, then released it through SSTORE-clear and SELFDESTRUCT
+//! refunds. EIP-3529 (London) cut the clear refund to 4800, removed the SELFDESTRUCT refund
+//! and capped refunds at a fifth of the gas used, which made these tokens unprofitable.
+
+use crate::disasm::disassemble;
+use crate::stack::{trace_operands, StackValue};
+use crate::Fork;
+
+/// Refund parameters of a fork
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RefundSchedule {
+    /// Refund for clearing a storage slot to zero
+    pub sstore_clear_refund: u64,
+    /// Refund for SELFDESTRUCT
+    pub selfdestruct_refund: u64,
+    /// Refunds are capped at `gas_used / max_refund_quotient`
+    pub max_refund_quotient: u64,
+}
+
+impl RefundSchedule {
+    /// Refund schedule in effect for a fork
+    pub fn for_fork(fork: Fork) -> Self {
+        if fork >= Fork::London {
+            Self {
+                sstore_clear_refund: 4800,
+                selfdestruct_refund: 0,
+                max_refund_quotient: 5,
+            }
+        } else {
+            Self {
+                sstore_clear_refund: 15000,
+                selfdestruct_refund: 24000,
+                max_refund_quotient: 2,
+            }
+        }
+    }
+
+    /// Maximum refund for a transaction that used `gas_used` gas
+    pub fn max_refund(&self, gas_used: u64) -> u64 {
+        gas_used / self.max_refund_quotient
+    }
+}
+
+/// Gas spent to trigger one storage-clear refund: an SSTORE resetting a cold, non-zero slot
+///
+/// 5000 in every fork; since Berlin it is split into 2900 for the reset and 2100 for the
+/// cold slot access.
+const SSTORE_CLEAR_COST: u64 = 5000;
+
+/// Gas spent to trigger one SELFDESTRUCT refund: calling a child that self-destructs
+fn selfdestruct_trigger_cost(fork: Fork) -> u64 {
+    let call = if fork >= Fork::Berlin {
+        2600 // cold account access
+    } else if fork >= Fork::TangerineWhistle {
+        700
+    } else {
+        40
+    };
+    let selfdestruct = if fork >= Fork::TangerineWhistle {
+        5000
+    } else {
+        0
+    };
+    call + selfdestruct
+}
+
+/// A recognized gas-token shape
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GasTokenPattern {
+    /// Repeated SSTOREs of zero, as used by storage-based tokens (GST1)
+    StorageClearing,
+    /// Tiny contract whose only purpose is to self-destruct (GST2/CHI child)
+    SelfdestructChild,
+    /// Contract that deploys code containing SELFDESTRUCT (GST2/CHI minter)
+    SelfdestructFactory,
+}
+
+/// A gas-token pattern with its economics under the analyzed fork
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GasTokenFinding {
+    /// The pattern
+    pub pattern: GasTokenPattern,
+    /// Program counters of the instructions that make up the pattern
+    pub pcs: Vec<usize>,
+    /// Refund released per unit (per cleared slot or destroyed child)
+    pub refund_per_unit: u64,
+    /// Gas spent to release one unit
+    pub cost_per_unit: u64,
+}
+
+impl GasTokenFinding {
+    /// Check if releasing a unit refunds more than it costs under the fork's rules
+    pub fn is_profitable(&self) -> bool {
+        self.refund_per_unit > self.cost_per_unit
+    }
+}
+
+/// Minimum number of zero-writing SSTOREs before storage clearing is reported
+const MIN_STORAGE_CLEARS: usize = 3;
+/// Runtime code at most this long that self-destructs is treated as a gas-token child
+const MAX_CHILD_SIZE: usize = 32;
+/// Embedded init code at least this long is considered when looking for factories
+const MIN_EMBEDDED_CODE: usize = 10;
+
+/// Detect gas-token patterns and evaluate them against the fork's refund rules
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(?fork, len = bytecode.len()))
+)]
+pub fn detect_gas_token_patterns(bytecode: &[u8], fork: Fork) -> Vec<GasTokenFinding> {
+    let schedule = RefundSchedule::for_fork(fork);
+    let instructions = disassemble(bytecode);
+    let mut findings = Vec::new();
+
+    // SSTORE inputs: key, value
+    let zero = StackValue::from_u64(0);
+    let clears: Vec<usize> = trace_operands(&instructions, fork, |i| i.opcode == 0x55)
+        .into_iter()
+        .filter(|snapshot| snapshot.inputs.get(1) == Some(&zero))
+        .map(|snapshot| snapshot.pc)
+        .collect();
+    if clears.len() >= MIN_STORAGE_CLEARS {
+        findings.push(GasTokenFinding {
+            pattern: GasTokenPattern::StorageClearing,
+            pcs: clears,
+            refund_per_unit: schedule.sstore_clear_refund,
+            cost_per_unit: SSTORE_CLEAR_COST,
+        });
+    }
+
+    let selfdestructs: Vec<usize> = instructions
+        .iter()
+        .filter(|i| i.opcode == 0xff)
+        .map(|i| i.pc)
+        .collect();
+    if bytecode.len() <= MAX_CHILD_SIZE && !selfdestructs.is_empty() {
+        findings.push(GasTokenFinding {
+            pattern: GasTokenPattern::SelfdestructChild,
+            pcs: selfdestructs,
+            refund_per_unit: schedule.selfdestruct_refund,
+            cost_per_unit: selfdestruct_trigger_cost(fork),
+        });
+    }
+
+    let creates: Vec<usize> = instructions
+        .iter()
+        .filter(|i| matches!(i.opcode, 0xf0 | 0xf5))
+        .map(|i| i.pc)
+        .collect();
+    let embeds_selfdestruct = instructions.iter().any(|i| {
+        i.immediate.len() >= MIN_EMBEDDED_CODE
+            && disassemble(&i.immediate)
+                .iter()
+                .any(|inner| inner.opcode == 0xff)
+    });
+    if !creates.is_empty() && embeds_selfdestruct {
+        findings.push(GasTokenFinding {
+            pattern: GasTokenPattern::SelfdestructFactory,
+            pcs: creates,
+            refund_per_unit: schedule.selfdestruct_refund,
+            cost_per_unit: selfdestruct_trigger_cost(fork),
+        });
+    }
+
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_storage_clearing_profitability_changes_at_london() {
+        // Three times: PUSH1 0x00, PUSH1 slot, SSTORE
+        let code = [
+            0x60, 0x00, 0x60, 0x01, 0x55, 0x60, 0x00, 0x60, 0x02, 0x55, 0x60, 0x00, 0x60, 0x03,
+            0x55,
+        ];
+        let before = detect_gas_token_patterns(&code, Fork::Berlin);
+        assert_eq!(before[0].pattern, GasTokenPattern::StorageClearing);
+        assert_eq!(before[0].pcs.len(), 3);
+        assert!(before[0].is_profitable());
+
+        let after = detect_gas_token_patterns(&code, Fork::London);
+        assert!(!after[0].is_profitable());
+    }
+
+    #[test]
+    fn test_selfdestruct_child_and_factory() {
+        // CALLER, SELFDESTRUCT
+        let child = detect_gas_token_patterns(&[0x33, 0xff], Fork::Istanbul);
+        assert_eq!(child[0].pattern, GasTokenPattern::SelfdestructChild);
+        assert!(child[0].is_profitable());
+        assert!(!detect_gas_token_patterns(&[0x33, 0xff], Fork::London)[0].is_profitable());
+
+        // PUSH11 <init code ending in CALLER, SELFDESTRUCT>, PUSH1 0, MSTORE, ..., CREATE
+        let mut factory = vec![0x6a];
+        factory.extend_from_slice(&[
+            0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x00, 0x33, 0xff,
+        ]);
+        factory.extend_from_slice(&[0x60, 0x00, 0x52, 0x60, 0x0b, 0x60, 0x15, 0x60, 0x00, 0xf0]);
+        let findings = detect_gas_token_patterns(&factory, Fork::Istanbul);
+        assert!(findings
+            .iter()
+            .any(|f| f.pattern == GasTokenPattern::SelfdestructFactory));
+    }
+
+    #[test]
+    fn test_refund_cap() {
+        assert_eq!(
+            RefundSchedule::for_fork(Fork::Berlin).max_refund(100_000),
+            50_000
+        );
+        assert_eq!(
+            RefundSchedule::for_fork(Fork::London).max_refund(100_000),
+            20_000
+        );
+    }
+}
@@ -0,0 +1,466 @@
+//! Hypothetical gas repricings ("what if SLOAD cost 500?") and their impact on bytecode
+
+use super::{DynamicGasCalculator, GasAnalysisResult};
+use crate::disasm::disassemble;
+use crate::{AnalysisConfig, CancellationToken, Fork, OpcodeRegistry};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// How an opcode's price is overridden
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PriceOverride {
+    /// Replace the whole cost (base and dynamic) with a fixed amount
+    Fixed(u64),
+    /// Replace the base cost, keeping the dynamic part (memory expansion, cold access, ...)
+    Base(u64),
+}
+
+impl PriceOverride {
+    /// Cost of an opcode after the override, given its dynamic cost under the fork rules
+    pub fn apply(self, dynamic_cost: u64) -> u64 {
+        match self {
+            Self::Fixed(cost) => cost,
+            Self::Base(cost) => cost.saturating_add(dynamic_cost),
+        }
+    }
+}
+
+/// A named set of hypothetical price changes applied on top of a fork's schedule
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Repricing {
+    /// Short name of the scenario (e.g. "cheap SLOAD")
+    pub name: String,
+    /// Price overrides by opcode
+    pub overrides: BTreeMap<u8, PriceOverride>,
+    /// Opcodes of the fork that are not available (e.g. unsupported on a chain)
+    pub removed: BTreeSet<u8>,
+}
+
+impl Repricing {
+    /// Create an empty repricing scenario
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            overrides: BTreeMap::new(),
+            removed: BTreeSet::new(),
+        }
+    }
+
+    /// Set the total cost of an opcode, ignoring its dynamic component
+    ///
+    /// The opcode does not have to exist in the fork, which models proposed new opcodes.
+    pub fn with_cost(mut self, opcode: u8, gas: u64) -> Self {
+        self.removed.remove(&opcode);
+        self.overrides.insert(opcode, PriceOverride::Fixed(gas));
+        self
+    }
+
+    /// Set the base cost of an opcode, keeping its dynamic component
+    pub fn with_base_cost(mut self, opcode: u8, gas: u64) -> Self {
+        self.removed.remove(&opcode);
+        self.overrides.insert(opcode, PriceOverride::Base(gas));
+        self
+    }
+
+    /// Remove an opcode, so analyses treat it as unknown
+    pub fn without_opcode(mut self, opcode: u8) -> Self {
+        self.overrides.remove(&opcode);
+        self.removed.insert(opcode);
+        self
+    }
+
+    /// Add a table of overrides, replacing earlier overrides of the same opcodes
+    pub fn with_overrides(mut self, table: impl IntoIterator<Item = (u8, PriceOverride)>) -> Self {
+        for (opcode, price) in table {
+            self.removed.remove(&opcode);
+            self.overrides.insert(opcode, price);
+        }
+        self
+    }
+
+    /// Apply another scenario on top of this one; its overrides and removals win
+    pub fn layered_with(self, top: &Repricing) -> Self {
+        let mut layered = self.with_overrides(top.overrides.iter().map(|(o, p)| (*o, *p)));
+        for opcode in &top.removed {
+            layered = layered.without_opcode(*opcode);
+        }
+        layered
+    }
+
+    /// Parse an override table, one `OPCODE = PRICE` entry per line
+    ///
+    /// The opcode is a mnemonic of `fork` (any case) or a hex byte such as `0x54`. The price is
+    /// a total cost (`800`), a base cost keeping the dynamic part (`base 800`) or `removed`.
+    /// Empty lines and text after `#` are ignored.
+    pub fn parse_table(name: &str, fork: Fork, table: &str) -> Result<Self, String> {
+        let opcodes = OpcodeRegistry::new().get_opcodes(fork);
+        let mut repricing = Repricing::new(name);
+        for (number, line) in table.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |message: &str| format!("Line {}: {message}: {line}", number + 1);
+            let (opcode, price) = line
+                .split_once('=')
+                .ok_or_else(|| error("expected OPCODE = PRICE"))?;
+            let opcode = opcode.trim();
+            let opcode = match opcode.strip_prefix("0x") {
+                Some(hex) => u8::from_str_radix(hex, 16).ok(),
+                None => opcodes
+                    .iter()
+                    .find(|(_, metadata)| metadata.name.eq_ignore_ascii_case(opcode))
+                    .map(|(byte, _)| *byte),
+            }
+            .ok_or_else(|| error("unknown opcode"))?;
+            let price = price.trim();
+            repricing = if price == "removed" {
+                repricing.without_opcode(opcode)
+            } else {
+                let (base, gas) = match price.strip_prefix("base") {
+                    Some(gas) => (true, gas.trim()),
+                    None => (false, price),
+                };
+                let gas: u64 = gas.parse().map_err(|_| error("invalid gas amount"))?;
+                if base {
+                    repricing.with_base_cost(opcode, gas)
+                } else {
+                    repricing.with_cost(opcode, gas)
+                }
+            };
+        }
+        Ok(repricing)
+    }
+
+    /// Check if the scenario changes any price
+    pub fn is_empty(&self) -> bool {
+        self.overrides.is_empty() && self.removed.is_empty()
+    }
+
+    /// Check if the scenario removes an opcode
+    pub fn removes(&self, opcode: u8) -> bool {
+        self.removed.contains(&opcode)
+    }
+
+    /// The override for an opcode, if any
+    pub fn override_for(&self, opcode: u8) -> Option<PriceOverride> {
+        self.overrides.get(&opcode).copied()
+    }
+
+    /// Compare an (opcode, operands) sequence under the fork schedule and under this scenario
+    ///
+    /// Opcodes the scenario introduces that do not exist in the fork cost nothing in the
+    /// baseline, so both breakdowns line up instruction by instruction.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(scenario = %self.name, ?fork))
+    )]
+    pub fn evaluate_sequence(
+        &self,
+        fork: Fork,
+        opcodes: &[(u8, Vec<u64>)],
+    ) -> Result<RepricingImpact, String> {
+        let known_opcodes = OpcodeRegistry::new().get_opcodes(fork);
+        let new_opcodes = self
+            .overrides
+            .keys()
+            .filter(|opcode| !known_opcodes.contains_key(opcode))
+            .fold(Repricing::new("baseline"), |baseline, opcode| {
+                baseline.with_cost(*opcode, 0)
+            });
+        let baseline = DynamicGasCalculator::new(fork)
+            .with_repricing(new_opcodes)
+            .analyze_sequence_gas(opcodes)?;
+        let repriced = DynamicGasCalculator::new(fork)
+            .with_repricing(self.clone())
+            .analyze_sequence_gas(opcodes)?;
+        Ok(RepricingImpact { baseline, repriced })
+    }
+
+    /// Compare raw bytecode under the fork schedule and under this scenario
+    ///
+    /// Operands come from the estimator selected in `config`.
+    pub fn evaluate_bytecode(
+        &self,
+        fork: Fork,
+        bytecode: &[u8],
+        config: &AnalysisConfig,
+    ) -> Result<RepricingImpact, String> {
+        let sequence = config.operand_sequence(&disassemble(bytecode), fork);
+        self.evaluate_sequence(fork, &sequence)
+    }
+
+    /// Evaluate a corpus of bytecodes, one impact per contract
+    pub fn evaluate_corpus(
+        &self,
+        fork: Fork,
+        corpus: &[&[u8]],
+        config: &AnalysisConfig,
+    ) -> Result<Vec<RepricingImpact>, String> {
+        corpus
+            .iter()
+            .map(|bytecode| self.evaluate_bytecode(fork, bytecode, config))
+            .collect()
+    }
+
+    /// Evaluate a corpus until `token` is cancelled, returning the impacts computed so far
+    pub fn evaluate_corpus_cancellable(
+        &self,
+        fork: Fork,
+        corpus: &[&[u8]],
+        config: &AnalysisConfig,
+        token: &CancellationToken,
+    ) -> Result<Vec<RepricingImpact>, String> {
+        corpus
+            .iter()
+            .take_while(|_| !token.is_cancelled())
+            .map(|bytecode| self.evaluate_bytecode(fork, bytecode, config))
+            .collect()
+    }
+
+    /// Evaluate a corpus of (category, bytecode) pairs and aggregate the impact
+    ///
+    /// Categories are free-form labels chosen by the caller (e.g. "erc20", "amm", "proxy").
+    pub fn evaluate_categorized_corpus(
+        &self,
+        fork: Fork,
+        corpus: &[(&str, &[u8])],
+        config: &AnalysisConfig,
+    ) -> Result<CorpusImpact, String> {
+        let mut aggregate = CorpusImpact {
+            scenario: self.name.clone(),
+            total: CategoryImpact::default(),
+            categories: BTreeMap::new(),
+            opcode_deltas: BTreeMap::new(),
+        };
+        for (category, bytecode) in corpus {
+            let impact = self.evaluate_bytecode(fork, bytecode, config)?;
+            aggregate.total.add(&impact);
+            aggregate
+                .categories
+                .entry(category.to_string())
+                .or_default()
+                .add(&impact);
+            for (opcode, delta) in impact.opcode_deltas() {
+                *aggregate.opcode_deltas.entry(opcode).or_insert(0) += delta;
+            }
+        }
+        Ok(aggregate)
+    }
+}
+
+/// Gas totals of a group of contracts before and after a repricing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CategoryImpact {
+    /// Number of contracts
+    pub contracts: usize,
+    /// Total gas under the unmodified fork schedule
+    pub baseline_gas: u64,
+    /// Total gas with the repricing applied
+    pub repriced_gas: u64,
+}
+
+impl CategoryImpact {
+    fn add(&mut self, impact: &RepricingImpact) {
+        self.contracts += 1;
+        self.baseline_gas += impact.baseline.total_gas;
+        self.repriced_gas += impact.repriced.total_gas;
+    }
+
+    /// Change in total gas (negative when the repricing makes the code cheaper)
+    pub fn gas_delta(&self) -> i128 {
+        self.repriced_gas as i128 - self.baseline_gas as i128
+    }
+
+    /// Change in total gas relative to the baseline, in percent
+    pub fn percent_change(&self) -> f64 {
+        if self.baseline_gas == 0 {
+            return 0.0;
+        }
+        self.gas_delta() as f64 / self.baseline_gas as f64 * 100.0
+    }
+}
+
+/// Aggregate impact of a repricing over a categorized corpus
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorpusImpact {
+    /// Name of the repricing scenario
+    pub scenario: String,
+    /// Totals over the whole corpus
+    pub total: CategoryImpact,
+    /// Totals per contract category
+    pub categories: BTreeMap<String, CategoryImpact>,
+    /// Change in cost per opcode summed over the corpus, for opcodes whose cost changed
+    pub opcode_deltas: BTreeMap<u8, i128>,
+}
+
+impl CorpusImpact {
+    /// Render the per-category totals as CSV, with a final row for the whole corpus
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("category,contracts,baseline_gas,repriced_gas,delta,percent\n");
+        let rows = self
+            .categories
+            .iter()
+            .map(|(name, impact)| (name.as_str(), impact))
+            .chain(std::iter::once(("total", &self.total)));
+        for (name, impact) in rows {
+            csv.push_str(&format!(
+                "{name},{},{},{},{},{:.2}\n",
+                impact.contracts,
+                impact.baseline_gas,
+                impact.repriced_gas,
+                impact.gas_delta(),
+                impact.percent_change()
+            ));
+        }
+        csv
+    }
+}
+
+/// Gas usage of the same code before and after a repricing
+#[derive(Debug, Clone)]
+pub struct RepricingImpact {
+    /// Analysis under the unmodified fork schedule
+    pub baseline: GasAnalysisResult,
+    /// Analysis with the repricing applied
+    pub repriced: GasAnalysisResult,
+}
+
+impl RepricingImpact {
+    /// Change in total gas (negative when the repricing makes the code cheaper)
+    pub fn gas_delta(&self) -> i128 {
+        self.repriced.total_gas as i128 - self.baseline.total_gas as i128
+    }
+
+    /// Change in total gas relative to the baseline, in percent
+    pub fn percent_change(&self) -> f64 {
+        if self.baseline.total_gas == 0 {
+            return 0.0;
+        }
+        self.gas_delta() as f64 / self.baseline.total_gas as f64 * 100.0
+    }
+
+    /// Change in cost per opcode, for opcodes whose cost changed
+    pub fn opcode_deltas(&self) -> BTreeMap<u8, i128> {
+        let mut deltas = BTreeMap::new();
+        for ((opcode, before), (_, after)) in
+            self.baseline.breakdown.iter().zip(&self.repriced.breakdown)
+        {
+            if before != after {
+                *deltas.entry(*opcode).or_insert(0) += *after as i128 - *before as i128;
+            }
+        }
+        deltas
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_what_if_sload_costs_500() {
+        let repricing = Repricing::new("cheap SLOAD").with_cost(0x54, 500);
+        let impact = repricing
+            .evaluate_sequence(Fork::London, &[(0x54, vec![0x0]), (0x01, vec![])])
+            .unwrap();
+
+        let baseline_sload = impact.baseline.breakdown[0].1;
+        assert_eq!(impact.repriced.breakdown[0].1, 500);
+        assert_eq!(impact.gas_delta(), 500 - baseline_sload as i128);
+        assert_eq!(impact.opcode_deltas().len(), 1);
+        assert!(impact.percent_change() < 0.0);
+    }
+
+    #[test]
+    fn test_base_override_keeps_dynamic_cost() {
+        // MSTORE at offset 0x40 pays memory expansion on top of its base cost
+        let repricing = Repricing::new("MSTORE base 10").with_base_cost(0x52, 10);
+        let config = AnalysisConfig::default();
+        let impact = repricing
+            .evaluate_bytecode(Fork::London, &[0x52], &config)
+            .unwrap();
+        let expansion = impact.baseline.breakdown[0].1 - 3;
+        assert_eq!(impact.repriced.breakdown[0].1, 10 + expansion);
+
+        // PUSH1 0, PUSH2 0x1000, MSTORE: the simulated offset expands memory further
+        let config = config.with_operand_estimator(crate::gas::StackSimEstimator);
+        let impact = repricing
+            .evaluate_bytecode(Fork::London, &[0x60, 0x00, 0x61, 0x10, 0x00, 0x52], &config)
+            .unwrap();
+        assert!(impact.repriced.breakdown[2].1 > 10 + expansion);
+    }
+
+    #[test]
+    fn test_removed_opcode_is_unknown() {
+        let repricing = Repricing::new("no BLOBHASH").without_opcode(0x49);
+        let calculator = DynamicGasCalculator::new(Fork::Cancun).with_repricing(repricing);
+        let context = crate::gas::ExecutionContext::default();
+        assert!(calculator.calculate_gas_cost(0x49, &context, &[]).is_err());
+        assert!(calculator.analyze_sequence_gas(&[(0x49, vec![])]).is_err());
+    }
+
+    #[test]
+    fn test_override_table_layers_on_chain_overrides() {
+        let table = "
+            # proposed repricing
+            SLOAD = 800
+            mstore = base 10
+            0x49 = removed
+        ";
+        let proposal = Repricing::parse_table("proposal", Fork::Cancun, table).unwrap();
+        assert_eq!(proposal.override_for(0x54), Some(PriceOverride::Fixed(800)));
+        assert_eq!(proposal.override_for(0x52), Some(PriceOverride::Base(10)));
+        assert!(proposal.removes(0x49));
+        assert!(Repricing::parse_table("bad", Fork::Cancun, "SLOAD 800").is_err());
+        assert!(Repricing::parse_table("bad", Fork::London, "MCOPY = 1").is_err());
+
+        let chain = Repricing::new("chain")
+            .with_cost(0x54, 1)
+            .with_cost(0x01, 1);
+        let layered = chain.clone().layered_with(&proposal);
+        assert_eq!(layered.override_for(0x54), Some(PriceOverride::Fixed(800)));
+        assert_eq!(layered.override_for(0x01), Some(PriceOverride::Fixed(1)));
+
+        let calculator = DynamicGasCalculator::new(Fork::Cancun)
+            .with_repricing(chain)
+            .with_gas_overrides([(0x54, PriceOverride::Fixed(800))]);
+        let result = calculator
+            .analyze_sequence_gas(&[(0x54, vec![0x0]), (0x01, vec![])])
+            .unwrap();
+        assert_eq!(result.breakdown[0].1, 800);
+        assert_eq!(result.breakdown[1].1, 1);
+    }
+
+    #[test]
+    fn test_categorized_corpus() {
+        let repricing = Repricing::new("cheap SLOAD").with_cost(0x54, 100);
+        // PUSH1 0, SLOAD and PUSH1 0, PUSH1 1, ADD
+        let reader: &[u8] = &[0x60, 0x00, 0x54];
+        let adder: &[u8] = &[0x60, 0x00, 0x60, 0x01, 0x01];
+        let impact = repricing
+            .evaluate_categorized_corpus(
+                Fork::London,
+                &[("token", reader), ("token", reader), ("math", adder)],
+                &AnalysisConfig::default(),
+            )
+            .unwrap();
+        assert_eq!(impact.total.contracts, 3);
+        assert_eq!(impact.categories["math"].gas_delta(), 0);
+        let single = repricing
+            .evaluate_bytecode(Fork::London, reader, &AnalysisConfig::default())
+            .unwrap();
+        assert_eq!(
+            impact.categories["token"].gas_delta(),
+            2 * single.gas_delta()
+        );
+        assert_eq!(
+            impact.opcode_deltas,
+            BTreeMap::from([(0x54, 2 * single.gas_delta())])
+        );
+        let csv = impact.to_csv();
+        assert!(csv.starts_with("category,contracts"));
+        assert!(csv.contains("\nmath,1,"));
+        assert_eq!(csv.lines().count(), 4);
+        assert!(csv.lines().last().unwrap().starts_with("total,3,"));
+    }
+}
@@ -0,0 +1,223 @@
+//! Account state for new-account charges (EIP-161)
+//!
+//! Whether CALL and SELFDESTRUCT pay the 25000 gas new-account charge depends on the target
+//! account, which bytecode alone does not tell. A [`StateOracle`] answers for the
+//! [`DynamicGasCalculator`](super::DynamicGasCalculator) when given with
+//! [`with_state_oracle`](super::DynamicGasCalculator::with_state_oracle):
+//!
+//! - Before Spurious Dragon the charge applies whenever the target does not exist, even
+//!   without value (SELFDESTRUCT only pays it since Tangerine Whistle).
+//! - Since Spurious Dragon it applies only when value is sent to a dead account: one that
+//!   does not exist or is empty (no code, zero nonce and zero balance). Empty accounts touched
+//!   by a transaction are deleted at its end, see [`cleared_accounts`].
+//!
+//! Operands are `u64`, so addresses are looked up as
+//! [`ExecutionContext::from_vec_address`](super::ExecutionContext::from_vec_address) builds
+//! them from the operand's big-endian bytes.
+
+use super::context::Address;
+use crate::Fork;
+use std::collections::HashMap;
+
+/// Gas charged for sending to or creating a new account
+pub const NEW_ACCOUNT_COST: u64 = 25000;
+
+/// An account in the state trie
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct AccountInfo {
+    /// Transaction or creation count
+    pub nonce: u64,
+    /// Balance in wei
+    pub balance: u128,
+    /// Whether the account has code
+    pub has_code: bool,
+}
+
+impl AccountInfo {
+    /// Empty per EIP-161: no code, zero nonce and zero balance
+    pub fn is_empty(&self) -> bool {
+        self.nonce == 0 && self.balance == 0 && !self.has_code
+    }
+}
+
+/// Account lookups for state-dependent gas
+pub trait StateOracle: Send + Sync {
+    /// The account at `address`, `None` if it does not exist
+    fn account(&self, address: &Address) -> Option<AccountInfo>;
+}
+
+/// Whether sending `value` to `account` pays the new-account charge in `fork`
+pub fn pays_new_account(fork: Fork, account: Option<&AccountInfo>, value: u128) -> bool {
+    if fork >= Fork::SpuriousDragon {
+        value > 0 && account.is_none_or(AccountInfo::is_empty)
+    } else {
+        account.is_none()
+    }
+}
+
+/// Touched accounts that `fork` deletes at the end of a transaction: the empty ones since
+/// Spurious Dragon, none before
+pub fn cleared_accounts(
+    fork: Fork,
+    state: &dyn StateOracle,
+    touched: impl IntoIterator<Item = Address>,
+) -> Vec<Address> {
+    if fork < Fork::SpuriousDragon {
+        return Vec::new();
+    }
+    let mut cleared: Vec<Address> = touched
+        .into_iter()
+        .filter(|address| state.account(address).is_some_and(|a| a.is_empty()))
+        .collect();
+    cleared.sort_unstable();
+    cleared.dedup();
+    cleared
+}
+
+/// Accounts held in memory
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryState {
+    accounts: HashMap<Address, AccountInfo>,
+}
+
+impl MemoryState {
+    /// No accounts
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or replace an account
+    pub fn with_account(mut self, address: Address, account: AccountInfo) -> Self {
+        self.accounts.insert(address, account);
+        self
+    }
+
+    /// Delete the touched accounts `fork` clears at the end of a transaction, returning them
+    pub fn clear_touched(
+        &mut self,
+        fork: Fork,
+        touched: impl IntoIterator<Item = Address>,
+    ) -> Vec<Address> {
+        let cleared = cleared_accounts(fork, self, touched);
+        for address in &cleared {
+            self.accounts.remove(address);
+        }
+        cleared
+    }
+}
+
+impl StateOracle for MemoryState {
+    fn account(&self, address: &Address) -> Option<AccountInfo> {
+        self.accounts.get(address).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gas::{DynamicGasCalculator, ExecutionContext, Repricing};
+
+    fn address(id: u64) -> Address {
+        ExecutionContext::from_vec_address(&id.to_be_bytes())
+    }
+
+    #[test]
+    fn test_call_new_account_charge_across_eip161() {
+        let funded = AccountInfo {
+            balance: 1,
+            ..Default::default()
+        };
+        let state = MemoryState::new()
+            .with_account(address(1), funded)
+            .with_account(address(2), AccountInfo::default());
+        let context = ExecutionContext::new();
+        // CALL to `target` sending `value`, no memory
+        let call = |fork: Fork, target: u64, value: u64| {
+            DynamicGasCalculator::new(fork)
+                .with_state_oracle(state.clone())
+                .calculate_gas_cost(0xf1, &context, &[0, target, value, 0, 0, 0, 0])
+                .unwrap()
+        };
+
+        // Homestead: a missing account costs 25000 even without value, an empty one nothing
+        let base = call(Fork::Homestead, 1, 0);
+        assert_eq!(call(Fork::Homestead, 3, 0), base + NEW_ACCOUNT_COST);
+        assert_eq!(call(Fork::Homestead, 2, 0), base);
+        assert_eq!(call(Fork::Homestead, 2, 1), base + 9000);
+        // Spurious Dragon: only value sent to a dead account pays
+        let base = call(Fork::SpuriousDragon, 1, 0);
+        assert_eq!(call(Fork::SpuriousDragon, 3, 0), base);
+        assert_eq!(
+            call(Fork::SpuriousDragon, 2, 1),
+            base + 9000 + NEW_ACCOUNT_COST
+        );
+        assert_eq!(call(Fork::SpuriousDragon, 1, 1), base + 9000);
+    }
+
+    #[test]
+    fn test_extcall_new_account_charge() {
+        let funded = AccountInfo {
+            balance: 1,
+            ..Default::default()
+        };
+        let state = MemoryState::new().with_account(address(1), funded);
+        let calculator = DynamicGasCalculator::new(Fork::Cancun)
+            .with_repricing(Repricing::new("eof").with_base_cost(0xf8, 100))
+            .with_state_oracle(state);
+        let context = ExecutionContext::new();
+        // EXTCALL to `target` sending `value`, no input
+        let extcall = |target: u64, value: u64| {
+            calculator
+                .calculate_gas_cost(0xf8, &context, &[target, 0, 0, value])
+                .unwrap()
+        };
+
+        // Both targets are cold; only the missing one pays for its creation
+        assert_eq!(extcall(1, 1), 100 + 2500 + 9000);
+        assert_eq!(extcall(2, 1), 100 + 2500 + 9000 + NEW_ACCOUNT_COST);
+        assert_eq!(extcall(2, 0), 100 + 2500);
+    }
+
+    #[test]
+    fn test_selfdestruct_and_state_clearing() {
+        let contract = AccountInfo {
+            balance: 10,
+            has_code: true,
+            ..Default::default()
+        };
+        let state = MemoryState::new().with_account(address(9), contract);
+        let mut context = ExecutionContext::new();
+        context.current_address = address(9);
+        let selfdestruct = |fork: Fork, state: &MemoryState| {
+            DynamicGasCalculator::new(fork)
+                .with_state_oracle(state.clone())
+                .calculate_gas_cost(0xff, &context, &[5])
+                .unwrap()
+        };
+        assert_eq!(selfdestruct(Fork::Homestead, &state), 0);
+        assert_eq!(
+            selfdestruct(Fork::TangerineWhistle, &state),
+            5000 + NEW_ACCOUNT_COST
+        );
+        // A contract without balance sends nothing, so creates nothing
+        let broke = state
+            .clone()
+            .with_account(address(9), AccountInfo::default());
+        assert_eq!(selfdestruct(Fork::SpuriousDragon, &broke), 5000);
+
+        let mut state = state.with_account(address(2), AccountInfo::default());
+        assert!(state
+            .clone()
+            .clear_touched(Fork::TangerineWhistle, [address(2)])
+            .is_empty());
+        assert_eq!(
+            state.clear_touched(Fork::SpuriousDragon, [address(2), address(9)]),
+            [address(2)]
+        );
+        assert_eq!(state.account(&address(2)), None);
+
+        // An oracle keeps the calculator shareable across threads
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+        assert_send_sync(&DynamicGasCalculator::new(Fork::Cancun).with_state_oracle(state));
+    }
+}
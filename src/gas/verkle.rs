@@ -0,0 +1,278 @@
+//! Preview of stateless gas costs under Verkle trees (EIP-4762)
+//!
+//! EIP-4762 replaces the EIP-2929 cold access surcharges with charges for the witness data a
+//! stateless client needs: every first access to a tree stem or leaf, every first write to
+//! them, and every 31-byte chunk of executed code. [`compare_stateless_gas`] prices bytecode
+//! both ways: state opcodes cost the warm read cost plus witness charges, all other
+//! instructions keep the fork's pricing. Enabled by the `unstable-verkle` feature; the EIP is
+//! a draft and its constants may change.
+
+use super::DynamicGasCalculator;
+use crate::disasm::disassemble;
+use crate::stack::{trace_operands, StackValue, Word};
+use crate::{AnalysisConfig, Fork};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// First access to a stem (a group of 256 leaves)
+pub const WITNESS_BRANCH_COST: u64 = 1900;
+/// First access to a leaf
+pub const WITNESS_CHUNK_COST: u64 = 200;
+/// First write to a stem
+pub const SUBTREE_EDIT_COST: u64 = 3000;
+/// First write to a leaf
+pub const CHUNK_EDIT_COST: u64 = 500;
+/// Write to a leaf that was empty
+pub const CHUNK_FILL_COST: u64 = 6200;
+/// Cost of a state access once its witness is paid for
+pub const WARM_STORAGE_READ_COST: u64 = 100;
+
+/// Leaf of the account's basic data (version, nonce, balance, code size)
+const BASIC_DATA_LEAF: u8 = 0;
+/// Leaf of the account's code hash
+const CODE_HASH_LEAF: u8 = 1;
+/// First header leaf holding storage slots
+const HEADER_STORAGE_OFFSET: u8 = 64;
+/// First header leaf holding code chunks
+const CODE_OFFSET: usize = 128;
+
+/// Stem and leaf of a piece of account state
+type TreeKey = ([u8; 20], Word, u8);
+
+/// Witness charges of the accesses in one transaction
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WitnessTracker {
+    accessed_stems: BTreeSet<([u8; 20], Word)>,
+    accessed_leaves: BTreeSet<TreeKey>,
+    edited_stems: BTreeSet<([u8; 20], Word)>,
+    edited_leaves: BTreeSet<TreeKey>,
+}
+
+impl WitnessTracker {
+    /// Create a tracker for a transaction sent to `address`, whose header is in the witness
+    /// already
+    pub fn new(address: [u8; 20]) -> Self {
+        Self {
+            accessed_stems: BTreeSet::from([(address, [0; 32])]),
+            accessed_leaves: BTreeSet::from([
+                (address, [0; 32], BASIC_DATA_LEAF),
+                (address, [0; 32], CODE_HASH_LEAF),
+            ]),
+            ..Self::default()
+        }
+    }
+
+    /// Charge an access to a leaf; `fill` means a write to a leaf that was empty
+    fn access(&mut self, key: TreeKey, write: bool, fill: bool) -> u64 {
+        let (address, stem, _) = key;
+        let mut gas = 0;
+        if self.accessed_stems.insert((address, stem)) {
+            gas += WITNESS_BRANCH_COST;
+        }
+        if self.accessed_leaves.insert(key) {
+            gas += WITNESS_CHUNK_COST;
+        }
+        if write {
+            if self.edited_stems.insert((address, stem)) {
+                gas += SUBTREE_EDIT_COST;
+            }
+            if self.edited_leaves.insert(key) {
+                gas += CHUNK_EDIT_COST;
+                if fill {
+                    gas += CHUNK_FILL_COST;
+                }
+            }
+        }
+        gas
+    }
+
+    /// Charge an access to an account's basic data
+    pub fn basic_data(&mut self, address: [u8; 20], write: bool) -> u64 {
+        self.access((address, [0; 32], BASIC_DATA_LEAF), write, false)
+    }
+
+    /// Charge an access to an account's code hash
+    pub fn code_hash(&mut self, address: [u8; 20]) -> u64 {
+        self.access((address, [0; 32], CODE_HASH_LEAF), false, false)
+    }
+
+    /// Charge an access to a storage slot; slots below 64 live in the account header
+    pub fn storage(&mut self, address: [u8; 20], slot: Word, write: bool, fill: bool) -> u64 {
+        let key = if slot[..31].iter().all(|b| *b == 0) && slot[31] < HEADER_STORAGE_OFFSET {
+            (address, [0; 32], HEADER_STORAGE_OFFSET + slot[31])
+        } else {
+            // Main storage starts at 256^31, so the stem is 256^30 + slot / 256
+            let mut stem = [0u8; 32];
+            stem[1..].copy_from_slice(&slot[..31]);
+            let (sum, carry) = stem[1].overflowing_add(1);
+            stem[1] = sum;
+            stem[0] += carry as u8;
+            (address, stem, slot[31])
+        };
+        self.access(key, write, fill)
+    }
+
+    /// Charge an access to the code chunk holding byte `pc` of an account's code
+    pub fn code_chunk(&mut self, address: [u8; 20], pc: usize) -> u64 {
+        let position = CODE_OFFSET + pc / 31;
+        let mut stem = [0u8; 32];
+        stem[24..].copy_from_slice(&((position / 256) as u64).to_be_bytes());
+        self.access((address, stem, (position % 256) as u8), false, false)
+    }
+}
+
+/// Gas of bytecode under the fork's pricing and under EIP-4762
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatelessGasComparison {
+    /// Total gas under the fork's pricing, including the base transaction cost
+    pub legacy_gas: u64,
+    /// Total gas with stateless access pricing, including the base transaction cost
+    pub stateless_gas: u64,
+    /// Witness charges for executed code chunks
+    pub code_chunk_gas: u64,
+    /// Witness charges of each state opcode
+    pub witness_gas: BTreeMap<u8, u64>,
+}
+
+impl StatelessGasComparison {
+    /// Stateless gas minus legacy gas
+    pub fn gas_delta(&self) -> i128 {
+        self.stateless_gas as i128 - self.legacy_gas as i128
+    }
+}
+
+/// Price `bytecode`, deployed at `address`, under `fork` and under EIP-4762
+///
+/// Instructions are executed once, in code order. Storage writes are assumed to fill empty
+/// slots, and slots or addresses computed at runtime are assumed distinct from every other.
+/// Legacy operands come from the estimator selected in `config`.
+pub fn compare_stateless_gas(
+    bytecode: &[u8],
+    address: [u8; 20],
+    fork: Fork,
+    config: &AnalysisConfig,
+) -> Result<StatelessGasComparison, String> {
+    let instructions = disassemble(bytecode);
+    let sequence = config.operand_sequence(&instructions, fork);
+    let legacy = DynamicGasCalculator::new(fork).analyze_sequence_gas(&sequence)?;
+    let snapshots: BTreeMap<usize, Vec<StackValue>> =
+        trace_operands(&instructions, fork, |i| is_state_opcode(i.opcode))
+            .into_iter()
+            .map(|snapshot| (snapshot.pc, snapshot.inputs))
+            .collect();
+
+    let mut tracker = WitnessTracker::new(address);
+    let mut unknown = 0u64;
+    let mut fresh_word = || {
+        unknown += 1;
+        let mut word = [0xff; 32];
+        word[24..].copy_from_slice(&unknown.to_be_bytes());
+        word
+    };
+    let mut stateless_gas = legacy.total_gas;
+    let mut code_chunk_gas = 0;
+    let mut witness_gas = BTreeMap::new();
+
+    for (instruction, (opcode, legacy_cost)) in instructions.iter().zip(&legacy.breakdown) {
+        let end = instruction.pc + instruction.size() - 1;
+        for pc in (instruction.pc / 31..=end / 31).map(|chunk| chunk * 31) {
+            code_chunk_gas += tracker.code_chunk(address, pc);
+        }
+        let Some(inputs) = snapshots.get(&instruction.pc) else {
+            continue;
+        };
+        let word = |index: usize, fresh: &mut dyn FnMut() -> Word| {
+            inputs
+                .get(index)
+                .and_then(StackValue::word)
+                .unwrap_or_else(fresh)
+        };
+        let account = |index: usize, fresh: &mut dyn FnMut() -> Word| {
+            let word = word(index, fresh);
+            let mut address = [0u8; 20];
+            address.copy_from_slice(&word[12..]);
+            address
+        };
+        let gas = match opcode {
+            0x54 => tracker.storage(address, word(0, &mut fresh_word), false, false),
+            0x55 => tracker.storage(address, word(0, &mut fresh_word), true, true),
+            0x31 | 0x3b => tracker.basic_data(account(0, &mut fresh_word), false),
+            0x3f => tracker.code_hash(account(0, &mut fresh_word)),
+            0x3c => {
+                let target = account(0, &mut fresh_word);
+                tracker.basic_data(target, false) + tracker.code_chunk(target, 0)
+            }
+            0xf1 | 0xf2 | 0xf4 | 0xfa => {
+                let target = account(1, &mut fresh_word);
+                let transfers_value = matches!(opcode, 0xf1 | 0xf2)
+                    && inputs.get(2).and_then(StackValue::as_u64) != Some(0);
+                let mut gas = tracker.basic_data(target, transfers_value);
+                if transfers_value {
+                    gas += tracker.basic_data(address, true);
+                }
+                gas
+            }
+            _ => 0,
+        };
+        *witness_gas.entry(*opcode).or_insert(0) += gas;
+        stateless_gas = stateless_gas - legacy_cost + WARM_STORAGE_READ_COST + gas;
+    }
+
+    Ok(StatelessGasComparison {
+        legacy_gas: legacy.total_gas,
+        stateless_gas: stateless_gas + code_chunk_gas,
+        code_chunk_gas,
+        witness_gas,
+    })
+}
+
+/// Opcodes whose access surcharges EIP-4762 replaces with witness charges
+fn is_state_opcode(opcode: u8) -> bool {
+    matches!(
+        opcode,
+        0x31 | 0x3b | 0x3c | 0x3f | 0x54 | 0x55 | 0xf1 | 0xf2 | 0xf4 | 0xfa
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTRACT: [u8; 20] = [0xcc; 20];
+
+    #[test]
+    fn test_header_slots_are_cheaper() {
+        let mut tracker = WitnessTracker::new(CONTRACT);
+        let mut slot = [0u8; 32];
+        // Slot 0 shares the header stem, which the transaction already opened
+        assert_eq!(
+            tracker.storage(CONTRACT, slot, false, false),
+            WITNESS_CHUNK_COST
+        );
+        assert_eq!(tracker.storage(CONTRACT, slot, false, false), 0);
+        slot[31] = 64;
+        assert_eq!(
+            tracker.storage(CONTRACT, slot, false, false),
+            WITNESS_BRANCH_COST + WITNESS_CHUNK_COST
+        );
+        assert_eq!(
+            tracker.storage(CONTRACT, slot, true, true),
+            SUBTREE_EDIT_COST + CHUNK_EDIT_COST + CHUNK_FILL_COST
+        );
+        // The first 128 code chunks are in the header as well
+        assert_eq!(tracker.code_chunk(CONTRACT, 0), WITNESS_CHUNK_COST);
+        assert_eq!(tracker.code_chunk(CONTRACT, 30), 0);
+    }
+
+    #[test]
+    fn test_compare_stateless_gas() {
+        // PUSH1 0, SLOAD, PUSH1 1, SLOAD, STOP
+        let code = [0x60, 0x00, 0x54, 0x60, 0x01, 0x54, 0x00];
+        let comparison =
+            compare_stateless_gas(&code, CONTRACT, Fork::Prague, &AnalysisConfig::default())
+                .unwrap();
+        assert_eq!(comparison.code_chunk_gas, WITNESS_CHUNK_COST);
+        assert_eq!(comparison.witness_gas[&0x54], 2 * WITNESS_CHUNK_COST);
+        // Two cold SLOADs of header slots get much cheaper
+        assert!(comparison.gas_delta() < 0);
+    }
+}
@@ -0,0 +1,174 @@
+//! Gas heatmaps over the code, for rendering on top of a disassembly view
+//!
+//! A [`Heatmap`] attributes gas to each instruction and to every byte of code: the opcode
+//! byte and its PUSH immediate all carry the instruction's gas, and bytes in detected data
+//! regions carry none. Gas comes either from the fork's static costs or from per-pc totals
+//! measured by a tracer.
+
+use crate::disasm::{detect_data_regions, disassemble};
+use crate::format::NumberFormat;
+use crate::{Fork, OpcodeId, OpcodeRegistry};
+use std::collections::BTreeMap;
+
+/// Gas attributed to one instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HeatmapCell {
+    /// Program counter of the instruction
+    pub pc: usize,
+    /// The opcode
+    pub opcode: u8,
+    /// Bytes covered, including the PUSH immediate
+    pub size: usize,
+    /// Gas attributed to the instruction
+    pub gas: u64,
+}
+
+/// Gas attributed to the instructions and bytes of some code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heatmap {
+    /// Fork the costs were taken from
+    pub fork: Fork,
+    /// One cell per decoded instruction, in code order
+    pub instructions: Vec<HeatmapCell>,
+    /// Gas per byte of code
+    pub bytes: Vec<u64>,
+}
+
+impl Heatmap {
+    /// Heatmap of the fork's static gas cost of each instruction
+    pub fn from_static_costs(bytecode: &[u8], fork: Fork) -> Self {
+        let opcodes = OpcodeRegistry::new().get_opcodes(fork);
+        Self::build(bytecode, fork, |pc| {
+            opcodes
+                .get(&bytecode[pc])
+                .map_or(0, |metadata| metadata.gas_cost_in(fork) as u64)
+        })
+    }
+
+    /// Heatmap of measured gas, e.g. per-pc totals summed over the steps of a trace
+    ///
+    /// Instructions missing from `gas_by_pc` get zero.
+    pub fn from_measured_gas(
+        bytecode: &[u8],
+        fork: Fork,
+        gas_by_pc: &BTreeMap<usize, u64>,
+    ) -> Self {
+        Self::build(bytecode, fork, |pc| {
+            gas_by_pc.get(&pc).copied().unwrap_or(0)
+        })
+    }
+
+    fn build(bytecode: &[u8], fork: Fork, gas_at: impl Fn(usize) -> u64) -> Self {
+        let data_start = detect_data_regions(bytecode, fork)
+            .first()
+            .map_or(bytecode.len(), |region| region.start);
+        let mut bytes = vec![0; bytecode.len()];
+        let instructions: Vec<HeatmapCell> = disassemble(bytecode)
+            .into_iter()
+            .filter(|i| i.pc < data_start)
+            .map(|i| {
+                let cell = HeatmapCell {
+                    pc: i.pc,
+                    opcode: i.opcode,
+                    size: i.size(),
+                    gas: gas_at(i.pc),
+                };
+                let end = (cell.pc + cell.size).min(bytes.len());
+                bytes[cell.pc..end].fill(cell.gas);
+                cell
+            })
+            .collect();
+        Self {
+            fork,
+            instructions,
+            bytes,
+        }
+    }
+
+    /// Total gas over all instructions
+    pub fn total_gas(&self) -> u64 {
+        self.instructions.iter().map(|cell| cell.gas).sum()
+    }
+
+    /// Largest gas value of any instruction, for scaling colors
+    pub fn max_gas(&self) -> u64 {
+        self.instructions
+            .iter()
+            .map(|cell| cell.gas)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Render as JSON: `{"fork", "length", "max", "total", "instructions": [{"pc", "id",
+    /// "size", "gas"}], "bytes": [...]}`, with the fork slug and [`OpcodeId`]s
+    pub fn to_json(&self) -> String {
+        self.to_json_with(&NumberFormat::default())
+    }
+
+    /// Render as JSON like [`to_json`](Self::to_json), adding `"total_cost_wei"` and a
+    /// formatted `"total_cost"` when `format` has a gas price
+    pub fn to_json_with(&self, format: &NumberFormat) -> String {
+        let instructions: Vec<String> = self
+            .instructions
+            .iter()
+            .map(|cell| {
+                format!(
+                    r#"{{"pc":{},"id":"{}","size":{},"gas":{}}}"#,
+                    cell.pc,
+                    OpcodeId::new(cell.opcode, self.fork),
+                    cell.size,
+                    cell.gas
+                )
+            })
+            .collect();
+        let bytes: Vec<String> = self.bytes.iter().map(u64::to_string).collect();
+        let cost = match (
+            format.cost_wei(self.total_gas()),
+            format.eth(self.total_gas()),
+        ) {
+            (Some(wei), Some(eth)) => format!(r#","total_cost_wei":{wei},"total_cost":"{eth}""#),
+            _ => String::new(),
+        };
+        format!(
+            r#"{{"fork":"{}","length":{},"max":{},"total":{}{cost},"instructions":[{}],"bytes":[{}]}}"#,
+            self.fork.slug(),
+            self.bytes.len(),
+            self.max_gas(),
+            self.total_gas(),
+            instructions.join(","),
+            bytes.join(",")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_static_heatmap() {
+        // PUSH1 0x00, SLOAD, STOP, then trailing data
+        let code = [0x60, 0x00, 0x54, 0x00, 0x0c, 0x0d];
+        let heatmap = Heatmap::from_static_costs(&code, Fork::Istanbul);
+        assert_eq!(heatmap.instructions.len(), 3);
+        assert_eq!(heatmap.bytes, vec![3, 3, 800, 0, 0, 0]);
+        assert_eq!(heatmap.total_gas(), 803);
+        assert_eq!(
+            heatmap.to_json(),
+            r#"{"fork":"istanbul","length":6,"max":800,"total":803,"instructions":[{"pc":0,"id":"evm/0x60@istanbul","size":2,"gas":3},{"pc":2,"id":"evm/0x54@istanbul","size":1,"gas":800},{"pc":3,"id":"evm/0x00@istanbul","size":1,"gas":0}],"bytes":[3,3,800,0,0,0]}"#
+        );
+        let json = heatmap.to_json_with(&NumberFormat::readable().with_gas_price_gwei(100));
+        assert!(json.contains(
+            r#""total":803,"total_cost_wei":80300000000000,"total_cost":"0.000080 ETH","#
+        ));
+    }
+
+    #[test]
+    fn test_measured_heatmap() {
+        let code = [0x60, 0x00, 0x54, 0x00];
+        let measured = BTreeMap::from([(2, 2100 * 3)]);
+        let heatmap = Heatmap::from_measured_gas(&code, Fork::London, &measured);
+        assert_eq!(heatmap.bytes, vec![0, 0, 6300, 0]);
+        assert_eq!(heatmap.max_gas(), 6300);
+    }
+}
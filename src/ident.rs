@@ -0,0 +1,134 @@
+//! Stable machine-readable opcode identifiers
+//!
+//! An [`OpcodeId`] names an opcode as priced in one fork, written `evm/0x54@berlin`: the
+//! scheme, the opcode byte in lowercase hex and the fork's [slug](Fork::slug). Exported
+//! reports use these identifiers so external systems can refer to the same opcode and fork
+//! across crate versions. Slugs are spelled out rather than derived from the variant names,
+//! and never change once published.
+
+use crate::Fork;
+use std::fmt;
+use std::str::FromStr;
+
+/// Scheme prefix of every opcode identifier
+pub const OPCODE_ID_SCHEME: &str = "evm";
+
+impl Fork {
+    /// Stable lowercase name of the fork, as used in [`OpcodeId`]s
+    pub fn slug(self) -> &'static str {
+        match self {
+            Fork::Frontier => "frontier",
+            Fork::IceAge => "ice_age",
+            Fork::Homestead => "homestead",
+            Fork::DaoFork => "dao_fork",
+            Fork::TangerineWhistle => "tangerine_whistle",
+            Fork::SpuriousDragon => "spurious_dragon",
+            Fork::Byzantium => "byzantium",
+            Fork::Constantinople => "constantinople",
+            Fork::Petersburg => "petersburg",
+            Fork::Istanbul => "istanbul",
+            Fork::MuirGlacier => "muir_glacier",
+            Fork::Berlin => "berlin",
+            Fork::London => "london",
+            Fork::Altair => "altair",
+            Fork::ArrowGlacier => "arrow_glacier",
+            Fork::GrayGlacier => "gray_glacier",
+            Fork::Bellatrix => "bellatrix",
+            Fork::Paris => "paris",
+            Fork::Shanghai => "shanghai",
+            Fork::Capella => "capella",
+            Fork::Cancun => "cancun",
+            Fork::Deneb => "deneb",
+            Fork::Prague => "prague",
+            Fork::Electra => "electra",
+            #[cfg(feature = "unstable-forks")]
+            Fork::Osaka => "osaka",
+        }
+    }
+
+    /// The fork with the given [slug](Fork::slug)
+    pub fn from_slug(slug: &str) -> Option<Fork> {
+        Fork::ALL.iter().copied().find(|fork| fork.slug() == slug)
+    }
+}
+
+/// An opcode in a fork, e.g. `evm/0x54@berlin`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OpcodeId {
+    /// The opcode
+    pub opcode: u8,
+    /// The fork
+    pub fork: Fork,
+}
+
+impl OpcodeId {
+    /// Identifier of `opcode` in `fork`
+    pub fn new(opcode: u8, fork: Fork) -> Self {
+        Self { opcode, fork }
+    }
+}
+
+impl fmt::Display for OpcodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{OPCODE_ID_SCHEME}/0x{:02x}@{}",
+            self.opcode,
+            self.fork.slug()
+        )
+    }
+}
+
+impl FromStr for OpcodeId {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let error = || format!("Invalid opcode identifier: {text}");
+        let rest = text
+            .strip_prefix(OPCODE_ID_SCHEME)
+            .and_then(|rest| rest.strip_prefix('/'))
+            .ok_or_else(error)?;
+        let (opcode, fork) = rest.split_once('@').ok_or_else(error)?;
+        let hex = opcode.strip_prefix("0x").ok_or_else(error)?;
+        if hex.len() != 2 || hex.chars().any(|c| c.is_ascii_uppercase()) {
+            return Err(error());
+        }
+        let opcode = u8::from_str_radix(hex, 16).map_err(|_| error())?;
+        let fork = Fork::from_slug(fork).ok_or_else(|| format!("Unknown fork slug: {fork}"))?;
+        Ok(Self { opcode, fork })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opcode_id_round_trip() {
+        let id = OpcodeId::new(0x54, Fork::Berlin);
+        assert_eq!(id.to_string(), "evm/0x54@berlin");
+        assert_eq!("evm/0x54@berlin".parse::<OpcodeId>(), Ok(id));
+        for fork in Fork::ALL {
+            let id = OpcodeId::new(0x0a, *fork);
+            assert_eq!(id.to_string().parse::<OpcodeId>(), Ok(id));
+        }
+        assert_eq!(
+            OpcodeId::new(0xf1, Fork::TangerineWhistle).to_string(),
+            "evm/0xf1@tangerine_whistle"
+        );
+    }
+
+    #[test]
+    fn test_malformed_ids_rejected() {
+        for text in [
+            "evm/0x54",
+            "evm/54@berlin",
+            "evm/0x5@berlin",
+            "evm/0x5A@berlin",
+            "wasm/0x54@berlin",
+            "evm/0x54@Berlin",
+        ] {
+            assert!(text.parse::<OpcodeId>().is_err(), "{text}");
+        }
+    }
+}
@@ -0,0 +1,212 @@
+//! Incremental re-analysis of patched bytecode
+//!
+//! Bytecode is split into basic blocks: a block starts at offset 0, at every JUMPDEST and
+//! after every JUMPI or terminator. Gas and stack effects are computed per block, so after a
+//! patch only blocks whose bytes changed are analyzed again; blocks that merely moved keep
+//! their previous results.
+
+use crate::cfg::split_blocks;
+use crate::disasm::{disassemble, Instruction};
+use crate::gas::DynamicGasCalculator;
+use crate::patch::{patch, Edit, PatchedBytecode};
+use crate::{AnalysisConfig, Fork, OpcodeMetadata, OpcodeRegistry};
+use std::collections::HashMap;
+
+/// Net effect of a block on the stack
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StackEffect {
+    /// Items that must be on the stack when the block is entered
+    pub required: usize,
+    /// Change in stack height from entry to exit
+    pub delta: isize,
+    /// Highest stack height reached, relative to the height on entry
+    pub max_growth: usize,
+}
+
+/// Analysis results for one basic block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockAnalysis {
+    /// Program counter of the first instruction
+    pub start: usize,
+    /// Program counter just past the last instruction
+    pub end: usize,
+    /// Gas of the block's instructions, with estimated operands and a fresh execution context
+    pub gas: u64,
+    /// Stack effect of the block
+    pub stack: StackEffect,
+}
+
+/// Per-block gas and stack analysis that can be updated after patches
+pub struct IncrementalAnalysis {
+    bytecode: Vec<u8>,
+    blocks: Vec<BlockAnalysis>,
+    calculator: DynamicGasCalculator,
+    config: AnalysisConfig,
+    opcodes: HashMap<u8, OpcodeMetadata>,
+    recomputed: usize,
+}
+
+impl IncrementalAnalysis {
+    /// Analyze every block of the bytecode, with operands from the estimator in `config`
+    pub fn new(bytecode: &[u8], fork: Fork, config: &AnalysisConfig) -> Result<Self, String> {
+        let mut analysis = Self {
+            bytecode: Vec::new(),
+            blocks: Vec::new(),
+            calculator: DynamicGasCalculator::new(fork),
+            config: config.clone(),
+            opcodes: OpcodeRegistry::new().get_opcodes(fork),
+            recomputed: 0,
+        };
+        analysis.reanalyze(bytecode.to_vec())?;
+        Ok(analysis)
+    }
+
+    /// The current bytecode
+    pub fn bytecode(&self) -> &[u8] {
+        &self.bytecode
+    }
+
+    /// Results for every block, in code order
+    pub fn blocks(&self) -> &[BlockAnalysis] {
+        &self.blocks
+    }
+
+    /// Sum of the gas of all blocks
+    pub fn total_gas(&self) -> u64 {
+        self.blocks.iter().map(|block| block.gas).sum()
+    }
+
+    /// Number of blocks analyzed by the last construction or patch
+    pub fn recomputed_blocks(&self) -> usize {
+        self.recomputed
+    }
+
+    /// Apply edits with [`patch`] and re-analyze only the blocks whose code changed
+    ///
+    /// Blocks containing a jump whose target moved change too, since their PUSH is rewritten.
+    pub fn apply(&mut self, edits: &[Edit]) -> Result<PatchedBytecode, String> {
+        let patched = patch(&self.bytecode, edits)?;
+        self.reanalyze(patched.bytecode.clone())?;
+        Ok(patched)
+    }
+
+    fn reanalyze(&mut self, bytecode: Vec<u8>) -> Result<(), String> {
+        let previous: HashMap<&[u8], &BlockAnalysis> = self
+            .blocks
+            .iter()
+            .map(|block| (&self.bytecode[block.start..block.end], block))
+            .collect();
+
+        let instructions = disassemble(&bytecode);
+        let mut blocks = Vec::new();
+        let mut recomputed = 0;
+        for block in split_blocks(&instructions) {
+            let start = block[0].pc;
+            let end = block[block.len() - 1].next_pc().min(bytecode.len());
+            let (gas, stack) = match previous.get(&bytecode[start..end]) {
+                Some(old) => (old.gas, old.stack),
+                None => {
+                    recomputed += 1;
+                    (self.block_gas(block)?, self.stack_effect(block))
+                }
+            };
+            blocks.push(BlockAnalysis {
+                start,
+                end,
+                gas,
+                stack,
+            });
+        }
+
+        self.bytecode = bytecode;
+        self.blocks = blocks;
+        self.recomputed = recomputed;
+        Ok(())
+    }
+
+    fn block_gas(&self, block: &[Instruction]) -> Result<u64, String> {
+        let sequence = self.config.operand_sequence(block, self.calculator.fork());
+        let result = self
+            .calculator
+            .analyze_sequence_gas_with_config(&sequence, &self.config)?;
+        Ok(result.breakdown.iter().map(|(_, cost)| cost).sum())
+    }
+
+    fn stack_effect(&self, block: &[Instruction]) -> StackEffect {
+        let (mut height, mut lowest, mut highest) = (0isize, 0isize, 0isize);
+        for instruction in block {
+            let (inputs, outputs) = self.opcodes.get(&instruction.opcode).map_or((0, 0), |m| {
+                (m.stack_inputs as isize, m.stack_outputs as isize)
+            });
+            height -= inputs;
+            lowest = lowest.min(height);
+            height += outputs;
+            highest = highest.max(height);
+        }
+        StackEffect {
+            required: lowest.unsigned_abs(),
+            delta: height,
+            max_growth: highest as usize,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // PUSH1 0x04, JUMP | STOP | JUMPDEST, PUSH1 0x01, PUSH1 0x00, SSTORE, STOP
+    const CODE: [u8; 11] = [
+        0x60, 0x04, 0x56, 0x00, 0x5b, 0x60, 0x01, 0x60, 0x00, 0x55, 0x00,
+    ];
+
+    #[test]
+    fn test_only_changed_blocks_are_recomputed() {
+        let mut analysis =
+            IncrementalAnalysis::new(&CODE, Fork::London, &AnalysisConfig::default()).unwrap();
+        assert_eq!(analysis.blocks().len(), 3);
+        assert_eq!(analysis.recomputed_blocks(), 3);
+        let before = analysis.total_gas();
+
+        // PC, POP before the final STOP: only the last block changes
+        analysis
+            .apply(&[Edit::InsertBefore {
+                pc: 10,
+                code: vec![0x58, 0x50],
+            }])
+            .unwrap();
+        assert_eq!(analysis.recomputed_blocks(), 1);
+        assert_eq!(analysis.total_gas(), before + 4);
+
+        // Inserting before the JUMPDEST moves it, so the jump block is rewritten as well
+        analysis
+            .apply(&[Edit::InsertBefore {
+                pc: 3,
+                code: vec![0x58, 0x50],
+            }])
+            .unwrap();
+        assert_eq!(analysis.recomputed_blocks(), 2);
+        assert_eq!(analysis.bytecode()[1], 0x06);
+    }
+
+    #[test]
+    fn test_stack_effect() {
+        let analysis =
+            IncrementalAnalysis::new(&CODE, Fork::London, &AnalysisConfig::default()).unwrap();
+        let last = &analysis.blocks()[2];
+        assert_eq!(
+            last.stack,
+            StackEffect {
+                required: 0,
+                delta: 0,
+                max_growth: 2
+            }
+        );
+        // POP, ADD: three items needed on entry, two fewer on exit
+        let analysis =
+            IncrementalAnalysis::new(&[0x50, 0x01], Fork::London, &AnalysisConfig::default())
+                .unwrap();
+        assert_eq!(analysis.blocks()[0].stack.required, 3);
+        assert_eq!(analysis.blocks()[0].stack.delta, -2);
+    }
+}
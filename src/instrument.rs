@@ -0,0 +1,137 @@
+//! Instrumentation pass that injects code stubs around selected opcodes
+
+use crate::disasm::disassemble;
+use crate::patch::{patch, Edit, PatchedBytecode};
+use std::collections::BTreeSet;
+
+/// Where a stub is injected relative to the instrumented instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum StubPosition {
+    /// Run the stub just before the instruction executes
+    #[default]
+    Before,
+    /// Run the stub when execution falls through past the instruction
+    After,
+}
+
+/// Injects a user-supplied stub around every occurrence of the selected opcodes
+///
+/// The stub should leave the stack as it found it. Jump targets are fixed up by [`patch`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct InstrumentationPass {
+    /// Code injected at each instrumented site
+    pub stub: Vec<u8>,
+    /// Opcodes to instrument
+    pub targets: BTreeSet<u8>,
+    /// Where the stub is placed
+    pub position: StubPosition,
+}
+
+impl InstrumentationPass {
+    /// Create a pass injecting `stub` before each target opcode
+    pub fn new(stub: Vec<u8>) -> Self {
+        Self {
+            stub,
+            ..Self::default()
+        }
+    }
+
+    /// Instrument occurrences of `opcode`
+    pub fn with_target(mut self, opcode: u8) -> Self {
+        self.targets.insert(opcode);
+        self
+    }
+
+    /// Set where the stub is placed
+    pub fn with_position(mut self, position: StubPosition) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Apply the pass to bytecode
+    ///
+    /// A few placements are adjusted so the stub actually runs: a stub before a JUMPDEST goes
+    /// after it (jumps land on the JUMPDEST), and no stub is placed after a terminator since
+    /// that code would be unreachable.
+    pub fn apply(&self, bytecode: &[u8]) -> Result<PatchedBytecode, String> {
+        let instructions = disassemble(bytecode);
+        let mut sites = BTreeSet::new();
+
+        for instruction in &instructions {
+            if !self.targets.contains(&instruction.opcode) {
+                continue;
+            }
+            let site = match self.position {
+                StubPosition::Before if instruction.opcode == 0x5b => instruction.next_pc(),
+                StubPosition::Before => instruction.pc,
+                StubPosition::After if instruction.is_terminator() => continue,
+                StubPosition::After => instruction.next_pc(),
+            };
+            sites.insert(site.min(bytecode.len()));
+        }
+
+        let edits: Vec<Edit> = sites
+            .into_iter()
+            .map(|pc| Edit::InsertBefore {
+                pc,
+                code: self.stub.clone(),
+            })
+            .collect();
+        patch(bytecode, &edits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stub_before_sstore() {
+        // PUSH1 0x01, PUSH1 0x00, SSTORE, STOP
+        let bytecode = [0x60, 0x01, 0x60, 0x00, 0x55, 0x00];
+        let patched = InstrumentationPass::new(vec![0x5a, 0x50]) // GAS, POP
+            .with_target(0x55)
+            .apply(&bytecode)
+            .unwrap();
+        assert_eq!(
+            patched.bytecode,
+            vec![0x60, 0x01, 0x60, 0x00, 0x5a, 0x50, 0x55, 0x00]
+        );
+    }
+
+    #[test]
+    fn test_stub_placement_around_jumps() {
+        // PUSH1 0x04, JUMP, INVALID, JUMPDEST, STOP
+        let bytecode = [0x60, 0x04, 0x56, 0xfe, 0x5b, 0x00];
+        let patched = InstrumentationPass::new(vec![0x58, 0x50]) // PC, POP
+            .with_target(0x56)
+            .with_target(0x5b)
+            .apply(&bytecode)
+            .unwrap();
+        assert_eq!(
+            patched.bytecode,
+            vec![0x60, 0x06, 0x58, 0x50, 0x56, 0xfe, 0x5b, 0x58, 0x50, 0x00]
+        );
+
+        let after = InstrumentationPass::new(vec![0x58, 0x50])
+            .with_target(0x56)
+            .with_position(StubPosition::After)
+            .apply(&bytecode)
+            .unwrap();
+        assert_eq!(after.bytecode, bytecode.to_vec());
+    }
+
+    #[test]
+    fn test_stub_before_internal_call_and_return() {
+        // PUSH1 ret, PUSH1 fn, JUMP, ret: JUMPDEST, STOP, fn: JUMPDEST, JUMP
+        let bytecode = [0x60, 0x05, 0x60, 0x07, 0x56, 0x5b, 0x00, 0x5b, 0x56];
+        let patched = InstrumentationPass::new(vec![0x58, 0x50]) // PC, POP
+            .with_target(0x56)
+            .apply(&bytecode)
+            .unwrap();
+        assert_eq!(
+            patched.bytecode,
+            vec![0x60, 0x07, 0x60, 0x09, 0x58, 0x50, 0x56, 0x5b, 0x00, 0x5b, 0x58, 0x50, 0x56]
+        );
+    }
+}
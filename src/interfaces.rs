@@ -0,0 +1,229 @@
+//! Heuristic detection of standard token and introspection interfaces
+//!
+//! The selectors found by [`detect_dispatcher`] are matched against the functions of the
+//! standards in [`STANDARD_INTERFACES`]. An ERC-165 interface id pushed as a constant (what
+//! `supportsInterface` compares its argument with) counts as the contract advertising the
+//! interface. A standard is reported when all its functions are dispatched or it is
+//! advertised; shared selectors (ERC-20 and ERC-721 both have `approve(address,uint256)`)
+//! mean a partial match says little on its own.
+
+use crate::disasm::disassemble;
+use crate::functions::{detect_dispatcher, FunctionEntry};
+use crate::keccak::keccak256;
+use std::collections::BTreeSet;
+
+/// A standard interface, by the signatures of its functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StandardInterface {
+    /// Interface name
+    pub name: &'static str,
+    /// ERC number
+    pub erc: u16,
+    /// Function signatures
+    pub functions: &'static [&'static str],
+    /// Whether implementations announce the interface through ERC-165
+    pub erc165: bool,
+}
+
+impl StandardInterface {
+    /// Selectors of the interface's functions
+    pub fn selectors(&self) -> Vec<[u8; 4]> {
+        self.functions
+            .iter()
+            .map(|signature| {
+                let hash = keccak256(signature.as_bytes());
+                [hash[0], hash[1], hash[2], hash[3]]
+            })
+            .collect()
+    }
+
+    /// ERC-165 interface id: the XOR of the selectors
+    pub fn interface_id(&self) -> [u8; 4] {
+        let id = self
+            .selectors()
+            .into_iter()
+            .fold(0, |id, selector| id ^ u32::from_be_bytes(selector));
+        id.to_be_bytes()
+    }
+}
+
+/// The bundled interface table
+pub const STANDARD_INTERFACES: &[StandardInterface] = &[
+    StandardInterface {
+        name: "ERC-165",
+        erc: 165,
+        functions: &["supportsInterface(bytes4)"],
+        erc165: true,
+    },
+    StandardInterface {
+        name: "ERC-20",
+        erc: 20,
+        functions: &[
+            "totalSupply()",
+            "balanceOf(address)",
+            "transfer(address,uint256)",
+            "transferFrom(address,address,uint256)",
+            "approve(address,uint256)",
+            "allowance(address,address)",
+        ],
+        erc165: false,
+    },
+    StandardInterface {
+        name: "ERC-721",
+        erc: 721,
+        functions: &[
+            "balanceOf(address)",
+            "ownerOf(uint256)",
+            "safeTransferFrom(address,address,uint256,bytes)",
+            "safeTransferFrom(address,address,uint256)",
+            "transferFrom(address,address,uint256)",
+            "approve(address,uint256)",
+            "setApprovalForAll(address,bool)",
+            "getApproved(uint256)",
+            "isApprovedForAll(address,address)",
+        ],
+        erc165: true,
+    },
+    StandardInterface {
+        name: "ERC-721 Metadata",
+        erc: 721,
+        functions: &["name()", "symbol()", "tokenURI(uint256)"],
+        erc165: true,
+    },
+    StandardInterface {
+        name: "ERC-721 Enumerable",
+        erc: 721,
+        functions: &[
+            "totalSupply()",
+            "tokenOfOwnerByIndex(address,uint256)",
+            "tokenByIndex(uint256)",
+        ],
+        erc165: true,
+    },
+    StandardInterface {
+        name: "ERC-1155",
+        erc: 1155,
+        functions: &[
+            "safeTransferFrom(address,address,uint256,uint256,bytes)",
+            "safeBatchTransferFrom(address,address,uint256[],uint256[],bytes)",
+            "balanceOf(address,uint256)",
+            "balanceOfBatch(address[],uint256[])",
+            "setApprovalForAll(address,bool)",
+            "isApprovedForAll(address,address)",
+        ],
+        erc165: true,
+    },
+];
+
+/// A standard the bytecode plausibly implements
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InterfaceMatch {
+    /// Interface name, as in [`STANDARD_INTERFACES`]
+    pub name: &'static str,
+    /// ERC number
+    pub erc: u16,
+    /// Selectors of the interface the dispatcher handles
+    pub found: Vec<[u8; 4]>,
+    /// Selectors of the interface the dispatcher does not handle
+    pub missing: Vec<[u8; 4]>,
+    /// The ERC-165 interface id appears as a constant
+    pub advertised: bool,
+}
+
+impl InterfaceMatch {
+    /// Whether every function of the interface is dispatched
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+/// Standards implemented by a contract with the given functions, in table order
+pub fn match_interfaces(functions: &[FunctionEntry], bytecode: &[u8]) -> Vec<InterfaceMatch> {
+    let dispatched: BTreeSet<[u8; 4]> = functions.iter().map(|f| f.selector).collect();
+    let constants = selector_constants(bytecode);
+    STANDARD_INTERFACES
+        .iter()
+        .filter_map(|interface| {
+            let (found, missing) = interface
+                .selectors()
+                .into_iter()
+                .partition::<Vec<_>, _>(|selector| dispatched.contains(selector));
+            let advertised = interface.erc165 && constants.contains(&interface.interface_id());
+            (missing.is_empty() || advertised).then_some(InterfaceMatch {
+                name: interface.name,
+                erc: interface.erc,
+                found,
+                missing,
+                advertised,
+            })
+        })
+        .collect()
+}
+
+/// Standards implemented by a contract, from its dispatcher
+pub fn detect_interfaces(bytecode: &[u8]) -> Vec<InterfaceMatch> {
+    match_interfaces(&detect_dispatcher(bytecode), bytecode)
+}
+
+/// Four-byte constants pushed by PUSH4, or left-aligned by PUSH32 as Solidity does for
+/// `bytes4` values
+fn selector_constants(bytecode: &[u8]) -> BTreeSet<[u8; 4]> {
+    disassemble(bytecode)
+        .iter()
+        .filter_map(|instruction| match instruction.opcode {
+            0x63 => instruction.immediate.as_slice().try_into().ok(),
+            0x7f => {
+                let (constant, rest) = instruction.immediate.split_first_chunk::<4>()?;
+                rest.iter().all(|byte| *byte == 0).then_some(*constant)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Linear dispatcher over `selectors`, all jumping to one JUMPDEST, STOP
+    fn dispatcher(selectors: &[[u8; 4]]) -> Vec<u8> {
+        let target = 6 + selectors.len() * 10 + 1;
+        let mut code = vec![0x60, 0x00, 0x35, 0x60, 0xe0, 0x1c];
+        for selector in selectors {
+            code.extend([0x80, 0x63]);
+            code.extend(selector);
+            code.extend([0x14, 0x60, target as u8, 0x57]);
+        }
+        code.extend([0x00, 0x5b, 0x00]);
+        code
+    }
+
+    #[test]
+    fn test_known_interface_ids() {
+        let id = |name: &str| {
+            let interface = STANDARD_INTERFACES.iter().find(|i| i.name == name);
+            interface.unwrap().interface_id()
+        };
+        assert_eq!(id("ERC-165"), [0x01, 0xff, 0xc9, 0xa7]);
+        assert_eq!(id("ERC-721"), [0x80, 0xac, 0x58, 0xcd]);
+        assert_eq!(id("ERC-721 Metadata"), [0x5b, 0x5e, 0x13, 0x9f]);
+        assert_eq!(id("ERC-1155"), [0xd9, 0xb6, 0x7a, 0x26]);
+    }
+
+    #[test]
+    fn test_token_interfaces_detected() {
+        let erc20 = STANDARD_INTERFACES[1].selectors();
+        let matches = detect_interfaces(&dispatcher(&erc20));
+        assert_eq!(matches.len(), 1);
+        assert_eq!((matches[0].name, matches[0].found.len()), ("ERC-20", 6));
+
+        // ownerOf and supportsInterface only, but the ERC-721 id is pushed
+        let mut code = dispatcher(&[[0x63, 0x52, 0x21, 0x1e], [0x01, 0xff, 0xc9, 0xa7]]);
+        code.extend([0x63, 0x80, 0xac, 0x58, 0xcd]);
+        let names: Vec<_> = detect_interfaces(&code)
+            .into_iter()
+            .map(|m| (m.name, m.is_complete()))
+            .collect();
+        assert_eq!(names, vec![("ERC-165", true), ("ERC-721", false)]);
+    }
+}
@@ -0,0 +1,180 @@
+//! Recognition of computed jumps with a bounded set of targets
+//!
+//! Most jumps push their target right before JUMP or JUMPI and are resolved directly. Two
+//! other idioms still have a small, statically known set of targets:
+//! - a constant target that reaches the jump through DUP, SWAP or bitwise operations
+//! - a packed jump table: a CODECOPY with constant operands copies a table of fixed-width
+//!   entries into memory, and the jump target is an entry loaded with MLOAD and shifted down
+//!   with `PUSH1 k SHR`, where the entry width is `(256 - k) / 8` bytes (Huff's
+//!   `__tablestart` / `__tablesize` pattern)
+//!
+//! Only tables whose entries are all valid JUMPDESTs are accepted.
+
+use crate::disasm::{disassemble, static_jump_target, valid_jump_destinations};
+use crate::stack::trace_operands;
+use crate::Fork;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// How the targets of a computed jump were found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JumpTableKind {
+    /// The target is a constant that is not pushed directly before the jump
+    Constant,
+    /// The target is an entry of a table copied from code
+    CodeTable {
+        /// Code offset of the table
+        offset: usize,
+        /// Width of each entry in bytes
+        entry_size: usize,
+    },
+}
+
+/// A computed jump and every target it can reach
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct JumpTable {
+    /// Program counter of the JUMP or JUMPI
+    pub jump_pc: usize,
+    /// The idiom that was recognized
+    pub kind: JumpTableKind,
+    /// Possible targets, sorted and deduplicated
+    pub targets: Vec<usize>,
+}
+
+/// Find computed jumps whose targets can be enumerated
+///
+/// Jumps whose target is pushed directly before them are not included.
+pub fn find_jump_tables(bytecode: &[u8], fork: Fork) -> Vec<JumpTable> {
+    let instructions = disassemble(bytecode);
+    let destinations = valid_jump_destinations(&instructions);
+
+    let mut tables = Vec::new();
+    let mut copied_tables = Vec::new();
+    for snapshot in trace_operands(&instructions, fork, |i| {
+        matches!(i.opcode, 0x39 | 0x56 | 0x57)
+    }) {
+        if snapshot.opcode == 0x39 {
+            // CODECOPY inputs: destOffset, offset, size
+            if let (Some(offset), Some(size)) =
+                (snapshot.inputs[1].as_u64(), snapshot.inputs[2].as_u64())
+            {
+                copied_tables.push((offset as usize, size as usize));
+            }
+            continue;
+        }
+        let index = instructions
+            .iter()
+            .position(|i| i.pc == snapshot.pc)
+            .expect("snapshot of a decoded instruction");
+        let previous = index.checked_sub(1).map(|prev| &instructions[prev]);
+        if previous.is_some_and(|push| static_jump_target(push, &destinations).is_some()) {
+            continue;
+        }
+        if let Some(target) = snapshot.inputs[0].as_u64().map(|t| t as usize) {
+            if destinations.contains(&target) {
+                tables.push(JumpTable {
+                    jump_pc: snapshot.pc,
+                    kind: JumpTableKind::Constant,
+                    targets: vec![target],
+                });
+            }
+            continue;
+        }
+        // MLOAD, PUSH1 k, SHR, JUMP
+        let shifted = index >= 3
+            && instructions[index - 3].opcode == 0x51
+            && instructions[index - 2].opcode == 0x60
+            && instructions[index - 1].opcode == 0x1c;
+        if !shifted {
+            continue;
+        }
+        let shift = instructions[index - 2].immediate[0] as usize;
+        if !shift.is_multiple_of(8) || shift < 256 - 32 {
+            continue;
+        }
+        let entry_size = (256 - shift) / 8;
+        let table = copied_tables.iter().rev().find_map(|&(offset, size)| {
+            let targets = table_entries(bytecode, offset, size, entry_size)?;
+            targets
+                .iter()
+                .all(|target| destinations.contains(target))
+                .then_some((offset, targets))
+        });
+        if let Some((offset, targets)) = table {
+            tables.push(JumpTable {
+                jump_pc: snapshot.pc,
+                kind: JumpTableKind::CodeTable { offset, entry_size },
+                targets: targets.into_iter().collect(),
+            });
+        }
+    }
+    tables
+}
+
+/// Targets of every resolvable computed jump, keyed by the pc of the jump
+pub fn jump_table_targets(bytecode: &[u8], fork: Fork) -> BTreeMap<usize, Vec<usize>> {
+    find_jump_tables(bytecode, fork)
+        .into_iter()
+        .map(|table| (table.jump_pc, table.targets))
+        .collect()
+}
+
+/// Decode a table of big-endian entries, if it lies within the code
+fn table_entries(
+    bytecode: &[u8],
+    offset: usize,
+    size: usize,
+    entry_size: usize,
+) -> Option<BTreeSet<usize>> {
+    if size == 0 || !size.is_multiple_of(entry_size) {
+        return None;
+    }
+    let table = bytecode.get(offset..offset.checked_add(size)?)?;
+    Some(
+        table
+            .chunks(entry_size)
+            .map(|entry| entry.iter().fold(0usize, |acc, b| acc << 8 | *b as usize))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constant_target_through_swap() {
+        // PUSH1 0x07, PUSH1 0x01, SWAP1, JUMP, INVALID, INVALID, JUMPDEST, STOP
+        let code = [0x60, 0x07, 0x60, 0x01, 0x90, 0x56, 0xfe, 0x5b, 0x00];
+        let tables = find_jump_tables(&code, Fork::London);
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].kind, JumpTableKind::Constant);
+        assert_eq!(tables[0].targets, vec![7]);
+    }
+
+    #[test]
+    fn test_packed_code_table() {
+        let code = [
+            0x60, 0x04, 0x60, 0x13, 0x60, 0x00, 0x39, // CODECOPY(0, 0x13, 4)
+            0x60, 0x00, 0x35, 0x51, // CALLDATALOAD(0), MLOAD
+            0x60, 0xf0, 0x1c, 0x56, // PUSH1 0xf0, SHR, JUMP
+            0x5b, 0x00, // 0x0f: JUMPDEST, STOP
+            0x5b, 0x00, // 0x11: JUMPDEST, STOP
+            0x00, 0x0f, 0x00, 0x11, // 0x13: table
+        ];
+        let tables = find_jump_tables(&code, Fork::London);
+        assert_eq!(tables.len(), 1);
+        assert_eq!(
+            tables[0].kind,
+            JumpTableKind::CodeTable {
+                offset: 0x13,
+                entry_size: 2
+            }
+        );
+        assert_eq!(tables[0].targets, vec![0x0f, 0x11]);
+
+        // A table entry that is not a JUMPDEST makes the jump unresolvable
+        let mut broken = code;
+        broken[22] = 0x10;
+        assert!(find_jump_tables(&broken, Fork::London).is_empty());
+    }
+}
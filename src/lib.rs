@@ -4,11 +4,31 @@
 #![deny(missing_docs)]
 #![warn(clippy::all)]
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 pub mod forks;
 pub use forks::*;
 
+// Number formatting for rendered reports
+pub mod format;
+pub use format::NumberFormat;
+
+// Stable opcode identifiers
+pub mod ident;
+pub use ident::{OpcodeId, OPCODE_ID_SCHEME};
+
+// Execution and consensus layer forks
+pub mod upgrades;
+pub use upgrades::{ConsensusFork, ExecutionFork, NetworkUpgrade};
+
+// Fork activation schedules
+pub mod schedule;
+pub use schedule::{Activation, ForkInfo, ForkSchedule};
+
+// Chain profiles
+pub mod chain;
+pub use chain::{ArbOsVersion, BscHardfork, ChainProfile, L1DataPricing, OpStackHardfork};
+
 // Core traits and types
 pub mod traits;
 pub use traits::*;
@@ -17,18 +37,199 @@ pub use traits::*;
 pub mod validation;
 pub use validation::*;
 
+// Composable sequence validation rules
+pub mod rules;
+pub use rules::{
+    ChainDivergenceRule, EofBannedOpcodeRule, OpcodeAllowlistRule, SequenceRule, SequenceValidator,
+    Violation,
+};
+
+// Opcode subsets for sandboxed EVMs
+pub mod sandbox;
+
+// Analysis configuration
+pub mod config;
+pub use config::{AnalysisBudget, AnalysisConfig, CancellationToken, UnknownOpcodePolicy};
+
+// Bytecode from and to hex
+pub mod bytecode;
+pub use bytecode::{decode_hex, encode_hex, Bytecode};
+
+// Bytecode disassembly
+pub mod disasm;
+pub use disasm::{
+    disassemble, reassemble, symbolic_listing, DataRegion, DataRegionKind, Disassembly,
+    Instruction, InstructionIter, InstructionRef,
+};
+
+// Annotated disassembly listings
+pub mod pretty;
+pub use pretty::PrettyPrinter;
+
+// Compiler metadata trailer decoding
+pub mod metadata;
+pub use metadata::{decode_metadata, strip_metadata, CompilerMetadata, SourceHash};
+
+// Constructor, runtime code and data splitting
+pub mod split;
+pub use split::{split_code, CodeSplit};
+
+// Heuristic compiler identification
+pub mod compiler;
+pub use compiler::{identify_compiler, CompilerFamily, CompilerGuess, Confidence};
+
+// Keccak-256
+pub mod keccak;
+pub use keccak::keccak256;
+
+// Abstract stack simulation
+pub mod stack;
+pub use stack::{StackSimulator, StackValue};
+
+// Call data taint tracking
+pub mod taint;
+pub use taint::{find_tainted_sinks, TaintSink, TaintedSink};
+
+// Return data and revert reasons
+pub mod reverts;
+pub use reverts::{find_exit_data, revert_reasons, ExitData, RevertReason};
+
+// Computed jump resolution
+pub mod jumptable;
+pub use jumptable::{find_jump_tables, JumpTable, JumpTableKind};
+
+// Control-flow graph construction
+pub mod cfg;
+pub use cfg::{BasicBlock, ControlFlowGraph, Edge, EdgeKind};
+
+// External call allowlists
+pub mod allowlist;
+pub use allowlist::{find_external_calls, CallAllowlist, ExternalCall};
+
+// Linked library calls
+pub mod libraries;
+pub use libraries::{
+    find_linked_libraries, link_placeholders, LibraryReport, LinkKind, LinkPlaceholder,
+    LinkedLibrary,
+};
+
+// Storage vs immutable read classification
+pub mod reads;
+pub use reads::{classify_reads, ReadKind, ReadSummary};
+
+// Per-function analysis
+pub mod functions;
+pub use functions::{analyze_functions, detect_dispatcher, FunctionEntry, FunctionSummary};
+
+// Dispatcher gas and selector ordering
+pub mod dispatch;
+pub use dispatch::{dispatch_report, selector_collisions, DispatchCost, DispatchReport};
+
+// Standard interface detection
+pub mod interfaces;
+pub use interfaces::{detect_interfaces, InterfaceMatch, StandardInterface, STANDARD_INTERFACES};
+
+// Semantic changes of opcodes across forks
+pub mod semantics;
+pub use semantics::{semantic_changes_between, semantic_notes, SemanticChange, SEMANTIC_CHANGES};
+
+// Fork drift between compilation and deployment
+pub mod drift;
+pub use drift::{check_fork_drift, Drift, DriftKind, DriftReport};
+
+// State-access footprints for bundle conflict detection
+pub mod footprint;
+pub use footprint::{state_footprint, AccessMode, StateAccess, StateFootprint, StateKey};
+
+// EIP-7702 set-code transactions
+pub mod setcode;
+pub use setcode::{
+    authorization_refund, delegation_target, find_eoa_checks, set_code_intrinsic_gas, EoaCheck,
+};
+
+// Wallet pre-sign checks
+pub mod presign;
+pub use presign::{presign_check, PresignFlag, PresignReport};
+
+// Selector and event topic resolution
+pub mod resolver;
+pub use resolver::{NoopResolver, SelectorResolver, SignatureDatabase};
+
+// Proxy pattern recognition
+pub mod proxy;
+pub use proxy::{detect_proxy, ProxyPattern, ProxyReport};
+
+// Bytecode patching
+pub mod patch;
+pub use patch::{jump_links, lower_push0, patch, Edit, JumpLinks, PatchedBytecode, Patcher};
+
+// Target-fork lowering rules
+pub mod lowering;
+pub use lowering::{lower_opcode, lowering_table, Lowering, LoweringRule, LOWERING_RULES};
+
+// Instruction sequence builder
+pub mod seq;
+pub use seq::Seq;
+
+// Instrumentation on top of patching
+pub mod instrument;
+pub use instrument::{InstrumentationPass, StubPosition};
+
+// Incremental re-analysis after patches
+pub mod incremental;
+pub use incremental::{BlockAnalysis, IncrementalAnalysis, StackEffect};
+
+// Instruction stream normalization
+pub mod normalize;
+pub use normalize::normalize;
+
+// Deterministic all-in-one analysis
+pub mod report;
+pub use report::{analyze, analyze_batch, Report};
+
+// Corpus-level opcode statistics
+pub mod census;
+pub use census::OpcodeCensus;
+
+// Gas heatmaps
+pub mod heatmap;
+pub use heatmap::{Heatmap, HeatmapCell};
+
+// Report caching keyed by code hash
+pub mod cache;
+pub use cache::{AnalysisCache, CacheKey, CachedAnalyzer, MemoryCache};
+
 // Gas analysis system
 pub mod gas;
 pub use gas::{
-    DynamicGasCalculator, ExecutionContext, GasAnalysis, GasAnalysisResult, GasCostCategory,
+    DynamicGasCalculator, EstimateConfidence, ExecutionContext, GasAnalysis, GasAnalysisResult,
+    GasCostCategory,
 };
 
+// Golden gas vectors for pricers
+pub mod conformance;
+pub use conformance::{run_gas_vectors, GasVector, VectorReport};
+
+// Cross-fork differential testing
+pub mod differential;
+pub use differential::{DifferenceKind, DifferentialReport, DifferentialTest, ForkDifference};
+
+// Dataset versioning and changelog
+pub mod version;
+pub use version::{DataChange, CHANGELOG, DATA_VERSION};
+
 // Unified opcodes feature for bytecode manipulation tools
 #[cfg(feature = "unified-opcodes")]
 pub mod unified;
 #[cfg(feature = "unified-opcodes")]
 pub use unified::UnifiedOpcode;
 
+// Mnemonic assembler
+#[cfg(feature = "unified-opcodes")]
+pub mod asm;
+#[cfg(feature = "unified-opcodes")]
+pub use asm::assemble;
+
 /// Ethereum hard fork identifiers in chronological order
 #[derive(Clone, Copy, Debug, PartialOrd, Ord, PartialEq, Eq, Hash)]
 pub enum Fork {
@@ -76,6 +277,13 @@ pub enum Fork {
     Cancun,
     /// Deneb (March 13, 2024) - Beacon Chain blobs
     Deneb,
+    /// Prague (May 7, 2025) - Pectra execution layer, EIP-7702 set code transactions
+    Prague,
+    /// Electra (May 7, 2025) - Pectra consensus layer
+    Electra,
+    /// Osaka (scheduled) - Fusaka execution layer, CLZ opcode
+    #[cfg(feature = "unstable-forks")]
+    Osaka,
 }
 
 /// EVM opcode groups for better organization
@@ -130,6 +338,53 @@ pub struct OpcodeMetadata {
     pub gas_history: &'static [(Fork, u16)],
 }
 
+impl OpcodeMetadata {
+    /// Check if this is the designated INVALID instruction (0xfe)
+    pub fn is_designated_invalid(&self) -> bool {
+        self.opcode == 0xfe
+    }
+
+    /// Check if executing this opcode consumes all remaining gas
+    ///
+    /// The table lists INVALID with a base cost of 0, but reaching it is an exceptional
+    /// halt that burns the whole gas limit of the current call.
+    pub fn consumes_all_gas(&self) -> bool {
+        self.is_designated_invalid()
+    }
+
+    /// Check if this opcode belongs to a scheduled fork and may still change
+    pub fn is_scheduled(&self) -> bool {
+        self.introduced_in.is_scheduled()
+    }
+
+    /// Base gas cost in a fork, taking the gas history into account
+    pub fn gas_cost_in(&self, fork: Fork) -> u16 {
+        self.gas_history
+            .iter()
+            .rev()
+            .find(|(f, _)| *f <= fork)
+            .map_or(self.gas_cost, |(_, cost)| *cost)
+    }
+}
+
+/// How a byte behaves when executed as an opcode in a given fork
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ByteClass {
+    /// A regular opcode assigned in the fork
+    Assigned,
+    /// The designated INVALID instruction (0xfe)
+    DesignatedInvalid,
+    /// A byte with no opcode assigned in the fork
+    Unassigned,
+}
+
+impl ByteClass {
+    /// Check if executing the byte is an exceptional halt consuming all remaining gas
+    pub fn consumes_all_gas(self) -> bool {
+        !matches!(self, Self::Assigned)
+    }
+}
+
 /// Core trait that all opcode enums must implement
 pub trait OpCode: From<u8> + Into<u8> + Clone + Copy + std::fmt::Debug {
     /// Get complete metadata for this opcode
@@ -146,6 +401,13 @@ pub trait OpCode: From<u8> + Into<u8> + Clone + Copy + std::fmt::Debug {
         Self::all_opcodes().iter().any(|op| (*op).into() == opcode)
     }
 
+    /// Convert a byte into an opcode, returning `None` instead of panicking for unknown bytes
+    fn try_from_byte(byte: u8) -> Option<Self> {
+        Self::all_opcodes()
+            .into_iter()
+            .find(|op| (*op).into() == byte)
+    }
+
     /// Get gas cost for this opcode in this fork
     fn gas_cost(&self) -> u16 {
         let metadata = self.metadata();
@@ -202,33 +464,55 @@ pub trait ForkOpcodes {
 }
 
 /// Comprehensive opcode registry that manages all forks
+#[derive(Clone)]
 pub struct OpcodeRegistry {
-    opcodes: HashMap<Fork, HashMap<u8, OpcodeMetadata>>,
+    /// Ordered by fork so inheritance applies later forks over earlier ones
+    opcodes: BTreeMap<ExecutionFork, HashMap<u8, OpcodeMetadata>>,
+    /// User-defined forks by name
+    custom_forks: BTreeMap<String, CustomFork>,
+    /// Opcodes a [subset](Self::subset) is restricted to, `None` for the full tables
+    allowlist: Option<BTreeSet<u8>>,
+}
+
+/// A user-defined fork layered on top of a built-in one
+#[derive(Clone)]
+struct CustomFork {
+    base: Fork,
+    /// Full opcode table: the base fork's opcodes with additions and overrides applied
+    opcodes: HashMap<u8, OpcodeMetadata>,
 }
 
 impl OpcodeRegistry {
     /// Create a new opcode registry with all known opcodes
     pub fn new() -> Self {
         let mut registry = Self {
-            opcodes: HashMap::new(),
+            opcodes: BTreeMap::new(),
+            custom_forks: BTreeMap::new(),
+            allowlist: None,
         };
 
         // Register all forks
         registry.register_fork::<forks::Frontier>();
         registry.register_fork::<forks::Homestead>();
+        registry.register_fork::<forks::TangerineWhistle>();
+        registry.register_fork::<forks::SpuriousDragon>();
         registry.register_fork::<forks::Byzantium>();
         registry.register_fork::<forks::Constantinople>();
+        registry.register_fork::<forks::Petersburg>();
         registry.register_fork::<forks::Istanbul>();
         registry.register_fork::<forks::Berlin>();
         registry.register_fork::<forks::London>();
         registry.register_fork::<forks::Shanghai>();
         registry.register_fork::<forks::Cancun>();
+        registry.register_fork::<forks::Prague>();
+        #[cfg(feature = "unstable-forks")]
+        registry.register_fork::<forks::Osaka>();
 
         registry
     }
 
     fn register_fork<T: OpCode>(&mut self) {
-        let fork = T::fork();
+        let fork = ExecutionFork::try_from(T::fork()).expect("opcode tables belong to EVM forks");
         let mut opcodes = HashMap::new();
 
         for opcode_enum in T::all_opcodes() {
@@ -244,13 +528,11 @@ impl OpcodeRegistry {
     pub fn get_opcodes(&self, fork: Fork) -> HashMap<u8, OpcodeMetadata> {
         let mut result = HashMap::new();
 
-        // Collect opcodes from all previous forks (inheritance)
-        for f in self.opcodes.keys() {
-            if *f <= fork {
-                if let Some(fork_opcodes) = self.opcodes.get(f) {
-                    result.extend(fork_opcodes.clone());
-                }
-            }
+        // Collect opcodes from all previous forks (inheritance), oldest first so newer
+        // metadata wins
+        let rules = fork.evm_rules();
+        for fork_opcodes in self.opcodes.range(..=rules).map(|(_, opcodes)| opcodes) {
+            result.extend(fork_opcodes.clone());
         }
 
         result
@@ -261,6 +543,100 @@ impl OpcodeRegistry {
         self.get_opcodes(fork).contains_key(&opcode)
     }
 
+    /// Classify a byte as an assigned opcode, the designated INVALID, or unassigned
+    pub fn classify_byte(&self, fork: Fork, byte: u8) -> ByteClass {
+        if byte == 0xfe {
+            ByteClass::DesignatedInvalid
+        } else if self.is_opcode_available(fork, byte) {
+            ByteClass::Assigned
+        } else {
+            ByteClass::Unassigned
+        }
+    }
+
+    /// Bytes with no opcode assigned in a fork, ascending
+    ///
+    /// These are the targets for invalid-opcode inputs when fuzzing or differentially testing
+    /// an EVM. The designated INVALID (0xfe) is assigned and therefore not included; add it
+    /// for inputs that should halt on every fork.
+    pub fn unassigned_opcodes(&self, fork: Fork) -> Vec<u8> {
+        let opcodes = self.get_opcodes(fork);
+        (0..=u8::MAX)
+            .filter(|byte| *byte != 0xfe && !opcodes.contains_key(byte))
+            .collect()
+    }
+
+    /// Register a fork for a private chain or testnet on top of a built-in fork
+    ///
+    /// `additions` must use bytes that are unassigned in `base_fork` and `overrides` must
+    /// replace assigned ones. Custom forks are only visible through this registry (see
+    /// [`get_custom_fork_opcodes`](Self::get_custom_fork_opcodes)); analyses that take a
+    /// [`Fork`] keep using the built-in tables.
+    pub fn register_custom_fork(
+        &mut self,
+        name: &str,
+        base_fork: Fork,
+        additions: Vec<OpcodeMetadata>,
+        overrides: Vec<OpcodeMetadata>,
+    ) -> Result<(), String> {
+        if Fork::ALL
+            .iter()
+            .any(|fork| format!("{fork:?}").eq_ignore_ascii_case(name))
+        {
+            return Err(format!("{name} is the name of a built-in fork"));
+        }
+        if self.custom_forks.contains_key(name) {
+            return Err(format!("Custom fork {name} is already registered"));
+        }
+
+        let mut opcodes = self.get_opcodes(base_fork);
+        for metadata in additions {
+            if opcodes.contains_key(&metadata.opcode) {
+                return Err(format!(
+                    "Cannot add {} as 0x{:02x}: the byte is already assigned in {base_fork:?}",
+                    metadata.name, metadata.opcode
+                ));
+            }
+            opcodes.insert(metadata.opcode, metadata);
+        }
+        for metadata in overrides {
+            if !opcodes.contains_key(&metadata.opcode) {
+                return Err(format!(
+                    "Cannot override 0x{:02x}: the byte is not assigned in {base_fork:?}",
+                    metadata.opcode
+                ));
+            }
+            opcodes.insert(metadata.opcode, metadata);
+        }
+        if let Some(allowlist) = &self.allowlist {
+            opcodes.retain(|opcode, _| allowlist.contains(opcode));
+        }
+
+        self.custom_forks.insert(
+            name.to_string(),
+            CustomFork {
+                base: base_fork,
+                opcodes,
+            },
+        );
+        Ok(())
+    }
+
+    /// Get all opcodes available in a custom fork
+    pub fn get_custom_fork_opcodes(&self, name: &str) -> Option<HashMap<u8, OpcodeMetadata>> {
+        self.custom_forks.get(name).map(|fork| fork.opcodes.clone())
+    }
+
+    /// The built-in fork a custom fork was registered on
+    pub fn custom_fork_base(&self, name: &str) -> Option<Fork> {
+        self.custom_forks.get(name).map(|fork| fork.base)
+    }
+
+    /// Names of the registered custom forks, sorted
+    pub fn custom_forks(&self) -> impl Iterator<Item = &str> {
+        self.custom_forks.keys().map(String::as_str)
+    }
+
     /// Validate opcode consistency across forks
     pub fn validate(&self) -> Result<(), Vec<String>> {
         validation::validate_registry(self)
@@ -351,6 +727,15 @@ macro_rules! opcodes {
                 $crate::Fork::$fork
             }
 
+            fn try_from_byte(byte: u8) -> Option<Self> {
+                match byte {
+                    $(
+                        $opcode => Some(Self::$name),
+                    )*
+                    _ => None,
+                }
+            }
+
             fn all_opcodes() -> Vec<Self> {
                 vec![
                     $(
@@ -0,0 +1,207 @@
+//! Linked Solidity libraries
+//!
+//! Calls to public library functions compile to a DELEGATECALL whose target is a constant:
+//! a PUSH20 filled in by the linker, or a PUSH32 immutable. Unlinked code carries a
+//! placeholder in place of the address (`__$<34 hex digits>$__` in the hex output, see
+//! [`link_placeholders`]); code where the placeholders were zeroed delegates to the zero
+//! address. Library calls are reported per library, separately from ordinary external calls,
+//! with the gas the DELEGATECALLs themselves cost.
+
+use crate::allowlist::{find_external_calls, ExternalCall};
+use crate::disasm::{disassemble, Instruction};
+use crate::gas::{DynamicGasCalculator, ExecutionContext};
+use crate::Fork;
+
+/// How a library address got into the code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LinkKind {
+    /// A PUSH20 of the linked address
+    Linked,
+    /// A PUSH32 holding the address, as Solidity inlines immutables
+    Immutable,
+    /// The zero address, left by an unfilled placeholder
+    Placeholder,
+}
+
+/// A library called with DELEGATECALL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkedLibrary {
+    /// Library address
+    pub address: [u8; 20],
+    /// How the address is pushed, from the first call
+    pub kind: LinkKind,
+    /// Program counters of the DELEGATECALLs to the library
+    pub calls: Vec<usize>,
+    /// Gas of the DELEGATECALLs if each runs once in code order, without the library's own
+    /// execution: on Berlin and later the first call pays for a cold account
+    pub call_overhead: u64,
+}
+
+/// Library calls and the remaining external calls of some code
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LibraryReport {
+    /// Called libraries, in order of first call
+    pub libraries: Vec<LinkedLibrary>,
+    /// External calls that are not library calls
+    pub external_calls: Vec<ExternalCall>,
+}
+
+impl LibraryReport {
+    /// Whether the code calls a library that was never linked
+    pub fn has_unlinked(&self) -> bool {
+        self.libraries
+            .iter()
+            .any(|library| library.kind == LinkKind::Placeholder)
+    }
+
+    /// Gas of all library DELEGATECALLs
+    pub fn total_call_overhead(&self) -> u64 {
+        self.libraries.iter().map(|l| l.call_overhead).sum()
+    }
+}
+
+/// Find library calls: DELEGATECALLs to a constant address
+pub fn find_linked_libraries(bytecode: &[u8], fork: Fork) -> LibraryReport {
+    let instructions = disassemble(bytecode);
+    let calculator = DynamicGasCalculator::new(fork);
+    let mut context = ExecutionContext::new();
+    let mut report = LibraryReport::default();
+
+    for call in find_external_calls(bytecode, fork) {
+        let Some(address) = call.target.filter(|_| call.opcode == 0xf4) else {
+            report.external_calls.push(call);
+            continue;
+        };
+        let index = match report.libraries.iter().position(|l| l.address == address) {
+            Some(index) => index,
+            None => {
+                report.libraries.push(LinkedLibrary {
+                    address,
+                    kind: link_kind(&instructions, call.pc, address),
+                    calls: Vec::new(),
+                    call_overhead: 0,
+                });
+                report.libraries.len() - 1
+            }
+        };
+        // The calculator identifies accounts by a u64 operand, so each library gets its own;
+        // its call operands always include a value slot
+        let account = index as u64 + 1;
+        let gas = calculator
+            .calculate_gas_cost(0xf4, &context, &[0, account, 0, 0, 0, 0, 0])
+            .unwrap_or_default();
+        context.mark_address_accessed(&ExecutionContext::from_vec_address(&account.to_be_bytes()));
+        let library = &mut report.libraries[index];
+        library.calls.push(call.pc);
+        library.call_overhead += gas;
+    }
+
+    report
+}
+
+/// A library placeholder in unlinked hex bytecode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkPlaceholder {
+    /// Byte offset of the placeholder in the code
+    pub offset: usize,
+    /// The placeholder text: `__$<hash>$__`, or `__<name>___…` before Solidity 0.5
+    pub placeholder: String,
+}
+
+/// Find the library placeholders in unlinked hex bytecode (with or without `0x`)
+pub fn link_placeholders(hex: &str) -> Vec<LinkPlaceholder> {
+    let hex = hex.trim().strip_prefix("0x").unwrap_or(hex.trim());
+    let mut placeholders = Vec::new();
+    let mut position = 0;
+    while let Some(start) = hex[position..].find("__").map(|start| position + start) {
+        let Some(placeholder) = hex.get(start..start + 40) else {
+            break;
+        };
+        placeholders.push(LinkPlaceholder {
+            offset: start / 2,
+            placeholder: placeholder.to_string(),
+        });
+        position = start + 40;
+    }
+    placeholders
+}
+
+/// The PUSH that put the address on the stack, searched backwards from the call
+fn link_kind(instructions: &[Instruction], pc: usize, address: [u8; 20]) -> LinkKind {
+    if address == [0; 20] {
+        return LinkKind::Placeholder;
+    }
+    let push = instructions
+        .iter()
+        .rev()
+        .skip_while(|i| i.pc >= pc)
+        .find(|i| i.push_value().is_some_and(|value| value[12..] == address));
+    match push.map(|i| i.opcode) {
+        Some(0x7f) => LinkKind::Immutable,
+        _ => LinkKind::Linked,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// PUSH0 x4 for the memory ranges, `push` the target, GAS, DELEGATECALL, POP
+    fn library_call(push: &[u8]) -> Vec<u8> {
+        let mut code = vec![0x5f, 0x5f, 0x5f, 0x5f];
+        code.extend(push);
+        code.extend([0x5a, 0xf4, 0x50]);
+        code
+    }
+
+    #[test]
+    fn test_library_calls_and_overhead() {
+        let mut push20 = vec![0x73];
+        push20.extend([0x11; 20]);
+        let mut push32 = vec![0x7f];
+        push32.extend([0; 12]);
+        push32.extend([0x22; 20]);
+
+        let mut code = library_call(&push20);
+        code.extend(library_call(&push20));
+        code.extend(library_call(&push32));
+        let mut zero = vec![0x73];
+        zero.extend([0; 20]);
+        code.extend(library_call(&zero));
+        code.extend(library_call(&[0x30])); // ADDRESS: not a constant
+        let report = find_linked_libraries(&code, Fork::Cancun);
+
+        let kinds: Vec<_> = report.libraries.iter().map(|l| l.kind).collect();
+        assert_eq!(
+            kinds,
+            [LinkKind::Linked, LinkKind::Immutable, LinkKind::Placeholder]
+        );
+        assert_eq!(report.libraries[0].calls.len(), 2);
+        // Cold then warm
+        assert_eq!(report.libraries[0].call_overhead, 2600 + 100);
+        assert_eq!(report.libraries[1].call_overhead, 2600);
+        assert!(report.has_unlinked());
+        assert_eq!(report.external_calls.len(), 1);
+        assert_eq!(report.external_calls[0].target, None);
+
+        // Before Berlin every call pays the flat price
+        let report = find_linked_libraries(&library_call(&push20), Fork::Istanbul);
+        assert_eq!(report.total_call_overhead(), 700);
+    }
+
+    #[test]
+    fn test_link_placeholders() {
+        let hex = format!(
+            "0x6080{}73{}5af4{:_<40}",
+            "00".repeat(2),
+            "__$1e5bd4b6c8f2a8e3e8d3b5d4a1f9c0b7e2$__",
+            "__Math"
+        );
+        let placeholders = link_placeholders(&hex);
+        assert_eq!(placeholders.len(), 2);
+        assert_eq!(placeholders[0].offset, 5);
+        assert!(placeholders[0].placeholder.starts_with("__$1e5b"));
+        assert_eq!(placeholders[1].offset, 27);
+        assert!(link_placeholders("6080604052").is_empty());
+    }
+}
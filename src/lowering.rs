@@ -0,0 +1,236 @@
+//! Target-fork lowering rules for code generators
+//!
+//! [`LOWERING_RULES`] lists, for opcodes introduced after Frontier, a sequence of older
+//! instructions with the same stack effect. [`lower_opcode`] answers "how do I express this
+//! opcode on that fork": natively when the fork has it, with the substitute when every
+//! instruction of the substitute is available, or not at all.
+
+use crate::{Fork, OpcodeRegistry};
+use std::collections::BTreeMap;
+
+/// How to replace an opcode on forks that do not have it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LoweringRule {
+    /// The opcode being replaced
+    pub opcode: u8,
+    /// Instructions with the same stack inputs and outputs
+    pub substitute: &'static [u8],
+    /// Gas charged on top of the substitute's instructions (e.g. by a precompile)
+    pub extra_gas: u64,
+    /// Whether the substitute's CALL targets a precompile, which is always warm from Berlin on
+    pub precompile_call: bool,
+    /// Whether the substitute behaves the same, apart from gas
+    pub exact: bool,
+    /// What the substitute does and where it differs
+    pub note: &'static str,
+}
+
+/// Substitutes for opcodes introduced after Frontier
+pub const LOWERING_RULES: &[LoweringRule] = &[
+    LoweringRule {
+        opcode: 0x1b,
+        // PUSH1 2, EXP, MUL
+        substitute: &[0x60, 0x02, 0x0a, 0x02],
+        extra_gas: 0,
+        precompile_call: false,
+        exact: true,
+        note: "SHL: multiply the value by 2 to the power of the shift",
+    },
+    LoweringRule {
+        opcode: 0x1c,
+        // PUSH1 2, EXP, SWAP1, DIV
+        substitute: &[0x60, 0x02, 0x0a, 0x90, 0x04],
+        extra_gas: 0,
+        precompile_call: false,
+        exact: true,
+        note: "SHR: divide the value by 2 to the power of the shift",
+    },
+    LoweringRule {
+        opcode: 0x47,
+        // ADDRESS, BALANCE
+        substitute: &[0x30, 0x31],
+        extra_gas: 0,
+        precompile_call: false,
+        exact: true,
+        note: "SELFBALANCE: BALANCE of ADDRESS",
+    },
+    LoweringRule {
+        opcode: 0x5c,
+        substitute: &[0x54],
+        extra_gas: 0,
+        precompile_call: false,
+        exact: false,
+        note: "TLOAD: SLOAD, which persists across transactions and needs a dedicated slot range",
+    },
+    LoweringRule {
+        opcode: 0x5d,
+        substitute: &[0x55],
+        extra_gas: 0,
+        precompile_call: false,
+        exact: false,
+        note: "TSTORE: SSTORE, which persists across transactions and needs a dedicated slot range",
+    },
+    LoweringRule {
+        opcode: 0x5e,
+        // SWAP1, DUP3, SWAP1, PUSH1 0, PUSH1 4, GAS, CALL, POP
+        substitute: &[0x90, 0x82, 0x90, 0x60, 0x00, 0x60, 0x04, 0x5a, 0xf1, 0x50],
+        extra_gas: 15,
+        precompile_call: true,
+        exact: true,
+        note: "MCOPY: CALL the identity precompile with the source as input and the destination as output; the precompile also charges 3 gas per word",
+    },
+    LoweringRule {
+        opcode: 0x5f,
+        // PUSH1 0
+        substitute: &[0x60, 0x00],
+        extra_gas: 0,
+        precompile_call: false,
+        exact: true,
+        note: "PUSH0: PUSH1 0",
+    },
+    LoweringRule {
+        opcode: 0xfa,
+        // PUSH1 0, SWAP2, SWAP1, CALL
+        substitute: &[0x60, 0x00, 0x91, 0x90, 0xf1],
+        extra_gas: 0,
+        precompile_call: false,
+        exact: false,
+        note: "STATICCALL: CALL without value, which lets the callee modify state",
+    },
+    LoweringRule {
+        opcode: 0xfd,
+        // POP, POP, INVALID
+        substitute: &[0x50, 0x50, 0xfe],
+        extra_gas: 0,
+        precompile_call: false,
+        exact: false,
+        note: "REVERT: INVALID, which consumes all remaining gas and returns no data",
+    },
+];
+
+/// How to express an opcode on a target fork
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Lowering {
+    /// The target fork has the opcode
+    Native {
+        /// Base gas cost in the target fork
+        gas: u64,
+    },
+    /// The opcode can be replaced
+    Substitute {
+        /// Replacement code
+        code: Vec<u8>,
+        /// Base gas of the replacement in the target fork, without memory expansion or other
+        /// size-dependent costs
+        gas: u64,
+        /// Whether the replacement behaves the same, apart from gas
+        exact: bool,
+        /// What the replacement does and where it differs
+        note: &'static str,
+    },
+    /// There is no straight-line equivalent in the target fork
+    Unsupported,
+}
+
+/// Look up how to express `opcode` on `target`
+///
+/// Fails if no fork defines the opcode.
+pub fn lower_opcode(opcode: u8, target: Fork) -> Result<Lowering, String> {
+    let registry = OpcodeRegistry::new();
+    let latest = Fork::execution_forks()
+        .last()
+        .expect("at least one execution fork");
+    if !registry.get_opcodes(latest).contains_key(&opcode) {
+        return Err(format!("Unknown opcode: 0x{opcode:02x}"));
+    }
+    let opcodes = registry.get_opcodes(target);
+    if let Some(metadata) = opcodes.get(&opcode) {
+        return Ok(Lowering::Native {
+            gas: metadata.gas_cost_in(target) as u64,
+        });
+    }
+    let Some(rule) = LOWERING_RULES.iter().find(|rule| rule.opcode == opcode) else {
+        return Ok(Lowering::Unsupported);
+    };
+    let mut gas = rule.extra_gas;
+    let mut pc = 0;
+    while let Some(&byte) = rule.substitute.get(pc) {
+        let Some(metadata) = opcodes.get(&byte) else {
+            return Ok(Lowering::Unsupported);
+        };
+        gas += match byte {
+            0xf1 if rule.precompile_call && target >= Fork::Berlin => 100,
+            _ => metadata.gas_cost_in(target) as u64,
+        };
+        pc += 1 + match byte {
+            0x60..=0x7f => (byte - 0x5f) as usize,
+            _ => 0,
+        };
+    }
+    Ok(Lowering::Substitute {
+        code: rule.substitute.to_vec(),
+        gas,
+        exact: rule.exact,
+        note: rule.note,
+    })
+}
+
+/// How to express every opcode the target fork is missing
+pub fn lowering_table(target: Fork) -> BTreeMap<u8, Lowering> {
+    let registry = OpcodeRegistry::new();
+    let latest = Fork::execution_forks()
+        .last()
+        .expect("at least one execution fork");
+    let available = registry.get_opcodes(target);
+    registry
+        .get_opcodes(latest)
+        .keys()
+        .filter(|opcode| !available.contains_key(opcode))
+        .filter_map(|&opcode| Some((opcode, lower_opcode(opcode, target).ok()?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mcopy_on_london() {
+        let Lowering::Substitute {
+            code, gas, exact, ..
+        } = lower_opcode(0x5e, Fork::London).unwrap()
+        else {
+            panic!("MCOPY should have a substitute on London");
+        };
+        assert_eq!(code[code.len() - 2..], [0xf1, 0x50]);
+        assert!(exact);
+        // Stack shuffling, GAS, a warm CALL, POP and the precompile base cost
+        assert_eq!(gas, 3 * 5 + 2 + 100 + 2 + 15);
+
+        assert_eq!(
+            lower_opcode(0x5e, Fork::Cancun).unwrap(),
+            Lowering::Native { gas: 3 }
+        );
+        assert_eq!(
+            lower_opcode(0x49, Fork::London).unwrap(),
+            Lowering::Unsupported
+        );
+        assert!(lower_opcode(0x0c, Fork::London).is_err());
+    }
+
+    #[test]
+    fn test_lowering_table() {
+        let table = lowering_table(Fork::Byzantium);
+        assert!(table.contains_key(&0x1b) && table.contains_key(&0x5f));
+        assert!(!table.contains_key(&0xfa));
+        assert!(matches!(
+            table[&0x1c],
+            Lowering::Substitute { exact: true, .. }
+        ));
+        // SHL on Frontier: PUSH1, EXP and MUL
+        assert!(matches!(
+            lower_opcode(0x1b, Fork::Frontier).unwrap(),
+            Lowering::Substitute { gas: 18, .. }
+        ));
+    }
+}
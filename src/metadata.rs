@@ -0,0 +1,302 @@
+//! Decoding the compiler metadata trailer
+//!
+//! Solidity and Vyper append CBOR-encoded metadata to runtime code, followed by its length
+//! as a two-byte big-endian integer. The map holds the compiler version, whether experimental
+//! features were enabled and the hash of the metadata JSON on IPFS or Swarm, which source
+//! verification uses to find the matching sources.
+
+use crate::bytecode::encode_hex;
+use crate::disasm::{DataRegion, DataRegionKind};
+
+/// Hash of the metadata JSON file
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SourceHash {
+    /// IPFS multihash (`ipfs` key)
+    Ipfs(Vec<u8>),
+    /// Swarm hash, version 0 (`bzzr0` key)
+    Bzzr0(Vec<u8>),
+    /// Swarm hash, version 1 (`bzzr1` key)
+    Bzzr1(Vec<u8>),
+}
+
+impl SourceHash {
+    /// The raw hash bytes
+    pub fn bytes(&self) -> &[u8] {
+        match self {
+            Self::Ipfs(bytes) | Self::Bzzr0(bytes) | Self::Bzzr1(bytes) => bytes,
+        }
+    }
+
+    /// URI of the metadata file, `ipfs://<base58>` or `bzz-raw://<hex>`
+    pub fn uri(&self) -> String {
+        match self {
+            Self::Ipfs(bytes) => format!("ipfs://{}", base58(bytes)),
+            Self::Bzzr0(bytes) | Self::Bzzr1(bytes) => format!("bzz-raw://{}", encode_hex(bytes)),
+        }
+    }
+}
+
+/// Decoded compiler metadata
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CompilerMetadata {
+    /// Bytes holding the CBOR map and its length suffix
+    pub region: DataRegion,
+    /// Solidity version, e.g. `0.8.24`
+    pub solc: Option<String>,
+    /// Vyper version
+    pub vyper: Option<String>,
+    /// Experimental compiler features were enabled
+    pub experimental: bool,
+    /// Hash of the metadata JSON file
+    pub source_hash: Option<SourceHash>,
+    /// Keys that are not interpreted above, in encoding order
+    pub other_keys: Vec<String>,
+}
+
+/// Decode the metadata trailer at the end of runtime code, if there is one
+///
+/// Solidity and Vyper before 0.3.10 append a CBOR map and a length that excludes the
+/// two-byte suffix. Later Vyper releases append a CBOR array whose last item is the map,
+/// with a length that includes the suffix.
+pub fn decode_metadata(bytecode: &[u8]) -> Option<CompilerMetadata> {
+    let suffix = bytecode.len().checked_sub(2)?;
+    let length = u16::from_be_bytes([bytecode[suffix], bytecode[suffix + 1]]) as usize;
+    [
+        suffix.checked_sub(length),
+        bytecode.len().checked_sub(length),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|&start| start <= suffix)
+    .find_map(|start| {
+        let region = DataRegion::new(start, bytecode.len(), DataRegionKind::Metadata);
+        decode_trailer(&bytecode[start..suffix], region)
+    })
+}
+
+/// The bytecode without its metadata trailer
+pub fn strip_metadata(bytecode: &[u8]) -> &[u8] {
+    match decode_metadata(bytecode) {
+        Some(metadata) => &bytecode[..metadata.region.start],
+        None => bytecode,
+    }
+}
+
+fn decode_trailer(bytes: &[u8], region: DataRegion) -> Option<CompilerMetadata> {
+    let mut reader = Reader { bytes, position: 0 };
+    let value = reader.value(0)?;
+    // The length suffix must cover the CBOR exactly, otherwise this is not a trailer
+    if reader.position != bytes.len() {
+        return None;
+    }
+    let entries = match value {
+        Value::Map(entries) => entries,
+        Value::Array(mut items) => match items.pop() {
+            Some(Value::Map(entries)) => entries,
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    let mut metadata = CompilerMetadata {
+        region,
+        solc: None,
+        vyper: None,
+        experimental: false,
+        source_hash: None,
+        other_keys: Vec::new(),
+    };
+    for (key, value) in entries {
+        let Value::Text(key) = key else {
+            return None;
+        };
+        match (key.as_str(), value) {
+            ("solc", value) => metadata.solc = Some(version(value)?),
+            ("vyper", value) => metadata.vyper = Some(version(value)?),
+            ("experimental", Value::Bool(flag)) => metadata.experimental = flag,
+            ("ipfs", Value::Bytes(hash)) => metadata.source_hash = Some(SourceHash::Ipfs(hash)),
+            ("bzzr0", Value::Bytes(hash)) => metadata.source_hash = Some(SourceHash::Bzzr0(hash)),
+            ("bzzr1", Value::Bytes(hash)) => metadata.source_hash = Some(SourceHash::Bzzr1(hash)),
+            _ => metadata.other_keys.push(key),
+        }
+    }
+    Some(metadata)
+}
+
+/// The subset of CBOR values compilers emit
+enum Value {
+    Uint(u64),
+    Bytes(Vec<u8>),
+    Text(String),
+    Bool(bool),
+    Array(Vec<Value>),
+    Map(Vec<(Value, Value)>),
+    Other,
+}
+
+/// Versions are three bytes (solc) or an array of three integers (Vyper) in releases and a
+/// string in prereleases
+fn version(value: Value) -> Option<String> {
+    match value {
+        Value::Bytes(bytes) if bytes.len() == 3 => {
+            Some(format!("{}.{}.{}", bytes[0], bytes[1], bytes[2]))
+        }
+        Value::Array(parts) => {
+            let parts: Option<Vec<String>> = parts
+                .iter()
+                .map(|part| match part {
+                    Value::Uint(number) => Some(number.to_string()),
+                    _ => None,
+                })
+                .collect();
+            Some(parts?.join("."))
+        }
+        Value::Text(text) => Some(text),
+        _ => None,
+    }
+}
+
+/// Nesting deeper than compilers produce is rejected instead of recursing further
+const MAX_DEPTH: usize = 4;
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, count: usize) -> Option<&[u8]> {
+        let end = self.position.checked_add(count)?;
+        let slice = self.bytes.get(self.position..end)?;
+        self.position = end;
+        Some(slice)
+    }
+
+    /// Major type and argument of the next item; indefinite lengths are not supported
+    fn header(&mut self) -> Option<(u8, u64)> {
+        let initial = *self.take(1)?.first()?;
+        let argument = match initial & 0x1f {
+            small @ 0..=23 => small as u64,
+            24 => self.take(1)?[0] as u64,
+            25 => u16::from_be_bytes(self.take(2)?.try_into().ok()?) as u64,
+            26 => u32::from_be_bytes(self.take(4)?.try_into().ok()?) as u64,
+            27 => u64::from_be_bytes(self.take(8)?.try_into().ok()?),
+            _ => return None,
+        };
+        Some((initial >> 5, argument))
+    }
+
+    fn value(&mut self, depth: usize) -> Option<Value> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        let (major, argument) = self.header()?;
+        let length = usize::try_from(argument).ok();
+        Some(match (major, argument) {
+            (0, number) => Value::Uint(number),
+            (2, _) => Value::Bytes(self.take(length?)?.to_vec()),
+            (3, _) => Value::Text(String::from_utf8(self.take(length?)?.to_vec()).ok()?),
+            (4, _) => Value::Array(
+                (0..length?)
+                    .map(|_| self.value(depth + 1))
+                    .collect::<Option<_>>()?,
+            ),
+            (5, _) => Value::Map(
+                (0..length?)
+                    .map(|_| Some((self.value(depth + 1)?, self.value(depth + 1)?)))
+                    .collect::<Option<_>>()?,
+            ),
+            (7, 20) => Value::Bool(false),
+            (7, 21) => Value::Bool(true),
+            (1 | 7, _) => Value::Other,
+            _ => return None,
+        })
+    }
+}
+
+fn base58(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+    let mut digits: Vec<u8> = Vec::new();
+    for &byte in bytes {
+        let mut carry = byte as u32;
+        for digit in &mut digits {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    let zeros = bytes.iter().take_while(|&&b| b == 0).count();
+    std::iter::repeat_n(b'1', zeros)
+        .chain(digits.iter().rev().map(|&d| ALPHABET[d as usize]))
+        .map(char::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `{"ipfs": <34 bytes>, "solc": 0.8.24}` as emitted by solc
+    fn trailer() -> Vec<u8> {
+        let mut cbor = vec![0xa2, 0x64];
+        cbor.extend_from_slice(b"ipfs");
+        cbor.extend_from_slice(&[0x58, 0x22, 0x12, 0x20]);
+        cbor.extend_from_slice(&[0u8; 32]);
+        cbor.push(0x64);
+        cbor.extend_from_slice(b"solc");
+        cbor.extend_from_slice(&[0x43, 0x00, 0x08, 0x18]);
+        let length = cbor.len() as u16;
+        cbor.extend_from_slice(&length.to_be_bytes());
+        cbor
+    }
+
+    #[test]
+    fn test_decode_solc_metadata() {
+        let mut code = vec![0x60, 0x00, 0x54, 0x00, 0xfe];
+        code.extend(trailer());
+
+        let metadata = decode_metadata(&code).unwrap();
+        assert_eq!(metadata.solc.as_deref(), Some("0.8.24"));
+        assert!(!metadata.experimental);
+        assert_eq!(metadata.region.start, 5);
+        assert_eq!(metadata.region.end, code.len());
+        assert_eq!(
+            metadata.source_hash.unwrap().uri(),
+            "ipfs://QmNLei78zWmzUdbeRB3CiUfAizWUrbeeZh5K1rhAQKCh51"
+        );
+        assert_eq!(strip_metadata(&code), &code[..5]);
+    }
+
+    #[test]
+    fn test_decode_vyper_metadata() {
+        // Vyper 0.3.7: {"vyper": [0, 3, 7]}, length without the suffix
+        let mut code = vec![0x00, 0xa1, 0x65];
+        code.extend_from_slice(b"vyper");
+        code.extend_from_slice(&[0x83, 0x00, 0x03, 0x07, 0x00, 0x0b]);
+        let metadata = decode_metadata(&code).unwrap();
+        assert_eq!(metadata.vyper.as_deref(), Some("0.3.7"));
+        assert_eq!(metadata.region.start, 1);
+
+        // Vyper 0.4.0: [runtime size, data sizes, immutables size, {"vyper": [0, 4, 0]}],
+        // length including the suffix
+        let mut code = vec![0x00, 0x84, 0x19, 0x01, 0x00, 0x80, 0x00, 0xa1, 0x65];
+        code.extend_from_slice(b"vyper");
+        code.extend_from_slice(&[0x83, 0x00, 0x04, 0x00, 0x00, 0x13]);
+        let metadata = decode_metadata(&code).unwrap();
+        assert_eq!(metadata.vyper.as_deref(), Some("0.4.0"));
+        assert!(metadata.solc.is_none());
+        assert_eq!(strip_metadata(&code), &[0x00]);
+    }
+
+    #[test]
+    fn test_code_without_trailer() {
+        let code = [0x60, 0x00, 0x54, 0x00];
+        assert!(decode_metadata(&code).is_none());
+        assert_eq!(strip_metadata(&code), &code);
+        assert!(decode_metadata(&[]).is_none());
+    }
+}
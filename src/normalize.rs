@@ -0,0 +1,136 @@
+//! Normalization of instruction streams for comparing obfuscated contracts
+//!
+//! The pass is meant for similarity comparison, not for producing executable code: PUSH
+//! widths are canonicalized and jump targets are not preserved.
+
+use crate::disasm::Instruction;
+
+/// Canonicalize an instruction stream
+///
+/// - PUSH immediates are shrunk to their minimal width (`PUSH2 0x0001` becomes `PUSH1 0x01`,
+///   zero becomes `PUSH0`)
+/// - constant arithmetic on two PUSHes (ADD, MUL, SUB, AND, OR, XOR) is folded into one PUSH
+///   when the result fits in 64 bits
+/// - junk sequences with no net effect are removed: `PUSH x; POP`, `DUPn; POP` and
+///   `SWAPn; SWAPn`
+///
+/// Rewrites are applied until none match, so nested idioms collapse completely. Each
+/// resulting instruction keeps the pc of the first original instruction it came from.
+pub fn normalize(instructions: &[Instruction]) -> Vec<Instruction> {
+    let mut output: Vec<Instruction> = Vec::with_capacity(instructions.len());
+    for instruction in instructions {
+        output.push(canonical(instruction));
+        while simplify_tail(&mut output) {}
+    }
+    output
+}
+
+/// Opcode bytes of the normalized stream
+pub fn normalized_opcodes(instructions: &[Instruction]) -> Vec<u8> {
+    normalize(instructions).iter().map(|i| i.opcode).collect()
+}
+
+/// Try one rewrite on the end of the stream, returning whether anything changed
+fn simplify_tail(output: &mut Vec<Instruction>) -> bool {
+    let len = output.len();
+    if len >= 2 {
+        let (first, second) = (&output[len - 2], &output[len - 1]);
+        let junk = second.opcode == 0x50 && (first.is_push() || is_dup(first.opcode)) // POP
+            || is_swap(first.opcode) && first.opcode == second.opcode;
+        if junk {
+            output.truncate(len - 2);
+            return true;
+        }
+    }
+    if len >= 3 {
+        let (b, a, op) = (&output[len - 3], &output[len - 2], &output[len - 1]);
+        if let (Some(b), Some(a)) = (constant(b), constant(a)) {
+            if let Some(result) = fold(op.opcode, a, b) {
+                let pc = output[len - 3].pc;
+                output.truncate(len - 3);
+                output.push(push_constant(pc, result));
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// Evaluate `op` with `a` on top of the stack and `b` below it
+fn fold(op: u8, a: u64, b: u64) -> Option<u64> {
+    match op {
+        0x01 => a.checked_add(b),
+        0x02 => a.checked_mul(b),
+        0x03 => a.checked_sub(b),
+        0x16 => Some(a & b),
+        0x17 => Some(a | b),
+        0x18 => Some(a ^ b),
+        _ => None,
+    }
+}
+
+/// The value of a complete PUSH that fits in 64 bits
+fn constant(instruction: &Instruction) -> Option<u64> {
+    if instruction.is_truncated() {
+        return None;
+    }
+    instruction.push_value_u64()
+}
+
+fn canonical(instruction: &Instruction) -> Instruction {
+    if !instruction.is_push() || instruction.is_truncated() {
+        return instruction.clone();
+    }
+    let immediate: Vec<u8> = instruction
+        .immediate
+        .iter()
+        .copied()
+        .skip_while(|b| *b == 0)
+        .collect();
+    Instruction::new(instruction.pc, 0x5f + immediate.len() as u8, immediate)
+}
+
+fn push_constant(pc: usize, value: u64) -> Instruction {
+    let bytes = value.to_be_bytes();
+    let immediate: Vec<u8> = bytes.iter().copied().skip_while(|b| *b == 0).collect();
+    Instruction::new(pc, 0x5f + immediate.len() as u8, immediate)
+}
+
+fn is_dup(opcode: u8) -> bool {
+    (0x80..=0x8f).contains(&opcode)
+}
+
+fn is_swap(opcode: u8) -> bool {
+    (0x90..=0x9f).contains(&opcode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disasm::disassemble;
+
+    #[test]
+    fn test_constant_unfolding() {
+        // PUSH1 0x03, PUSH1 0x02, ADD, PUSH2 0x0004, MUL, SLOAD
+        let code = disassemble(&[0x60, 0x03, 0x60, 0x02, 0x01, 0x61, 0x00, 0x04, 0x02, 0x54]);
+        let normalized = normalize(&code);
+        assert_eq!(normalized.len(), 2);
+        assert_eq!(normalized[0], Instruction::new(0, 0x60, vec![0x14]));
+        assert_eq!(normalized[1].opcode, 0x54);
+    }
+
+    #[test]
+    fn test_junk_removal_matches_plain_code() {
+        // CALLER, DUP1, POP, SWAP1, SWAP1, PUSH1 0x00, POP, SLOAD
+        let obfuscated = disassemble(&[0x33, 0x80, 0x50, 0x90, 0x90, 0x60, 0x00, 0x50, 0x54]);
+        let plain = disassemble(&[0x33, 0x54]);
+        assert_eq!(normalized_opcodes(&obfuscated), normalized_opcodes(&plain));
+    }
+
+    #[test]
+    fn test_overflowing_fold_is_kept() {
+        // PUSH1 0x01, PUSH1 0x00, SUB (0 - 1 underflows)
+        let code = disassemble(&[0x60, 0x01, 0x60, 0x00, 0x03]);
+        assert_eq!(normalized_opcodes(&code), vec![0x60, 0x5f, 0x03]);
+    }
+}
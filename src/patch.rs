@@ -0,0 +1,841 @@
+//! Bytecode patching with automatic jump target fix-up
+//!
+//! [`patch`] applies a list of [`Edit`]s at once; [`Patcher`] collects them against the
+//! decoded instructions. Either way the result carries the new program counter of every
+//! original instruction, so callers never track offsets themselves.
+//!
+//! Pushed jump destinations are found by [`jump_links`], which follows each value across
+//! basic blocks back to the PUSHes that may have produced it. Jumps to targets computed at
+//! runtime cannot be relinked; they are reported instead.
+
+use crate::cfg::split_blocks;
+use crate::disasm::{
+    detect_data_regions, disassemble, static_jump_target, valid_jump_destinations, Instruction,
+};
+use crate::metadata::decode_metadata;
+use crate::stack::trace_operands;
+use crate::{Fork, OpcodeMetadata, OpcodeRegistry};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::ops::Range;
+
+/// A single edit applied to bytecode, addressed by the program counter of an existing instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Edit {
+    /// Insert raw code before the instruction at `pc` (use the code length to append)
+    InsertBefore {
+        /// Program counter of the instruction to insert before
+        pc: usize,
+        /// Code to insert
+        code: Vec<u8>,
+    },
+    /// Replace the instruction at `pc` with raw code
+    Replace {
+        /// Program counter of the instruction to replace
+        pc: usize,
+        /// Replacement code
+        code: Vec<u8>,
+    },
+    /// Remove the instruction at `pc`
+    Delete {
+        /// Program counter of the instruction to remove
+        pc: usize,
+    },
+}
+
+/// Result of a successful patch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchedBytecode {
+    /// The patched bytecode
+    pub bytecode: Vec<u8>,
+    /// New program counter of every original instruction that was kept or replaced
+    pub relocations: BTreeMap<usize, usize>,
+}
+
+impl PatchedBytecode {
+    /// New program counter of the instruction originally at `pc`, `None` if it was removed
+    pub fn remap(&self, pc: usize) -> Option<usize> {
+        self.relocations.get(&pc).copied()
+    }
+}
+
+/// Edits collected against decoded bytecode and applied together by [`patch`]
+///
+/// Code to insert can come from [`reassemble`](crate::disasm::reassemble), a
+/// [`Seq`](crate::seq::Seq) or the assembler.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Patcher<'a> {
+    bytecode: &'a [u8],
+    instructions: Vec<Instruction>,
+    edits: Vec<Edit>,
+}
+
+impl<'a> Patcher<'a> {
+    /// Start patching `bytecode`
+    pub fn new(bytecode: &'a [u8]) -> Self {
+        Self {
+            bytecode,
+            instructions: disassemble(bytecode),
+            edits: Vec::new(),
+        }
+    }
+
+    /// The original instructions, which edits are addressed by
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    /// Program counters of the original instructions with `opcode`
+    pub fn find(&self, opcode: u8) -> impl Iterator<Item = usize> + '_ {
+        self.instructions
+            .iter()
+            .filter(move |i| i.opcode == opcode)
+            .map(|i| i.pc)
+    }
+
+    /// Insert code before the instruction at `pc`
+    pub fn insert_before(mut self, pc: usize, code: impl AsRef<[u8]>) -> Self {
+        self.edits.push(Edit::InsertBefore {
+            pc,
+            code: code.as_ref().to_vec(),
+        });
+        self
+    }
+
+    /// Insert code after the instruction at `pc`, where execution falls through to
+    pub fn insert_after(self, pc: usize, code: impl AsRef<[u8]>) -> Self {
+        let next = self
+            .instructions
+            .iter()
+            .find(|i| i.pc == pc)
+            .map_or(pc, |i| i.next_pc().min(self.bytecode.len()));
+        self.insert_before(next, code)
+    }
+
+    /// Append code at the end
+    pub fn append(self, code: impl AsRef<[u8]>) -> Self {
+        let end = self.bytecode.len();
+        self.insert_before(end, code)
+    }
+
+    /// Replace the instruction at `pc`
+    pub fn replace(mut self, pc: usize, code: impl AsRef<[u8]>) -> Self {
+        self.edits.push(Edit::Replace {
+            pc,
+            code: code.as_ref().to_vec(),
+        });
+        self
+    }
+
+    /// Remove the instruction at `pc`
+    pub fn remove(mut self, pc: usize) -> Self {
+        self.edits.push(Edit::Delete { pc });
+        self
+    }
+
+    /// The edits collected so far
+    pub fn edits(&self) -> &[Edit] {
+        &self.edits
+    }
+
+    /// The jumps that applying the edits relinks, and those it cannot
+    pub fn jump_links(&self) -> JumpLinks {
+        jump_links(self.bytecode)
+    }
+
+    /// Apply the edits, see [`patch`] for when they are refused
+    pub fn apply(&self) -> Result<PatchedBytecode, String> {
+        patch(self.bytecode, &self.edits)
+    }
+}
+
+/// Jump destinations pushed by the code, as found by [`jump_links`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JumpLinks {
+    /// PUSH pc -> JUMPDEST pc of every pushed destination that is rewritten when code moves
+    pub references: BTreeMap<usize, usize>,
+    /// JUMP and JUMPI pcs whose every possible target comes from the references
+    pub resolved: BTreeSet<usize>,
+    /// JUMP and JUMPI pcs whose target may be computed at runtime, in code order
+    pub unlinkable: Vec<usize>,
+}
+
+/// Find the pushed jump destinations that [`patch`] relinks
+///
+/// Stack values are followed from block to block, through DUPs and SWAPs, along the jumps
+/// resolved so far and fall-through edges, until nothing changes. A PUSH of a JUMPDEST
+/// offset is a reference when its value reaches a JUMP or JUMPI, which covers return
+/// addresses pushed by callers. A jump is unlinkable when any path may give it another
+/// value. Only the code section is analyzed: the metadata trailer and trailing data are left
+/// as they are.
+pub fn jump_links(bytecode: &[u8]) -> JumpLinks {
+    let instructions = disassemble(bytecode);
+    let code = code_section(bytecode, &instructions);
+    link_jumps(code, &valid_jump_destinations(code))
+}
+
+/// The instructions before the metadata trailer and detected trailing data
+fn code_section<'a>(bytecode: &[u8], instructions: &'a [Instruction]) -> &'a [Instruction] {
+    let trailer = decode_metadata(bytecode).map_or(bytecode.len(), |m| m.region.start);
+    let end = detect_data_regions(&bytecode[..trailer], Fork::Prague)
+        .first()
+        .map_or(trailer, |region| region.start);
+    &instructions[..instructions.partition_point(|i| i.pc < end)]
+}
+
+/// The PUSHes a stack value may come from
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Origins {
+    /// PUSH pcs
+    pushes: BTreeSet<usize>,
+    /// The value may also be computed, or come from below the known stack
+    computed: bool,
+}
+
+impl Origins {
+    fn computed() -> Self {
+        Self {
+            pushes: BTreeSet::new(),
+            computed: true,
+        }
+    }
+
+    /// Add the origins of `other`, returning whether anything was added
+    fn merge(&mut self, other: &Origins) -> bool {
+        let before = (self.pushes.len(), self.computed);
+        self.pushes.extend(other.pushes.iter().copied());
+        self.computed |= other.computed;
+        before != (self.pushes.len(), self.computed)
+    }
+}
+
+fn link_jumps(instructions: &[Instruction], destinations: &BTreeSet<usize>) -> JumpLinks {
+    let opcodes = OpcodeRegistry::new().get_opcodes(Fork::Prague);
+    let pushed: BTreeMap<usize, usize> = instructions
+        .iter()
+        .filter_map(|i| Some((i.pc, static_jump_target(i, destinations)?)))
+        .collect();
+    // Every JUMPDEST a jump may reach, or `None` when it may go elsewhere
+    let targets = |origins: &Origins| -> Option<Vec<usize>> {
+        if origins.computed || origins.pushes.is_empty() {
+            return None;
+        }
+        origins
+            .pushes
+            .iter()
+            .map(|pc| pushed.get(pc).copied())
+            .collect()
+    };
+    let blocks = split_blocks(instructions);
+    let block_index: BTreeMap<usize, usize> = blocks
+        .iter()
+        .enumerate()
+        .map(|(index, block)| (block[0].pc, index))
+        .collect();
+
+    // Stacks on entry to each block, top last, merged over the paths found so far
+    let mut entries: Vec<Option<Vec<Origins>>> = vec![None; blocks.len()];
+    let mut queue = VecDeque::new();
+    let mut queued = vec![false; blocks.len()];
+    if !blocks.is_empty() {
+        entries[0] = Some(Vec::new());
+        queue.push_back(0);
+        queued[0] = true;
+    }
+    while let Some(index) = queue.pop_front() {
+        queued[index] = false;
+        let block = blocks[index];
+        let entry = entries[index].clone().unwrap_or_default();
+        let mut successors = Vec::new();
+        let exit = run_block(block, entry, &opcodes, |_, target| {
+            successors.extend(targets(target).unwrap_or_default());
+        });
+        let last = &block[block.len() - 1];
+        if !last.is_terminator() {
+            successors.push(last.next_pc());
+        }
+        for successor in successors {
+            let Some(&next) = block_index.get(&successor) else {
+                continue;
+            };
+            let changed = match &mut entries[next] {
+                Some(stack) => merge_stacks(stack, &exit),
+                slot @ None => {
+                    *slot = Some(exit.clone());
+                    true
+                }
+            };
+            if changed && !queued[next] {
+                queue.push_back(next);
+                queued[next] = true;
+            }
+        }
+    }
+
+    // Blocks no path reaches start from an unknown stack
+    let mut links = JumpLinks::default();
+    for (block, entry) in blocks.iter().zip(entries) {
+        run_block(
+            block,
+            entry.unwrap_or_default(),
+            &opcodes,
+            |jump, target| match targets(target) {
+                Some(_) => {
+                    for pc in &target.pushes {
+                        links.references.insert(*pc, pushed[pc]);
+                    }
+                    links.resolved.insert(jump.pc);
+                }
+                None => links.unlinkable.push(jump.pc),
+            },
+        );
+    }
+    links
+}
+
+/// Simulate a block from `stack`, calling `on_jump` with the target of its JUMP or JUMPI
+///
+/// Returns the stack on exit. Values below the known stack are computed.
+fn run_block(
+    block: &[Instruction],
+    mut stack: Vec<Origins>,
+    opcodes: &HashMap<u8, OpcodeMetadata>,
+    mut on_jump: impl FnMut(&Instruction, &Origins),
+) -> Vec<Origins> {
+    for instruction in block {
+        let opcode = instruction.opcode;
+        match opcode {
+            0x5f..=0x7f => stack.push(Origins {
+                pushes: BTreeSet::from([instruction.pc]),
+                computed: false,
+            }),
+            0x80..=0x8f => {
+                let depth = (opcode - 0x80) as usize;
+                let value = stack
+                    .len()
+                    .checked_sub(depth + 1)
+                    .map_or_else(Origins::computed, |index| stack[index].clone());
+                stack.push(value);
+            }
+            0x90..=0x9f => {
+                let depth = (opcode - 0x8f) as usize;
+                while stack.len() <= depth {
+                    stack.insert(0, Origins::computed());
+                }
+                let top = stack.len() - 1;
+                stack.swap(top, top - depth);
+            }
+            0x56 | 0x57 => {
+                let target = stack.pop().unwrap_or_else(Origins::computed);
+                if opcode == 0x57 {
+                    stack.pop();
+                }
+                on_jump(instruction, &target);
+            }
+            _ => {
+                let (inputs, outputs) = opcodes
+                    .get(&opcode)
+                    .map_or((0, 0), |m| (m.stack_inputs, m.stack_outputs));
+                for _ in 0..inputs {
+                    stack.pop();
+                }
+                stack.extend((0..outputs).map(|_| Origins::computed()));
+            }
+        }
+    }
+    stack
+}
+
+/// Merge `other` into `stack`, aligned at the top; slots only one of them has are dropped
+fn merge_stacks(stack: &mut Vec<Origins>, other: &[Origins]) -> bool {
+    let mut changed = false;
+    if stack.len() > other.len() {
+        stack.drain(..stack.len() - other.len());
+        changed = true;
+    }
+    let offset = other.len() - stack.len();
+    for (slot, other) in stack.iter_mut().zip(&other[offset..]) {
+        changed |= slot.merge(other);
+    }
+    changed
+}
+
+/// What happens to an original instruction
+#[derive(Debug, Default)]
+struct Slot {
+    inserted: Vec<u8>,
+    replacement: Option<Vec<u8>>,
+    deleted: bool,
+}
+
+/// Apply edits to bytecode, rewriting pushed jump destinations
+///
+/// The PUSHes found by [`jump_links`] are rewritten (and widened if needed) so they keep
+/// pointing at the same JUMPDEST. Inserted code may go anywhere, including between a jump and
+/// the PUSH of its target, as long as it leaves the stack as it found it. The patch is refused
+/// when it would break a jump: editing a referencing PUSH or a resolved jump, removing a
+/// referenced JUMPDEST, inserting code containing jumps, or moving any JUMPDEST while the
+/// code also contains unlinkable jumps, which the error lists. It is also refused when it
+/// moves or changes bytes that a CODECOPY reads, such as constants or the metadata trailer;
+/// a CODECOPY whose source offset is computed at runtime may read any byte.
+pub fn patch(bytecode: &[u8], edits: &[Edit]) -> Result<PatchedBytecode, String> {
+    let instructions = disassemble(bytecode);
+    let boundaries: BTreeSet<usize> = instructions.iter().map(|i| i.pc).collect();
+    let code = code_section(bytecode, &instructions);
+    let destinations = valid_jump_destinations(code);
+    let JumpLinks {
+        references,
+        resolved,
+        unlinkable,
+    } = link_jumps(code, &destinations);
+    let referenced: BTreeSet<usize> = references.values().copied().collect();
+
+    let mut slots: BTreeMap<usize, Slot> = BTreeMap::new();
+    let mut appended = Vec::new();
+    for edit in edits {
+        match edit {
+            Edit::InsertBefore { pc, code } => {
+                check_inserted_code(code)?;
+                if *pc == bytecode.len() {
+                    appended.extend_from_slice(code);
+                    continue;
+                }
+                check_boundary(&boundaries, *pc)?;
+                slots
+                    .entry(*pc)
+                    .or_default()
+                    .inserted
+                    .extend_from_slice(code);
+            }
+            Edit::Replace { pc, .. } | Edit::Delete { pc }
+                if references.contains_key(pc) || resolved.contains(pc) =>
+            {
+                return Err(format!(
+                    "Cannot modify instruction at pc {pc}: it is part of a static jump"
+                ));
+            }
+            Edit::Replace { pc, .. } | Edit::Delete { pc } if referenced.contains(pc) => {
+                return Err(format!(
+                    "Cannot modify JUMPDEST at pc {pc}: it is the target of a static jump"
+                ));
+            }
+            Edit::Replace { pc, code } => {
+                check_inserted_code(code)?;
+                check_boundary(&boundaries, *pc)?;
+                let slot = slots.entry(*pc).or_default();
+                if slot.replacement.is_some() || slot.deleted {
+                    return Err(format!("Conflicting edits for instruction at pc {pc}"));
+                }
+                slot.replacement = Some(code.clone());
+            }
+            Edit::Delete { pc } => {
+                check_boundary(&boundaries, *pc)?;
+                let slot = slots.entry(*pc).or_default();
+                if slot.replacement.is_some() || slot.deleted {
+                    return Err(format!("Conflicting edits for instruction at pc {pc}"));
+                }
+                slot.deleted = true;
+            }
+        }
+    }
+
+    // Lay out the new code, widening jump PUSHes until every target fits
+    let mut widths: BTreeMap<usize, usize> = references
+        .keys()
+        .map(|pc| (*pc, immediate_len(&instructions, *pc)))
+        .collect();
+    let relocations = loop {
+        let relocations = layout(&instructions, &slots, &widths);
+        let mut widened = false;
+        for (push_pc, target) in &references {
+            let needed = byte_len(relocations[target]);
+            let width = widths.get_mut(push_pc).expect("width for every reference");
+            if needed > *width {
+                *width = needed;
+                widened = true;
+            }
+        }
+        if !widened {
+            break relocations;
+        }
+    };
+
+    if !unlinkable.is_empty() {
+        if let Some(moved) = destinations
+            .iter()
+            .find(|pc| relocations.get(pc) != Some(pc))
+        {
+            let jumps: Vec<String> = unlinkable.iter().map(|pc| pc.to_string()).collect();
+            return Err(format!(
+                "Cannot move JUMPDEST at pc {moved}: jumps at pc {} cannot be relinked",
+                jumps.join(", ")
+            ));
+        }
+    }
+
+    let mut output = Vec::with_capacity(bytecode.len());
+    for instruction in &instructions {
+        let slot = slots.get(&instruction.pc);
+        if let Some(slot) = slot {
+            output.extend_from_slice(&slot.inserted);
+            if slot.deleted {
+                continue;
+            }
+            if let Some(code) = &slot.replacement {
+                output.extend_from_slice(code);
+                continue;
+            }
+        }
+        match references.get(&instruction.pc) {
+            Some(target) => {
+                let width = widths[&instruction.pc];
+                let value = relocations[target].to_be_bytes();
+                output.push(0x5f + width as u8);
+                output.extend_from_slice(&value[value.len() - width..]);
+            }
+            None => {
+                output.push(instruction.opcode);
+                output.extend_from_slice(&instruction.immediate);
+            }
+        }
+    }
+    for (copy_pc, range) in copied_ranges(bytecode, code) {
+        if output.get(range.clone()) != bytecode.get(range.clone()) {
+            return Err(format!(
+                "Cannot move bytes {}..{}: CODECOPY at pc {copy_pc} reads them",
+                range.start, range.end
+            ));
+        }
+    }
+    output.extend_from_slice(&appended);
+
+    Ok(PatchedBytecode {
+        bytecode: output,
+        relocations,
+    })
+}
+
+/// Rewrite every PUSH0 as `PUSH1 0x00` so the code runs on forks before Shanghai
+///
+/// Each rewrite adds a byte, so jump targets are fixed up as in [`patch`]. A PUSH0 that is
+/// itself the target operand of a static jump cannot be lowered and is reported as an error.
+/// Bytes in the metadata trailer and trailing data are left alone.
+pub fn lower_push0(bytecode: &[u8]) -> Result<PatchedBytecode, String> {
+    let instructions = disassemble(bytecode);
+    let edits: Vec<Edit> = code_section(bytecode, &instructions)
+        .iter()
+        .filter(|i| i.opcode == 0x5f)
+        .map(|i| Edit::Replace {
+            pc: i.pc,
+            code: vec![0x60, 0x00],
+        })
+        .collect();
+    patch(bytecode, &edits)
+}
+
+/// The code each CODECOPY in `code` may read, by CODECOPY pc
+///
+/// An unknown source offset may be anywhere and an unknown size reaches the end of the code.
+fn copied_ranges(bytecode: &[u8], code: &[Instruction]) -> Vec<(usize, Range<usize>)> {
+    trace_operands(code, Fork::Prague, |i| i.opcode == 0x39)
+        .into_iter()
+        .filter_map(|copy| {
+            // CODECOPY inputs: memory offset, code offset, size
+            let size = copy.inputs[2].as_u64();
+            if size == Some(0) {
+                return None;
+            }
+            let Some(offset) = copy.inputs[1].as_u64() else {
+                return Some((copy.pc, 0..bytecode.len()));
+            };
+            let start = (offset as usize).min(bytecode.len());
+            let end = size.map_or(bytecode.len(), |size| {
+                start.saturating_add(size as usize).min(bytecode.len())
+            });
+            Some((copy.pc, start..end))
+        })
+        .collect()
+}
+
+/// Compute the new pc of every kept or replaced instruction
+fn layout(
+    instructions: &[Instruction],
+    slots: &BTreeMap<usize, Slot>,
+    widths: &BTreeMap<usize, usize>,
+) -> BTreeMap<usize, usize> {
+    let mut relocations = BTreeMap::new();
+    let mut pc = 0;
+    for instruction in instructions {
+        let slot = slots.get(&instruction.pc);
+        if let Some(slot) = slot {
+            pc += slot.inserted.len();
+            if slot.deleted {
+                continue;
+            }
+        }
+        relocations.insert(instruction.pc, pc);
+        pc += match (
+            slot.and_then(|s| s.replacement.as_ref()),
+            widths.get(&instruction.pc),
+        ) {
+            (Some(code), _) => code.len(),
+            (None, Some(width)) => 1 + width,
+            (None, None) => instruction.size(),
+        };
+    }
+    relocations
+}
+
+fn immediate_len(instructions: &[Instruction], pc: usize) -> usize {
+    instructions
+        .iter()
+        .find(|i| i.pc == pc)
+        .map_or(0, |i| i.immediate.len())
+}
+
+/// Number of bytes needed to encode `value` as a PUSH immediate
+fn byte_len(value: usize) -> usize {
+    ((usize::BITS - value.leading_zeros()) as usize).div_ceil(8)
+}
+
+fn check_boundary(boundaries: &BTreeSet<usize>, pc: usize) -> Result<(), String> {
+    if boundaries.contains(&pc) {
+        Ok(())
+    } else {
+        Err(format!("No instruction starts at pc {pc}"))
+    }
+}
+
+fn check_inserted_code(code: &[u8]) -> Result<(), String> {
+    for instruction in disassemble(code) {
+        if instruction.is_truncated() {
+            return Err(format!(
+                "Patch code ends inside the immediate of {}",
+                instruction.name()
+            ));
+        }
+        if matches!(instruction.opcode, 0x56 | 0x57) {
+            return Err("Patch code must not contain JUMP or JUMPI".to_string());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // PUSH1 0x04, JUMP, INVALID, JUMPDEST, STOP
+    const JUMP_OVER: [u8; 6] = [0x60, 0x04, 0x56, 0xfe, 0x5b, 0x00];
+
+    #[test]
+    fn test_insert_rewrites_jump_target() {
+        let patched = patch(
+            &JUMP_OVER,
+            &[Edit::InsertBefore {
+                pc: 3,
+                code: vec![0x5b, 0x5b],
+            }],
+        )
+        .unwrap();
+        assert_eq!(
+            patched.bytecode,
+            vec![0x60, 0x06, 0x56, 0x5b, 0x5b, 0xfe, 0x5b, 0x00]
+        );
+        assert_eq!(patched.relocations[&4], 6);
+    }
+
+    #[test]
+    fn test_push_is_widened_when_target_grows() {
+        let patched = patch(
+            &JUMP_OVER,
+            &[Edit::Replace {
+                pc: 3,
+                code: vec![0x5b; 300],
+            }],
+        )
+        .unwrap();
+        // PUSH2 0x0130, JUMP, 300 x JUMPDEST, JUMPDEST, STOP
+        assert_eq!(&patched.bytecode[..4], &[0x61, 0x01, 0x30, 0x56]);
+        assert_eq!(patched.relocations[&4], 0x130);
+        assert_eq!(patched.bytecode[0x130], 0x5b);
+    }
+
+    #[test]
+    fn test_unsafe_patches_are_refused() {
+        // Removing the jump target
+        assert!(patch(&JUMP_OVER, &[Edit::Delete { pc: 4 }]).is_err());
+        // Replacing the jump
+        assert!(patch(
+            &JUMP_OVER,
+            &[Edit::Replace {
+                pc: 2,
+                code: vec![0x00],
+            }]
+        )
+        .is_err());
+        // Moving a JUMPDEST when a dynamic jump exists: CALLDATASIZE, JUMP, JUMPDEST, STOP
+        assert!(patch(
+            &[0x36, 0x56, 0x5b, 0x00],
+            &[Edit::InsertBefore {
+                pc: 2,
+                code: vec![0x5b],
+            }]
+        )
+        .is_err());
+        // Edits that do not move JUMPDESTs are fine
+        assert!(patch(
+            &[0x36, 0x56, 0x5b, 0x00],
+            &[Edit::Replace {
+                pc: 3,
+                code: vec![0xfd],
+            }]
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_codecopied_bytes_must_not_move() {
+        // PUSH1 2, PUSH1 9, PUSH1 0, CODECOPY, STOP, INVALID, then 2 bytes of constants
+        let code = [
+            0x60, 0x02, 0x60, 0x09, 0x60, 0x00, 0x39, 0x00, 0xfe, 0xaa, 0xbb,
+        ];
+        assert_eq!(
+            Patcher::new(&code).insert_before(7, [0x58, 0x50]).apply(),
+            Err("Cannot move bytes 9..11: CODECOPY at pc 6 reads them".to_string())
+        );
+        // Code after the copied bytes may change
+        let patched = Patcher::new(&code).append([0x00]).apply().unwrap();
+        assert_eq!(&patched.bytecode[..11], &code);
+
+        // The offset comes from calldata, so any byte may be read
+        let code = [0x60, 0x02, 0x36, 0x60, 0x00, 0x39, 0x00];
+        assert!(Patcher::new(&code)
+            .insert_before(6, [0x58, 0x50])
+            .apply()
+            .is_err());
+    }
+
+    #[test]
+    fn test_lower_push0() {
+        // PUSH0, PUSH1 0x05, JUMP, INVALID, JUMPDEST, PUSH0, STOP
+        let code = [0x5f, 0x60, 0x05, 0x56, 0xfe, 0x5b, 0x5f, 0x00];
+        let lowered = lower_push0(&code).unwrap();
+        assert_eq!(
+            lowered.bytecode,
+            vec![0x60, 0x00, 0x60, 0x06, 0x56, 0xfe, 0x5b, 0x60, 0x00, 0x00]
+        );
+        assert_eq!(lowered.relocations[&5], 6);
+    }
+
+    #[test]
+    fn test_patcher_remaps_pcs() {
+        // PUSH1 0x04, JUMP, INVALID, JUMPDEST, STOP
+        let patcher = Patcher::new(&JUMP_OVER);
+        let invalid = patcher.find(0xfe).next().unwrap();
+        let patched = patcher
+            .insert_before(0, [0x5f, 0x50]) // PUSH0, POP
+            .remove(invalid)
+            .insert_after(4, [0x58]) // PC
+            .append([0x00])
+            .apply()
+            .unwrap();
+        assert_eq!(
+            patched.bytecode,
+            [0x5f, 0x50, 0x60, 0x05, 0x56, 0x5b, 0x58, 0x00, 0x00]
+        );
+        assert_eq!(patched.remap(0), Some(2));
+        assert_eq!(patched.remap(3), None);
+        assert_eq!(patched.remap(4), Some(5));
+        assert_eq!(patched.remap(5), Some(7));
+
+        let conflicting = Patcher::new(&JUMP_OVER).remove(3).replace(3, [0x00]);
+        assert_eq!(conflicting.edits().len(), 2);
+        assert!(conflicting.apply().is_err());
+    }
+
+    #[test]
+    fn test_return_addresses_and_shuffled_targets_are_relinked() {
+        // PUSH1 ret, PUSH1 fn, JUMP, INVALID, INVALID, ret: JUMPDEST, STOP, INVALID,
+        // fn: JUMPDEST, JUMP (returns through the address left on the stack)
+        let call = [
+            0x60, 0x07, 0x60, 0x0a, 0x56, 0xfe, 0xfe, 0x5b, 0x00, 0xfe, 0x5b, 0x56,
+        ];
+        let links = jump_links(&call);
+        assert_eq!(links.references, BTreeMap::from([(0, 7), (2, 10)]));
+        assert_eq!(links.resolved, BTreeSet::from([4, 11]));
+        assert!(links.unlinkable.is_empty());
+        let patched = Patcher::new(&call)
+            .insert_before(5, [0x5b])
+            .apply()
+            .unwrap();
+        assert_eq!(
+            patched.bytecode,
+            [0x60, 0x08, 0x60, 0x0b, 0x56, 0x5b, 0xfe, 0xfe, 0x5b, 0x00, 0xfe, 0x5b, 0x56]
+        );
+
+        // Code before the return jump, whose target was pushed by the caller
+        let patched = Patcher::new(&call)
+            .insert_before(5, [0x5b])
+            .insert_before(11, [0x58, 0x50]) // PC, POP
+            .apply()
+            .unwrap();
+        assert_eq!(
+            patched.bytecode,
+            [
+                0x60, 0x08, 0x60, 0x0b, 0x56, 0x5b, 0xfe, 0xfe, 0x5b, 0x00, 0xfe, 0x5b, 0x58, 0x50,
+                0x56
+            ]
+        );
+        assert_eq!(patched.remap(11), Some(14));
+
+        // PUSH1 dest, PUSH1 1, SWAP1, JUMPI, INVALID, dest: JUMPDEST, STOP
+        let branch = [0x60, 0x07, 0x60, 0x01, 0x90, 0x57, 0xfe, 0x5b, 0x00];
+        let patched = patch(
+            &branch,
+            &[Edit::InsertBefore {
+                pc: 6,
+                code: vec![0x5b],
+            }],
+        )
+        .unwrap();
+        assert_eq!(patched.bytecode[1], 0x08);
+        assert_eq!(patched.bytecode[8], 0x5b);
+    }
+
+    #[test]
+    fn test_trailing_data_is_not_relinked() {
+        // JUMP_OVER, then data that decodes as UNKNOWN(0x0c), PUSH1 0x04, JUMP
+        let mut code = JUMP_OVER.to_vec();
+        code.extend([0x0c, 0x60, 0x04, 0x56]);
+        let patched = Patcher::new(&code)
+            .insert_before(3, [0x5b])
+            .apply()
+            .unwrap();
+        assert_eq!(
+            patched.bytecode,
+            [0x60, 0x05, 0x56, 0x5b, 0xfe, 0x5b, 0x00, 0x0c, 0x60, 0x04, 0x56]
+        );
+        assert_eq!(jump_links(&code).references, BTreeMap::from([(0, 4)]));
+    }
+
+    #[test]
+    fn test_unlinkable_jumps_are_reported() {
+        // PUSH1 0, CALLDATALOAD | JUMPDEST, JUMP | JUMPDEST, STOP: the target comes from
+        // the previous block
+        let code = [0x60, 0x00, 0x35, 0x5b, 0x56, 0x5b, 0x00];
+        assert_eq!(jump_links(&code).unlinkable, [4]);
+        assert!(Patcher::new(&code)
+            .insert_before(5, [0x5b])
+            .apply()
+            .is_err());
+
+        // PUSH1 0, CALLDATALOAD, JUMP, JUMPDEST, STOP
+        let code = [0x60, 0x00, 0x35, 0x56, 0x5b, 0x00];
+        let patcher = Patcher::new(&code);
+        assert_eq!(patcher.jump_links().unlinkable, [3]);
+        assert_eq!(
+            patcher.insert_before(4, [0x5b]).apply(),
+            Err("Cannot move JUMPDEST at pc 4: jumps at pc 3 cannot be relinked".to_string())
+        );
+    }
+}
@@ -0,0 +1,234 @@
+//! Gas range and risk flags of a transaction before it is signed, for wallet warnings
+//!
+//! When the call data selector matches a function found by [`detect_dispatcher`], only the
+//! code reachable from that function's entry is considered; otherwise the whole contract is.
+//! The range goes from the base costs of the reachable instructions (everything warm, no
+//! memory expansion) to their worst-case dynamic costs (everything cold), both including the
+//! intrinsic transaction cost.
+
+use crate::allowlist::find_external_calls;
+use crate::disasm::{disassemble, static_jump_target, valid_jump_destinations};
+use crate::functions::{detect_dispatcher, reachable_from, FunctionEntry};
+use crate::gas::{calldata_gas, DynamicGasCalculator};
+use crate::{AnalysisConfig, Fork, OpcodeRegistry};
+
+/// Selectors of token approvals: `approve(address,uint256)`,
+/// `increaseAllowance(address,uint256)` and `setApprovalForAll(address,bool)`
+const APPROVAL_SELECTORS: [[u8; 4]; 3] = [
+    [0x09, 0x5e, 0xa7, 0xb3],
+    [0x39, 0x50, 0x93, 0x51],
+    [0xa2, 0x2c, 0xb4, 0x65],
+];
+
+/// Something a wallet should warn about before signing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PresignFlag {
+    /// SELFDESTRUCT is reachable
+    SelfDestruct {
+        /// Program counter of the SELFDESTRUCT
+        pc: usize,
+    },
+    /// DELEGATECALL to an address that is cold when the transaction starts (Berlin and
+    /// later); the callee runs with the contract's storage and balance
+    ColdDelegateCall {
+        /// Program counter of the DELEGATECALL
+        pc: usize,
+        /// The callee, if it is a constant
+        target: Option<[u8; 20]>,
+    },
+    /// The code writes storage
+    StorageWrites {
+        /// Number of reachable SSTOREs
+        count: usize,
+    },
+    /// The call data is a token approval
+    TokenApproval {
+        /// Selector of the approval function
+        selector: [u8; 4],
+    },
+    /// A backward jump makes a loop, so the maximum only counts one iteration
+    Loop {
+        /// Program counter of the backward jump
+        pc: usize,
+    },
+}
+
+/// Result of [`presign_check`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PresignReport {
+    /// Fork the transaction was checked against
+    pub fork: Fork,
+    /// The function selected by the call data, if the dispatcher was recognized
+    pub function: Option<FunctionEntry>,
+    /// Lower end of the gas estimate
+    pub min_gas: u64,
+    /// Upper end of the gas estimate
+    pub max_gas: u64,
+    /// Warnings for the wallet to show
+    pub flags: Vec<PresignFlag>,
+}
+
+impl PresignReport {
+    /// Check if the upper end of the range is a real bound (no loops)
+    pub fn is_bounded(&self) -> bool {
+        !self
+            .flags
+            .iter()
+            .any(|flag| matches!(flag, PresignFlag::Loop { .. }))
+    }
+}
+
+/// Estimate the gas range of calling `bytecode` with `calldata` and flag risky behavior
+///
+/// The worst case is priced with operands from the estimator selected in `config`.
+pub fn presign_check(
+    bytecode: &[u8],
+    calldata: &[u8],
+    fork: Fork,
+    config: &AnalysisConfig,
+) -> Result<PresignReport, String> {
+    let instructions = disassemble(bytecode);
+    let function = calldata.get(..4).and_then(|selector| {
+        detect_dispatcher(bytecode)
+            .into_iter()
+            .find(|function| function.selector == selector)
+    });
+    let reachable = reachable_from(
+        bytecode,
+        fork,
+        &instructions,
+        function.map_or(0, |function| function.entry),
+    );
+
+    let mut flags = Vec::new();
+    if let Some(selector) = function
+        .map(|function| function.selector)
+        .filter(|selector| APPROVAL_SELECTORS.contains(selector))
+    {
+        flags.push(PresignFlag::TokenApproval { selector });
+    }
+
+    let opcodes = OpcodeRegistry::new().get_opcodes(fork);
+    let destinations = valid_jump_destinations(&instructions);
+    let mut static_gas = 0u64;
+    let mut storage_writes = 0;
+    let operands = config.operand_sequence(&instructions, fork);
+    let mut sequence = Vec::with_capacity(reachable.len());
+    for &index in &reachable {
+        let instruction = &instructions[index];
+        static_gas += opcodes
+            .get(&instruction.opcode)
+            .map_or(0, |metadata| metadata.gas_cost_in(fork) as u64);
+        sequence.push(operands[index].clone());
+        match instruction.opcode {
+            0x55 => storage_writes += 1,
+            0xff => flags.push(PresignFlag::SelfDestruct { pc: instruction.pc }),
+            0x56 | 0x57 => {
+                let target = index
+                    .checked_sub(1)
+                    .and_then(|prev| static_jump_target(&instructions[prev], &destinations));
+                if target.is_some_and(|target| target <= instruction.pc) {
+                    flags.push(PresignFlag::Loop { pc: instruction.pc });
+                }
+            }
+            _ => {}
+        }
+    }
+    if storage_writes > 0 {
+        flags.push(PresignFlag::StorageWrites {
+            count: storage_writes,
+        });
+    }
+    if fork >= Fork::Berlin {
+        let reachable_pcs: Vec<usize> = reachable.iter().map(|&i| instructions[i].pc).collect();
+        flags.extend(
+            find_external_calls(bytecode, fork)
+                .into_iter()
+                .filter(|call| call.opcode == 0xf4 && reachable_pcs.contains(&call.pc))
+                .filter(|call| !call.target.is_some_and(is_precompile))
+                .map(|call| PresignFlag::ColdDelegateCall {
+                    pc: call.pc,
+                    target: call.target,
+                }),
+        );
+    }
+
+    let intrinsic = 21_000 + calldata_gas(calldata, fork);
+    let worst_case =
+        DynamicGasCalculator::new(fork).analyze_sequence_gas_with_config(&sequence, config)?;
+    let min_gas = intrinsic + static_gas;
+    Ok(PresignReport {
+        fork,
+        function,
+        min_gas,
+        // The worst-case total includes the 21000 base cost
+        max_gas: (worst_case.total_gas + intrinsic - 21_000).max(min_gas),
+        flags,
+    })
+}
+
+/// Precompiles are always warm
+fn is_precompile(address: [u8; 20]) -> bool {
+    address[..19].iter().all(|byte| *byte == 0) && (1..=0x11).contains(&address[19])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presign_check() {
+        let code = [
+            0x60, 0x00, 0x35, 0x60, 0xe0, 0x1c, // selector
+            0x80, 0x63, 0x09, 0x5e, 0xa7, 0xb3, 0x14, 0x60, 0x11, 0x57, // approve?
+            0xff, // fallback: SELFDESTRUCT
+            0x5b, 0x60, 0x01, 0x60, 0x00, 0x55, 0x00, // approve: SSTORE(0, 1)
+        ];
+        let mut calldata = vec![0x09, 0x5e, 0xa7, 0xb3];
+        calldata.extend([0u8; 64]);
+        let approve =
+            presign_check(&code, &calldata, Fork::London, &AnalysisConfig::default()).unwrap();
+        assert_eq!(approve.function.map(|f| f.entry), Some(0x11));
+        assert_eq!(
+            approve.flags,
+            vec![
+                PresignFlag::TokenApproval {
+                    selector: [0x09, 0x5e, 0xa7, 0xb3]
+                },
+                PresignFlag::StorageWrites { count: 1 },
+            ]
+        );
+        assert!(approve.min_gas > 21_000 + 4 * 4 * 16);
+        // Nothing on the approve path costs more than its table price
+        assert_eq!(approve.max_gas, approve.min_gas);
+        assert!(approve.is_bounded());
+
+        let fallback = presign_check(&code, &[], Fork::London, &AnalysisConfig::default()).unwrap();
+        assert!(fallback.function.is_none());
+        assert!(fallback
+            .flags
+            .contains(&PresignFlag::SelfDestruct { pc: 16 }));
+    }
+
+    #[test]
+    fn test_cold_delegatecall() {
+        let mut code = [0x60, 0x00].repeat(4);
+        code.push(0x73);
+        code.extend([0xaa; 20]);
+        code.extend([0x5a, 0xf4, 0x00]); // GAS, DELEGATECALL, STOP
+        let report = presign_check(&code, &[], Fork::Cancun, &AnalysisConfig::default()).unwrap();
+        assert_eq!(
+            report.flags,
+            vec![PresignFlag::ColdDelegateCall {
+                pc: 30,
+                target: Some([0xaa; 20]),
+            }]
+        );
+        assert!(
+            presign_check(&code, &[], Fork::Istanbul, &AnalysisConfig::default())
+                .unwrap()
+                .flags
+                .is_empty()
+        );
+    }
+}
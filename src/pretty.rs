@@ -0,0 +1,199 @@
+//! Annotated disassembly listings
+//!
+//! A [`PrettyPrinter`] lays out one instruction per line in aligned columns: program
+//! counter, raw bytes, mnemonic, decoded immediate and the fork's static gas cost. With
+//! annotations on, lines also get `; ` notes for local gas savings and suspicious code:
+//!
+//! ```text
+//! 0x0000  6000  PUSH1  0     3  ; PUSH0 saves 1 gas
+//! 0x0002  80    DUP1         3
+//! 0x0003  50    POP          2  ; DUP then POP cancel out
+//! ```
+//!
+//! Trailing data detected by [`detect_data_regions`] is printed as a single line.
+
+use crate::bytecode::encode_hex;
+use crate::disasm::{detect_data_regions, disassemble, Instruction};
+use crate::format::NumberFormat;
+use crate::{Fork, OpcodeRegistry};
+
+/// Renders disassembly listings
+#[derive(Debug, Clone)]
+pub struct PrettyPrinter {
+    fork: Fork,
+    annotations: bool,
+    number_format: NumberFormat,
+}
+
+impl PrettyPrinter {
+    /// Listings with the gas costs of `fork`, without annotations
+    pub fn new(fork: Fork) -> Self {
+        Self {
+            fork,
+            annotations: false,
+            number_format: NumberFormat::default(),
+        }
+    }
+
+    /// Add optimization and code-quality notes after the instructions they concern
+    pub fn with_annotations(mut self, annotations: bool) -> Self {
+        self.annotations = annotations;
+        self
+    }
+
+    /// Format decoded immediates and gas with `format`
+    pub fn with_number_format(mut self, format: NumberFormat) -> Self {
+        self.number_format = format;
+        self
+    }
+
+    /// Render the listing of `bytecode`
+    pub fn render(&self, bytecode: &[u8]) -> String {
+        let opcodes = OpcodeRegistry::new().get_opcodes(self.fork);
+        let data_start = detect_data_regions(bytecode, self.fork)
+            .first()
+            .map_or(bytecode.len(), |region| region.start);
+        let instructions: Vec<Instruction> = disassemble(bytecode)
+            .into_iter()
+            .filter(|i| i.pc < data_start)
+            .collect();
+
+        let mut rows: Vec<[String; 5]> = Vec::with_capacity(instructions.len() + 1);
+        let mut notes = Vec::with_capacity(instructions.len() + 1);
+        for (index, instruction) in instructions.iter().enumerate() {
+            let metadata = opcodes.get(&instruction.opcode);
+            rows.push([
+                format!("0x{:04x}", instruction.pc),
+                encode_hex(&bytecode[instruction.pc..instruction.next_pc().min(bytecode.len())]),
+                instruction.name(),
+                self.immediate(instruction),
+                metadata.map_or("-".to_string(), |m| {
+                    self.number_format.integer(m.gas_cost_in(self.fork) as u128)
+                }),
+            ]);
+            notes.push(if self.annotations {
+                self.notes(&instructions, index, metadata.is_some())
+            } else {
+                Vec::new()
+            });
+        }
+        if data_start < bytecode.len() {
+            rows.push([
+                format!("0x{data_start:04x}"),
+                String::new(),
+                "DATA".to_string(),
+                format!("{} bytes", bytecode.len() - data_start),
+                String::new(),
+            ]);
+            notes.push(Vec::new());
+        }
+
+        let widths: Vec<usize> = (0..5)
+            .map(|column| rows.iter().map(|row| row[column].len()).max().unwrap_or(0))
+            .collect();
+        let mut listing = String::new();
+        for (row, notes) in rows.iter().zip(notes) {
+            let mut line = format!(
+                "{:<w0$}  {:<w1$}  {:<w2$}  {:<w3$}  {:>w4$}",
+                row[0],
+                row[1],
+                row[2],
+                row[3],
+                row[4],
+                w0 = widths[0],
+                w1 = widths[1],
+                w2 = widths[2],
+                w3 = widths[3],
+                w4 = widths[4],
+            );
+            if !notes.is_empty() {
+                line.push_str("  ; ");
+                line.push_str(&notes.join("; "));
+            }
+            listing.push_str(line.trim_end());
+            listing.push('\n');
+        }
+        listing
+    }
+
+    /// The immediate as a number, or as hex when wider than 8 bytes
+    fn immediate(&self, instruction: &Instruction) -> String {
+        if instruction.immediate.is_empty() {
+            return String::new();
+        }
+        match instruction.push_value_u64() {
+            Some(value) if instruction.immediate.len() <= 8 && !instruction.is_truncated() => {
+                self.number_format.integer(value as u128)
+            }
+            _ => format!("0x{}", encode_hex(&instruction.immediate)),
+        }
+    }
+
+    /// Notes on the instruction at `index`
+    fn notes(&self, instructions: &[Instruction], index: usize, assigned: bool) -> Vec<String> {
+        let instruction = &instructions[index];
+        let previous = index.checked_sub(1).map(|i| &instructions[i]);
+        let mut notes = Vec::new();
+        if !assigned {
+            notes.push(format!("not an opcode in {:?}", self.fork));
+        }
+        if instruction.is_truncated() {
+            notes.push("immediate runs past the end of the code".to_string());
+        } else if self.fork >= Fork::Shanghai
+            && instruction.opcode != 0x5f
+            && instruction.push_value_u64() == Some(0)
+        {
+            notes.push("PUSH0 saves 1 gas".to_string());
+        }
+        if let Some(previous) = previous {
+            if (0x80..=0x8f).contains(&previous.opcode) && instruction.opcode == 0x50 {
+                notes.push("DUP then POP cancel out".to_string());
+            }
+            if previous.is_terminator() && instruction.opcode != 0x5b {
+                notes.push("unreachable".to_string());
+            }
+        }
+        notes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aligned_columns() {
+        // PUSH1 0x80, PUSH2 0x0100, MSTORE, STOP
+        let code = [0x60, 0x80, 0x61, 0x01, 0x00, 0x52, 0x00];
+        let listing = PrettyPrinter::new(Fork::Cancun).render(&code);
+        assert_eq!(
+            listing,
+            "0x0000  6080    PUSH1   128  3\n\
+             0x0002  610100  PUSH2   256  3\n\
+             0x0005  52      MSTORE       3\n\
+             0x0006  00      STOP         0\n"
+        );
+    }
+
+    #[test]
+    fn test_annotations_and_data() {
+        // PUSH1 0, DUP1, POP, STOP, ADD, STOP
+        let code = [0x60, 0x00, 0x80, 0x50, 0x00, 0x01, 0x00];
+        let printer = PrettyPrinter::new(Fork::Cancun).with_annotations(true);
+        let lines: Vec<String> = printer.render(&code).lines().map(String::from).collect();
+        assert!(lines[0].ends_with("; PUSH0 saves 1 gas"), "{}", lines[0]);
+        assert!(lines[2].ends_with("; DUP then POP cancel out"));
+        assert!(lines[4].ends_with("; unreachable"));
+        assert_eq!(lines.len(), 6);
+
+        // STOP, then bytes that do not decode in Cancun
+        let listing = printer.render(&[0x00, 0x01, 0x0c]);
+        assert_eq!(listing.lines().nth(1), Some("0x0001      DATA  2 bytes"));
+
+        // Before Shanghai there is no PUSH0 to suggest
+        let listing = PrettyPrinter::new(Fork::London)
+            .with_annotations(true)
+            .render(&code);
+        assert!(!listing.contains("PUSH0"));
+    }
+}
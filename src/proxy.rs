@@ -0,0 +1,282 @@
+//! Recognition of common proxy patterns from bytecode
+//!
+//! Detects EIP-1167 minimal proxies, EIP-1967 storage slot constants and the dispatch
+//! shapes of UUPS, transparent and beacon proxies. Detection is heuristic: it reports what
+//! the bytecode contains, not what it will do at runtime.
+
+use crate::disasm::disassemble;
+use crate::stack::{trace_operands, Word};
+use crate::Fork;
+
+/// Prefix of the EIP-1167 runtime code, up to the PUSH of the implementation address
+const MINIMAL_PROXY_PREFIX: [u8; 9] = [0x36, 0x3d, 0x3d, 0x37, 0x3d, 0x3d, 0x3d, 0x36, 0x3d];
+/// EIP-1167 runtime code following the implementation address, up to the return jump
+const MINIMAL_PROXY_BODY: [u8; 9] = [0x5a, 0xf4, 0x3d, 0x82, 0x80, 0x3e, 0x90, 0x3d, 0x91];
+/// EIP-1167 tail after the `PUSH1 <return label>`
+const MINIMAL_PROXY_TAIL: [u8; 4] = [0x57, 0xfd, 0x5b, 0xf3];
+
+/// `proxiableUUID()`, exposed by UUPS implementations (ERC-1822)
+const PROXIABLE_UUID_SELECTOR: [u8; 4] = [0x52, 0xd1, 0x90, 0x2d];
+/// `implementation()`, called on the beacon by beacon proxies
+const IMPLEMENTATION_SELECTOR: [u8; 4] = [0x5c, 0x60, 0xda, 0x1b];
+
+/// A well-known proxy storage slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KnownSlot {
+    /// EIP-1967 implementation slot: `keccak256("eip1967.proxy.implementation") - 1`
+    Eip1967Implementation,
+    /// EIP-1967 admin slot: `keccak256("eip1967.proxy.admin") - 1`
+    Eip1967Admin,
+    /// EIP-1967 beacon slot: `keccak256("eip1967.proxy.beacon") - 1`
+    Eip1967Beacon,
+    /// Legacy ZeppelinOS implementation slot: `keccak256("org.zeppelinos.proxy.implementation")`
+    ZeppelinOsImplementation,
+}
+
+impl KnownSlot {
+    /// All known slots
+    pub const ALL: [KnownSlot; 4] = [
+        Self::Eip1967Implementation,
+        Self::Eip1967Admin,
+        Self::Eip1967Beacon,
+        Self::ZeppelinOsImplementation,
+    ];
+
+    /// The slot value
+    pub fn value(self) -> Word {
+        match self {
+            Self::Eip1967Implementation => [
+                0x36, 0x08, 0x94, 0xa1, 0x3b, 0xa1, 0xa3, 0x21, 0x06, 0x67, 0xc8, 0x28, 0x49, 0x2d,
+                0xb9, 0x8d, 0xca, 0x3e, 0x20, 0x76, 0xcc, 0x37, 0x35, 0xa9, 0x20, 0xa3, 0xca, 0x50,
+                0x5d, 0x38, 0x2b, 0xbc,
+            ],
+            Self::Eip1967Admin => [
+                0xb5, 0x31, 0x27, 0x68, 0x4a, 0x56, 0x8b, 0x31, 0x73, 0xae, 0x13, 0xb9, 0xf8, 0xa6,
+                0x01, 0x6e, 0x24, 0x3e, 0x63, 0xb6, 0xe8, 0xee, 0x11, 0x78, 0xd6, 0xa7, 0x17, 0x85,
+                0x0b, 0x5d, 0x61, 0x03,
+            ],
+            Self::Eip1967Beacon => [
+                0xa3, 0xf0, 0xad, 0x74, 0xe5, 0x42, 0x3a, 0xeb, 0xfd, 0x80, 0xd3, 0xef, 0x43, 0x46,
+                0x57, 0x83, 0x35, 0xa9, 0xa7, 0x2a, 0xea, 0xee, 0x59, 0xff, 0x6c, 0xb3, 0x58, 0x2b,
+                0x35, 0x13, 0x3d, 0x50,
+            ],
+            Self::ZeppelinOsImplementation => [
+                0x70, 0x50, 0xc9, 0xe0, 0xf4, 0xca, 0x76, 0x9c, 0x69, 0xbd, 0x3a, 0x8e, 0xf7, 0x40,
+                0xbc, 0x37, 0x93, 0x4f, 0x8e, 0x2c, 0x03, 0x6e, 0x5a, 0x72, 0x3f, 0xd8, 0xee, 0x04,
+                0x8e, 0xd3, 0xf8, 0xc3,
+            ],
+        }
+    }
+
+    /// Identify a slot value
+    pub fn from_value(value: &Word) -> Option<Self> {
+        Self::ALL.into_iter().find(|slot| slot.value() == *value)
+    }
+}
+
+/// A recognized proxy pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProxyPattern {
+    /// EIP-1167 minimal proxy (clone)
+    MinimalProxy,
+    /// Uses at least one EIP-1967 slot
+    Eip1967,
+    /// UUPS: upgrade logic lives in the implementation, which exposes `proxiableUUID()`
+    Uups,
+    /// Transparent proxy: admin slot plus a caller check in front of the delegatecall
+    Transparent,
+    /// Beacon proxy: asks a beacon for the implementation before delegating
+    Beacon,
+}
+
+/// A known slot constant found in the code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlotConstant {
+    /// Which slot it is
+    pub slot: KnownSlot,
+    /// Program counter of the PUSH that loads it
+    pub pc: usize,
+}
+
+/// Everything the proxy detector found
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProxyReport {
+    /// Recognized patterns
+    pub patterns: Vec<ProxyPattern>,
+    /// Implementation address hardcoded in an EIP-1167 proxy
+    pub implementation: Option<[u8; 20]>,
+    /// Well-known slot constants
+    pub slot_constants: Vec<SlotConstant>,
+    /// Constant storage slots read with SLOAD, known or not
+    pub sload_slots: Vec<Word>,
+    /// Program counters of DELEGATECALL instructions
+    pub delegatecalls: Vec<usize>,
+}
+
+impl ProxyReport {
+    /// Check if the code looks like any kind of proxy
+    pub fn is_proxy(&self) -> bool {
+        !self.patterns.is_empty()
+    }
+
+    /// Check if a pattern was recognized
+    pub fn has_pattern(&self, pattern: ProxyPattern) -> bool {
+        self.patterns.contains(&pattern)
+    }
+}
+
+/// Extract the implementation address of an EIP-1167 minimal proxy, including the
+/// shorter variants that push vanity addresses with fewer than 20 bytes
+pub fn minimal_proxy_implementation(bytecode: &[u8]) -> Option<[u8; 20]> {
+    let rest = bytecode.strip_prefix(&MINIMAL_PROXY_PREFIX[..])?;
+    let (push, rest) = rest.split_first()?;
+    let size = push
+        .checked_sub(0x5f)
+        .filter(|size| (1..=20).contains(size))? as usize;
+    if rest.len() < size {
+        return None;
+    }
+    let (address, rest) = rest.split_at(size);
+    let rest = rest.strip_prefix(&MINIMAL_PROXY_BODY[..])?;
+    match rest {
+        [0x60, _, tail @ ..] if tail == MINIMAL_PROXY_TAIL => {
+            let mut padded = [0u8; 20];
+            padded[20 - size..].copy_from_slice(address);
+            Some(padded)
+        }
+        _ => None,
+    }
+}
+
+/// Detect proxy patterns in runtime bytecode
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(?fork, len = bytecode.len()))
+)]
+pub fn detect_proxy(bytecode: &[u8], fork: Fork) -> ProxyReport {
+    let mut report = ProxyReport {
+        implementation: minimal_proxy_implementation(bytecode),
+        ..ProxyReport::default()
+    };
+    if report.implementation.is_some() {
+        report.patterns.push(ProxyPattern::MinimalProxy);
+    }
+
+    let instructions = disassemble(bytecode);
+    let mut selectors = Vec::new();
+    let mut has_caller = false;
+    let mut has_staticcall = false;
+    for instruction in &instructions {
+        match instruction.opcode {
+            0x7f => {
+                if let Some(slot) = instruction
+                    .immediate
+                    .as_slice()
+                    .try_into()
+                    .ok()
+                    .and_then(|word: Word| KnownSlot::from_value(&word))
+                {
+                    report.slot_constants.push(SlotConstant {
+                        slot,
+                        pc: instruction.pc,
+                    });
+                }
+            }
+            0x63 => selectors.extend(<[u8; 4]>::try_from(instruction.immediate.as_slice())),
+            0x33 => has_caller = true,
+            0xf4 => report.delegatecalls.push(instruction.pc),
+            0xfa => has_staticcall = true,
+            _ => {}
+        }
+    }
+
+    for snapshot in trace_operands(&instructions, fork, |i| i.opcode == 0x54) {
+        if let Some(slot) = snapshot.inputs.first().and_then(|value| value.word()) {
+            if !report.sload_slots.contains(&slot) {
+                report.sload_slots.push(slot);
+            }
+        }
+    }
+
+    let has_slot = |slot: KnownSlot| report.slot_constants.iter().any(|c| c.slot == slot);
+    let mut patterns = Vec::new();
+    if report
+        .slot_constants
+        .iter()
+        .any(|c| c.slot != KnownSlot::ZeppelinOsImplementation)
+    {
+        patterns.push(ProxyPattern::Eip1967);
+    }
+    if has_slot(KnownSlot::Eip1967Implementation) && selectors.contains(&PROXIABLE_UUID_SELECTOR) {
+        patterns.push(ProxyPattern::Uups);
+    }
+    if has_slot(KnownSlot::Eip1967Admin) && has_caller && !report.delegatecalls.is_empty() {
+        patterns.push(ProxyPattern::Transparent);
+    }
+    let beacon_dispatch = selectors.contains(&IMPLEMENTATION_SELECTOR)
+        && has_staticcall
+        && !report.delegatecalls.is_empty();
+    if has_slot(KnownSlot::Eip1967Beacon) || beacon_dispatch {
+        patterns.push(ProxyPattern::Beacon);
+    }
+    report.patterns.extend(patterns);
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minimal_proxy(implementation: [u8; 20]) -> Vec<u8> {
+        let mut code = MINIMAL_PROXY_PREFIX.to_vec();
+        code.push(0x73);
+        code.extend_from_slice(&implementation);
+        code.extend_from_slice(&MINIMAL_PROXY_BODY);
+        code.extend_from_slice(&[0x60, 0x2b]);
+        code.extend_from_slice(&MINIMAL_PROXY_TAIL);
+        code
+    }
+
+    #[test]
+    fn test_minimal_proxy() {
+        let code = minimal_proxy([0xbe; 20]);
+        assert_eq!(code.len(), 45);
+        let report = detect_proxy(&code, Fork::London);
+        assert!(report.has_pattern(ProxyPattern::MinimalProxy));
+        assert_eq!(report.implementation, Some([0xbe; 20]));
+        assert_eq!(report.delegatecalls.len(), 1);
+
+        assert!(minimal_proxy_implementation(&code[..44]).is_none());
+    }
+
+    #[test]
+    fn test_eip1967_transparent_proxy() {
+        // PUSH32 admin slot, SLOAD, CALLER, EQ, POP, PUSH32 implementation slot, SLOAD, GAS, DELEGATECALL
+        let mut code = vec![0x7f];
+        code.extend_from_slice(&KnownSlot::Eip1967Admin.value());
+        code.extend_from_slice(&[0x54, 0x33, 0x14, 0x50, 0x7f]);
+        code.extend_from_slice(&KnownSlot::Eip1967Implementation.value());
+        code.extend_from_slice(&[0x54, 0x5a, 0xf4]);
+
+        let report = detect_proxy(&code, Fork::London);
+        assert!(report.has_pattern(ProxyPattern::Eip1967));
+        assert!(report.has_pattern(ProxyPattern::Transparent));
+        assert!(!report.has_pattern(ProxyPattern::Uups));
+        assert_eq!(report.slot_constants.len(), 2);
+        assert_eq!(
+            report.sload_slots,
+            vec![
+                KnownSlot::Eip1967Admin.value(),
+                KnownSlot::Eip1967Implementation.value()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plain_contract_is_not_a_proxy() {
+        let report = detect_proxy(&[0x60, 0x00, 0x54, 0x00], Fork::London);
+        assert!(!report.is_proxy());
+        assert_eq!(report.sload_slots.len(), 1);
+    }
+}
@@ -0,0 +1,116 @@
+//! Classification of state reads: storage versus immutables read from code
+//!
+//! Immutables are part of the contract code, not storage. Solidity inlines them as PUSH32
+//! constants, while Vyper appends them to the runtime code and reads them with CODECOPY.
+//! Counting such reads as storage accesses overstates what caching or packing can save.
+
+use crate::disasm::disassemble;
+use crate::stack::trace_operands;
+use crate::Fork;
+
+/// What a read instruction accesses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReadKind {
+    /// SLOAD from persistent storage
+    Storage,
+    /// TLOAD from transient storage
+    TransientStorage,
+    /// CODECOPY of a single 32-byte word: an immutable value stored in the code
+    Immutable,
+    /// Any other CODECOPY (runtime code, constant tables, ...)
+    CodeCopy,
+}
+
+impl ReadKind {
+    /// Check if the read accesses storage (and is worth caching or packing)
+    pub fn is_storage(self) -> bool {
+        matches!(self, Self::Storage | Self::TransientStorage)
+    }
+}
+
+/// A single read instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateRead {
+    /// Program counter of the instruction
+    pub pc: usize,
+    /// What it reads
+    pub kind: ReadKind,
+}
+
+/// All reads found in a piece of bytecode
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReadSummary {
+    /// Reads in code order
+    pub reads: Vec<StateRead>,
+}
+
+impl ReadSummary {
+    /// Number of reads of one kind
+    pub fn count(&self, kind: ReadKind) -> usize {
+        self.reads.iter().filter(|read| read.kind == kind).count()
+    }
+
+    /// Number of persistent storage reads (SLOAD)
+    pub fn storage_reads(&self) -> usize {
+        self.count(ReadKind::Storage)
+    }
+
+    /// Number of immutable reads via CODECOPY, which cost no storage gas
+    pub fn immutable_reads(&self) -> usize {
+        self.count(ReadKind::Immutable)
+    }
+}
+
+/// Classify the SLOAD, TLOAD and CODECOPY instructions in bytecode
+///
+/// A CODECOPY is an immutable read when its size operand is the constant 32.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(?fork, len = bytecode.len()))
+)]
+pub fn classify_reads(bytecode: &[u8], fork: Fork) -> ReadSummary {
+    let instructions = disassemble(bytecode);
+    let reads = trace_operands(&instructions, fork, |i| {
+        matches!(i.opcode, 0x54 | 0x5c | 0x39)
+    })
+    .into_iter()
+    .map(|snapshot| {
+        let kind = match snapshot.opcode {
+            0x54 => ReadKind::Storage,
+            0x5c => ReadKind::TransientStorage,
+            // CODECOPY inputs: destOffset, offset, size
+            _ if snapshot.inputs.get(2).and_then(|size| size.as_u64()) == Some(32) => {
+                ReadKind::Immutable
+            }
+            _ => ReadKind::CodeCopy,
+        };
+        StateRead {
+            pc: snapshot.pc,
+            kind,
+        }
+    })
+    .collect();
+
+    ReadSummary { reads }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codecopy_immutables_are_not_storage() {
+        // PUSH1 0x20, PUSH2 0x0100, PUSH1 0x00, CODECOPY (immutable read)
+        // PUSH1 0x00, SLOAD (storage read)
+        // PUSH2 0x0200, PUSH1 0x00, PUSH1 0x00, CODECOPY (bulk copy)
+        let code = [
+            0x60, 0x20, 0x61, 0x01, 0x00, 0x60, 0x00, 0x39, 0x60, 0x00, 0x54, 0x61, 0x02, 0x00,
+            0x60, 0x00, 0x60, 0x00, 0x39,
+        ];
+        let summary = classify_reads(&code, Fork::London);
+        assert_eq!(summary.storage_reads(), 1);
+        assert_eq!(summary.immutable_reads(), 1);
+        assert_eq!(summary.count(ReadKind::CodeCopy), 1);
+        assert!(!ReadKind::Immutable.is_storage());
+    }
+}
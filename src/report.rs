@@ -0,0 +1,277 @@
+//! Deterministic all-in-one bytecode analysis
+//!
+//! [`analyze`] is a pure function: it reads no global state, performs no I/O and produces
+//! every collection in a stable order, so the same inputs give an identical [`Report`] on
+//! every platform, including sandboxed and WASM hosts.
+
+use crate::allowlist::{find_external_calls, ExternalCall};
+use crate::compiler::{identify_compiler, CompilerGuess};
+use crate::disasm::{disassemble_with_config, Disassembly};
+use crate::format::NumberFormat;
+use crate::gas::{detect_gas_token_patterns, GasAnalysis, GasAnalyzer, GasTokenFinding};
+use crate::interfaces::{detect_interfaces, InterfaceMatch};
+use crate::metadata::{decode_metadata, CompilerMetadata};
+use crate::proxy::{detect_proxy, ProxyReport};
+use crate::reads::{classify_reads, ReadSummary};
+use crate::rules::{EofBannedOpcodeRule, SequenceValidator, Violation};
+use crate::{AnalysisConfig, Fork};
+
+/// Combined results of the bytecode analyses for one fork
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    /// Fork the bytecode was analyzed for
+    pub fork: Fork,
+    /// Decoded instructions and data regions
+    pub disassembly: Disassembly,
+    /// Gas usage of the decoded instructions
+    pub gas: GasAnalysis,
+    /// Violations of the standard sequence rules
+    pub violations: Vec<Violation>,
+    /// Storage, transient storage and immutable reads
+    pub reads: ReadSummary,
+    /// Recognized proxy patterns
+    pub proxy: ProxyReport,
+    /// Standard interfaces the dispatcher suggests
+    pub interfaces: Vec<InterfaceMatch>,
+    /// External calls with their statically known targets and selectors
+    pub external_calls: Vec<ExternalCall>,
+    /// Gas-token refund patterns
+    pub gas_tokens: Vec<GasTokenFinding>,
+    /// Decoded compiler metadata trailer
+    pub metadata: Option<CompilerMetadata>,
+    /// Guessed compiler family and version
+    pub compiler: CompilerGuess,
+    /// The analysis budget ran out or was cancelled and the results only cover part of the code
+    pub budget_exhausted: bool,
+}
+
+impl Report {
+    /// Render a Markdown summary, with numbers written as `format` says
+    pub fn to_markdown(&self, format: &NumberFormat) -> String {
+        let code_size = self
+            .disassembly
+            .instructions
+            .last()
+            .map_or(0, |last| last.next_pc());
+        let mut markdown = format!("# Bytecode report ({})\n\n", self.fork.slug());
+        markdown.push_str("| | |\n|---|---|\n");
+        let rows = [
+            (
+                "Code size",
+                format!("{} bytes", format.integer(code_size as u128)),
+            ),
+            (
+                "Instructions",
+                format.integer(self.disassembly.instructions.len() as u128),
+            ),
+            ("Static gas", format.gas(self.gas.total_gas)),
+            (
+                "Storage reads",
+                format.integer(self.reads.storage_reads() as u128),
+            ),
+            (
+                "External calls",
+                format.integer(self.external_calls.len() as u128),
+            ),
+            ("Compiler", format!("{:?}", self.compiler.family)),
+        ];
+        for (name, value) in rows {
+            markdown.push_str(&format!("| {name} | {value} |\n"));
+        }
+        if let Some(gwei) = format.gwei(self.gas.total_gas) {
+            markdown.push_str(&format!("| Static cost | {gwei} |\n"));
+        }
+        if !self.interfaces.is_empty() {
+            let names: Vec<_> = self.interfaces.iter().map(|i| i.name).collect();
+            markdown.push_str(&format!("| Interfaces | {} |\n", names.join(", ")));
+        }
+        if self.budget_exhausted {
+            markdown.push_str("\n_The analysis budget ran out; results cover part of the code._\n");
+        }
+        if !self.violations.is_empty() {
+            markdown.push_str("\n## Violations\n\n");
+            for violation in &self.violations {
+                let location = violation
+                    .pc
+                    .map(|pc| format!(" at pc {}", format.integer(pc as u128)))
+                    .unwrap_or_default();
+                markdown.push_str(&format!(
+                    "- `{}`{location}: {}\n",
+                    violation.rule, violation.message
+                ));
+            }
+        }
+        markdown
+    }
+}
+
+/// Run every bytecode analysis for a fork and collect the results
+///
+/// Fails only when `config` asks for unknown opcodes to be reported as errors. The detection
+/// passes that follow gas analysis (validation, reads, proxy, interfaces, calls, gas tokens
+/// and compiler) share one budget, charged a step per decoded instruction each. Once
+/// disassembly or gas analysis stops early, or that budget runs out, the remaining passes are
+/// skipped and see no code.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(?fork, len = bytecode.len()))
+)]
+pub fn analyze(bytecode: &[u8], fork: Fork, config: &AnalysisConfig) -> Result<Report, String> {
+    let disassembly = disassemble_with_config(bytecode, fork, config)?;
+    let gas = GasAnalyzer::analyze_disassembly(bytecode, disassembly.clone(), fork, config)?;
+    let mut budget_exhausted = disassembly.budget_exhausted || gas.budget_exhausted;
+    let mut meter = config.budget.start();
+    let steps = disassembly.instructions.len();
+    // The code the next pass may look at, nothing once the budget is spent
+    let mut next_pass = || {
+        budget_exhausted = budget_exhausted || !(0..steps).all(|_| meter.step());
+        if budget_exhausted {
+            &[]
+        } else {
+            bytecode
+        }
+    };
+
+    let mut validator = SequenceValidator::standard();
+    if config.eof_mode {
+        validator = validator.with_rule(EofBannedOpcodeRule);
+    }
+    let violations = validator
+        .validate(next_pass(), fork)
+        .err()
+        .unwrap_or_default();
+    let reads = classify_reads(next_pass(), fork);
+    let proxy = detect_proxy(next_pass(), fork);
+    let interfaces = detect_interfaces(next_pass());
+    let external_calls = find_external_calls(next_pass(), fork);
+    let gas_tokens = detect_gas_token_patterns(next_pass(), fork);
+    let compiler = identify_compiler(next_pass());
+
+    Ok(Report {
+        fork,
+        disassembly,
+        gas,
+        violations,
+        reads,
+        proxy,
+        interfaces,
+        external_calls,
+        gas_tokens,
+        metadata: decode_metadata(bytecode),
+        compiler,
+        budget_exhausted,
+    })
+}
+
+/// Analyze a batch of contracts with the same fork and configuration
+///
+/// A cancellation token in the configured budget stops the current contract early and
+/// skips the remaining ones, so the result may be shorter than `corpus`.
+pub fn analyze_batch(
+    corpus: &[&[u8]],
+    fork: Fork,
+    config: &AnalysisConfig,
+) -> Vec<Result<Report, String>> {
+    let cancelled = || {
+        config
+            .budget
+            .cancellation
+            .as_ref()
+            .is_some_and(|token| token.is_cancelled())
+    };
+    corpus
+        .iter()
+        .take_while(|_| !cancelled())
+        .map(|bytecode| analyze(bytecode, fork, config))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AnalysisBudget, CancellationToken, UnknownOpcodePolicy};
+
+    #[test]
+    fn test_analyze_is_deterministic() {
+        // PUSH1 0x00, SLOAD, PUSH1 0x00, SSTORE, CALLER, SELFDESTRUCT
+        let code = [0x60, 0x00, 0x54, 0x60, 0x00, 0x55, 0x33, 0xff];
+        let config = AnalysisConfig::default();
+        let first = analyze(&code, Fork::Berlin, &config).unwrap();
+        for _ in 0..8 {
+            assert_eq!(analyze(&code, Fork::Berlin, &config).unwrap(), first);
+        }
+        assert_eq!(first.reads.storage_reads(), 1);
+        assert_eq!(first.gas_tokens.len(), 1);
+        assert!(!first.proxy.is_proxy());
+    }
+
+    #[test]
+    fn test_analyze_stops_at_budget() {
+        // 1000 times PUSH1 0x00, SLOAD
+        let code: Vec<u8> = [0x60, 0x00, 0x54].repeat(1000);
+        let config =
+            AnalysisConfig::new().with_budget(AnalysisBudget::unlimited().with_max_steps(10));
+        let report = analyze(&code, Fork::London, &config).unwrap();
+        assert!(report.budget_exhausted);
+        assert_eq!(report.disassembly.instructions.len(), 10);
+        // Detection is skipped once disassembly runs out
+        assert_eq!(report.reads.storage_reads(), 0);
+        assert!(report.violations.is_empty());
+
+        // Each detection pass is charged the 20 decoded instructions
+        let short = &code[..30];
+        let config =
+            AnalysisConfig::new().with_budget(AnalysisBudget::unlimited().with_max_steps(45));
+        let report = analyze(short, Fork::London, &config).unwrap();
+        assert!(report.budget_exhausted);
+        assert_eq!(report.reads.storage_reads(), 10);
+        assert!(!report.proxy.is_proxy());
+        assert!(report.external_calls.is_empty());
+
+        let full = analyze(&code, Fork::London, &AnalysisConfig::default()).unwrap();
+        assert!(!full.budget_exhausted);
+        assert_eq!(full.reads.storage_reads(), 1000);
+    }
+
+    #[test]
+    fn test_cancelled_batch_is_skipped() {
+        let token = CancellationToken::new();
+        let config = AnalysisConfig::new()
+            .with_budget(AnalysisBudget::unlimited().with_cancellation(token.clone()));
+        let corpus: [&[u8]; 2] = [&[0x60, 0x00, 0x54], &[0x33, 0xff]];
+        assert_eq!(analyze_batch(&corpus, Fork::London, &config).len(), 2);
+
+        token.cancel();
+        assert!(analyze_batch(&corpus, Fork::London, &config).is_empty());
+        let report = analyze(corpus[0], Fork::London, &config).unwrap();
+        assert!(report.budget_exhausted);
+        assert!(report.disassembly.instructions.is_empty());
+    }
+
+    #[test]
+    fn test_markdown_number_formatting() {
+        // 400 times PUSH1 0x00, SLOAD, then STOP
+        let mut code: Vec<u8> = [0x60, 0x00, 0x54].repeat(400);
+        code.push(0x00);
+        let report = analyze(&code, Fork::Cancun, &AnalysisConfig::default()).unwrap();
+
+        let raw = report.to_markdown(&NumberFormat::new());
+        assert!(raw.starts_with("# Bytecode report (cancun)"));
+        assert!(raw.contains("| Code size | 1201 bytes |"));
+        let total = report.gas.total_gas;
+        assert!(raw.contains(&format!("| Static gas | {total} gas |")));
+
+        let readable = report.to_markdown(&NumberFormat::readable().with_gas_price_gwei(10));
+        assert!(readable.contains("| Code size | 1,201 bytes |"));
+        assert!(readable.contains(&NumberFormat::readable().integer(total as u128)));
+        assert!(readable.contains(" ETH) |"));
+        assert!(readable.contains("| Static cost | "));
+    }
+
+    #[test]
+    fn test_analyze_honors_unknown_opcode_policy() {
+        let config = AnalysisConfig::new().with_unknown_opcode_policy(UnknownOpcodePolicy::Error);
+        assert!(analyze(&[0x0c], Fork::London, &config).is_err());
+        assert!(analyze(&[0x0c], Fork::London, &AnalysisConfig::default()).is_ok());
+    }
+}
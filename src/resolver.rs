@@ -0,0 +1,218 @@
+//! Resolving function selectors and event topics to human-readable signatures
+//!
+//! Analyses only see 4-byte selectors and 32-byte topics. A [`SelectorResolver`] maps them
+//! back to signatures; the crate never goes to the network itself. [`NoopResolver`] knows
+//! nothing, and [`SignatureDatabase`] answers from a local file, e.g. an export of the
+//! 4byte directory.
+
+use crate::bytecode::{decode_hex, encode_hex};
+use crate::disasm::disassemble;
+use crate::stack::{trace_operands, Word};
+use crate::Fork;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Looks up the signatures behind selectors and event topics
+pub trait SelectorResolver {
+    /// Candidate function signatures for a selector, best match first
+    fn function_signatures(&self, selector: [u8; 4]) -> Vec<String>;
+
+    /// Candidate event signatures for a topic, best match first
+    fn event_signatures(&self, topic: &Word) -> Vec<String>;
+}
+
+/// Resolver that never finds anything
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoopResolver;
+
+impl SelectorResolver for NoopResolver {
+    fn function_signatures(&self, _selector: [u8; 4]) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn event_signatures(&self, _topic: &Word) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// In-memory signature database, usually loaded from a local file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SignatureDatabase {
+    functions: BTreeMap<[u8; 4], Vec<String>>,
+    events: BTreeMap<Word, Vec<String>>,
+}
+
+impl SignatureDatabase {
+    /// Create an empty database
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a function signature
+    pub fn with_function(mut self, selector: [u8; 4], signature: &str) -> Self {
+        self.functions
+            .entry(selector)
+            .or_default()
+            .push(signature.to_string());
+        self
+    }
+
+    /// Add an event signature
+    pub fn with_event(mut self, topic: Word, signature: &str) -> Self {
+        self.events
+            .entry(topic)
+            .or_default()
+            .push(signature.to_string());
+        self
+    }
+
+    /// Parse lines of `hash signature`
+    ///
+    /// A 4-byte hash is a function selector and a 32-byte hash an event topic, both in hex
+    /// with an optional `0x` prefix. Empty lines and lines starting with `#` are skipped.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut database = Self::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((hash, signature)) = line.split_once(char::is_whitespace) else {
+                return Err(format!("Line {}: expected `hash signature`", index + 1));
+            };
+            let bytes = decode_hex(hash)
+                .map_err(|e| format!("Line {}: invalid hex {hash}: {e}", index + 1))?;
+            let signature = signature.trim();
+            database = match bytes.len() {
+                4 => database.with_function(bytes.try_into().expect("4 bytes"), signature),
+                32 => database.with_event(bytes.try_into().expect("32 bytes"), signature),
+                len => {
+                    return Err(format!(
+                        "Line {}: expected a 4 or 32 byte hash, got {len} bytes",
+                        index + 1
+                    ))
+                }
+            };
+        }
+        Ok(database)
+    }
+
+    /// Load a database file in the format accepted by [`parse`](Self::parse)
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read {}: {e}", path.display()))?;
+        Self::parse(&text)
+    }
+
+    /// Number of known function selectors
+    pub fn function_count(&self) -> usize {
+        self.functions.len()
+    }
+
+    /// Number of known event topics
+    pub fn event_count(&self) -> usize {
+        self.events.len()
+    }
+}
+
+impl SelectorResolver for SignatureDatabase {
+    fn function_signatures(&self, selector: [u8; 4]) -> Vec<String> {
+        self.functions.get(&selector).cloned().unwrap_or_default()
+    }
+
+    fn event_signatures(&self, topic: &Word) -> Vec<String> {
+        self.events.get(topic).cloned().unwrap_or_default()
+    }
+}
+
+/// The best known signature for a selector, or the selector in hex
+pub fn selector_label(resolver: &dyn SelectorResolver, selector: [u8; 4]) -> String {
+    resolver
+        .function_signatures(selector)
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| format!("0x{}", encode_hex(&selector)))
+}
+
+/// The best known signature for an event topic, or the topic in hex
+pub fn topic_label(resolver: &dyn SelectorResolver, topic: &Word) -> String {
+    resolver
+        .event_signatures(topic)
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| format!("0x{}", encode_hex(topic)))
+}
+
+/// An event emitted with a constant first topic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmittedEvent {
+    /// Program counter of the LOG instruction
+    pub pc: usize,
+    /// The first topic, which is the event signature hash for non-anonymous events
+    pub topic: Word,
+}
+
+/// Find LOG1-LOG4 instructions whose first topic is a constant
+pub fn find_event_topics(bytecode: &[u8], fork: Fork) -> Vec<EmittedEvent> {
+    // LOGn inputs: offset, size, topic0, ...
+    trace_operands(&disassemble(bytecode), fork, |i| {
+        (0xa1..=0xa4).contains(&i.opcode)
+    })
+    .into_iter()
+    .filter_map(|snapshot| {
+        let topic = snapshot.inputs.get(2)?.word()?;
+        Some(EmittedEvent {
+            pc: snapshot.pc,
+            topic,
+        })
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRANSFER_TOPIC: &str = "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+    #[test]
+    fn test_database_labels() {
+        let text = format!(
+            "# 4byte export\n0xa9059cbb transfer(address,uint256)\n{TRANSFER_TOPIC} Transfer(address,address,uint256)\n"
+        );
+        let database = SignatureDatabase::parse(&text).unwrap();
+        assert_eq!((database.function_count(), database.event_count()), (1, 1));
+        assert_eq!(
+            selector_label(&database, [0xa9, 0x05, 0x9c, 0xbb]),
+            "transfer(address,uint256)"
+        );
+        assert_eq!(
+            selector_label(&NoopResolver, [0xa9, 0x05, 0x9c, 0xbb]),
+            "0xa9059cbb"
+        );
+        assert!(SignatureDatabase::parse("0x1234 f()").is_err());
+        assert_eq!(
+            SignatureDatabase::parse("0xa9059cbx f()").unwrap_err(),
+            "Line 1: invalid hex 0xa9059cbx: Invalid hex digit 'x' at position 9"
+        );
+    }
+
+    #[test]
+    fn test_event_topics_are_resolved() {
+        let topic: Word = decode_hex(TRANSFER_TOPIC).unwrap().try_into().unwrap();
+        // PUSH32 topic, PUSH1 0x20, PUSH1 0x00, LOG1
+        let mut code = vec![0x7f];
+        code.extend_from_slice(&topic);
+        code.extend_from_slice(&[0x60, 0x20, 0x60, 0x00, 0xa1]);
+
+        let events = find_event_topics(&code, Fork::London);
+        assert_eq!(events, vec![EmittedEvent { pc: 37, topic }]);
+        let database =
+            SignatureDatabase::new().with_event(topic, "Transfer(address,address,uint256)");
+        assert_eq!(
+            topic_label(&database, &events[0].topic),
+            "Transfer(address,address,uint256)"
+        );
+    }
+}
@@ -0,0 +1,253 @@
+//! Return data and revert reasons known from bytecode alone
+//!
+//! Every RETURN and REVERT is traced with the [`StackSimulator`]: when its memory range and
+//! contents are constant the data is decoded exactly. Solidity usually builds revert data at
+//! the free memory pointer, which is unknown after a JUMPDEST, so REVERTs without known data
+//! fall back to the constants pushed in their basic block: the `Error(string)` and
+//! `Panic(uint256)` selectors, custom error selectors and the ASCII chunks of the message.
+
+use crate::disasm::{disassemble, is_terminator, Instruction};
+use crate::stack::StackSimulator;
+use crate::Fork;
+
+/// Selector of `Error(string)`
+pub const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+/// Selector of `Panic(uint256)`
+pub const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+/// Why a REVERT reverts, as far as the bytecode tells
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RevertReason {
+    /// No revert data
+    Empty,
+    /// `Error(string)` with its message
+    Error(String),
+    /// `Panic(uint256)` with its code, if found
+    Panic(Option<u64>),
+    /// A custom error, by selector
+    Custom([u8; 4]),
+    /// Revert data that could not be determined
+    Unknown,
+}
+
+/// A RETURN or REVERT and what is known about its data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExitData {
+    /// Program counter of the instruction
+    pub pc: usize,
+    /// RETURN (0xf3) or REVERT (0xfd)
+    pub opcode: u8,
+    /// Memory offset of the data, if constant
+    pub offset: Option<u64>,
+    /// Size of the data, if constant
+    pub size: Option<u64>,
+    /// The data, if every byte is known
+    pub data: Option<Vec<u8>>,
+    /// Revert reason, for REVERT only
+    pub reason: Option<RevertReason>,
+}
+
+/// Find every RETURN and REVERT with its statically known data
+pub fn find_exit_data(bytecode: &[u8], fork: Fork) -> Vec<ExitData> {
+    let instructions = disassemble(bytecode);
+    let mut simulator = StackSimulator::new(fork);
+    let mut block_start = 0;
+    let mut exits = Vec::new();
+    for (index, instruction) in instructions.iter().enumerate() {
+        if instruction.opcode == 0x5b {
+            block_start = index;
+        }
+        if matches!(instruction.opcode, 0xf3 | 0xfd) {
+            let offset = simulator.peek(0).as_u64();
+            let size = simulator.peek(1).as_u64();
+            let data = offset
+                .zip(size)
+                .and_then(|(offset, size)| simulator.memory_range(offset, size));
+            let reason = (instruction.opcode == 0xfd).then(|| match (&data, size) {
+                (Some(data), _) => decode_revert_data(data),
+                (None, Some(0)) => RevertReason::Empty,
+                (None, _) => reason_from_constants(&instructions[block_start..index]),
+            });
+            exits.push(ExitData {
+                pc: instruction.pc,
+                opcode: instruction.opcode,
+                offset,
+                size,
+                data,
+                reason,
+            });
+        }
+        simulator.step(instruction);
+        if is_terminator(instruction.opcode) || instruction.opcode == 0x57 {
+            block_start = index + 1;
+        }
+    }
+    exits
+}
+
+/// Revert reasons discoverable in bytecode, by program counter of the REVERT
+pub fn revert_reasons(bytecode: &[u8], fork: Fork) -> Vec<(usize, RevertReason)> {
+    find_exit_data(bytecode, fork)
+        .into_iter()
+        .filter_map(|exit| Some((exit.pc, exit.reason?)))
+        .collect()
+}
+
+/// Decode ABI-encoded revert data
+pub fn decode_revert_data(data: &[u8]) -> RevertReason {
+    let Some((selector, payload)) = data.split_first_chunk::<4>() else {
+        return if data.is_empty() {
+            RevertReason::Empty
+        } else {
+            RevertReason::Unknown
+        };
+    };
+    match *selector {
+        ERROR_SELECTOR => decode_string(payload)
+            .map(RevertReason::Error)
+            .unwrap_or(RevertReason::Unknown),
+        PANIC_SELECTOR => RevertReason::Panic(payload.get(..32).and_then(word_to_u64)),
+        selector => RevertReason::Custom(selector),
+    }
+}
+
+/// The string of an ABI-encoded `(string)` tuple
+fn decode_string(payload: &[u8]) -> Option<String> {
+    let offset = word_to_u64(payload.get(..32)?)? as usize;
+    let length = word_to_u64(payload.get(offset..offset.checked_add(32)?)?)? as usize;
+    let start = offset + 32;
+    let bytes = payload.get(start..start.checked_add(length)?)?;
+    String::from_utf8(bytes.to_vec()).ok()
+}
+
+/// A 32-byte big-endian word that fits in a u64
+fn word_to_u64(word: &[u8]) -> Option<u64> {
+    let (high, low) = word.split_at(24);
+    high.iter()
+        .all(|byte| *byte == 0)
+        .then(|| u64::from_be_bytes(low.try_into().unwrap_or_default()))
+}
+
+/// Guess the revert reason from the constants pushed in the REVERT's basic block
+fn reason_from_constants(block: &[Instruction]) -> RevertReason {
+    let Some(position) = block.iter().position(|i| selector_of(i).is_some()) else {
+        return RevertReason::Unknown;
+    };
+    let rest = &block[position + 1..];
+    match selector_of(&block[position]) {
+        Some(ERROR_SELECTOR) => {
+            let message: String = rest.iter().filter_map(ascii_chunk).collect();
+            if message.is_empty() {
+                RevertReason::Unknown
+            } else {
+                RevertReason::Error(message)
+            }
+        }
+        // Solidity stores the code with `PUSH code, PUSH1 0x04, MSTORE`
+        Some(PANIC_SELECTOR) => RevertReason::Panic(rest.windows(3).find_map(|window| {
+            let stores_at_4 = window[1].opcode == 0x60
+                && window[1].immediate == [0x04]
+                && window[2].opcode == 0x52;
+            (window[0].is_push() && stores_at_4).then(|| push_value(&window[0]))?
+        })),
+        Some(selector) => RevertReason::Custom(selector),
+        None => RevertReason::Unknown,
+    }
+}
+
+/// The selector pushed by an instruction: PUSH4 (shifted into place later) or a PUSH32
+/// holding it left-aligned
+fn selector_of(instruction: &Instruction) -> Option<[u8; 4]> {
+    match instruction.opcode {
+        0x63 => instruction.immediate.as_slice().try_into().ok(),
+        0x7f => {
+            let (selector, rest) = instruction.immediate.split_first_chunk::<4>()?;
+            (rest.iter().all(|byte| *byte == 0) && *selector != [0; 4]).then_some(*selector)
+        }
+        _ => None,
+    }
+}
+
+/// Printable ASCII pushed as a message chunk (trailing zero padding removed)
+fn ascii_chunk(instruction: &Instruction) -> Option<String> {
+    let end = instruction.immediate.iter().rposition(|byte| *byte != 0)? + 1;
+    let bytes = &instruction.immediate[..end];
+    let printable = bytes.len() >= 2 && bytes.iter().all(|byte| (0x20..0x7f).contains(byte));
+    (instruction.is_push() && printable).then(|| String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// Value of a PUSH immediate that fits in a u64
+fn push_value(instruction: &Instruction) -> Option<u64> {
+    let immediate = &instruction.immediate;
+    let skip = immediate.len().saturating_sub(8);
+    immediate[..skip].iter().all(|byte| *byte == 0).then(|| {
+        immediate[skip..]
+            .iter()
+            .fold(0, |value, byte| value << 8 | *byte as u64)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_revert_data_is_decoded() {
+        // MSTORE(0, selector << 224), REVERT(0, 4)
+        let code = [
+            0x63, 0xde, 0xad, 0xbe, 0xef, 0x60, 0xe0, 0x1b, 0x5f, 0x52, 0x60, 0x04, 0x5f, 0xfd,
+        ];
+        let exits = find_exit_data(&code, Fork::Cancun);
+        assert_eq!((exits[0].offset, exits[0].size), (Some(0), Some(4)));
+        assert_eq!(exits[0].data, Some(vec![0xde, 0xad, 0xbe, 0xef]));
+        assert_eq!(
+            exits[0].reason,
+            Some(RevertReason::Custom([0xde, 0xad, 0xbe, 0xef]))
+        );
+
+        // The code written at 4 overlaps the selector word, so the panic code comes from
+        // the constants
+        let mut code = vec![0x63];
+        code.extend(PANIC_SELECTOR);
+        code.extend([0x60, 0xe0, 0x1b, 0x5f, 0x52]);
+        code.extend([0x60, 0x11, 0x60, 0x04, 0x52, 0x60, 0x24, 0x5f, 0xfd]);
+        let exits = find_exit_data(&code, Fork::Cancun);
+        assert_eq!(exits[0].data, None);
+        assert_eq!(exits[0].reason, Some(RevertReason::Panic(Some(0x11))));
+
+        // PUSH0, PUSH0, RETURN: empty data
+        let exits = find_exit_data(&[0x5f, 0x5f, 0xf3], Fork::Cancun);
+        assert_eq!(exits[0].data, Some(vec![]));
+        assert_eq!(exits[0].reason, None);
+    }
+
+    #[test]
+    fn test_error_string_from_constants() {
+        // JUMPDEST, PUSH1 0x40, MLOAD, PUSH32 Error selector, DUP2, MSTORE,
+        // PUSH32 "not owner", ..., DUP2, REVERT
+        let mut code = vec![0x5b, 0x60, 0x40, 0x51, 0x7f];
+        code.extend(ERROR_SELECTOR);
+        code.extend([0; 28]);
+        code.extend([0x81, 0x52, 0x7f]);
+        let mut message = b"not owner".to_vec();
+        message.resize(32, 0);
+        code.extend(message);
+        code.extend([0x60, 0x44, 0x82, 0x52, 0x60, 0x64, 0x81, 0xfd]);
+        assert_eq!(
+            revert_reasons(&code, Fork::Cancun),
+            vec![(code.len() - 1, RevertReason::Error("not owner".to_string()))]
+        );
+
+        // Fully known Error(string) data
+        let mut data = ERROR_SELECTOR.to_vec();
+        data.extend([[0; 31].as_slice(), &[0x20], &[0; 31], &[0x02], b"no"].concat());
+        assert_eq!(
+            decode_revert_data(&data),
+            RevertReason::Error("no".to_string())
+        );
+        assert_eq!(
+            decode_revert_data(&[0xde, 0xad, 0xbe, 0xef]),
+            RevertReason::Custom([0xde, 0xad, 0xbe, 0xef])
+        );
+    }
+}
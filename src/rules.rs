@@ -0,0 +1,788 @@
+//! Composable rules for validating opcode sequences
+//!
+//! A [`SequenceValidator`] runs a set of [`SequenceRule`]s over decoded bytecode and collects
+//! every violation. The standard rule set mirrors
+//! [`validate_sequence`](crate::OpcodeRegistry::validate_sequence); rules can be
+//! removed by name or added, including user-defined ones.
+
+use crate::chain::ChainProfile;
+use crate::disasm::{
+    detect_data_regions, disassemble, disassemble_eof, valid_jump_destinations, Instruction,
+};
+use crate::gas::GasAnalysis;
+use crate::stack::{trace_operands, StackValue};
+use crate::{Fork, OpcodeMetadata, OpcodeRegistry};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/// Block gas limit used by the standard rule set
+pub const DEFAULT_BLOCK_GAS_LIMIT: u64 = 30_000_000;
+
+/// A single rule violation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// Name of the rule that reported the violation
+    pub rule: String,
+    /// Program counter of the offending instruction, if the rule is location-specific
+    pub pc: Option<usize>,
+    /// Human-readable description
+    pub message: String,
+}
+
+impl Violation {
+    /// Create a violation for a rule
+    pub fn new(rule: &str, pc: Option<usize>, message: impl Into<String>) -> Self {
+        Self {
+            rule: rule.to_string(),
+            pc,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Everything a rule may inspect, computed once per validation
+pub struct SequenceContext<'a> {
+    /// The raw bytecode
+    pub bytecode: &'a [u8],
+    /// Target fork
+    pub fork: Fork,
+    /// Decoded instructions up to the start of detected trailing data
+    pub instructions: Vec<Instruction>,
+    /// Offset where detected trailing data starts (the code length if there is none)
+    pub data_start: usize,
+    /// Gas analysis of the decoded opcodes
+    pub analysis: GasAnalysis,
+    /// Opcode metadata for the fork
+    pub opcodes: HashMap<u8, OpcodeMetadata>,
+}
+
+impl<'a> SequenceContext<'a> {
+    /// Decode and analyze bytecode for the given fork
+    pub fn new(bytecode: &'a [u8], fork: Fork) -> Self {
+        Self::with_registry(&OpcodeRegistry::new(), bytecode, fork)
+    }
+
+    /// Decode and analyze bytecode with the tables of `registry`
+    pub fn with_registry(registry: &OpcodeRegistry, bytecode: &'a [u8], fork: Fork) -> Self {
+        let data_start = detect_data_regions(bytecode, fork)
+            .first()
+            .map_or(bytecode.len(), |region| region.start);
+        let mut instructions = disassemble(bytecode);
+        instructions.retain(|i| i.pc < data_start);
+        let decoded: Vec<u8> = instructions.iter().map(|i| i.opcode).collect();
+
+        Self {
+            bytecode,
+            fork,
+            instructions,
+            data_start,
+            analysis: registry.analyze_gas(&decoded, fork),
+            opcodes: registry.get_opcodes(fork),
+        }
+    }
+}
+
+/// A check run over a decoded opcode sequence
+pub trait SequenceRule {
+    /// Unique name used to enable or disable the rule
+    fn name(&self) -> &str;
+
+    /// Return all violations of this rule
+    fn check(&self, context: &SequenceContext<'_>) -> Vec<Violation>;
+}
+
+/// Total gas must not exceed a block gas limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockGasLimitRule {
+    /// The limit to enforce
+    pub limit: u64,
+}
+
+impl Default for BlockGasLimitRule {
+    fn default() -> Self {
+        Self {
+            limit: DEFAULT_BLOCK_GAS_LIMIT,
+        }
+    }
+}
+
+impl SequenceRule for BlockGasLimitRule {
+    fn name(&self) -> &str {
+        "block-gas-limit"
+    }
+
+    fn check(&self, context: &SequenceContext<'_>) -> Vec<Violation> {
+        let total_gas = context.analysis.total_gas;
+        if total_gas <= self.limit {
+            return Vec::new();
+        }
+        vec![Violation::new(
+            self.name(),
+            None,
+            format!(
+                "Opcode sequence consumes {} gas, exceeding block limit of {}",
+                total_gas, self.limit
+            ),
+        )]
+    }
+}
+
+/// PUSH0 must not be used before Shanghai (EIP-3855)
+///
+/// Earlier forks treat 0x5f as an invalid opcode. [`lower_push0`](crate::patch::lower_push0)
+/// rewrites it as `PUSH1 0x00` for those targets.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Push0AvailabilityRule;
+
+impl SequenceRule for Push0AvailabilityRule {
+    fn name(&self) -> &str {
+        "push0-availability"
+    }
+
+    fn check(&self, context: &SequenceContext<'_>) -> Vec<Violation> {
+        if context.fork >= Fork::Shanghai {
+            return Vec::new();
+        }
+        context
+            .instructions
+            .iter()
+            .filter(|i| i.opcode == 0x5f)
+            .map(|i| {
+                Violation::new(
+                    self.name(),
+                    Some(i.pc),
+                    format!(
+                        "PUSH0 at pc {} requires Shanghai, target fork is {:?}; lower it to PUSH1 0x00 or target Shanghai or later",
+                        i.pc, context.fork
+                    ),
+                )
+            })
+            .collect()
+    }
+}
+
+/// The instruction after STOP, RETURN, REVERT, INVALID, SELFDESTRUCT or JUMP must be a JUMPDEST
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UnreachableCodeRule;
+
+impl SequenceRule for UnreachableCodeRule {
+    fn name(&self) -> &str {
+        "unreachable-code"
+    }
+
+    fn check(&self, context: &SequenceContext<'_>) -> Vec<Violation> {
+        context
+            .instructions
+            .windows(2)
+            .filter(|pair| pair[0].is_terminator() && pair[1].opcode != 0x5b)
+            .map(|pair| {
+                let (previous, current) = (&pair[0], &pair[1]);
+                Violation::new(
+                    self.name(),
+                    Some(current.pc),
+                    format!(
+                        "Unreachable code at pc {}: {} follows {} at pc {} without a JUMPDEST",
+                        current.pc,
+                        current.name(),
+                        previous.name(),
+                        previous.pc
+                    ),
+                )
+            })
+            .collect()
+    }
+}
+
+/// A PUSH directly before JUMP or JUMPI must push the pc of a JUMPDEST instruction
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StaticJumpTargetRule;
+
+impl SequenceRule for StaticJumpTargetRule {
+    fn name(&self) -> &str {
+        "static-jump-target"
+    }
+
+    fn check(&self, context: &SequenceContext<'_>) -> Vec<Violation> {
+        let code = &context.instructions;
+        let destinations = valid_jump_destinations(code);
+        let mut violations = Vec::new();
+
+        for pair in code.windows(2) {
+            let (push, jump) = (&pair[0], &pair[1]);
+            if !push.is_push() || !matches!(jump.opcode, 0x56 | 0x57) {
+                continue;
+            }
+            let Some(target) = push.push_value_u64().map(|value| value as usize) else {
+                continue;
+            };
+            if destinations.contains(&target) {
+                continue;
+            }
+            let reason = match code.iter().find(|i| i.pc < target && target < i.next_pc()) {
+                Some(containing) => format!(
+                    "inside the immediate of {} at pc {}",
+                    containing.name(),
+                    containing.pc
+                ),
+                None if target >= context.data_start => "outside the code".to_string(),
+                None => "not a JUMPDEST".to_string(),
+            };
+            violations.push(Violation::new(
+                self.name(),
+                Some(jump.pc),
+                format!(
+                    "{} at pc {} targets pc {target}, which is {reason}",
+                    jump.name(),
+                    jump.pc
+                ),
+            ));
+        }
+
+        violations
+    }
+}
+
+/// Flags consecutive instructions matching a pattern of opcode ranges
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForbiddenPatternRule {
+    /// Rule name
+    pub name: String,
+    /// Opcode ranges that consecutive instructions must match
+    pub pattern: Vec<RangeInclusive<u8>>,
+    /// Message reported for each match
+    pub message: String,
+}
+
+impl ForbiddenPatternRule {
+    /// Create a pattern rule
+    pub fn new(name: &str, pattern: Vec<RangeInclusive<u8>>, message: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            pattern,
+            message: message.to_string(),
+        }
+    }
+
+    /// SSTORE directly after JUMPI, which may create an expensive loop
+    pub fn sstore_after_jumpi() -> Self {
+        Self::new(
+            "sstore-after-jumpi",
+            vec![0x57..=0x57, 0x55..=0x55],
+            "SSTORE after JUMPI may create expensive loop",
+        )
+    }
+
+    /// DUP directly followed by POP, which has no effect
+    pub fn dup_pop() -> Self {
+        Self::new(
+            "dup-pop",
+            vec![0x80..=0x8f, 0x50..=0x50],
+            "DUP followed by POP detected - inefficient pattern",
+        )
+    }
+}
+
+impl SequenceRule for ForbiddenPatternRule {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn check(&self, context: &SequenceContext<'_>) -> Vec<Violation> {
+        if self.pattern.is_empty() {
+            return Vec::new();
+        }
+        context
+            .instructions
+            .windows(self.pattern.len())
+            .filter(|window| {
+                window
+                    .iter()
+                    .zip(&self.pattern)
+                    .all(|(instruction, range)| range.contains(&instruction.opcode))
+            })
+            .map(|window| Violation::new(&self.name, Some(window[0].pc), self.message.clone()))
+            .collect()
+    }
+}
+
+/// No operation may be flagged as a potential gas bomb by the gas analysis
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GasBombRule;
+
+impl SequenceRule for GasBombRule {
+    fn name(&self) -> &str {
+        "gas-bomb"
+    }
+
+    fn check(&self, context: &SequenceContext<'_>) -> Vec<Violation> {
+        let gas_bombs = context.analysis.find_gas_bombs();
+        if gas_bombs.is_empty() {
+            return Vec::new();
+        }
+        vec![Violation::new(
+            self.name(),
+            None,
+            format!("Potential gas bombs detected: {}", gas_bombs.join("; ")),
+        )]
+    }
+}
+
+/// Straight-line stack depth must not underflow or exceed 1024 items
+///
+/// Depth is tracked from the start of the code and becomes unknown at each JUMPDEST, since
+/// the entry depth of a jump target is not known without control-flow analysis.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StackDepthRule;
+
+impl SequenceRule for StackDepthRule {
+    fn name(&self) -> &str {
+        "stack-depth"
+    }
+
+    fn check(&self, context: &SequenceContext<'_>) -> Vec<Violation> {
+        const MAX_STACK_DEPTH: usize = 1024;
+        let mut violations = Vec::new();
+        let mut depth = Some(0usize);
+
+        for instruction in &context.instructions {
+            if instruction.opcode == 0x5b {
+                depth = None;
+                continue;
+            }
+            let Some(metadata) = context.opcodes.get(&instruction.opcode) else {
+                continue;
+            };
+            let Some(current) = depth else {
+                continue;
+            };
+            let inputs = metadata.stack_inputs as usize;
+            if current < inputs {
+                violations.push(Violation::new(
+                    self.name(),
+                    Some(instruction.pc),
+                    format!(
+                        "Stack underflow at pc {}: {} needs {} items, {} available",
+                        instruction.pc,
+                        instruction.name(),
+                        inputs,
+                        current
+                    ),
+                ));
+                depth = None;
+                continue;
+            }
+            let next = current - inputs + metadata.stack_outputs as usize;
+            if next > MAX_STACK_DEPTH {
+                violations.push(Violation::new(
+                    self.name(),
+                    Some(instruction.pc),
+                    format!(
+                        "Stack overflow at pc {}: depth exceeds {MAX_STACK_DEPTH}",
+                        instruction.pc
+                    ),
+                ));
+                depth = None;
+                continue;
+            }
+            depth = if instruction.is_terminator() {
+                None
+            } else {
+                Some(next)
+            };
+        }
+
+        violations
+    }
+}
+
+/// Code meant for a static call (STATICCALL) must not modify state
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StaticContextRule;
+
+impl SequenceRule for StaticContextRule {
+    fn name(&self) -> &str {
+        "static-context"
+    }
+
+    fn check(&self, context: &SequenceContext<'_>) -> Vec<Violation> {
+        context
+            .instructions
+            .iter()
+            .filter(|i| {
+                matches!(
+                    i.opcode,
+                    0x55 | 0x5d | 0xa0..=0xa4 | 0xf0 | 0xf5 | 0xff // SSTORE, TSTORE, LOGn, CREATE(2), SELFDESTRUCT
+                )
+            })
+            .map(|i| {
+                Violation::new(
+                    self.name(),
+                    Some(i.pc),
+                    format!(
+                        "{} at pc {} modifies state and would revert in a static context",
+                        i.name(),
+                        i.pc
+                    ),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Flags metamorphic contract risks: code that can be destroyed and redeployed at the same
+/// address with different code
+///
+/// Reports every SELFDESTRUCT (the contract becomes metamorphic if it is deployed with
+/// CREATE2) and every CREATE2 whose salt is not a compile-time constant, since a caller-chosen
+/// salt lets children be redeployed at a chosen address. From Cancun on, EIP-6780 limits
+/// SELFDESTRUCT to contracts created in the same transaction, which the message reflects.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MetamorphicRiskRule;
+
+impl SequenceRule for MetamorphicRiskRule {
+    fn name(&self) -> &str {
+        "metamorphic-risk"
+    }
+
+    fn check(&self, context: &SequenceContext<'_>) -> Vec<Violation> {
+        let selfdestruct_effect = if context.fork >= Fork::Cancun {
+            "only in the transaction that created it (EIP-6780)"
+        } else {
+            "at any time"
+        };
+        trace_operands(&context.instructions, context.fork, |i| {
+            matches!(i.opcode, 0xf5 | 0xff)
+        })
+        .into_iter()
+        .filter_map(|snapshot| match snapshot.opcode {
+            0xff => Some(Violation::new(
+                self.name(),
+                Some(snapshot.pc),
+                format!(
+                    "SELFDESTRUCT at pc {}: if deployed with CREATE2, the contract can be destroyed and redeployed with different code {selfdestruct_effect}",
+                    snapshot.pc
+                ),
+            )),
+            // CREATE2 inputs: value, offset, size, salt
+            _ if snapshot.inputs.get(3) == Some(&StackValue::Unknown) => Some(Violation::new(
+                self.name(),
+                Some(snapshot.pc),
+                format!(
+                    "CREATE2 at pc {} uses a salt that is not a constant; a caller-controlled salt allows redeploying children at chosen addresses",
+                    snapshot.pc
+                ),
+            )),
+            _ => None,
+        })
+        .collect()
+    }
+}
+
+/// Legacy opcodes that are not valid in EOF code (EIP-3670, EIP-7692): dynamic jumps, code
+/// and gas introspection, the legacy calls and creations, and SELFDESTRUCT
+pub const EOF_BANNED_OPCODES: &[u8] = &[
+    0x38, 0x39, 0x3b, 0x3c, 0x3f, 0x56, 0x57, 0x58, 0x5a, 0xf0, 0xf1, 0xf2, 0xf4, 0xf5, 0xfa, 0xff,
+];
+
+/// Code meant for an EOF container must not use the opcodes EOF bans
+///
+/// The bytecode is decoded as one EOF code section, so the immediates of RJUMP, CALLF and
+/// the other EOF-only opcodes are skipped. Not part of the standard rules; enabled by
+/// [`AnalysisConfig::eof_mode`](crate::AnalysisConfig::eof_mode) in [`analyze`](crate::analyze).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EofBannedOpcodeRule;
+
+impl SequenceRule for EofBannedOpcodeRule {
+    fn name(&self) -> &str {
+        "eof-banned-opcodes"
+    }
+
+    fn check(&self, context: &SequenceContext<'_>) -> Vec<Violation> {
+        disassemble_eof(context.bytecode)
+            .iter()
+            .filter(|i| EOF_BANNED_OPCODES.contains(&i.opcode))
+            .map(|i| {
+                Violation::new(
+                    self.name(),
+                    Some(i.pc),
+                    format!("{} at pc {} is not allowed in EOF code", i.name(), i.pc),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Flags opcodes a chain does not support or runs differently from Ethereum
+///
+/// Unsupported opcodes are the ones the chain's gas overrides remove; divergent ones are
+/// listed in [`ChainProfile::divergences`]. Not part of the standard rules, since they depend
+/// on the chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainDivergenceRule {
+    chain: ChainProfile,
+}
+
+impl ChainDivergenceRule {
+    /// Create a rule for a chain
+    pub fn new(chain: ChainProfile) -> Self {
+        Self { chain }
+    }
+}
+
+impl SequenceRule for ChainDivergenceRule {
+    fn name(&self) -> &str {
+        "chain-divergence"
+    }
+
+    fn check(&self, context: &SequenceContext<'_>) -> Vec<Violation> {
+        let chain = &self.chain.name;
+        context
+            .instructions
+            .iter()
+            .filter_map(|i| {
+                let name = context.opcodes.get(&i.opcode)?.name;
+                let message = if self.chain.gas_overrides.removes(i.opcode) {
+                    format!("{name} at pc {} is not supported on {chain}", i.pc)
+                } else {
+                    let note = self.chain.divergences.get(&i.opcode)?;
+                    format!(
+                        "{name} at pc {} behaves differently on {chain}: {note}",
+                        i.pc
+                    )
+                };
+                Some(Violation::new(self.name(), Some(i.pc), message))
+            })
+            .collect()
+    }
+}
+
+/// Only opcodes from an allowlist may be used, e.g. no calls or creations in a
+/// deterministic sandbox
+///
+/// Not part of the standard rules; [`OpcodeRegistry::sandbox_validator`] adds it for a
+/// [subset](OpcodeRegistry::subset) registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpcodeAllowlistRule {
+    allowed: BTreeSet<u8>,
+}
+
+impl OpcodeAllowlistRule {
+    /// Create a rule allowing the given opcodes
+    pub fn new(allowed: impl IntoIterator<Item = u8>) -> Self {
+        Self {
+            allowed: allowed.into_iter().collect(),
+        }
+    }
+}
+
+impl SequenceRule for OpcodeAllowlistRule {
+    fn name(&self) -> &str {
+        "opcode-allowlist"
+    }
+
+    fn check(&self, context: &SequenceContext<'_>) -> Vec<Violation> {
+        context
+            .instructions
+            .iter()
+            .filter(|i| !self.allowed.contains(&i.opcode))
+            .map(|i| {
+                Violation::new(
+                    self.name(),
+                    Some(i.pc),
+                    format!("{} at pc {} is not in the opcode allowlist", i.name(), i.pc),
+                )
+            })
+            .collect()
+    }
+}
+
+/// A configurable set of sequence rules
+pub struct SequenceValidator {
+    rules: Vec<Box<dyn SequenceRule>>,
+}
+
+impl SequenceValidator {
+    /// Create a validator with no rules
+    pub fn empty() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// Create a validator with the standard rules, in the order they are reported:
+    /// PUSH0 availability, block gas limit, unreachable code, static jump targets, SSTORE after JUMPI,
+    /// DUP followed by POP, and gas bombs
+    ///
+    /// [`StackDepthRule`], [`StaticContextRule`], [`MetamorphicRiskRule`],
+    /// [`ChainDivergenceRule`], [`EofBannedOpcodeRule`] and [`OpcodeAllowlistRule`] are
+    /// available but not enabled by default.
+    pub fn standard() -> Self {
+        Self::empty()
+            .with_rule(Push0AvailabilityRule)
+            .with_rule(BlockGasLimitRule::default())
+            .with_rule(UnreachableCodeRule)
+            .with_rule(StaticJumpTargetRule)
+            .with_rule(ForbiddenPatternRule::sstore_after_jumpi())
+            .with_rule(ForbiddenPatternRule::dup_pop())
+            .with_rule(GasBombRule)
+    }
+
+    /// Add a rule
+    pub fn with_rule(mut self, rule: impl SequenceRule + 'static) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// Remove every rule with the given name
+    pub fn without_rule(mut self, name: &str) -> Self {
+        self.rules.retain(|rule| rule.name() != name);
+        self
+    }
+
+    /// Names of the enabled rules, in order
+    pub fn rule_names(&self) -> Vec<&str> {
+        self.rules.iter().map(|rule| rule.name()).collect()
+    }
+
+    /// Run every rule and collect all violations
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(?fork, len = bytecode.len()))
+    )]
+    pub fn validate(&self, bytecode: &[u8], fork: Fork) -> Result<(), Vec<Violation>> {
+        self.validate_with_registry(&OpcodeRegistry::new(), bytecode, fork)
+    }
+
+    /// Run every rule with the tables of `registry`
+    pub fn validate_with_registry(
+        &self,
+        registry: &OpcodeRegistry,
+        bytecode: &[u8],
+        fork: Fork,
+    ) -> Result<(), Vec<Violation>> {
+        let context = SequenceContext::with_registry(registry, bytecode, fork);
+        let violations: Vec<Violation> = self
+            .rules
+            .iter()
+            .flat_map(|rule| {
+                let violations = rule.check(&context);
+                #[cfg(feature = "tracing")]
+                tracing::debug!(
+                    rule = rule.name(),
+                    violations = violations.len(),
+                    "checked rule"
+                );
+                violations
+            })
+            .collect();
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+impl Default for SequenceValidator {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eof_banned_opcodes() {
+        // RJUMP +0x56 (not a JUMP), CALLER, SELFDESTRUCT
+        let code = [0xe0, 0x00, 0x56, 0x33, 0xff];
+        let violations = SequenceValidator::empty()
+            .with_rule(EofBannedOpcodeRule)
+            .validate(&code, Fork::Cancun)
+            .unwrap_err();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].pc, Some(4));
+
+        let config = crate::AnalysisConfig::new().with_eof_mode(true);
+        let report = crate::analyze(&code, Fork::Cancun, &config).unwrap();
+        assert!(report
+            .violations
+            .iter()
+            .any(|v| v.rule == "eof-banned-opcodes"));
+    }
+
+    #[test]
+    fn test_all_violations_are_reported() {
+        // STOP, DUP1, POP: unreachable code and a DUP/POP pattern
+        let violations = SequenceValidator::standard()
+            .validate(&[0x00, 0x80, 0x50], Fork::London)
+            .unwrap_err();
+        let rules: Vec<&str> = violations.iter().map(|v| v.rule.as_str()).collect();
+        assert_eq!(rules, vec!["unreachable-code", "dup-pop"]);
+    }
+
+    #[test]
+    fn test_rules_can_be_disabled_and_added() {
+        let validator = SequenceValidator::standard()
+            .without_rule("unreachable-code")
+            .without_rule("dup-pop");
+        assert!(validator
+            .validate(&[0x00, 0x80, 0x50], Fork::London)
+            .is_ok());
+
+        // PUSH1 0x00, SLOAD, PUSH1 0x00, SSTORE
+        let code = [0x60, 0x00, 0x54, 0x60, 0x00, 0x55];
+        let validator = SequenceValidator::empty()
+            .with_rule(StaticContextRule)
+            .with_rule(ForbiddenPatternRule::new(
+                "no-sload",
+                vec![0x54..=0x54],
+                "SLOAD is not allowed",
+            ));
+        let violations = validator.validate(&code, Fork::London).unwrap_err();
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0].pc, Some(5));
+        assert_eq!(violations[1].rule, "no-sload");
+    }
+
+    #[test]
+    fn test_stack_depth_rule() {
+        let validator = SequenceValidator::empty().with_rule(StackDepthRule);
+        // PUSH1 0x01, ADD: ADD needs two items
+        let violations = validator
+            .validate(&[0x60, 0x01, 0x01], Fork::London)
+            .unwrap_err();
+        assert_eq!(violations[0].pc, Some(2));
+        // PUSH1 0x01, PUSH1 0x02, ADD
+        assert!(validator
+            .validate(&[0x60, 0x01, 0x60, 0x02, 0x01], Fork::London)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_metamorphic_risk_rule() {
+        let validator = SequenceValidator::empty().with_rule(MetamorphicRiskRule);
+
+        // CALLER, SELFDESTRUCT
+        let violations = validator.validate(&[0x33, 0xff], Fork::London).unwrap_err();
+        assert!(violations[0].message.contains("at any time"));
+        let violations = validator.validate(&[0x33, 0xff], Fork::Cancun).unwrap_err();
+        assert!(violations[0].message.contains("EIP-6780"));
+
+        // Salt from CALLDATALOAD vs a constant salt: salt, size, offset, value, CREATE2
+        let dynamic_salt = [0x60, 0x00, 0x35, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0xf5];
+        assert_eq!(
+            validator.validate(&dynamic_salt, Fork::London).unwrap_err()[0].pc,
+            Some(9)
+        );
+        let constant_salt = [0x60, 0x2a, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0xf5];
+        assert!(validator.validate(&constant_salt, Fork::London).is_ok());
+    }
+}
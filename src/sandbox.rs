@@ -0,0 +1,116 @@
+//! Opcode subsets for sandboxed EVMs
+//!
+//! Constrained execution environments often run a restricted EVM, e.g. without calls,
+//! creations or environment reads so that execution stays deterministic.
+//! [`OpcodeRegistry::subset`] narrows a registry to an allowlist: its tables, and therefore
+//! [`is_opcode_available`](OpcodeRegistry::is_opcode_available), only know the allowed
+//! opcodes, and [`sandbox_validator`](OpcodeRegistry::sandbox_validator) rejects code that
+//! uses anything else.
+
+use crate::rules::{OpcodeAllowlistRule, SequenceValidator};
+use crate::{CustomFork, OpcodeRegistry};
+use std::collections::{BTreeMap, BTreeSet};
+
+impl OpcodeRegistry {
+    /// A registry restricted to the allowed opcodes
+    ///
+    /// Subsetting a subset keeps only the opcodes both allowlists contain. Custom forks are
+    /// restricted too, including ones registered on the subset later.
+    pub fn subset(&self, allowed: impl IntoIterator<Item = u8>) -> OpcodeRegistry {
+        let mut allowlist: BTreeSet<u8> = allowed.into_iter().collect();
+        if let Some(current) = &self.allowlist {
+            allowlist.retain(|opcode| current.contains(opcode));
+        }
+        let opcodes = self
+            .opcodes
+            .iter()
+            .map(|(fork, table)| {
+                let mut table = table.clone();
+                table.retain(|opcode, _| allowlist.contains(opcode));
+                (*fork, table)
+            })
+            .collect();
+        let mut registry = OpcodeRegistry {
+            opcodes,
+            custom_forks: BTreeMap::new(),
+            allowlist: Some(allowlist),
+        };
+        for (name, fork) in &self.custom_forks {
+            let mut opcodes = fork.opcodes.clone();
+            opcodes.retain(|opcode, _| registry.is_allowed(*opcode));
+            registry.custom_forks.insert(
+                name.clone(),
+                CustomFork {
+                    base: fork.base,
+                    opcodes,
+                },
+            );
+        }
+        registry
+    }
+
+    /// The opcodes this registry is restricted to, `None` unless it is a subset
+    pub fn allowlist(&self) -> Option<&BTreeSet<u8>> {
+        self.allowlist.as_ref()
+    }
+
+    /// Whether the registry's allowlist admits an opcode (always true for the full tables)
+    pub fn is_allowed(&self, opcode: u8) -> bool {
+        self.allowlist
+            .as_ref()
+            .is_none_or(|allowlist| allowlist.contains(&opcode))
+    }
+
+    /// The standard sequence validator, rejecting opcodes outside the allowlist
+    pub fn sandbox_validator(&self) -> SequenceValidator {
+        let validator = SequenceValidator::standard();
+        match &self.allowlist {
+            Some(allowlist) => {
+                validator.with_rule(OpcodeAllowlistRule::new(allowlist.iter().copied()))
+            }
+            None => validator,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Fork, OpcodeRegistry};
+
+    #[test]
+    fn test_subset_without_calls() {
+        let registry = OpcodeRegistry::new();
+        let banned = [0xf0, 0xf1, 0xf2, 0xf4, 0xf5, 0xfa, 0xff];
+        let sandbox = registry.subset((0..=255).filter(|opcode| !banned.contains(opcode)));
+        assert!(sandbox.is_opcode_available(Fork::Cancun, 0x01));
+        assert!(!sandbox.is_opcode_available(Fork::Cancun, 0xf1));
+        assert!(!sandbox.get_opcodes(Fork::Cancun).contains_key(&0xf0));
+        assert!(registry.is_opcode_available(Fork::Cancun, 0xf1));
+
+        let narrower = sandbox.subset([0x01, 0xf1]);
+        assert_eq!(
+            narrower.allowlist().unwrap().iter().collect::<Vec<_>>(),
+            [&0x01]
+        );
+    }
+
+    #[test]
+    fn test_sandbox_validator_rejects_disallowed_opcodes() {
+        let sandbox = OpcodeRegistry::new().subset([0x00, 0x01, 0x5f, 0x60]);
+        let validator = sandbox.sandbox_validator();
+        assert!(validator.rule_names().contains(&"opcode-allowlist"));
+        assert!(validator
+            .validate(&[0x60, 0x01, 0x5f, 0x01, 0x00], Fork::Cancun)
+            .is_ok());
+
+        // PUSH0 x7, CALL
+        let code = [0x5f, 0x5f, 0x5f, 0x5f, 0x5f, 0x5f, 0x5f, 0xf1, 0x00];
+        let violations = validator.validate(&code, Fork::Cancun).unwrap_err();
+        assert_eq!(violations[0].rule, "opcode-allowlist");
+        assert_eq!(violations[0].pc, Some(7));
+        assert!(!OpcodeRegistry::new()
+            .sandbox_validator()
+            .rule_names()
+            .contains(&"opcode-allowlist"));
+    }
+}
@@ -0,0 +1,223 @@
+//! Resolving the active fork from a block number or timestamp
+//!
+//! Forks before the Merge activate at a block number and later ones at a block timestamp.
+//! [`Fork::info`] describes the mainnet activation of every fork and
+//! [`ForkSchedule::mainnet`] collects those of the execution-layer forks; other networks can
+//! build their own schedule with [`ForkSchedule::with_fork`].
+
+use crate::Fork;
+
+/// When a fork activates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Activation {
+    /// First block number with the fork's rules
+    Block(u64),
+    /// First block timestamp (seconds since the Unix epoch) with the fork's rules
+    Timestamp(u64),
+}
+
+impl Activation {
+    /// Check if the fork is active in a block with this number and timestamp
+    pub fn is_active(&self, block: u64, timestamp: u64) -> bool {
+        match *self {
+            Self::Block(activation) => block >= activation,
+            Self::Timestamp(activation) => timestamp >= activation,
+        }
+    }
+}
+
+/// Mainnet activation and contents of a fork
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ForkInfo {
+    /// When the fork activated on mainnet; consensus forks use the timestamp of their epoch
+    pub activation: Activation,
+    /// Activation date (UTC), `YYYY-MM-DD`
+    pub date: &'static str,
+    /// EIPs activated by the fork, in ascending order
+    pub eips: &'static [u32],
+}
+
+impl Fork {
+    /// Mainnet activation, date and EIPs of this fork
+    pub fn info(self) -> ForkInfo {
+        use Activation::{Block, Timestamp};
+        let (activation, date, eips): (Activation, &'static str, &'static [u32]) = match self {
+            Fork::Frontier => (Block(0), "2015-07-30", &[]),
+            Fork::IceAge => (Block(200_000), "2015-09-07", &[]),
+            Fork::Homestead => (Block(1_150_000), "2016-03-14", &[2, 7, 8]),
+            Fork::DaoFork => (Block(1_920_000), "2016-07-20", &[]),
+            Fork::TangerineWhistle => (Block(2_463_000), "2016-10-18", &[150]),
+            Fork::SpuriousDragon => (Block(2_675_000), "2016-11-22", &[155, 160, 161, 170]),
+            Fork::Byzantium => (
+                Block(4_370_000),
+                "2017-10-16",
+                &[100, 140, 196, 197, 198, 211, 214, 649, 658],
+            ),
+            Fork::Constantinople => (
+                Block(7_280_000),
+                "2019-02-28",
+                &[145, 1014, 1052, 1234, 1283],
+            ),
+            Fork::Petersburg => (Block(7_280_000), "2019-02-28", &[145, 1014, 1052, 1234]),
+            Fork::Istanbul => (
+                Block(9_069_000),
+                "2019-12-08",
+                &[152, 1108, 1344, 1884, 2028, 2200],
+            ),
+            Fork::MuirGlacier => (Block(9_200_000), "2020-01-02", &[2384]),
+            Fork::Berlin => (Block(12_244_000), "2021-04-15", &[2565, 2718, 2929, 2930]),
+            Fork::London => (
+                Block(12_965_000),
+                "2021-08-05",
+                &[1559, 3198, 3529, 3541, 3554],
+            ),
+            Fork::Altair => (Timestamp(1_635_332_183), "2021-10-27", &[]),
+            Fork::ArrowGlacier => (Block(13_773_000), "2021-12-09", &[4345]),
+            Fork::GrayGlacier => (Block(15_050_000), "2022-06-30", &[5133]),
+            Fork::Bellatrix => (Timestamp(1_662_464_087), "2022-09-06", &[]),
+            Fork::Paris => (Block(15_537_394), "2022-09-15", &[3675, 4399]),
+            Fork::Shanghai => (
+                Timestamp(1_681_338_455),
+                "2023-04-12",
+                &[3651, 3855, 3860, 4895, 6049],
+            ),
+            Fork::Capella => (Timestamp(1_681_338_455), "2023-04-12", &[4895]),
+            Fork::Cancun => (
+                Timestamp(1_710_338_135),
+                "2024-03-13",
+                &[1153, 4788, 4844, 5656, 6780, 7516],
+            ),
+            Fork::Deneb => (
+                Timestamp(1_710_338_135),
+                "2024-03-13",
+                &[4788, 4844, 7044, 7045, 7514],
+            ),
+            Fork::Prague => (
+                Timestamp(1_746_612_311),
+                "2025-05-07",
+                &[
+                    2537, 2935, 6110, 7002, 7251, 7549, 7623, 7685, 7691, 7702, 7840,
+                ],
+            ),
+            Fork::Electra => (
+                Timestamp(1_746_612_311),
+                "2025-05-07",
+                &[6110, 7002, 7251, 7549, 7685, 7691],
+            ),
+            #[cfg(feature = "unstable-forks")]
+            Fork::Osaka => (
+                Timestamp(1_764_798_551),
+                "2025-12-03",
+                &[
+                    7594, 7642, 7823, 7825, 7883, 7892, 7910, 7917, 7918, 7934, 7935, 7939, 7951,
+                ],
+            ),
+        };
+        ForkInfo {
+            activation,
+            date,
+            eips,
+        }
+    }
+}
+
+/// Fork activations of a network
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ForkSchedule {
+    /// Sorted by fork
+    activations: Vec<(Fork, Activation)>,
+}
+
+impl ForkSchedule {
+    /// Create an empty schedule, in which only Frontier is ever active
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ethereum mainnet activations of the execution-layer forks
+    pub fn mainnet() -> Self {
+        Fork::execution_forks().fold(Self::new(), |schedule, fork| {
+            schedule.with_fork(fork, fork.info().activation)
+        })
+    }
+
+    /// Add or replace the activation of a fork
+    pub fn with_fork(mut self, fork: Fork, activation: Activation) -> Self {
+        self.activations.retain(|(scheduled, _)| *scheduled != fork);
+        self.activations.push((fork, activation));
+        self.activations.sort_by_key(|(fork, _)| *fork);
+        self
+    }
+
+    /// Activation of a fork, if it is scheduled
+    pub fn activation(&self, fork: Fork) -> Option<Activation> {
+        self.activations
+            .iter()
+            .find(|(scheduled, _)| *scheduled == fork)
+            .map(|(_, activation)| *activation)
+    }
+
+    /// The latest fork active in a block with this number and timestamp
+    ///
+    /// When several forks activate at once (Constantinople and Petersburg on mainnet), the
+    /// later one wins.
+    pub fn fork_at(&self, block: u64, timestamp: u64) -> Fork {
+        self.activations
+            .iter()
+            .rev()
+            .find(|(_, activation)| activation.is_active(block, timestamp))
+            .map_or(Fork::Frontier, |(fork, _)| *fork)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mainnet_fork_at() {
+        let mainnet = ForkSchedule::mainnet();
+        assert_eq!(mainnet.fork_at(0, 1_438_269_973), Fork::Frontier);
+        assert_eq!(mainnet.fork_at(4_369_999, 0), Fork::SpuriousDragon);
+        assert_eq!(mainnet.fork_at(7_280_000, 0), Fork::Petersburg);
+        assert_eq!(mainnet.fork_at(15_537_394, 1_663_224_179), Fork::Paris);
+        assert_eq!(mainnet.fork_at(19_426_587, 1_710_338_135), Fork::Cancun);
+        assert_eq!(mainnet.fork_at(22_431_084, 1_746_612_311), Fork::Prague);
+    }
+
+    #[test]
+    fn test_fork_info() {
+        let shanghai = Fork::Shanghai.info();
+        assert_eq!(shanghai.date, "2023-04-12");
+        assert!(shanghai.eips.contains(&3855));
+        assert_eq!(Fork::Capella.info().activation, shanghai.activation);
+        assert_eq!(
+            Fork::Homestead.info().activation,
+            Activation::Block(1_150_000)
+        );
+        for fork in Fork::ALL {
+            let info = fork.info();
+            assert!(info.eips.windows(2).all(|pair| pair[0] < pair[1]));
+            if let Some(previous) = fork.previous() {
+                assert!(previous.info().date <= info.date, "{fork:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_custom_schedule() {
+        let schedule = ForkSchedule::new()
+            .with_fork(Fork::London, Activation::Block(0))
+            .with_fork(Fork::Shanghai, Activation::Timestamp(100));
+        assert_eq!(schedule.fork_at(5, 99), Fork::London);
+        assert_eq!(schedule.fork_at(5, 100), Fork::Shanghai);
+        assert_eq!(
+            schedule.activation(Fork::Shanghai),
+            Some(Activation::Timestamp(100))
+        );
+        assert_eq!(
+            ForkSchedule::new().fork_at(u64::MAX, u64::MAX),
+            Fork::Frontier
+        );
+    }
+}
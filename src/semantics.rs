@@ -0,0 +1,131 @@
+//! Behavior changes of existing opcodes across forks
+//!
+//! The opcode tables record gas and stack effects; changes to what an opcode does (or to what
+//! surrounds it, like the EIP-2935 history contract next to BLOCKHASH) are listed in
+//! [`SEMANTIC_CHANGES`]. Fork comparisons report them as
+//! [`ChangeType::SemanticsChanged`](crate::gas::ChangeType::SemanticsChanged) and the drift
+//! report flags code that uses them.
+
+use crate::{Fork, OpcodeId};
+
+/// A change to the behavior of an opcode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SemanticChange {
+    /// The opcode
+    pub opcode: u8,
+    /// Fork that made the change
+    pub fork: Fork,
+    /// EIP that made the change
+    pub eip: u32,
+    /// What changed
+    pub note: &'static str,
+}
+
+impl SemanticChange {
+    /// Stable identifier of the opcode in the fork that changed it
+    pub fn id(&self) -> OpcodeId {
+        OpcodeId::new(self.opcode, self.fork)
+    }
+}
+
+/// Semantic changes, in fork order
+pub const SEMANTIC_CHANGES: &[SemanticChange] = &[
+    SemanticChange {
+        opcode: 0xf1,
+        fork: Fork::TangerineWhistle,
+        eip: 150,
+        note: "calls forward at most 63/64 of the remaining gas",
+    },
+    SemanticChange {
+        opcode: 0xff,
+        fork: Fork::TangerineWhistle,
+        eip: 150,
+        note: "SELFDESTRUCT to a new account costs 25000 more",
+    },
+    SemanticChange {
+        opcode: 0x55,
+        fork: Fork::Istanbul,
+        eip: 2200,
+        note: "SSTORE uses net gas metering and fails when no more than 2300 gas is left",
+    },
+    SemanticChange {
+        opcode: 0x55,
+        fork: Fork::London,
+        eip: 3529,
+        note: "SSTORE refunds are reduced and capped at a fifth of the gas used",
+    },
+    SemanticChange {
+        opcode: 0xff,
+        fork: Fork::London,
+        eip: 3529,
+        note: "SELFDESTRUCT no longer refunds gas",
+    },
+    SemanticChange {
+        opcode: 0x44,
+        fork: Fork::Paris,
+        eip: 4399,
+        note: "DIFFICULTY became PREVRANDAO and returns beacon chain randomness",
+    },
+    SemanticChange {
+        opcode: 0xf0,
+        fork: Fork::Shanghai,
+        eip: 3860,
+        note: "init code is limited to 49152 bytes and charged per word",
+    },
+    SemanticChange {
+        opcode: 0xf5,
+        fork: Fork::Shanghai,
+        eip: 3860,
+        note: "init code is limited to 49152 bytes and charged per word",
+    },
+    SemanticChange {
+        opcode: 0xff,
+        fork: Fork::Cancun,
+        eip: 6780,
+        note: "SELFDESTRUCT only deletes the contract in the transaction that created it",
+    },
+    SemanticChange {
+        opcode: 0x40,
+        fork: Fork::Prague,
+        eip: 2935,
+        note: "the last 8191 block hashes are served by the history system contract at 0x0000F90827F1C53a10cb7A02335B175320002935; BLOCKHASH itself still covers 256 blocks",
+    },
+];
+
+/// Semantic changes of an opcode made up to and including `fork`
+pub fn semantic_notes(opcode: u8, fork: Fork) -> Vec<&'static SemanticChange> {
+    SEMANTIC_CHANGES
+        .iter()
+        .filter(|change| change.opcode == opcode && change.fork <= fork)
+        .collect()
+}
+
+/// Semantic changes made after `from` and up to and including `to` (in either order)
+pub fn semantic_changes_between(from: Fork, to: Fork) -> Vec<&'static SemanticChange> {
+    let (low, high) = if from <= to { (from, to) } else { (to, from) };
+    SEMANTIC_CHANGES
+        .iter()
+        .filter(|change| low < change.fork && change.fork <= high)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gas::{ChangeType, GasComparator};
+
+    #[test]
+    fn test_blockhash_history_contract() {
+        assert!(semantic_notes(0x40, Fork::Cancun).is_empty());
+        let notes = semantic_notes(0x40, Fork::Prague);
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].eip, 2935);
+
+        let changes = GasComparator::get_changes_between_forks(Fork::Cancun, Fork::Prague);
+        assert!(changes
+            .iter()
+            .any(|c| c.opcode == 0x40 && c.change_type == ChangeType::SemanticsChanged));
+        let report = GasComparator::generate_comparison_report(Fork::Cancun, Fork::Prague);
+        assert_eq!(report.summary.semantic_changes, 1);
+    }
+}
@@ -0,0 +1,426 @@
+//! Builder for instruction sequences
+//!
+//! [`Seq`] writes correctly encoded bytecode and, alongside it, the `(opcode, operands)` list
+//! taken by [`DynamicGasCalculator::analyze_sequence_gas`](crate::gas::DynamicGasCalculator::analyze_sequence_gas).
+//! Helpers that take operands push them first (last operand first, so the first one ends up
+//! on top of the stack) and record them on the instruction that consumes them.
+//!
+//! ```
+//! use eot::{gas::DynamicGasCalculator, seq::Seq, Fork};
+//!
+//! let seq = Seq::new().push1(0x10).push1(0x20).add().sstore(0x0, 0x1);
+//! assert_eq!(seq.bytecode(), [0x60, 0x10, 0x60, 0x20, 0x01, 0x60, 0x01, 0x60, 0x00, 0x55]);
+//! let result = DynamicGasCalculator::new(Fork::Cancun)
+//!     .analyze_sequence_gas(seq.sequence())
+//!     .unwrap();
+//! assert_eq!(result.breakdown.len(), 6);
+//! ```
+
+/// An instruction sequence under construction
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Seq {
+    bytecode: Vec<u8>,
+    sequence: Vec<(u8, Vec<u64>)>,
+}
+
+impl Seq {
+    /// Empty sequence
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Encoded bytecode
+    pub fn bytecode(&self) -> &[u8] {
+        &self.bytecode
+    }
+
+    /// `(opcode, operands)` list for the gas calculator, one entry per instruction
+    pub fn sequence(&self) -> &[(u8, Vec<u64>)] {
+        &self.sequence
+    }
+
+    /// Consume the builder, returning the bytecode and the operand list
+    pub fn build(self) -> (Vec<u8>, Vec<(u8, Vec<u64>)>) {
+        (self.bytecode, self.sequence)
+    }
+
+    /// Append an opcode without immediates or operands
+    pub fn op(self, opcode: u8) -> Self {
+        self.op_with(opcode, &[])
+    }
+
+    /// Push `operands` (first one on top) and append `opcode` consuming them
+    pub fn op_with(mut self, opcode: u8, operands: &[u64]) -> Self {
+        for &operand in operands.iter().rev() {
+            self = self.push(operand);
+        }
+        self.bytecode.push(opcode);
+        self.sequence.push((opcode, operands.to_vec()));
+        self
+    }
+
+    /// PUSHn with the given immediate bytes (PUSH0 when empty)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes` is longer than 32.
+    pub fn push_bytes(mut self, bytes: &[u8]) -> Self {
+        assert!(bytes.len() <= 32, "PUSH immediates are at most 32 bytes");
+        let opcode = 0x5f + bytes.len() as u8;
+        self.bytecode.push(opcode);
+        self.bytecode.extend_from_slice(bytes);
+        self.sequence.push((opcode, vec![]));
+        self
+    }
+
+    /// Push `value` with the narrowest PUSH1..PUSH8 that holds it (PUSH1 for zero, which
+    /// unlike PUSH0 works on every fork)
+    pub fn push(self, value: u64) -> Self {
+        let bytes = value.to_be_bytes();
+        let skip = (value.leading_zeros() as usize / 8).min(7);
+        self.push_bytes(&bytes[skip..])
+    }
+
+    /// PUSHn of `value`, `width` bytes wide (PUSH0 when `width` is zero)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is over 32 or `value` does not fit in it.
+    pub fn push_n(self, width: usize, value: u128) -> Self {
+        assert!(width <= 32, "PUSH{width} does not exist");
+        let mut bytes = [0u8; 32];
+        bytes[16..].copy_from_slice(&value.to_be_bytes());
+        assert!(
+            bytes[..32 - width].iter().all(|b| *b == 0),
+            "{value:#x} does not fit in PUSH{width}"
+        );
+        self.push_bytes(&bytes[32 - width..])
+    }
+
+    /// PUSH1..PUSH32 given by opcode, for [`evm_asm!`](crate::evm_asm)
+    #[doc(hidden)]
+    pub fn push_opcode(self, opcode: u8, value: u128) -> Self {
+        assert!(
+            (0x60..=0x7f).contains(&opcode),
+            "opcode {opcode:#04x} takes no immediate"
+        );
+        self.push_n((opcode - 0x5f) as usize, value)
+    }
+
+    /// PUSH0 (Shanghai)
+    pub fn push0(self) -> Self {
+        self.push_bytes(&[])
+    }
+
+    /// PUSH1
+    pub fn push1(self, value: u8) -> Self {
+        self.push_bytes(&[value])
+    }
+
+    /// PUSH2
+    pub fn push2(self, value: u16) -> Self {
+        self.push_bytes(&value.to_be_bytes())
+    }
+
+    /// PUSH4
+    pub fn push4(self, value: u32) -> Self {
+        self.push_bytes(&value.to_be_bytes())
+    }
+
+    /// PUSH20 of an address
+    pub fn push20(self, address: [u8; 20]) -> Self {
+        self.push_bytes(&address)
+    }
+
+    /// PUSH32
+    pub fn push32(self, word: [u8; 32]) -> Self {
+        self.push_bytes(&word)
+    }
+
+    /// DUPn
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `n` is between 1 and 16.
+    pub fn dup(self, n: u8) -> Self {
+        assert!((1..=16).contains(&n), "DUP{n} does not exist");
+        self.op(0x7f + n)
+    }
+
+    /// SWAPn
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `n` is between 1 and 16.
+    pub fn swap(self, n: u8) -> Self {
+        assert!((1..=16).contains(&n), "SWAP{n} does not exist");
+        self.op(0x8f + n)
+    }
+
+    /// STOP
+    pub fn stop(self) -> Self {
+        self.op(0x00)
+    }
+
+    /// ADD
+    pub fn add(self) -> Self {
+        self.op(0x01)
+    }
+
+    /// MUL
+    pub fn mul(self) -> Self {
+        self.op(0x02)
+    }
+
+    /// SUB
+    pub fn sub(self) -> Self {
+        self.op(0x03)
+    }
+
+    /// DIV
+    pub fn div(self) -> Self {
+        self.op(0x04)
+    }
+
+    /// EXP with the exponent priced from `exponent`; base and exponent must already be on the
+    /// stack
+    pub fn exp(mut self, exponent: u64) -> Self {
+        self.bytecode.push(0x0a);
+        self.sequence.push((0x0a, vec![exponent]));
+        self
+    }
+
+    /// LT
+    pub fn lt(self) -> Self {
+        self.op(0x10)
+    }
+
+    /// GT
+    pub fn gt(self) -> Self {
+        self.op(0x11)
+    }
+
+    /// EQ
+    pub fn eq(self) -> Self {
+        self.op(0x14)
+    }
+
+    /// ISZERO
+    pub fn iszero(self) -> Self {
+        self.op(0x15)
+    }
+
+    /// KECCAK256 of `size` bytes at `offset`
+    pub fn keccak256(self, offset: u64, size: u64) -> Self {
+        self.op_with(0x20, &[offset, size])
+    }
+
+    /// CALLER
+    pub fn caller(self) -> Self {
+        self.op(0x33)
+    }
+
+    /// CALLVALUE
+    pub fn callvalue(self) -> Self {
+        self.op(0x34)
+    }
+
+    /// CALLDATALOAD at `offset`
+    pub fn calldataload(self, offset: u64) -> Self {
+        self.op_with(0x35, &[offset])
+    }
+
+    /// POP
+    pub fn pop(self) -> Self {
+        self.op(0x50)
+    }
+
+    /// MLOAD at `offset`
+    pub fn mload(self, offset: u64) -> Self {
+        self.op_with(0x51, &[offset])
+    }
+
+    /// MSTORE of `value` at `offset`
+    pub fn mstore(self, offset: u64, value: u64) -> Self {
+        self.op_with(0x52, &[offset, value])
+    }
+
+    /// SLOAD of `slot`
+    pub fn sload(self, slot: u64) -> Self {
+        self.op_with(0x54, &[slot])
+    }
+
+    /// SSTORE of `value` to `slot`
+    pub fn sstore(self, slot: u64, value: u64) -> Self {
+        self.op_with(0x55, &[slot, value])
+    }
+
+    /// JUMP; the destination must already be on the stack
+    pub fn jump(self) -> Self {
+        self.op(0x56)
+    }
+
+    /// JUMPI; the destination and condition must already be on the stack
+    pub fn jumpi(self) -> Self {
+        self.op(0x57)
+    }
+
+    /// JUMPDEST
+    pub fn jumpdest(self) -> Self {
+        self.op(0x5b)
+    }
+
+    /// TLOAD of `slot` (Cancun)
+    pub fn tload(self, slot: u64) -> Self {
+        self.op_with(0x5c, &[slot])
+    }
+
+    /// TSTORE of `value` to `slot` (Cancun)
+    pub fn tstore(self, slot: u64, value: u64) -> Self {
+        self.op_with(0x5d, &[slot, value])
+    }
+
+    /// MCOPY of `size` bytes from `src` to `dst` (Cancun)
+    pub fn mcopy(self, dst: u64, src: u64, size: u64) -> Self {
+        self.op_with(0x5e, &[dst, src, size])
+    }
+
+    /// LOGn of `size` bytes at `offset` with `topics` (at most four)
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are more than four topics.
+    pub fn log(self, offset: u64, size: u64, topics: &[u64]) -> Self {
+        assert!(topics.len() <= 4, "LOG takes at most four topics");
+        let mut operands = vec![offset, size];
+        operands.extend_from_slice(topics);
+        self.op_with(0xa0 + topics.len() as u8, &operands)
+    }
+
+    /// RETURN of `size` bytes at `offset`
+    pub fn return_(self, offset: u64, size: u64) -> Self {
+        self.op_with(0xf3, &[offset, size])
+    }
+
+    /// REVERT with `size` bytes at `offset`
+    pub fn revert(self, offset: u64, size: u64) -> Self {
+        self.op_with(0xfd, &[offset, size])
+    }
+}
+
+/// Build a [`Seq`] from mnemonics
+///
+/// Items are separated by `;`. `NAME` appends an opcode, `NAME(a, b)` pushes its operands
+/// first as [`Seq::op_with`] does, `PUSHn value` encodes an immediate of that width and
+/// `PUSH value` picks the narrowest PUSH as [`Seq::push`] does. Mnemonics are the names in
+/// the newest stable fork table and are resolved at compile time, so a typo does not build.
+///
+/// ```
+/// use eot::evm_asm;
+///
+/// let seq = evm_asm!(PUSH1 0x10; PUSH 0x20; ADD; SSTORE(0x0, 0x1); STOP);
+/// assert_eq!(
+///     seq.bytecode(),
+///     [0x60, 0x10, 0x60, 0x20, 0x01, 0x60, 0x01, 0x60, 0x00, 0x55, 0x00]
+/// );
+/// assert_eq!(seq.sequence()[5], (0x55, vec![0x0, 0x1]));
+/// ```
+#[macro_export]
+macro_rules! evm_asm {
+    (@seq $seq:expr $(;)?) => {
+        $seq
+    };
+    (@seq $seq:expr; PUSH $value:literal $($rest:tt)*) => {
+        $crate::evm_asm!(@seq $seq.push($value) $($rest)*)
+    };
+    (@seq $seq:expr; $name:ident ($($operand:expr),* $(,)?) $($rest:tt)*) => {
+        $crate::evm_asm!(@seq $seq.op_with($crate::evm_asm!(@op $name), &[$($operand),*]) $($rest)*)
+    };
+    (@seq $seq:expr; $name:ident $value:literal $($rest:tt)*) => {
+        $crate::evm_asm!(@seq $seq.push_opcode($crate::evm_asm!(@op $name), $value) $($rest)*)
+    };
+    (@seq $seq:expr; $name:ident $($rest:tt)*) => {
+        $crate::evm_asm!(@seq $seq.op($crate::evm_asm!(@op $name)) $($rest)*)
+    };
+    (@op $name:ident) => {
+        u8::from($crate::forks::Prague::$name)
+    };
+    ($($items:tt)*) => {
+        $crate::evm_asm!(@seq $crate::seq::Seq::new(); $($items)*)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gas::{DynamicGasCalculator, GasAnalyzer};
+    use crate::{AnalysisConfig, Fork};
+
+    #[test]
+    fn test_push_widths() {
+        let seq = Seq::new()
+            .push(0)
+            .push(0x1234)
+            .push(u64::MAX)
+            .push0()
+            .dup(2)
+            .swap(1);
+        let mut expected = vec![0x60, 0x00, 0x61, 0x12, 0x34, 0x67];
+        expected.extend([0xff; 8]);
+        expected.extend([0x5f, 0x81, 0x90]);
+        assert_eq!(seq.bytecode(), expected);
+        assert_eq!(seq.sequence().len(), 6);
+    }
+
+    #[test]
+    fn test_bytecode_matches_sequence() {
+        let (bytecode, sequence) = Seq::new()
+            .mstore(0x40, 0x80)
+            .sload(0x0)
+            .sstore(0x0, 0x1)
+            .log(0x0, 0x20, &[0x1])
+            .stop()
+            .build();
+        assert_eq!(sequence[2], (0x52, vec![0x40, 0x80]));
+        assert_eq!(sequence[11], (0xa1, vec![0x0, 0x20, 0x1]));
+
+        let from_sequence = DynamicGasCalculator::new(Fork::Cancun)
+            .analyze_sequence_gas(&sequence)
+            .unwrap();
+        let from_bytecode =
+            GasAnalyzer::analyze_bytecode(&bytecode, Fork::Cancun, &AnalysisConfig::default())
+                .unwrap();
+        let sequence_opcodes: Vec<u8> = from_sequence.breakdown.iter().map(|(op, _)| *op).collect();
+        let bytecode_opcodes: Vec<u8> = from_bytecode.breakdown.iter().map(|(op, _)| *op).collect();
+        assert_eq!(sequence_opcodes, bytecode_opcodes);
+    }
+
+    #[test]
+    fn test_evm_asm_matches_builder() {
+        let built = Seq::new()
+            .push0()
+            .calldataload(0x4)
+            .mstore(0x40, 0x80)
+            .push_n(2, 0x1234)
+            .dup(1)
+            .tstore(0x1, 0x2)
+            .revert(0x0, 0x0);
+        let asm = crate::evm_asm!(
+            PUSH0;
+            CALLDATALOAD(0x4);
+            MSTORE(0x40, 0x80);
+            PUSH2 0x1234;
+            DUP1;
+            TSTORE(0x1, 0x2);
+            REVERT(0x0, 0x0);
+        );
+        assert_eq!(asm, built);
+        assert_eq!(crate::evm_asm!(), Seq::new());
+    }
+
+    #[test]
+    #[should_panic(expected = "does not fit in PUSH1")]
+    fn test_push_n_overflow() {
+        Seq::new().push_n(1, 0x100);
+    }
+}
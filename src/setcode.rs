@@ -0,0 +1,144 @@
+//! EIP-7702 set-code transactions (Prague)
+//!
+//! A set-code transaction carries authorizations that make EOAs delegate to contract code:
+//! the EOA's code becomes the 23-byte delegation designator `0xef0100 || address`. Each
+//! authorization adds to the intrinsic gas, and code that treats "no code" as "is an EOA"
+//! (the classic `extcodesize(account) == 0` check) no longer holds once EOAs can have code.
+
+use crate::disasm::disassemble;
+use crate::gas::calldata_gas;
+use crate::Fork;
+
+/// Prefix of a delegation designator
+pub const DELEGATION_PREFIX: [u8; 3] = [0xef, 0x01, 0x00];
+/// Intrinsic gas per authorization
+pub const PER_EMPTY_ACCOUNT_COST: u64 = 25_000;
+/// Gas per authorization left after the refund for an authority that already exists
+pub const PER_AUTH_BASE_COST: u64 = 12_500;
+
+/// The delegate of an account whose code is a delegation designator
+pub fn delegation_target(code: &[u8]) -> Option<[u8; 20]> {
+    if code.len() != 23 || code[..3] != DELEGATION_PREFIX {
+        return None;
+    }
+    code[3..].try_into().ok()
+}
+
+/// Intrinsic gas of a set-code transaction with `authorizations` authorizations
+///
+/// Fails before Prague, where the transaction type does not exist, and for transactions
+/// without authorizations, which are invalid.
+pub fn set_code_intrinsic_gas(
+    calldata: &[u8],
+    authorizations: usize,
+    fork: Fork,
+) -> Result<u64, String> {
+    if fork < Fork::Prague {
+        return Err(format!(
+            "Set-code transactions require Prague, got {fork:?}"
+        ));
+    }
+    if authorizations == 0 {
+        return Err("Set-code transactions need at least one authorization".to_string());
+    }
+    Ok(21_000 + calldata_gas(calldata, fork) + authorizations as u64 * PER_EMPTY_ACCOUNT_COST)
+}
+
+/// Gas refunded when `existing` of the authorities already exist in the state
+pub fn authorization_refund(existing: usize) -> u64 {
+    existing as u64 * (PER_EMPTY_ACCOUNT_COST - PER_AUTH_BASE_COST)
+}
+
+/// An EXTCODESIZE whose result is compared, which usually means "has no code, so is an EOA"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EoaCheck {
+    /// Program counter of the EXTCODESIZE
+    pub pc: usize,
+    /// Whether the checked account is the caller (`msg.sender`)
+    pub on_caller: bool,
+}
+
+impl EoaCheck {
+    /// Warning describing why the check is unreliable
+    pub fn warning(&self) -> String {
+        let account = if self.on_caller {
+            "the caller"
+        } else {
+            "an account"
+        };
+        format!(
+            "EXTCODESIZE at pc {} checks whether {account} has code; since Prague (EIP-7702) EOAs can have delegated code, so this no longer identifies EOAs",
+            self.pc
+        )
+    }
+}
+
+/// Find EXTCODESIZE results compared by one of the next few instructions
+pub fn find_eoa_checks(bytecode: &[u8]) -> Vec<EoaCheck> {
+    let instructions = disassemble(bytecode);
+    instructions
+        .iter()
+        .enumerate()
+        .filter(|(_, i)| i.opcode == 0x3b)
+        .filter(|(index, _)| {
+            instructions[index + 1..]
+                .iter()
+                .take(4)
+                .take_while(|i| !i.is_terminator() && !matches!(i.opcode, 0x56 | 0x57))
+                .any(|i| matches!(i.opcode, 0x10 | 0x11 | 0x14 | 0x15))
+        })
+        .map(|(index, i)| EoaCheck {
+            pc: i.pc,
+            on_caller: index
+                .checked_sub(1)
+                .is_some_and(|prev| instructions[prev].opcode == 0x33),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delegation_and_intrinsic_gas() {
+        let mut code = DELEGATION_PREFIX.to_vec();
+        code.extend([0xaa; 20]);
+        assert_eq!(delegation_target(&code), Some([0xaa; 20]));
+        assert_eq!(delegation_target(&code[..22]), None);
+
+        assert_eq!(
+            set_code_intrinsic_gas(&[], 2, Fork::Prague).unwrap(),
+            21_000 + 2 * PER_EMPTY_ACCOUNT_COST
+        );
+        assert!(set_code_intrinsic_gas(&[], 1, Fork::Cancun).is_err());
+        assert!(set_code_intrinsic_gas(&[], 0, Fork::Prague).is_err());
+        assert_eq!(authorization_refund(2), 25_000);
+    }
+
+    #[test]
+    fn test_find_eoa_checks() {
+        // CALLER, EXTCODESIZE, ISZERO, PUSH1 0x08, JUMPI, STOP
+        let code = [0x33, 0x3b, 0x15, 0x60, 0x08, 0x57, 0x00];
+        assert_eq!(
+            find_eoa_checks(&code),
+            vec![EoaCheck {
+                pc: 1,
+                on_caller: true
+            }]
+        );
+        // PUSH1 0, EXTCODESIZE, POP
+        assert!(find_eoa_checks(&[0x60, 0x00, 0x3b, 0x50]).is_empty());
+
+        let config = crate::AnalysisConfig::default();
+        let warned = |fork| {
+            crate::gas::GasAnalyzer::analyze_bytecode(&code, fork, &config)
+                .unwrap()
+                .warnings
+                .iter()
+                .any(|warning| warning.contains("EIP-7702"))
+        };
+        assert!(warned(Fork::Prague));
+        assert!(!warned(Fork::Cancun));
+    }
+}
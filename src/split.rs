@@ -0,0 +1,176 @@
+//! Splitting creation code into constructor, runtime code and data
+//!
+//! Creation code runs the constructor, which copies the runtime code out of its own bytes
+//! with CODECOPY and returns it. [`split_code`] finds that copy by stack simulation, so the
+//! constructor and the runtime code can be analyzed separately, and marks the bytes that
+//! are not code: the metadata trailer, unreachable data and appended constructor arguments.
+//! Input without such a copy is taken to be runtime code.
+
+use crate::disasm::{detect_data_regions, disassemble, DataRegion, DataRegionKind};
+use crate::metadata::decode_metadata;
+use crate::stack::trace_operands;
+use crate::Fork;
+use std::ops::Range;
+
+/// Parts of creation or runtime code, as byte ranges of the input
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CodeSplit {
+    /// Constructor code, empty for runtime code
+    pub constructor: Range<usize>,
+    /// Runtime code including its data
+    pub runtime: Range<usize>,
+    /// Bytes that are not code, sorted by start
+    pub data: Vec<DataRegion>,
+}
+
+impl CodeSplit {
+    /// Whether the input was creation code
+    pub fn is_creation(&self) -> bool {
+        !self.constructor.is_empty()
+    }
+
+    /// The constructor without its data
+    pub fn constructor_code<'a>(&self, bytecode: &'a [u8]) -> &'a [u8] {
+        &bytecode[self.code_range(&self.constructor)]
+    }
+
+    /// The runtime code without its data
+    pub fn runtime_code<'a>(&self, bytecode: &'a [u8]) -> &'a [u8] {
+        &bytecode[self.code_range(&self.runtime)]
+    }
+
+    /// `part` up to its first data region
+    fn code_range(&self, part: &Range<usize>) -> Range<usize> {
+        let end = self
+            .data
+            .iter()
+            .find(|region| part.contains(&region.start))
+            .map_or(part.end, |region| region.start);
+        part.start..end
+    }
+}
+
+/// Split `bytecode` into constructor, runtime code and data
+pub fn split_code(bytecode: &[u8], fork: Fork) -> CodeSplit {
+    let Some(runtime) = deployed_range(bytecode, fork) else {
+        return CodeSplit {
+            constructor: 0..0,
+            runtime: 0..bytecode.len(),
+            data: code_data(bytecode, 0..bytecode.len(), fork),
+        };
+    };
+
+    let mut data = code_data(bytecode, 0..runtime.start, fork);
+    data.extend(code_data(bytecode, runtime.clone(), fork));
+    if runtime.end < bytecode.len() {
+        data.push(DataRegion::new(
+            runtime.end,
+            bytecode.len(),
+            DataRegionKind::ConstructorArguments,
+        ));
+    }
+    CodeSplit {
+        constructor: 0..runtime.start,
+        runtime,
+        data,
+    }
+}
+
+/// The bytes a constructor copies out of its code and returns
+///
+/// The copy must start after the CODECOPY and be returned whole by a RETURN before it.
+fn deployed_range(bytecode: &[u8], fork: Fork) -> Option<Range<usize>> {
+    let instructions = disassemble(bytecode);
+    let snapshots = trace_operands(&instructions, fork, |i| matches!(i.opcode, 0x39 | 0xf3));
+    snapshots
+        .iter()
+        .filter(|copy| copy.opcode == 0x39)
+        .find_map(|copy| {
+            // CODECOPY inputs: memory offset, code offset, size
+            let offset = copy.inputs[1].as_u64()? as usize;
+            let size = copy.inputs[2].as_u64()? as usize;
+            let end = offset.checked_add(size)?;
+            if offset <= copy.pc || size == 0 || end > bytecode.len() {
+                return None;
+            }
+            // RETURN inputs: memory offset, size
+            snapshots
+                .iter()
+                .any(|ret| {
+                    ret.opcode == 0xf3
+                        && ret.pc > copy.pc
+                        && ret.pc < offset
+                        && ret.inputs[1].as_u64() == Some(size as u64)
+                })
+                .then_some(offset..end)
+        })
+}
+
+/// Data regions of the code in `range`: the metadata trailer and unreachable bytes
+fn code_data(bytecode: &[u8], range: Range<usize>, fork: Fork) -> Vec<DataRegion> {
+    let code = &bytecode[range.clone()];
+    let shift = |region: DataRegion| {
+        DataRegion::new(
+            range.start + region.start,
+            range.start + region.end,
+            region.kind,
+        )
+    };
+    let trailer = decode_metadata(code).map(|metadata| metadata.region);
+    let code_end = trailer.as_ref().map_or(code.len(), |region| region.start);
+    let mut data: Vec<DataRegion> = detect_data_regions(&code[..code_end], fork)
+        .into_iter()
+        .chain(trailer)
+        .map(shift)
+        .collect();
+    data.sort_by_key(|region| region.start);
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// PUSH1 0x00, SLOAD, STOP, INVALID, then `{"solc": 0.8.24}` and its length
+    const RUNTIME: [u8; 17] = [
+        0x60, 0x00, 0x54, 0x00, 0xfe, 0xa1, 0x64, b's', b'o', b'l', b'c', 0x43, 0x00, 0x08, 0x18,
+        0x00, 0x0a,
+    ];
+
+    #[test]
+    fn test_split_creation_code() {
+        // PUSH1 size, DUP1, PUSH1 offset, PUSH1 0, CODECOPY, PUSH1 0, RETURN, INVALID
+        let mut code = vec![
+            0x60, 0x11, 0x80, 0x60, 0x0c, 0x60, 0x00, 0x39, 0x60, 0x00, 0xf3, 0xfe,
+        ];
+        code.extend(RUNTIME);
+        code.extend([0x01; 32]);
+
+        let split = split_code(&code, Fork::Cancun);
+        assert!(split.is_creation());
+        assert_eq!(split.constructor, 0..12);
+        assert_eq!(split.runtime, 12..29);
+        assert_eq!(
+            split.data,
+            [
+                DataRegion::new(17, 29, DataRegionKind::Metadata),
+                DataRegion::new(29, 61, DataRegionKind::ConstructorArguments),
+            ]
+        );
+        assert_eq!(split.constructor_code(&code), &code[..12]);
+        assert_eq!(split.runtime_code(&code), &RUNTIME[..5]);
+    }
+
+    #[test]
+    fn test_split_runtime_code() {
+        let split = split_code(&RUNTIME, Fork::Cancun);
+        assert!(!split.is_creation());
+        assert_eq!(split.runtime, 0..17);
+        assert_eq!(
+            split.data,
+            [DataRegion::new(5, 17, DataRegionKind::Metadata)]
+        );
+        assert_eq!(split.runtime_code(&RUNTIME), &RUNTIME[..5]);
+        assert!(split.constructor_code(&RUNTIME).is_empty());
+    }
+}
@@ -0,0 +1,457 @@
+//! Abstract stack simulation for recovering constant operands
+//!
+//! Values pushed by PUSH instructions are tracked through stack shuffling (DUP, SWAP, POP)
+//! and folded through the arithmetic, comparison and bitwise operations compilers apply to
+//! constants (ADD, MUL, SUB, DIV, MOD, LT, GT, EQ, ISZERO, AND, OR, XOR, NOT, BYTE, SHL, SHR).
+//! Words stored with MSTORE at constant offsets are remembered as well, so KECCAK256 over a
+//! fully known memory range is evaluated; this resolves mapping slots
+//! (`keccak256(key . slot)`) and hashed CREATE2 salts. Everything else produces
+//! [`StackValue::Unknown`]. Knowledge is reset at every JUMPDEST, since the state
+//! on entry to a jump target depends on the path taken.
+
+use crate::disasm::Instruction;
+use crate::keccak::keccak256;
+use crate::{Fork, OpcodeMetadata, OpcodeRegistry};
+use std::collections::{BTreeMap, HashMap};
+
+/// A 256-bit EVM word, big-endian
+pub type Word = [u8; 32];
+
+/// A stack item whose value may or may not be known statically
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StackValue {
+    /// A constant known at analysis time
+    Known(Word),
+    /// A value that depends on runtime state
+    Unknown,
+}
+
+impl StackValue {
+    /// A known value from an integer
+    pub fn from_u64(value: u64) -> Self {
+        let mut word = [0u8; 32];
+        word[24..].copy_from_slice(&value.to_be_bytes());
+        Self::Known(word)
+    }
+
+    /// A known value from big-endian bytes of at most 32 bytes
+    pub fn from_be_bytes(bytes: &[u8]) -> Self {
+        let mut word = [0u8; 32];
+        let len = bytes.len().min(32);
+        word[32 - len..].copy_from_slice(&bytes[bytes.len() - len..]);
+        Self::Known(word)
+    }
+
+    /// The word, if known
+    pub fn word(&self) -> Option<Word> {
+        match self {
+            Self::Known(word) => Some(*word),
+            Self::Unknown => None,
+        }
+    }
+
+    /// The value as u64, if known and small enough
+    pub fn as_u64(&self) -> Option<u64> {
+        let word = self.word()?;
+        if word[..24].iter().any(|b| *b != 0) {
+            return None;
+        }
+        Some(u64::from_be_bytes(word[24..].try_into().expect("8 bytes")))
+    }
+
+    /// The value as a 20-byte address, if known and it fits in 160 bits
+    pub fn as_address(&self) -> Option<[u8; 20]> {
+        let word = self.word()?;
+        if word[..12].iter().any(|b| *b != 0) {
+            return None;
+        }
+        Some(word[12..].try_into().expect("20 bytes"))
+    }
+}
+
+/// Steps through instructions, maintaining an abstract stack and memory
+pub struct StackSimulator {
+    opcodes: HashMap<u8, OpcodeMetadata>,
+    stack: Vec<StackValue>,
+    memory: BTreeMap<u64, Word>,
+}
+
+impl StackSimulator {
+    /// Create a simulator with an empty stack for a fork
+    pub fn new(fork: Fork) -> Self {
+        Self {
+            opcodes: OpcodeRegistry::new().get_opcodes(fork),
+            stack: Vec::new(),
+            memory: BTreeMap::new(),
+        }
+    }
+
+    /// Forget everything known about the stack and memory
+    pub fn reset(&mut self) {
+        self.stack.clear();
+        self.memory.clear();
+    }
+
+    /// The item `depth` positions below the top (0 is the top); unknown beyond the tracked stack
+    pub fn peek(&self, depth: usize) -> StackValue {
+        self.stack
+            .len()
+            .checked_sub(depth + 1)
+            .map_or(StackValue::Unknown, |index| self.stack[index])
+    }
+
+    /// The word stored at a constant memory offset, if known
+    pub fn memory_word(&self, offset: u64) -> Option<Word> {
+        self.memory.get(&offset).copied()
+    }
+
+    /// Apply one instruction to the abstract state
+    pub fn step(&mut self, instruction: &Instruction) {
+        let opcode = instruction.opcode;
+        match opcode {
+            0x5b => self.reset(), // JUMPDEST
+            0x5f..=0x7f => self
+                .stack
+                .push(StackValue::from_be_bytes(&instruction.immediate)),
+            0x80..=0x8f => {
+                let value = self.peek((opcode - 0x80) as usize);
+                self.stack.push(value);
+            }
+            0x90..=0x9f => {
+                let depth = (opcode - 0x8f) as usize;
+                if self.stack.len() > depth {
+                    let top = self.stack.len() - 1;
+                    self.stack.swap(top, top - depth);
+                } else {
+                    // The swapped item is below what we track
+                    self.pop();
+                    self.stack.push(StackValue::Unknown);
+                }
+            }
+            0x01..=0x04 | 0x06 | 0x10 | 0x11 | 0x14 | 0x16..=0x18 | 0x1a..=0x1c => {
+                let (a, b) = (self.pop(), self.pop());
+                self.stack.push(fold_binary(opcode, a, b));
+            }
+            0x15 => {
+                let value = self.pop().word().map_or(StackValue::Unknown, |word| {
+                    StackValue::from_u64(word.iter().all(|b| *b == 0) as u64)
+                });
+                self.stack.push(value);
+            }
+            0x19 => {
+                let value = self.pop().word().map_or(StackValue::Unknown, |word| {
+                    StackValue::Known(word.map(|byte| !byte))
+                });
+                self.stack.push(value);
+            }
+            0x20 => {
+                let (offset, size) = (self.pop(), self.pop());
+                let value = match (offset.as_u64(), size.as_u64()) {
+                    (Some(offset), Some(size)) => self
+                        .memory_range(offset, size)
+                        .map_or(StackValue::Unknown, |bytes| {
+                            StackValue::Known(keccak256(&bytes))
+                        }),
+                    _ => StackValue::Unknown,
+                };
+                self.stack.push(value);
+            }
+            0x52 => {
+                let (offset, value) = (self.pop(), self.pop());
+                match (offset.as_u64(), value.word()) {
+                    (Some(offset), Some(word)) => {
+                        // Later writes replace any overlapping word
+                        self.memory
+                            .retain(|stored, _| stored.abs_diff(offset) >= 32);
+                        self.memory.insert(offset, word);
+                    }
+                    _ => self.memory.clear(),
+                }
+            }
+            _ => {
+                let (inputs, outputs) = self
+                    .opcodes
+                    .get(&opcode)
+                    .map_or((0, 0), |m| (m.stack_inputs, m.stack_outputs));
+                for _ in 0..inputs {
+                    self.pop();
+                }
+                for _ in 0..outputs {
+                    self.stack.push(StackValue::Unknown);
+                }
+                if writes_memory(opcode) {
+                    self.memory.clear();
+                }
+            }
+        }
+    }
+
+    /// Bytes of a memory range, if every byte was written with a known value
+    pub fn memory_range(&self, offset: u64, size: u64) -> Option<Vec<u8>> {
+        // Larger ranges are never fully tracked in practice
+        if size > 1024 {
+            return None;
+        }
+        (offset..offset.checked_add(size)?)
+            .map(|position| {
+                let (start, word) = self.memory.range(..=position).next_back()?;
+                word.get((position - start) as usize).copied()
+            })
+            .collect()
+    }
+
+    fn pop(&mut self) -> StackValue {
+        self.stack.pop().unwrap_or(StackValue::Unknown)
+    }
+}
+
+/// Operands of an instruction as seen just before it executes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperandSnapshot {
+    /// Program counter of the instruction
+    pub pc: usize,
+    /// The opcode
+    pub opcode: u8,
+    /// Stack inputs, top of stack first
+    pub inputs: Vec<StackValue>,
+}
+
+/// Collect the stack inputs of every instruction matching `filter`
+pub fn trace_operands(
+    instructions: &[Instruction],
+    fork: Fork,
+    filter: impl Fn(&Instruction) -> bool,
+) -> Vec<OperandSnapshot> {
+    let mut simulator = StackSimulator::new(fork);
+    let mut snapshots = Vec::new();
+    for instruction in instructions {
+        if filter(instruction) {
+            let inputs = simulator
+                .opcodes
+                .get(&instruction.opcode)
+                .map_or(0, |m| m.stack_inputs as usize);
+            snapshots.push(OperandSnapshot {
+                pc: instruction.pc,
+                opcode: instruction.opcode,
+                inputs: (0..inputs).map(|depth| simulator.peek(depth)).collect(),
+            });
+        }
+        simulator.step(instruction);
+    }
+    snapshots
+}
+
+/// Opcodes that write memory at locations the simulator does not track
+fn writes_memory(opcode: u8) -> bool {
+    matches!(
+        opcode,
+        0x37 | 0x39 | 0x3c | 0x3e | 0x53 | 0x5e | 0xf1 | 0xf2 | 0xf4 | 0xfa
+    )
+}
+
+/// Evaluate a binary arithmetic, comparison or bitwise operation with `a` on top of the stack
+fn fold_binary(opcode: u8, a: StackValue, b: StackValue) -> StackValue {
+    let (Some(a), Some(b)) = (a.word(), b.word()) else {
+        return StackValue::Unknown;
+    };
+    let mut result = [0u8; 32];
+    match opcode {
+        0x01 => return StackValue::Known(from_limbs(add(to_limbs(a), to_limbs(b)))),
+        0x02 => return StackValue::Known(from_limbs(mul(to_limbs(a), to_limbs(b)))),
+        0x03 => return StackValue::Known(from_limbs(sub(to_limbs(a), to_limbs(b)))),
+        0x04 | 0x06 => {
+            let (quotient, remainder) = div_rem(to_limbs(a), to_limbs(b));
+            let value = if opcode == 0x04 { quotient } else { remainder };
+            return StackValue::Known(from_limbs(value));
+        }
+        0x10 => return StackValue::from_u64((a < b) as u64),
+        0x11 => return StackValue::from_u64((a > b) as u64),
+        0x14 => return StackValue::from_u64((a == b) as u64),
+        0x1a => {
+            // a is the byte index, b the value
+            let index = StackValue::Known(a).as_u64().unwrap_or(u64::MAX);
+            return StackValue::from_u64(b.get(index as usize).map_or(0, |byte| *byte as u64));
+        }
+        0x16 => (0..32).for_each(|i| result[i] = a[i] & b[i]),
+        0x17 => (0..32).for_each(|i| result[i] = a[i] | b[i]),
+        0x18 => (0..32).for_each(|i| result[i] = a[i] ^ b[i]),
+        0x1b | 0x1c => {
+            // a is the shift amount, b the value
+            let shift = StackValue::Known(a).as_u64().unwrap_or(u64::MAX);
+            if shift >= 256 {
+                return StackValue::Known(result);
+            }
+            let (bytes, bits) = ((shift / 8) as usize, (shift % 8) as u32);
+            for (i, out) in result.iter_mut().enumerate() {
+                *out = if opcode == 0x1b {
+                    let hi = b.get(i + bytes).copied().unwrap_or(0);
+                    let lo = b.get(i + bytes + 1).copied().unwrap_or(0);
+                    ((hi as u16) << bits | (lo as u16) >> (8 - bits)) as u8
+                } else {
+                    let hi = i.checked_sub(bytes + 1).map_or(0, |index| b[index]);
+                    let lo = i.checked_sub(bytes).map_or(0, |index| b[index]);
+                    (((hi as u16) << 8 | lo as u16) >> bits) as u8
+                };
+            }
+        }
+        _ => return StackValue::Unknown,
+    }
+    StackValue::Known(result)
+}
+
+/// Little-endian 64-bit limbs of a word
+type Limbs = [u64; 4];
+
+fn to_limbs(word: Word) -> Limbs {
+    std::array::from_fn(|i| {
+        let start = 32 - 8 * (i + 1);
+        u64::from_be_bytes(word[start..start + 8].try_into().expect("8 bytes"))
+    })
+}
+
+fn from_limbs(limbs: Limbs) -> Word {
+    let mut word = [0u8; 32];
+    for (i, limb) in limbs.iter().enumerate() {
+        let start = 32 - 8 * (i + 1);
+        word[start..start + 8].copy_from_slice(&limb.to_be_bytes());
+    }
+    word
+}
+
+/// Addition modulo 2^256
+fn add(a: Limbs, b: Limbs) -> Limbs {
+    let mut carry = false;
+    std::array::from_fn(|i| {
+        let (sum, first) = a[i].overflowing_add(b[i]);
+        let (sum, second) = sum.overflowing_add(carry as u64);
+        carry = first || second;
+        sum
+    })
+}
+
+/// Subtraction modulo 2^256
+fn sub(a: Limbs, b: Limbs) -> Limbs {
+    add(add(a, b.map(|limb| !limb)), [1, 0, 0, 0])
+}
+
+/// Multiplication modulo 2^256
+fn mul(a: Limbs, b: Limbs) -> Limbs {
+    let mut result = [0u64; 4];
+    for i in 0..4 {
+        let mut carry = 0u128;
+        for j in 0..4 - i {
+            let product = a[i] as u128 * b[j] as u128 + result[i + j] as u128 + carry;
+            result[i + j] = product as u64;
+            carry = product >> 64;
+        }
+    }
+    result
+}
+
+/// Quotient and remainder, both zero for a zero divisor as in the EVM
+fn div_rem(a: Limbs, b: Limbs) -> (Limbs, Limbs) {
+    if b == [0; 4] {
+        return ([0; 4], [0; 4]);
+    }
+    let (mut quotient, mut remainder) = ([0u64; 4], [0u64; 4]);
+    for bit in (0..256).rev() {
+        // remainder = remainder << 1 | bit of a
+        for i in (1..4).rev() {
+            remainder[i] = remainder[i] << 1 | remainder[i - 1] >> 63;
+        }
+        remainder[0] = remainder[0] << 1 | (a[bit / 64] >> (bit % 64)) & 1;
+        if remainder.iter().rev().cmp(b.iter().rev()).is_ge() {
+            remainder = sub(remainder, b);
+            quotient[bit / 64] |= 1 << (bit % 64);
+        }
+    }
+    (quotient, remainder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disasm::disassemble;
+
+    #[test]
+    fn test_constants_flow_through_dup_and_swap() {
+        // PUSH1 0x01, PUSH1 0x02, SWAP1, DUP2, SSTORE
+        let code = disassemble(&[0x60, 0x01, 0x60, 0x02, 0x90, 0x81, 0x55]);
+        let snapshots = trace_operands(&code, Fork::London, |i| i.opcode == 0x55);
+        assert_eq!(
+            snapshots[0].inputs,
+            vec![StackValue::from_u64(2), StackValue::from_u64(1)]
+        );
+    }
+
+    #[test]
+    fn test_selector_shift_and_memory() {
+        // PUSH4 0xa9059cbb, PUSH1 0xe0, SHL, PUSH1 0x00, MSTORE
+        let code = disassemble(&[
+            0x63, 0xa9, 0x05, 0x9c, 0xbb, 0x60, 0xe0, 0x1b, 0x60, 0x00, 0x52,
+        ]);
+        let mut simulator = StackSimulator::new(Fork::London);
+        code.iter().for_each(|i| simulator.step(i));
+        let word = simulator.memory_word(0).unwrap();
+        assert_eq!(&word[..4], &[0xa9, 0x05, 0x9c, 0xbb]);
+        assert!(word[4..].iter().all(|b| *b == 0));
+
+        // Shifting back right recovers the selector
+        let shifted = fold_binary(0x1c, StackValue::from_u64(0xe0), StackValue::Known(word));
+        assert_eq!(shifted.as_u64(), Some(0xa9059cbb));
+    }
+
+    #[test]
+    fn test_jumpdest_resets_knowledge() {
+        // PUSH1 0x01, JUMPDEST, DUP1
+        let code = disassemble(&[0x60, 0x01, 0x5b, 0x80]);
+        let snapshots = trace_operands(&code, Fork::London, |i| i.opcode == 0x80);
+        assert_eq!(snapshots[0].inputs, vec![StackValue::Unknown]);
+    }
+
+    #[test]
+    fn test_arithmetic_folding() {
+        // PUSH1 0x20, PUSH1 0x03, MUL, PUSH1 0x04, ADD, SLOAD: slot 3 * 32 + 4
+        let code = disassemble(&[0x60, 0x20, 0x60, 0x03, 0x02, 0x60, 0x04, 0x01, 0x54]);
+        let snapshots = trace_operands(&code, Fork::London, |i| i.opcode == 0x54);
+        assert_eq!(snapshots[0].inputs, vec![StackValue::from_u64(100)]);
+
+        let (seven, two) = (StackValue::from_u64(7), StackValue::from_u64(2));
+        let mut minus_five = [0xff; 32];
+        minus_five[31] = 0xfb;
+        assert_eq!(fold_binary(0x03, two, seven), StackValue::Known(minus_five));
+        assert_eq!(fold_binary(0x04, seven, two).as_u64(), Some(3));
+        assert_eq!(fold_binary(0x06, seven, two).as_u64(), Some(1));
+        assert_eq!(
+            fold_binary(0x04, seven, StackValue::from_u64(0)).as_u64(),
+            Some(0)
+        );
+        assert_eq!(fold_binary(0x10, two, seven).as_u64(), Some(1));
+        assert_eq!(
+            fold_binary(0x1a, StackValue::from_u64(31), seven).as_u64(),
+            Some(7)
+        );
+    }
+
+    #[test]
+    fn test_mapping_slot_is_hashed() {
+        // MSTORE(0, 0xaa), MSTORE(0x20, 3), KECCAK256(0, 0x40), SLOAD
+        let code = disassemble(&[
+            0x60, 0xaa, 0x60, 0x00, 0x52, 0x60, 0x03, 0x60, 0x20, 0x52, 0x60, 0x40, 0x60, 0x00,
+            0x20, 0x54,
+        ]);
+        let snapshots = trace_operands(&code, Fork::London, |i| i.opcode == 0x54);
+        let mut preimage = [0u8; 64];
+        preimage[31] = 0xaa;
+        preimage[63] = 3;
+        assert_eq!(
+            snapshots[0].inputs,
+            vec![StackValue::Known(keccak256(&preimage))]
+        );
+
+        // Hashing past the written words is unknown
+        let mut simulator = StackSimulator::new(Fork::London);
+        code.iter().for_each(|i| simulator.step(i));
+        assert!(simulator.memory_range(0x20, 0x40).is_none());
+    }
+}
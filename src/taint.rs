@@ -0,0 +1,243 @@
+//! Tracking of values derived from call data
+//!
+//! Values loaded with CALLDATALOAD, and memory written by CALLDATACOPY, are tainted. Taint
+//! propagates through every instruction that consumes a tainted input and through memory
+//! (MSTORE and MLOAD at constant offsets; any tainted write to an unknown offset taints all
+//! memory). Like [`StackSimulator`], knowledge is reset at every JUMPDEST.
+//!
+//! Tainted operands reaching a sink let callers control memory expansion, copy lengths,
+//! forwarded gas or jump targets, which is the basis for DoS and safety lints.
+
+use crate::disasm::{disassemble, Instruction};
+use crate::stack::StackSimulator;
+use crate::{Fork, OpcodeMetadata, OpcodeRegistry};
+use std::collections::HashMap;
+
+/// Operands where a caller-controlled value is worth reporting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TaintSink {
+    /// Size of a memory range read or returned (KECCAK256, RETURN, REVERT, LOGn, CREATE,
+    /// CREATE2, call arguments and return data)
+    MemorySize,
+    /// Length of a copy (CALLDATACOPY, CODECOPY, EXTCODECOPY, RETURNDATACOPY, MCOPY)
+    CopyLength,
+    /// Gas forwarded by CALL, CALLCODE, DELEGATECALL or STATICCALL
+    CallGas,
+    /// Target of JUMP or JUMPI
+    JumpTarget,
+}
+
+/// A tainted operand reaching a sink
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TaintedSink {
+    /// Program counter of the instruction
+    pub pc: usize,
+    /// The opcode
+    pub opcode: u8,
+    /// Kind of sink
+    pub sink: TaintSink,
+    /// Index of the stack input, 0 being the top of the stack
+    pub operand: usize,
+}
+
+/// Sink operands of an opcode, as (stack input index, kind)
+fn sinks(opcode: u8) -> &'static [(usize, TaintSink)] {
+    use TaintSink::*;
+    match opcode {
+        0x20 | 0xf3 | 0xfd | 0xa0..=0xa4 => &[(1, MemorySize)],
+        0xf0 | 0xf5 => &[(2, MemorySize)],
+        0xf1 | 0xf2 => &[(0, CallGas), (4, MemorySize), (6, MemorySize)],
+        0xf4 | 0xfa => &[(0, CallGas), (3, MemorySize), (5, MemorySize)],
+        0x37 | 0x39 | 0x3e | 0x5e => &[(2, CopyLength)],
+        0x3c => &[(3, CopyLength)],
+        0x56 | 0x57 => &[(0, JumpTarget)],
+        _ => &[],
+    }
+}
+
+/// Taint state of the stack and memory
+struct Taint {
+    stack: Vec<bool>,
+    /// Tainted memory ranges, end exclusive
+    ranges: Vec<(u64, u64)>,
+    /// Memory at unknown offsets was tainted
+    all_memory: bool,
+}
+
+impl Taint {
+    fn reset(&mut self) {
+        self.stack.clear();
+        self.ranges.clear();
+        self.all_memory = false;
+    }
+
+    fn peek(&self, depth: usize) -> bool {
+        self.stack
+            .len()
+            .checked_sub(depth + 1)
+            .is_some_and(|index| self.stack[index])
+    }
+
+    fn pop(&mut self) -> bool {
+        self.stack.pop().unwrap_or(false)
+    }
+
+    fn taint_memory(&mut self, offset: Option<u64>, length: Option<u64>) {
+        match (offset, length) {
+            (Some(offset), Some(length)) => {
+                self.ranges.push((offset, offset.saturating_add(length)))
+            }
+            _ => self.all_memory = true,
+        }
+    }
+
+    fn memory_is_tainted(&self, offset: Option<u64>) -> bool {
+        if self.all_memory {
+            return true;
+        }
+        match offset {
+            Some(offset) => {
+                let end = offset.saturating_add(32);
+                self.ranges
+                    .iter()
+                    .any(|&(start, stop)| start < end && offset < stop)
+            }
+            None => !self.ranges.is_empty(),
+        }
+    }
+}
+
+/// Find every sink operand that receives a value derived from call data
+pub fn find_tainted_sinks(bytecode: &[u8], fork: Fork) -> Vec<TaintedSink> {
+    let instructions = disassemble(bytecode);
+    let opcodes = OpcodeRegistry::new().get_opcodes(fork);
+    let mut constants = StackSimulator::new(fork);
+    let mut taint = Taint {
+        stack: Vec::new(),
+        ranges: Vec::new(),
+        all_memory: false,
+    };
+    let mut found = Vec::new();
+
+    for instruction in &instructions {
+        for &(operand, sink) in sinks(instruction.opcode) {
+            if taint.peek(operand) {
+                found.push(TaintedSink {
+                    pc: instruction.pc,
+                    opcode: instruction.opcode,
+                    sink,
+                    operand,
+                });
+            }
+        }
+        step(&mut taint, &constants, &opcodes, instruction);
+        constants.step(instruction);
+    }
+    found
+}
+
+/// Apply one instruction to the taint state; `constants` holds the state before it
+fn step(
+    taint: &mut Taint,
+    constants: &StackSimulator,
+    opcodes: &HashMap<u8, OpcodeMetadata>,
+    instruction: &Instruction,
+) {
+    let opcode = instruction.opcode;
+    match opcode {
+        0x5b => taint.reset(),
+        0x5f..=0x7f => taint.stack.push(false),
+        0x80..=0x8f => {
+            let value = taint.peek((opcode - 0x80) as usize);
+            taint.stack.push(value);
+        }
+        0x90..=0x9f => {
+            let depth = (opcode - 0x8f) as usize;
+            if taint.stack.len() > depth {
+                let top = taint.stack.len() - 1;
+                taint.stack.swap(top, top - depth);
+            } else {
+                taint.pop();
+                taint.stack.push(false);
+            }
+        }
+        0x35 => {
+            taint.pop();
+            taint.stack.push(true);
+        }
+        0x37 => {
+            let (offset, length) = (constants.peek(0).as_u64(), constants.peek(2).as_u64());
+            taint.stack.truncate(taint.stack.len().saturating_sub(3));
+            taint.taint_memory(offset, length);
+        }
+        0x51 => {
+            let offset = constants.peek(0).as_u64();
+            let tainted = taint.pop() || taint.memory_is_tainted(offset);
+            taint.stack.push(tainted);
+        }
+        0x52 => {
+            let offset = constants.peek(0).as_u64();
+            taint.pop();
+            if taint.pop() {
+                taint.taint_memory(offset, Some(32));
+            }
+        }
+        _ => {
+            let (inputs, outputs) = opcodes
+                .get(&opcode)
+                .map_or((0, 0), |m| (m.stack_inputs, m.stack_outputs));
+            let tainted = (0..inputs).fold(false, |tainted, _| taint.pop() | tainted);
+            for _ in 0..outputs {
+                taint.stack.push(tainted);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calldata_flows_into_sinks() {
+        let code = [
+            0x60, 0x04, 0x35, // PUSH1 4, CALLDATALOAD
+            0x60, 0x00, 0xf3, // PUSH1 0, RETURN: tainted size
+        ];
+        let found = find_tainted_sinks(&code, Fork::London);
+        assert_eq!(
+            found,
+            vec![TaintedSink {
+                pc: 5,
+                opcode: 0xf3,
+                sink: TaintSink::MemorySize,
+                operand: 1,
+            }]
+        );
+
+        // PUSH1 0, CALLDATALOAD, PUSH1 1, ADD, JUMP
+        let code = [0x60, 0x00, 0x35, 0x60, 0x01, 0x01, 0x56];
+        let found = find_tainted_sinks(&code, Fork::London);
+        assert_eq!(found[0].sink, TaintSink::JumpTarget);
+    }
+
+    #[test]
+    fn test_taint_through_memory() {
+        let mut code = vec![
+            0x60, 0x20, 0x60, 0x04, 0x60, 0x40, 0x37, // CALLDATACOPY(0x40, 4, 32)
+            0x60, 0x00, 0x51, // MLOAD(0): clean
+            0x60, 0x40, 0x51, // MLOAD(0x40): tainted
+        ];
+        code.extend_from_slice(&[0x60, 0x00].repeat(6)); // CALL arguments after gas
+        code.extend_from_slice(&[0x87, 0xf1]); // DUP8 (clean load), CALL
+
+        assert!(find_tainted_sinks(&code, Fork::London).is_empty());
+
+        // DUP7 forwards the tainted load as gas
+        let dup = code.len() - 2;
+        code[dup] = 0x86;
+        let found = find_tainted_sinks(&code, Fork::London);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].sink, TaintSink::CallGas);
+    }
+}
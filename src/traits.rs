@@ -269,20 +269,31 @@ pub trait ForkValidation {
 }
 
 /// Enhanced trait for opcode analysis with gas considerations
+///
+/// The functions build their own tables on every call; the `&self` methods of
+/// [`OpcodeRegistry`](crate::OpcodeRegistry) reuse the registry's tables instead.
+#[allow(deprecated)]
 pub trait OpcodeAnalysis {
     /// Analyze gas usage patterns for a sequence of opcodes
+    #[deprecated(since = "0.1.6", note = "use `OpcodeRegistry::analyze_gas`")]
     fn analyze_gas_usage(opcodes: &[u8], fork: Fork) -> GasAnalysis;
 
     /// Check if a sequence of opcodes is valid for a given fork
+    #[deprecated(since = "0.1.6", note = "use `OpcodeRegistry::validate_sequence`")]
     fn validate_opcode_sequence(opcodes: &[u8], fork: Fork) -> Result<(), String>;
 
     /// Get optimization suggestions for a sequence of opcodes
+    #[deprecated(
+        since = "0.1.6",
+        note = "use `OpcodeRegistry::optimization_suggestions`"
+    )]
     fn get_optimization_suggestions(opcodes: &[u8], fork: Fork) -> Vec<String> {
         let analysis = Self::analyze_gas_usage(opcodes, fork);
         analysis.get_optimization_recommendations()
     }
 
     /// Estimate gas savings from proposed optimizations
+    #[deprecated(since = "0.1.6", note = "use `OpcodeRegistry::gas_savings`")]
     fn estimate_gas_savings(opcodes: &[u8], fork: Fork) -> u64 {
         let analysis = Self::analyze_gas_usage(opcodes, fork);
         analysis.estimate_optimization_savings()
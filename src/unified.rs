@@ -195,6 +195,38 @@ pub enum UnifiedOpcode {
     /// Append log record with four topics (0xa4)
     LOG4,
 
+    // 0xd0-0xd3: Data section access (EIP-7480, EOF code only)
+    /// Load a word of the data section at an offset from the stack (0xd0)
+    DATALOAD,
+    /// Load a word of the data section at an immediate offset (0xd1)
+    DATALOADN,
+    /// Get size of the data section (0xd2)
+    DATASIZE,
+    /// Copy part of the data section to memory (0xd3)
+    DATACOPY,
+
+    // 0xe0-0xe2: Static relative jumps (EIP-4200, EOF code only)
+    /// Jump by a signed 16-bit offset (0xe0)
+    RJUMP,
+    /// Conditionally jump by a signed 16-bit offset (0xe1)
+    RJUMPI,
+    /// Jump by the offset at an index of a jump table (0xe2)
+    RJUMPV,
+
+    // 0xe3-0xe5: Functions (EIP-4750, EIP-6206, EOF code only)
+    /// Call a code section (0xe3)
+    CALLF,
+    /// Return from a code section (0xe4)
+    RETF,
+    /// Jump to a code section without returning (0xe5)
+    JUMPF,
+
+    // 0xec, 0xee: Contract creation (EIP-7620, EOF code only)
+    /// Create a new account from a subcontainer (0xec)
+    EOFCREATE,
+    /// Deploy a subcontainer from init code (0xee)
+    RETURNCONTRACT,
+
     // 0xf0-0xff: System Operations
     /// Create a new account with associated code (0xf0)
     CREATE,
@@ -208,8 +240,14 @@ pub enum UnifiedOpcode {
     DELEGATECALL,
     /// Create a new account with associated code at a predictable address (0xf5)
     CREATE2,
+    /// Message-call into an account without a gas operand (0xf8, EOF code only)
+    EXTCALL,
+    /// Message-call into an account with caller's code, without a gas operand (0xf9, EOF)
+    EXTDELEGATECALL,
     /// Static message-call into an account (0xfa)
     STATICCALL,
+    /// Static message-call into an account without a gas operand (0xfb, EOF code only)
+    EXTSTATICCALL,
     /// Halt execution reverting state changes (0xfd)
     REVERT,
     /// Designated invalid instruction (0xfe)
@@ -223,7 +261,7 @@ pub enum UnifiedOpcode {
 
 impl UnifiedOpcode {
     /// Parse a byte into a unified opcode with immediate data size
-    /// Uses the latest fork (Cancun) by default for maximum compatibility
+    /// Uses the latest fork (Prague) by default for maximum compatibility
     ///
     /// # Returns
     /// A tuple of (opcode, immediate_data_size)
@@ -237,7 +275,7 @@ impl UnifiedOpcode {
     /// assert_eq!(imm_size, 1);
     /// ```
     pub fn parse(byte: u8) -> (Self, usize) {
-        Self::parse_with_fork(byte, Fork::Cancun)
+        Self::parse_with_fork(byte, Fork::Prague)
     }
 
     /// Parse a byte into a unified opcode for a specific fork
@@ -343,13 +381,29 @@ impl UnifiedOpcode {
             0xa3 => Self::LOG3,
             0xa4 => Self::LOG4,
 
+            0xd0 => Self::DATALOAD,
+            0xd1 => Self::DATALOADN,
+            0xd2 => Self::DATASIZE,
+            0xd3 => Self::DATACOPY,
+            0xe0 => Self::RJUMP,
+            0xe1 => Self::RJUMPI,
+            0xe2 => Self::RJUMPV,
+            0xe3 => Self::CALLF,
+            0xe4 => Self::RETF,
+            0xe5 => Self::JUMPF,
+
+            0xec => Self::EOFCREATE,
+            0xee => Self::RETURNCONTRACT,
             0xf0 => Self::CREATE,
             0xf1 => Self::CALL,
             0xf2 => Self::CALLCODE,
             0xf3 => Self::RETURN,
             0xf4 => Self::DELEGATECALL,
             0xf5 => Self::CREATE2,
+            0xf8 => Self::EXTCALL,
+            0xf9 => Self::EXTDELEGATECALL,
             0xfa => Self::STATICCALL,
+            0xfb => Self::EXTSTATICCALL,
             0xfd => Self::REVERT,
             0xfe => Self::INVALID,
             0xff => Self::SELFDESTRUCT,
@@ -447,13 +501,29 @@ impl UnifiedOpcode {
             Self::LOG3 => 0xa3,
             Self::LOG4 => 0xa4,
 
+            Self::DATALOAD => 0xd0,
+            Self::DATALOADN => 0xd1,
+            Self::DATASIZE => 0xd2,
+            Self::DATACOPY => 0xd3,
+            Self::RJUMP => 0xe0,
+            Self::RJUMPI => 0xe1,
+            Self::RJUMPV => 0xe2,
+            Self::CALLF => 0xe3,
+            Self::RETF => 0xe4,
+            Self::JUMPF => 0xe5,
+
+            Self::EOFCREATE => 0xec,
+            Self::RETURNCONTRACT => 0xee,
             Self::CREATE => 0xf0,
             Self::CALL => 0xf1,
             Self::CALLCODE => 0xf2,
             Self::RETURN => 0xf3,
             Self::DELEGATECALL => 0xf4,
             Self::CREATE2 => 0xf5,
+            Self::EXTCALL => 0xf8,
+            Self::EXTDELEGATECALL => 0xf9,
             Self::STATICCALL => 0xfa,
+            Self::EXTSTATICCALL => 0xfb,
             Self::REVERT => 0xfd,
             Self::INVALID => 0xfe,
             Self::SELFDESTRUCT => 0xff,
@@ -470,6 +540,12 @@ impl UnifiedOpcode {
                 | Self::JUMP
                 | Self::JUMPI
                 | Self::JUMPDEST
+                | Self::RJUMP
+                | Self::RJUMPI
+                | Self::RJUMPV
+                | Self::CALLF
+                | Self::RETF
+                | Self::JUMPF
                 | Self::RETURN
                 | Self::REVERT
                 | Self::INVALID
@@ -480,9 +556,29 @@ impl UnifiedOpcode {
                 | Self::CALLCODE
                 | Self::DELEGATECALL
                 | Self::STATICCALL
+                | Self::EXTCALL
+                | Self::EXTDELEGATECALL
+                | Self::EXTSTATICCALL
         )
     }
 
+    /// Check if this is the designated INVALID instruction (0xfe)
+    pub fn is_designated_invalid(&self) -> bool {
+        matches!(self, Self::INVALID)
+    }
+
+    /// Check if this is a byte with no opcode assigned
+    pub fn is_unassigned(&self) -> bool {
+        matches!(self, Self::UNKNOWN(_))
+    }
+
+    /// Check if executing this opcode is an exceptional halt consuming all remaining gas
+    ///
+    /// Both INVALID and unassigned bytes abort execution this way.
+    pub fn consumes_all_gas(&self) -> bool {
+        self.is_designated_invalid() || self.is_unassigned()
+    }
+
     /// Get the name of this opcode as a string
     pub fn name(&self) -> String {
         match self {
@@ -499,14 +595,118 @@ impl UnifiedOpcode {
     }
 
     /// Get immediate data size for this opcode
+    ///
+    /// RJUMPV's jump table follows its first immediate byte, see
+    /// [`immediate_len`](Self::immediate_len).
     fn immediate_size(opcode: &Self) -> usize {
         match opcode {
             Self::PUSH(n) => *n as usize,
             Self::PUSH0 => 0,
+            Self::DATALOADN | Self::RJUMP | Self::RJUMPI | Self::CALLF | Self::JUMPF => 2,
+            Self::RJUMPV | Self::EOFCREATE | Self::RETURNCONTRACT => 1,
             _ => 0,
         }
     }
 
+    /// Length of the immediate given the bytes following the opcode
+    ///
+    /// Returns `None` when the code ends before the immediate does.
+    pub fn immediate_len(&self, code: &[u8]) -> Option<usize> {
+        let len = match self {
+            // max_index, then max_index + 1 offsets
+            Self::RJUMPV => 1 + (*code.first()? as usize + 1) * 2,
+            _ => Self::immediate_size(self),
+        };
+        (code.len() >= len).then_some(len)
+    }
+
+    /// Signed offsets of a static relative jump: one for RJUMP and RJUMPI, the jump table
+    /// for RJUMPV
+    ///
+    /// Offsets are relative to the end of the instruction. Returns `None` for other opcodes
+    /// and truncated immediates.
+    pub fn relative_offsets(&self, immediate: &[u8]) -> Option<Vec<i16>> {
+        let offsets = match self {
+            Self::RJUMP | Self::RJUMPI => immediate.get(..2)?,
+            Self::RJUMPV => immediate.get(1..self.immediate_len(immediate)?)?,
+            _ => return None,
+        };
+        Some(
+            offsets
+                .chunks_exact(2)
+                .map(|pair| i16::from_be_bytes([pair[0], pair[1]]))
+                .collect(),
+        )
+    }
+
+    /// Destinations of a static relative jump at `pc`, skipping any before the start of code
+    pub fn relative_targets(&self, pc: usize, immediate: &[u8]) -> Option<Vec<usize>> {
+        let end = pc + 1 + self.immediate_len(immediate)?;
+        let offsets = self.relative_offsets(immediate)?;
+        Some(
+            offsets
+                .into_iter()
+                .filter_map(|offset| end.checked_add_signed(offset as isize))
+                .collect(),
+        )
+    }
+
+    /// Code section called by CALLF or jumped to by JUMPF
+    pub fn target_section(&self, immediate: &[u8]) -> Option<u16> {
+        match self {
+            Self::CALLF | Self::JUMPF => Some(u16::from_be_bytes([
+                *immediate.first()?,
+                *immediate.get(1)?,
+            ])),
+            _ => None,
+        }
+    }
+
+    /// Subcontainer created by EOFCREATE or deployed by RETURNCONTRACT
+    pub fn container_index(&self, immediate: &[u8]) -> Option<u8> {
+        match self {
+            Self::EOFCREATE | Self::RETURNCONTRACT => immediate.first().copied(),
+            _ => None,
+        }
+    }
+
+    /// Data section offset read by DATALOADN
+    pub fn data_offset(&self, immediate: &[u8]) -> Option<u16> {
+        match self {
+            Self::DATALOADN => Some(u16::from_be_bytes([
+                *immediate.first()?,
+                *immediate.get(1)?,
+            ])),
+            _ => None,
+        }
+    }
+
+    /// Instructions of the same code section that can run next, for EOF code
+    ///
+    /// CALLF continues after the callee returns; RETF, JUMPF and halting opcodes leave the
+    /// section. Returns `None` for truncated immediates.
+    pub fn section_successors(&self, pc: usize, immediate: &[u8]) -> Option<Vec<usize>> {
+        let next = pc + 1 + self.immediate_len(immediate)?;
+        let successors = match self {
+            Self::RJUMP => self.relative_targets(pc, immediate)?,
+            Self::RJUMPI | Self::RJUMPV => {
+                let mut targets = vec![next];
+                targets.extend(self.relative_targets(pc, immediate)?);
+                targets
+            }
+            Self::RETF
+            | Self::JUMPF
+            | Self::RETURNCONTRACT
+            | Self::STOP
+            | Self::RETURN
+            | Self::REVERT
+            | Self::INVALID
+            | Self::UNKNOWN(_) => vec![],
+            _ => vec![next],
+        };
+        Some(successors)
+    }
+
     /// Get metadata for this opcode from the registry for a specific fork
     pub fn metadata(&self, fork: Fork) -> Option<crate::OpcodeMetadata> {
         let registry = OpcodeRegistry::new();
@@ -514,9 +714,9 @@ impl UnifiedOpcode {
         opcodes.get(&self.to_byte()).cloned()
     }
 
-    /// Get metadata using the latest fork (Cancun)
+    /// Get metadata using the latest fork (Prague)
     pub fn metadata_latest(&self) -> Option<crate::OpcodeMetadata> {
-        self.metadata(Fork::Cancun)
+        self.metadata(Fork::Prague)
     }
 }
 
@@ -627,13 +827,29 @@ impl FromStr for UnifiedOpcode {
             "LOG3" => Ok(Self::LOG3),
             "LOG4" => Ok(Self::LOG4),
 
+            "DATALOAD" => Ok(Self::DATALOAD),
+            "DATALOADN" => Ok(Self::DATALOADN),
+            "DATASIZE" => Ok(Self::DATASIZE),
+            "DATACOPY" => Ok(Self::DATACOPY),
+            "RJUMP" => Ok(Self::RJUMP),
+            "RJUMPI" => Ok(Self::RJUMPI),
+            "RJUMPV" => Ok(Self::RJUMPV),
+            "CALLF" => Ok(Self::CALLF),
+            "RETF" => Ok(Self::RETF),
+            "JUMPF" => Ok(Self::JUMPF),
+
+            "EOFCREATE" => Ok(Self::EOFCREATE),
+            "RETURNCONTRACT" => Ok(Self::RETURNCONTRACT),
             "CREATE" => Ok(Self::CREATE),
             "CALL" => Ok(Self::CALL),
             "CALLCODE" => Ok(Self::CALLCODE),
             "RETURN" => Ok(Self::RETURN),
             "DELEGATECALL" => Ok(Self::DELEGATECALL),
             "CREATE2" => Ok(Self::CREATE2),
+            "EXTCALL" => Ok(Self::EXTCALL),
+            "EXTDELEGATECALL" => Ok(Self::EXTDELEGATECALL),
             "STATICCALL" => Ok(Self::STATICCALL),
+            "EXTSTATICCALL" => Ok(Self::EXTSTATICCALL),
             "REVERT" => Ok(Self::REVERT),
             "INVALID" => Ok(Self::INVALID),
             "SELFDESTRUCT" => Ok(Self::SELFDESTRUCT),
@@ -0,0 +1,383 @@
+//! Execution-layer and consensus-layer forks
+//!
+//! [`Fork`] lists every mainnet upgrade of both layers in one order. Only some of them change
+//! the EVM: consensus-layer forks (Altair, Bellatrix, Capella, Deneb, Electra) and
+//! difficulty bomb delays never touch the opcode table. [`ExecutionFork`] and
+//! [`ConsensusFork`] separate the layers, and [`ExecutionFork::evm_rules`] maps any
+//! execution fork to the last fork that changed the EVM.
+
+use crate::Fork;
+
+/// Execution-layer hard forks, in chronological order
+#[derive(Clone, Copy, Debug, PartialOrd, Ord, PartialEq, Eq, Hash)]
+pub enum ExecutionFork {
+    /// Frontier (July 30, 2015)
+    Frontier,
+    /// Ice Age (September 7, 2015)
+    IceAge,
+    /// Homestead (March 14, 2016)
+    Homestead,
+    /// DAO Fork (July 20, 2016)
+    DaoFork,
+    /// Tangerine Whistle (October 18, 2016)
+    TangerineWhistle,
+    /// Spurious Dragon (November 22, 2016)
+    SpuriousDragon,
+    /// Byzantium (October 16, 2017)
+    Byzantium,
+    /// Constantinople (February 28, 2019)
+    Constantinople,
+    /// Petersburg (February 28, 2019)
+    Petersburg,
+    /// Istanbul (December 8, 2019)
+    Istanbul,
+    /// Muir Glacier (January 2, 2020)
+    MuirGlacier,
+    /// Berlin (April 15, 2021)
+    Berlin,
+    /// London (August 5, 2021)
+    London,
+    /// Arrow Glacier (December 9, 2021)
+    ArrowGlacier,
+    /// Gray Glacier (June 30, 2022)
+    GrayGlacier,
+    /// Paris (September 15, 2022)
+    Paris,
+    /// Shanghai (April 12, 2023)
+    Shanghai,
+    /// Cancun (March 13, 2024)
+    Cancun,
+    /// Prague (May 7, 2025)
+    Prague,
+    /// Osaka (scheduled)
+    #[cfg(feature = "unstable-forks")]
+    Osaka,
+}
+
+/// Consensus-layer (beacon chain) forks, in chronological order
+#[derive(Clone, Copy, Debug, PartialOrd, Ord, PartialEq, Eq, Hash)]
+pub enum ConsensusFork {
+    /// Altair (October 27, 2021)
+    Altair,
+    /// Bellatrix (September 6, 2022)
+    Bellatrix,
+    /// Capella (April 12, 2023)
+    Capella,
+    /// Deneb (March 13, 2024)
+    Deneb,
+    /// Electra (May 7, 2025)
+    Electra,
+}
+
+/// A mainnet upgrade on either layer
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NetworkUpgrade {
+    /// Execution-layer fork
+    Execution(ExecutionFork),
+    /// Consensus-layer fork
+    Consensus(ConsensusFork),
+}
+
+impl ExecutionFork {
+    /// Execution forks whose opcode table differs from the previous one
+    ///
+    /// Paris only redefines DIFFICULTY as PREVRANDAO, which leaves the table unchanged.
+    pub const EVM_CHANGES: &'static [ExecutionFork] = &[
+        Self::Frontier,
+        Self::Homestead,
+        Self::TangerineWhistle,
+        Self::SpuriousDragon,
+        Self::Byzantium,
+        Self::Constantinople,
+        Self::Petersburg,
+        Self::Istanbul,
+        Self::Berlin,
+        Self::London,
+        Self::Shanghai,
+        Self::Cancun,
+        Self::Prague,
+        #[cfg(feature = "unstable-forks")]
+        Self::Osaka,
+    ];
+
+    /// Check if this fork changes the EVM
+    pub fn changes_evm(self) -> bool {
+        Self::EVM_CHANGES.contains(&self)
+    }
+
+    /// The last EVM-changing fork at or before this one
+    pub fn evm_rules(self) -> ExecutionFork {
+        Self::EVM_CHANGES
+            .iter()
+            .rev()
+            .copied()
+            .find(|fork| *fork <= self)
+            .unwrap_or(Self::Frontier)
+    }
+
+    /// The EVM-changing fork before this fork's EVM rules, `None` for Frontier
+    pub fn previous_evm_fork(self) -> Option<ExecutionFork> {
+        let rules = self.evm_rules();
+        Self::EVM_CHANGES
+            .iter()
+            .rev()
+            .copied()
+            .find(|fork| *fork < rules)
+    }
+}
+
+impl ConsensusFork {
+    /// The execution fork that was active when this fork activated
+    pub fn execution_fork(self) -> ExecutionFork {
+        match self {
+            Self::Altair => ExecutionFork::London,
+            Self::Bellatrix => ExecutionFork::GrayGlacier,
+            Self::Capella => ExecutionFork::Shanghai,
+            Self::Deneb => ExecutionFork::Cancun,
+            Self::Electra => ExecutionFork::Prague,
+        }
+    }
+}
+
+impl NetworkUpgrade {
+    /// The execution fork whose EVM rules apply after this upgrade
+    pub fn evm_rules(self) -> ExecutionFork {
+        match self {
+            Self::Execution(fork) => fork.evm_rules(),
+            Self::Consensus(fork) => fork.execution_fork().evm_rules(),
+        }
+    }
+}
+
+impl From<ExecutionFork> for Fork {
+    fn from(fork: ExecutionFork) -> Self {
+        match fork {
+            ExecutionFork::Frontier => Fork::Frontier,
+            ExecutionFork::IceAge => Fork::IceAge,
+            ExecutionFork::Homestead => Fork::Homestead,
+            ExecutionFork::DaoFork => Fork::DaoFork,
+            ExecutionFork::TangerineWhistle => Fork::TangerineWhistle,
+            ExecutionFork::SpuriousDragon => Fork::SpuriousDragon,
+            ExecutionFork::Byzantium => Fork::Byzantium,
+            ExecutionFork::Constantinople => Fork::Constantinople,
+            ExecutionFork::Petersburg => Fork::Petersburg,
+            ExecutionFork::Istanbul => Fork::Istanbul,
+            ExecutionFork::MuirGlacier => Fork::MuirGlacier,
+            ExecutionFork::Berlin => Fork::Berlin,
+            ExecutionFork::London => Fork::London,
+            ExecutionFork::ArrowGlacier => Fork::ArrowGlacier,
+            ExecutionFork::GrayGlacier => Fork::GrayGlacier,
+            ExecutionFork::Paris => Fork::Paris,
+            ExecutionFork::Shanghai => Fork::Shanghai,
+            ExecutionFork::Cancun => Fork::Cancun,
+            ExecutionFork::Prague => Fork::Prague,
+            #[cfg(feature = "unstable-forks")]
+            ExecutionFork::Osaka => Fork::Osaka,
+        }
+    }
+}
+
+impl From<ConsensusFork> for Fork {
+    fn from(fork: ConsensusFork) -> Self {
+        match fork {
+            ConsensusFork::Altair => Fork::Altair,
+            ConsensusFork::Bellatrix => Fork::Bellatrix,
+            ConsensusFork::Capella => Fork::Capella,
+            ConsensusFork::Deneb => Fork::Deneb,
+            ConsensusFork::Electra => Fork::Electra,
+        }
+    }
+}
+
+impl From<NetworkUpgrade> for Fork {
+    fn from(upgrade: NetworkUpgrade) -> Self {
+        match upgrade {
+            NetworkUpgrade::Execution(fork) => fork.into(),
+            NetworkUpgrade::Consensus(fork) => fork.into(),
+        }
+    }
+}
+
+impl From<Fork> for NetworkUpgrade {
+    fn from(fork: Fork) -> Self {
+        let execution = match fork {
+            Fork::Altair => return Self::Consensus(ConsensusFork::Altair),
+            Fork::Bellatrix => return Self::Consensus(ConsensusFork::Bellatrix),
+            Fork::Capella => return Self::Consensus(ConsensusFork::Capella),
+            Fork::Deneb => return Self::Consensus(ConsensusFork::Deneb),
+            Fork::Electra => return Self::Consensus(ConsensusFork::Electra),
+            Fork::Frontier => ExecutionFork::Frontier,
+            Fork::IceAge => ExecutionFork::IceAge,
+            Fork::Homestead => ExecutionFork::Homestead,
+            Fork::DaoFork => ExecutionFork::DaoFork,
+            Fork::TangerineWhistle => ExecutionFork::TangerineWhistle,
+            Fork::SpuriousDragon => ExecutionFork::SpuriousDragon,
+            Fork::Byzantium => ExecutionFork::Byzantium,
+            Fork::Constantinople => ExecutionFork::Constantinople,
+            Fork::Petersburg => ExecutionFork::Petersburg,
+            Fork::Istanbul => ExecutionFork::Istanbul,
+            Fork::MuirGlacier => ExecutionFork::MuirGlacier,
+            Fork::Berlin => ExecutionFork::Berlin,
+            Fork::London => ExecutionFork::London,
+            Fork::ArrowGlacier => ExecutionFork::ArrowGlacier,
+            Fork::GrayGlacier => ExecutionFork::GrayGlacier,
+            Fork::Paris => ExecutionFork::Paris,
+            Fork::Shanghai => ExecutionFork::Shanghai,
+            Fork::Cancun => ExecutionFork::Cancun,
+            Fork::Prague => ExecutionFork::Prague,
+            #[cfg(feature = "unstable-forks")]
+            Fork::Osaka => ExecutionFork::Osaka,
+        };
+        Self::Execution(execution)
+    }
+}
+
+impl TryFrom<Fork> for ExecutionFork {
+    type Error = String;
+
+    fn try_from(fork: Fork) -> Result<Self, String> {
+        match NetworkUpgrade::from(fork) {
+            NetworkUpgrade::Execution(fork) => Ok(fork),
+            NetworkUpgrade::Consensus(_) => Err(format!("{fork:?} is a consensus-layer fork")),
+        }
+    }
+}
+
+impl TryFrom<Fork> for ConsensusFork {
+    type Error = String;
+
+    fn try_from(fork: Fork) -> Result<Self, String> {
+        match NetworkUpgrade::from(fork) {
+            NetworkUpgrade::Consensus(fork) => Ok(fork),
+            NetworkUpgrade::Execution(_) => Err(format!("{fork:?} is an execution-layer fork")),
+        }
+    }
+}
+
+impl Fork {
+    /// Every fork of both layers, in chronological order
+    pub const ALL: &'static [Fork] = &[
+        Fork::Frontier,
+        Fork::IceAge,
+        Fork::Homestead,
+        Fork::DaoFork,
+        Fork::TangerineWhistle,
+        Fork::SpuriousDragon,
+        Fork::Byzantium,
+        Fork::Constantinople,
+        Fork::Petersburg,
+        Fork::Istanbul,
+        Fork::MuirGlacier,
+        Fork::Berlin,
+        Fork::London,
+        Fork::Altair,
+        Fork::ArrowGlacier,
+        Fork::GrayGlacier,
+        Fork::Bellatrix,
+        Fork::Paris,
+        Fork::Shanghai,
+        Fork::Capella,
+        Fork::Cancun,
+        Fork::Deneb,
+        Fork::Prague,
+        Fork::Electra,
+        #[cfg(feature = "unstable-forks")]
+        Fork::Osaka,
+    ];
+
+    /// Forks whose activation is not final; their opcode tables may still change
+    pub const SCHEDULED: &'static [Fork] = &[
+        #[cfg(feature = "unstable-forks")]
+        Fork::Osaka,
+    ];
+
+    /// The fork after this one, `None` for the latest
+    pub fn next(self) -> Option<Fork> {
+        Self::ALL.iter().copied().find(|fork| *fork > self)
+    }
+
+    /// The fork before this one, `None` for Frontier
+    pub fn previous(self) -> Option<Fork> {
+        Self::ALL.iter().rev().copied().find(|fork| *fork < self)
+    }
+
+    /// Execution-layer forks in chronological order
+    pub fn execution_forks() -> impl Iterator<Item = Fork> {
+        Self::ALL
+            .iter()
+            .copied()
+            .filter(|fork| ExecutionFork::try_from(*fork).is_ok())
+    }
+
+    /// Check if this fork is scheduled rather than activated
+    pub fn is_scheduled(self) -> bool {
+        Self::SCHEDULED.contains(&self)
+    }
+
+    /// Check if this fork changes the EVM
+    pub fn changes_evm(self) -> bool {
+        ExecutionFork::try_from(self).is_ok_and(ExecutionFork::changes_evm)
+    }
+
+    /// The execution fork whose EVM rules apply at this fork
+    pub fn evm_rules(self) -> ExecutionFork {
+        NetworkUpgrade::from(self).evm_rules()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversions_round_trip() {
+        for upgrade in [
+            NetworkUpgrade::Execution(ExecutionFork::MuirGlacier),
+            NetworkUpgrade::Consensus(ConsensusFork::Capella),
+        ] {
+            assert_eq!(NetworkUpgrade::from(Fork::from(upgrade)), upgrade);
+        }
+        assert!(ExecutionFork::try_from(Fork::Deneb).is_err());
+        assert_eq!(
+            ConsensusFork::try_from(Fork::Deneb),
+            Ok(ConsensusFork::Deneb)
+        );
+    }
+
+    #[test]
+    fn test_evm_rules_skip_non_evm_forks() {
+        assert_eq!(Fork::MuirGlacier.evm_rules(), ExecutionFork::Istanbul);
+        assert_eq!(Fork::Deneb.evm_rules(), ExecutionFork::Cancun);
+        assert_eq!(Fork::Paris.evm_rules(), ExecutionFork::London);
+        assert_eq!(
+            ExecutionFork::Berlin.previous_evm_fork(),
+            Some(ExecutionFork::Istanbul)
+        );
+        assert_eq!(ExecutionFork::IceAge.previous_evm_fork(), None);
+    }
+
+    #[test]
+    fn test_fork_navigation() {
+        assert!(Fork::ALL.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(Fork::London.next(), Some(Fork::Altair));
+        assert_eq!(Fork::Altair.previous(), Some(Fork::London));
+        assert_eq!(Fork::Frontier.previous(), None);
+        assert_eq!(Fork::ALL.last().and_then(|fork| fork.next()), None);
+        assert_eq!(Fork::execution_forks().count(), Fork::ALL.len() - 5);
+        assert!(Fork::execution_forks().all(|fork| Fork::from(fork.evm_rules()) <= fork));
+        assert!(Fork::Petersburg.changes_evm() && !Fork::MuirGlacier.changes_evm());
+    }
+
+    #[cfg(feature = "unstable-forks")]
+    #[test]
+    fn test_scheduled_fork() {
+        use crate::OpcodeRegistry;
+
+        assert!(Fork::Osaka.is_scheduled() && !Fork::Prague.is_scheduled());
+        let registry = OpcodeRegistry::new();
+        let clz = &registry.get_opcodes(Fork::Osaka)[&0x1e];
+        assert!(clz.is_scheduled());
+        assert!(!registry.is_opcode_available(Fork::Prague, 0x1e));
+        assert!(!registry.get_opcodes(Fork::Osaka)[&0x01].is_scheduled());
+    }
+}
@@ -1,7 +1,15 @@
 //! Validation and verification system for opcode consistency with gas analysis integration
 
-use crate::{gas::GasAnalysis, traits::OpcodeAnalysis, Fork, OpcodeRegistry};
-use std::collections::{HashMap, HashSet};
+use crate::rules::{
+    Push0AvailabilityRule, SequenceValidator, StaticJumpTargetRule, UnreachableCodeRule,
+};
+use crate::{
+    gas::{GasAnalysis, GasOptimizationAdvisor},
+    traits::OpcodeAnalysis,
+    ExecutionFork, Fork, OpcodeRegistry,
+};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
 
 /// Validate the entire opcode registry for consistency
 pub fn validate_registry(registry: &OpcodeRegistry) -> Result<(), Vec<String>> {
@@ -22,6 +30,41 @@ pub fn validate_registry(registry: &OpcodeRegistry) -> Result<(), Vec<String>> {
     }
 }
 
+/// Check the control-flow structure of bytecode
+///
+/// The bytecode is decoded first, so PUSH immediates are never treated as instructions.
+/// Rules, checked in this order:
+/// 1. Unreachable code: the instruction following STOP, RETURN, REVERT, INVALID,
+///    SELFDESTRUCT or JUMP must be a JUMPDEST. Trailing data found by
+///    [`detect_data_regions`](crate::disasm::detect_data_regions) is exempt.
+/// 2. Static jump targets: a PUSH directly before JUMP or JUMPI must push the pc of a
+///    JUMPDEST instruction, not an offset inside PUSH data or another instruction.
+pub fn validate_sequence_structure(bytecode: &[u8], fork: Fork) -> Result<(), String> {
+    SequenceValidator::empty()
+        .with_rule(UnreachableCodeRule)
+        .with_rule(StaticJumpTargetRule)
+        .validate(bytecode, fork)
+        .map_err(|violations| violations[0].message.clone())
+}
+
+/// Check that bytecode only uses PUSH0 on forks that support it, returning the code to deploy
+///
+/// Before Shanghai, PUSH0 is rejected with an error naming the required fork. With
+/// `lower_push0` set it is rewritten as `PUSH1 0x00` instead, see
+/// [`lower_push0`](crate::patch::lower_push0).
+pub fn check_push0_compatibility(
+    bytecode: &[u8],
+    fork: Fork,
+    lower_push0: bool,
+) -> Result<Vec<u8>, String> {
+    let validator = SequenceValidator::empty().with_rule(Push0AvailabilityRule);
+    match validator.validate(bytecode, fork) {
+        Ok(()) => Ok(bytecode.to_vec()),
+        Err(_) if lower_push0 => Ok(crate::patch::lower_push0(bytecode)?.bytecode),
+        Err(violations) => Err(violations[0].message.clone()),
+    }
+}
+
 /// Ensure no opcode is defined twice in the same fork
 fn validate_opcode_uniqueness(registry: &OpcodeRegistry) -> Vec<String> {
     let mut errors = Vec::new();
@@ -45,17 +88,10 @@ fn validate_opcode_uniqueness(registry: &OpcodeRegistry) -> Vec<String> {
 fn validate_fork_inheritance(registry: &OpcodeRegistry) -> Vec<String> {
     let mut errors = Vec::new();
 
-    let fork_order = [
-        Fork::Frontier,
-        Fork::Homestead,
-        Fork::Byzantium,
-        Fork::Constantinople,
-        Fork::Istanbul,
-        Fork::Berlin,
-        Fork::London,
-        Fork::Shanghai,
-        Fork::Cancun,
-    ];
+    let fork_order: Vec<Fork> = ExecutionFork::EVM_CHANGES
+        .iter()
+        .map(|&fork| Fork::from(fork))
+        .collect();
 
     for i in 1..fork_order.len() {
         let current_fork = fork_order[i];
@@ -105,7 +141,7 @@ fn validate_historical_accuracy(registry: &OpcodeRegistry) -> Vec<String> {
     ];
 
     for (opcode_byte, expected_fork, name) in &known_introductions {
-        let all_opcodes = registry.get_opcodes(Fork::Cancun); // Get from latest fork
+        let all_opcodes = registry.get_opcodes(Fork::Prague); // Get from latest fork
 
         if let Some(metadata) = all_opcodes.get(opcode_byte) {
             if metadata.introduced_in != *expected_fork {
@@ -226,7 +262,7 @@ fn validate_stack_consistency(registry: &OpcodeRegistry) -> Vec<String> {
 }
 
 /// Validate integration with gas analysis system
-fn validate_gas_analysis_integration(_registry: &OpcodeRegistry) -> Vec<String> {
+fn validate_gas_analysis_integration(registry: &OpcodeRegistry) -> Vec<String> {
     let mut errors = Vec::new();
 
     // Test gas analysis on a simple sequence for each fork
@@ -238,9 +274,10 @@ fn validate_gas_analysis_integration(_registry: &OpcodeRegistry) -> Vec<String>
         Fork::London,
         Fork::Shanghai,
         Fork::Cancun,
+        Fork::Prague,
     ] {
         match std::panic::catch_unwind(|| {
-            let analysis = OpcodeRegistry::analyze_gas_usage(&test_sequence, fork);
+            let analysis = registry.analyze_gas(&test_sequence, fork);
 
             // Basic sanity checks
             if analysis.total_gas < 21000 {
@@ -282,6 +319,20 @@ pub fn validate_known_gas_changes(registry: &OpcodeRegistry) -> Vec<String> {
 
     // Some known gas cost changes (this would be a comprehensive list)
     let known_changes = [
+        KnownGasChanges {
+            opcode: 0x54, // SLOAD
+            fork: Fork::TangerineWhistle,
+            old_cost: 50,
+            new_cost: 200,
+            reason: "EIP-150: Gas cost changes for IO-heavy operations",
+        },
+        KnownGasChanges {
+            opcode: 0x3b, // EXTCODESIZE
+            fork: Fork::TangerineWhistle,
+            old_cost: 20,
+            new_cost: 700,
+            reason: "EIP-150: Gas cost changes for IO-heavy operations",
+        },
         KnownGasChanges {
             opcode: 0x54, // SLOAD
             fork: Fork::Berlin,
@@ -291,8 +342,15 @@ pub fn validate_known_gas_changes(registry: &OpcodeRegistry) -> Vec<String> {
         },
         KnownGasChanges {
             opcode: 0x31, // BALANCE
-            fork: Fork::Berlin,
+            fork: Fork::Istanbul,
             old_cost: 400,
+            new_cost: 700,
+            reason: "EIP-1884: Repricing for trie-size-dependent opcodes",
+        },
+        KnownGasChanges {
+            opcode: 0x31, // BALANCE
+            fork: Fork::Berlin,
+            old_cost: 700,
             new_cost: 2600,
             reason: "EIP-2929: Gas cost increases for state access opcodes",
         },
@@ -312,8 +370,15 @@ pub fn validate_known_gas_changes(registry: &OpcodeRegistry) -> Vec<String> {
         },
         KnownGasChanges {
             opcode: 0x3f, // EXTCODEHASH
-            fork: Fork::Berlin,
+            fork: Fork::Istanbul,
             old_cost: 400,
+            new_cost: 700,
+            reason: "EIP-1884: Repricing for trie-size-dependent opcodes",
+        },
+        KnownGasChanges {
+            opcode: 0x3f, // EXTCODEHASH
+            fork: Fork::Berlin,
+            old_cost: 700,
             new_cost: 2600,
             reason: "EIP-2929: Gas cost increases for state access opcodes",
         },
@@ -354,32 +419,98 @@ pub fn validate_known_gas_changes(registry: &OpcodeRegistry) -> Vec<String> {
     errors
 }
 
-/// Get the fork that immediately precedes the given fork
-fn get_previous_fork(fork: Fork) -> Fork {
-    match fork {
-        Fork::IceAge => Fork::Frontier,
-        Fork::Homestead => Fork::IceAge,
-        Fork::DaoFork => Fork::Homestead,
-        Fork::TangerineWhistle => Fork::DaoFork,
-        Fork::SpuriousDragon => Fork::TangerineWhistle,
-        Fork::Byzantium => Fork::SpuriousDragon,
-        Fork::Constantinople => Fork::Byzantium,
-        Fork::Petersburg => Fork::Constantinople,
-        Fork::Istanbul => Fork::Petersburg,
-        Fork::MuirGlacier => Fork::Istanbul,
-        Fork::Berlin => Fork::MuirGlacier,
-        Fork::London => Fork::Berlin,
-        Fork::Altair => Fork::London,
-        Fork::ArrowGlacier => Fork::Altair,
-        Fork::GrayGlacier => Fork::ArrowGlacier,
-        Fork::Bellatrix => Fork::GrayGlacier,
-        Fork::Paris => Fork::Bellatrix,
-        Fork::Shanghai => Fork::Paris,
-        Fork::Capella => Fork::Shanghai,
-        Fork::Cancun => Fork::Capella,
-        Fork::Deneb => Fork::Cancun,
-        Fork::Frontier => Fork::Frontier, // No previous fork
+/// An EIP that changed the base gas cost of existing opcodes
+struct RepricingEip {
+    /// EIP number, as listed in [`ForkInfo::eips`](crate::ForkInfo::eips)
+    eip: u32,
+    /// Repriced opcodes and their base cost from this EIP on
+    opcodes: &'static [(u8, u16)],
+}
+
+/// Repricing EIPs; the fork each applies at comes from the fork's EIP list
+const REPRICING_EIPS: &[RepricingEip] = &[
+    // Gas cost changes for IO-heavy operations
+    RepricingEip {
+        eip: 150,
+        opcodes: &[
+            (0x31, 400),
+            (0x3b, 700),
+            (0x3c, 700),
+            (0x54, 200),
+            (0xf1, 700),
+            (0xf2, 700),
+            (0xf4, 700),
+            (0xff, 5000),
+        ],
+    },
+    // Repricing for trie-size-dependent opcodes
+    RepricingEip {
+        eip: 1884,
+        opcodes: &[(0x31, 700), (0x3f, 700), (0x54, 800)],
+    },
+    // Gas cost increases for state access opcodes
+    RepricingEip {
+        eip: 2929,
+        opcodes: &[
+            (0x31, 2600),
+            (0x3b, 2600),
+            (0x3c, 2600),
+            (0x3f, 2600),
+            (0x54, 2100),
+            (0xf1, 2600),
+            (0xf2, 2600),
+            (0xf4, 2600),
+            (0xfa, 2600),
+        ],
+    },
+];
+
+/// Find opcodes whose gas history has no entry, or a wrong cost, for a fork that repriced them
+///
+/// Without the entry, the cost at that fork and the forks up to the next entry falls back
+/// to an older or newer price.
+pub fn validate_gas_history_gaps(registry: &OpcodeRegistry) -> Vec<String> {
+    let mut errors = Vec::new();
+    let forks: Vec<Fork> = registry.opcodes.keys().map(|f| Fork::from(*f)).collect();
+
+    for (table_fork, opcodes) in &registry.opcodes {
+        let table_fork = Fork::from(*table_fork);
+        for &fork in forks.iter().filter(|fork| **fork <= table_fork) {
+            let repricings = REPRICING_EIPS
+                .iter()
+                .filter(|repricing| fork.info().eips.contains(&repricing.eip));
+            for repricing in repricings {
+                for &(opcode, cost) in repricing.opcodes {
+                    let Some(metadata) = opcodes.get(&opcode) else {
+                        continue;
+                    };
+                    if metadata.introduced_in >= fork {
+                        continue;
+                    }
+                    match metadata.gas_history.iter().find(|(f, _)| *f == fork) {
+                        None => errors.push(format!(
+                            "Gas history for opcode 0x{:02x} ({}) in fork {:?} has no entry for {:?} (EIP-{})",
+                            opcode, metadata.name, table_fork, fork, repricing.eip
+                        )),
+                        Some(&(_, found)) if found != cost => errors.push(format!(
+                            "Gas history for opcode 0x{:02x} ({}) in fork {:?} prices {:?} at {}, EIP-{} sets {}",
+                            opcode, metadata.name, table_fork, fork, found, repricing.eip, cost
+                        )),
+                        Some(_) => {}
+                    }
+                }
+            }
+        }
     }
+
+    errors
+}
+
+/// Get the last fork before the given one that changed the EVM
+fn get_previous_fork(fork: Fork) -> Fork {
+    fork.evm_rules()
+        .previous_evm_fork()
+        .map_or(Fork::Frontier, Fork::from)
 }
 
 /// Check for common validation patterns and issues
@@ -399,6 +530,7 @@ pub fn run_comprehensive_validation(registry: &OpcodeRegistry) -> ValidationRepo
     );
     report.add_errors("Stack Consistency", validate_stack_consistency(registry));
     report.add_errors("Known Gas Changes", validate_known_gas_changes(registry));
+    report.add_errors("Gas History Gaps", validate_gas_history_gaps(registry));
     report.add_errors(
         "Gas Analysis Integration",
         validate_gas_analysis_integration(registry),
@@ -416,11 +548,11 @@ pub fn run_comprehensive_validation(registry: &OpcodeRegistry) -> ValidationRepo
 #[derive(Debug, Default)]
 pub struct ValidationReport {
     /// Critical errors that must be fixed
-    pub errors: HashMap<String, Vec<String>>,
+    pub errors: BTreeMap<String, Vec<String>>,
     /// Warnings that should be addressed
-    pub warnings: HashMap<String, Vec<String>>,
+    pub warnings: BTreeMap<String, Vec<String>>,
     /// Informational messages
-    pub info: HashMap<String, Vec<String>>,
+    pub info: BTreeMap<String, Vec<String>>,
 }
 
 impl ValidationReport {
@@ -455,43 +587,52 @@ impl ValidationReport {
         !self.errors.is_empty()
     }
 
-    /// Print summary of validation report
+    /// Print summary of validation report to stdout
+    ///
+    /// The same text is available through the [`Display`](fmt::Display) implementation.
     pub fn print_summary(&self) {
-        println!("=== EOT Validation Report ===");
+        print!("{self}");
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "=== EOT Validation Report ===")?;
 
         if !self.errors.is_empty() {
-            println!("\n❌ ERRORS:");
+            writeln!(f, "\n❌ ERRORS:")?;
             for (category, errors) in &self.errors {
-                println!("  {category}:");
+                writeln!(f, "  {category}:")?;
                 for error in errors {
-                    println!("    - {error}");
+                    writeln!(f, "    - {error}")?;
                 }
             }
         }
 
         if !self.warnings.is_empty() {
-            println!("\n⚠️  WARNINGS:");
+            writeln!(f, "\n⚠️  WARNINGS:")?;
             for (category, warnings) in &self.warnings {
-                println!("  {category}:");
+                writeln!(f, "  {category}:")?;
                 for warning in warnings {
-                    println!("    - {warning}");
+                    writeln!(f, "    - {warning}")?;
                 }
             }
         }
 
         if !self.info.is_empty() {
-            println!("\nℹ️  INFO:");
+            writeln!(f, "\nℹ️  INFO:")?;
             for (category, info_items) in &self.info {
-                println!("  {category}:");
+                writeln!(f, "  {category}:")?;
                 for info in info_items {
-                    println!("    {info}");
+                    writeln!(f, "    {info}")?;
                 }
             }
         }
 
         if self.errors.is_empty() && self.warnings.is_empty() {
-            println!("\n✅ All validations passed!");
+            writeln!(f, "\n✅ All validations passed!")?;
         }
+        Ok(())
     }
 }
 
@@ -519,7 +660,7 @@ fn generate_coverage_info(registry: &OpcodeRegistry) -> Vec<String> {
     let mut info = Vec::new();
 
     let total_possible_opcodes = 256;
-    let latest_opcodes = registry.get_opcodes(Fork::Cancun);
+    let latest_opcodes = registry.get_opcodes(Fork::Prague);
     let coverage_percentage = (latest_opcodes.len() * 100) / total_possible_opcodes;
 
     info.push(format!(
@@ -530,17 +671,10 @@ fn generate_coverage_info(registry: &OpcodeRegistry) -> Vec<String> {
     ));
 
     // Count opcodes by fork
-    for fork in [
-        Fork::Frontier,
-        Fork::Homestead,
-        Fork::Byzantium,
-        Fork::Constantinople,
-        Fork::Istanbul,
-        Fork::Berlin,
-        Fork::London,
-        Fork::Shanghai,
-        Fork::Cancun,
-    ] {
+    for fork in ExecutionFork::EVM_CHANGES
+        .iter()
+        .map(|&fork| Fork::from(fork))
+    {
         let opcodes = registry.get_opcodes(fork);
         info.push(format!("{:?}: {} opcodes", fork, opcodes.len()));
     }
@@ -549,7 +683,7 @@ fn generate_coverage_info(registry: &OpcodeRegistry) -> Vec<String> {
 }
 
 /// Generate gas analysis system information
-fn generate_gas_analysis_info(_registry: &OpcodeRegistry) -> Vec<String> {
+fn generate_gas_analysis_info(registry: &OpcodeRegistry) -> Vec<String> {
     let mut info = Vec::new();
 
     // Test gas analysis capabilities
@@ -561,7 +695,7 @@ fn generate_gas_analysis_info(_registry: &OpcodeRegistry) -> Vec<String> {
     ];
 
     for (sequence, description) in test_sequences {
-        let analysis = OpcodeRegistry::analyze_gas_usage(&sequence, Fork::London);
+        let analysis = registry.analyze_gas(&sequence, Fork::London);
         info.push(format!(
             "{}: {} gas (efficiency: {}%)",
             description,
@@ -611,31 +745,56 @@ fn generate_gas_analysis_info(_registry: &OpcodeRegistry) -> Vec<String> {
     info
 }
 
+/// Sequence analysis with the registry's own tables
+impl OpcodeRegistry {
+    /// Analyze gas usage patterns for a sequence of opcodes
+    pub fn analyze_gas(&self, opcodes: &[u8], fork: Fork) -> GasAnalysis {
+        crate::gas::GasAnalyzer::analyze_gas_usage_in(self, opcodes, fork)
+    }
+
+    /// Check a sequence against the standard rules, returning the first violation
+    ///
+    /// See [`GasAnalyzer::validate_opcode_sequence`](crate::gas::GasAnalyzer::validate_opcode_sequence)
+    /// for the rules.
+    pub fn validate_sequence(&self, opcodes: &[u8], fork: Fork) -> Result<(), String> {
+        crate::rules::SequenceValidator::standard()
+            .validate_with_registry(self, opcodes, fork)
+            .map_err(|violations| violations[0].message.clone())
+    }
+
+    /// Optimization suggestions for a sequence of opcodes, including fork-specific ones
+    pub fn optimization_suggestions(&self, opcodes: &[u8], fork: Fork) -> Vec<String> {
+        let analysis = self.analyze_gas(opcodes, fork);
+        let mut suggestions = analysis.get_optimization_recommendations();
+        suggestions.extend(GasOptimizationAdvisor::analyze_pattern_of(
+            &analysis, opcodes, fork,
+        ));
+        suggestions
+    }
+
+    /// Estimate gas savings from proposed optimizations
+    pub fn gas_savings(&self, opcodes: &[u8], fork: Fork) -> u64 {
+        self.analyze_gas(opcodes, fork)
+            .estimate_optimization_savings()
+    }
+}
+
 /// Extended implementation of OpcodeAnalysis for the registry
+#[allow(deprecated)]
 impl OpcodeAnalysis for OpcodeRegistry {
     fn analyze_gas_usage(opcodes: &[u8], fork: Fork) -> GasAnalysis {
-        use crate::gas::GasAnalyzer;
-        GasAnalyzer::analyze_gas_usage(opcodes, fork)
+        OpcodeRegistry::new().analyze_gas(opcodes, fork)
     }
 
     fn validate_opcode_sequence(opcodes: &[u8], fork: Fork) -> Result<(), String> {
-        use crate::gas::GasAnalyzer;
-        GasAnalyzer::validate_opcode_sequence(opcodes, fork)
+        OpcodeRegistry::new().validate_sequence(opcodes, fork)
     }
 
     fn get_optimization_suggestions(opcodes: &[u8], fork: Fork) -> Vec<String> {
-        let analysis = Self::analyze_gas_usage(opcodes, fork);
-        let mut suggestions = analysis.get_optimization_recommendations();
-
-        // Add fork-specific suggestions
-        use crate::gas::GasOptimizationAdvisor;
-        suggestions.extend(GasOptimizationAdvisor::analyze_pattern(opcodes, fork));
-
-        suggestions
+        OpcodeRegistry::new().optimization_suggestions(opcodes, fork)
     }
 
     fn estimate_gas_savings(opcodes: &[u8], fork: Fork) -> u64 {
-        let analysis = Self::analyze_gas_usage(opcodes, fork);
-        analysis.estimate_optimization_savings()
+        OpcodeRegistry::new().gas_savings(opcodes, fork)
     }
 }
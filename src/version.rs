@@ -0,0 +1,159 @@
+//! Versioning of the bundled opcode dataset
+//!
+//! The opcode tables evolve independently of the crate's public API: a patch
+//! release may fix a gas cost or add a fork without changing any signatures.
+//! [`DATA_VERSION`] identifies the exact dataset compiled into the binary, and
+//! [`CHANGELOG`] records what changed between dataset versions so stored
+//! analyses can be traced back to (and migrated from) the tables that produced them.
+
+use crate::Fork;
+
+/// Version of the opcode dataset bundled with this build
+///
+/// Bumped every time a table entry, gas cost, gas history or fork
+/// registration changes. Always equal to the version of the last
+/// [`CHANGELOG`] entry.
+pub const DATA_VERSION: u32 = 6;
+
+/// A single entry in the dataset changelog
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DataChange {
+    /// Dataset version introduced by this change
+    pub version: u32,
+    /// Short summary of what changed in the tables
+    pub summary: &'static str,
+    /// Forks whose opcode tables are affected
+    pub forks: &'static [Fork],
+    /// Guidance for consumers holding results produced by the previous version
+    pub migration: &'static str,
+}
+
+/// Chronological changelog of the opcode dataset, oldest first
+pub const CHANGELOG: &[DataChange] = &[
+    DataChange {
+        version: 1,
+        summary: "Initial opcode tables for Frontier through Cancun",
+        forks: &[
+            Fork::Frontier,
+            Fork::Homestead,
+            Fork::Byzantium,
+            Fork::Constantinople,
+            Fork::Istanbul,
+            Fork::Berlin,
+            Fork::London,
+            Fork::Shanghai,
+            Fork::Cancun,
+        ],
+        migration: "None, first tracked dataset",
+    },
+    DataChange {
+        version: 2,
+        summary: "Add Prague (Pectra) opcode table",
+        forks: &[Fork::Prague],
+        migration: "None, Prague was not available before",
+    },
+    DataChange {
+        version: 3,
+        summary: "Add Tangerine Whistle, Spurious Dragon and Petersburg tables and date the EIP-150 repricings to Tangerine Whistle",
+        forks: &[
+            Fork::Frontier,
+            Fork::Homestead,
+            Fork::TangerineWhistle,
+            Fork::SpuriousDragon,
+            Fork::Byzantium,
+            Fork::Constantinople,
+            Fork::Petersburg,
+        ],
+        migration: "Recompute pre-Istanbul analyses: Byzantium and Constantinople used pre-EIP-150 costs for state access and calls, and SELFDESTRUCT cost 5000 before Tangerine Whistle",
+    },
+    DataChange {
+        version: 4,
+        summary: "Record the EIP-1884 BALANCE repricing to 700 gas at Istanbul",
+        forks: &[
+            Fork::Istanbul,
+            Fork::Berlin,
+            Fork::London,
+            Fork::Shanghai,
+            Fork::Cancun,
+            Fork::Prague,
+        ],
+        migration: "Recompute Istanbul analyses that use BALANCE, which were priced at 400 gas",
+    },
+    DataChange {
+        version: 5,
+        summary: "Price EXTCODEHASH at 400 gas from Constantinople (EIP-1052) and 700 gas from Istanbul (EIP-1884)",
+        forks: &[
+            Fork::Constantinople,
+            Fork::Petersburg,
+            Fork::Istanbul,
+            Fork::Berlin,
+            Fork::London,
+            Fork::Shanghai,
+            Fork::Cancun,
+            Fork::Prague,
+        ],
+        migration: "Recompute Constantinople through Istanbul analyses that use EXTCODEHASH, which were priced at 100 gas",
+    },
+    DataChange {
+        version: 6,
+        summary: "Regenerate every table from generate_forks.py: the Istanbul table prices the CALL family at the EIP-150 700 gas and STATICCALL records its 700 gas from Byzantium",
+        forks: &[
+            Fork::Byzantium,
+            Fork::Constantinople,
+            Fork::Petersburg,
+            Fork::Istanbul,
+        ],
+        migration: "Recompute Istanbul analyses that read the base cost of CALL, CALLCODE, DELEGATECALL or STATICCALL, which were 100 or 40 gas",
+    },
+];
+
+/// Get the changelog entry for a specific dataset version
+pub fn change_for_version(version: u32) -> Option<&'static DataChange> {
+    CHANGELOG.iter().find(|change| change.version == version)
+}
+
+/// Get all changes made after the given dataset version, oldest first
+///
+/// Useful to decide whether an analysis stored with `version` needs to be
+/// recomputed against the current tables.
+pub fn changes_since(version: u32) -> impl Iterator<Item = &'static DataChange> {
+    CHANGELOG
+        .iter()
+        .filter(move |change| change.version > version)
+}
+
+/// Get all changes affecting the opcode table of a specific fork
+pub fn changes_for_fork(fork: Fork) -> impl Iterator<Item = &'static DataChange> {
+    CHANGELOG
+        .iter()
+        .filter(move |change| change.forks.contains(&fork))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changelog_is_chronological() {
+        for window in CHANGELOG.windows(2) {
+            assert!(window[0].version < window[1].version);
+        }
+    }
+
+    #[test]
+    fn test_data_version_matches_changelog() {
+        assert_eq!(CHANGELOG.last().map(|c| c.version), Some(DATA_VERSION));
+        assert!(change_for_version(DATA_VERSION).is_some());
+    }
+
+    #[test]
+    fn test_changes_since() {
+        assert_eq!(changes_since(0).count(), CHANGELOG.len());
+        assert_eq!(changes_since(DATA_VERSION).count(), 0);
+    }
+
+    #[test]
+    fn test_changes_for_fork() {
+        assert!(changes_for_fork(Fork::Cancun).count() > 0);
+    }
+}
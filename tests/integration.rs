@@ -1,11 +1,11 @@
 //! Integration tests for real-world usage scenarios
 
-use eot::{forks::*, Fork, OpCode, OpcodeAnalysis, OpcodeRegistry};
+use eot::{forks::*, Fork, OpCode, OpcodeRegistry};
 
 #[test]
 fn test_gas_cost_analysis() {
     let opcodes = vec![0x01, 0x02]; // Simple ADD, MUL
-    let analysis = OpcodeRegistry::analyze_gas_usage(&opcodes, Fork::London);
+    let analysis = OpcodeRegistry::new().analyze_gas(&opcodes, Fork::London);
 
     let total_gas = analysis.total_gas;
 
@@ -113,6 +113,69 @@ fn test_upgrade_path_analysis() {
     assert!(Cancun::has_opcode(0x5c)); // TLOAD
 }
 
+#[test]
+fn test_intermediate_forks_are_registered() {
+    let registry = OpcodeRegistry::new();
+    let sload = |fork| registry.get_opcodes(fork)[&0x54].gas_cost;
+    assert_eq!(sload(Fork::Homestead), 50);
+    assert_eq!(sload(Fork::TangerineWhistle), 200);
+    assert_eq!(sload(Fork::SpuriousDragon), 200);
+    assert_eq!(sload(Fork::Byzantium), 200);
+    assert_eq!(
+        registry.get_opcodes(Fork::Petersburg).len(),
+        registry.get_opcodes(Fork::Constantinople).len()
+    );
+
+    // Later tables date the repricing to EIP-150
+    let call = &registry.get_opcodes(Fork::Istanbul)[&0xf1];
+    assert_eq!(call.gas_cost_in(Fork::TangerineWhistle), 700);
+    let errors = eot::validation::validate_known_gas_changes(&registry);
+    assert!(!errors.iter().any(|e| e.contains("TangerineWhistle")));
+}
+
+#[test]
+fn test_gas_history_covers_repricings() {
+    let registry = OpcodeRegistry::new();
+    assert_eq!(
+        eot::validation::validate_gas_history_gaps(&registry),
+        Vec::<String>::new()
+    );
+    // EIP-1884 sits between the EIP-150 and EIP-2929 prices
+    let balance = &registry.get_opcodes(Fork::Cancun)[&0x31];
+    assert_eq!(balance.gas_cost_in(Fork::Petersburg), 400);
+    assert_eq!(balance.gas_cost_in(Fork::Istanbul), 700);
+    assert_eq!(balance.gas_cost_in(Fork::Berlin), 2600);
+
+    // The table's own base cost agrees with the history
+    for opcode in [0xf1, 0xf2, 0xf4, 0xfa] {
+        let call = &registry.get_opcodes(Fork::Istanbul)[&opcode];
+        assert_eq!(call.gas_cost, call.gas_cost_in(Fork::Istanbul));
+    }
+
+    let extcodehash = &registry.get_opcodes(Fork::Cancun)[&0x3f];
+    assert_eq!(extcodehash.gas_cost_in(Fork::Constantinople), 400);
+    assert_eq!(extcodehash.gas_cost_in(Fork::Istanbul), 700);
+    assert_eq!(
+        eot::validation::validate_known_gas_changes(&registry),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn test_prague_keeps_cancun_table() {
+    // Pectra adds no opcodes and reprices none
+    assert_eq!(Prague::all_opcodes().len(), Cancun::all_opcodes().len());
+    assert_eq!(Prague::fork(), Fork::Prague);
+
+    let registry = OpcodeRegistry::new();
+    let prague = registry.get_opcodes(Fork::Prague);
+    let cancun = registry.get_opcodes(Fork::Cancun);
+    for (byte, metadata) in &cancun {
+        assert_eq!(prague[byte].gas_cost, metadata.gas_cost);
+    }
+    assert_eq!(prague[&0x40].name, "BLOCKHASH");
+}
+
 #[test]
 fn test_registry_comprehensive() {
     let registry = OpcodeRegistry::new();
@@ -166,21 +229,9 @@ impl ContractAnalysis {
 }
 
 fn find_minimal_fork(opcodes: &[u8]) -> Fork {
-    let forks = [
-        Fork::Frontier,
-        Fork::Homestead,
-        Fork::Byzantium,
-        Fork::Constantinople,
-        Fork::Istanbul,
-        Fork::Berlin,
-        Fork::London,
-        Fork::Shanghai,
-        Fork::Cancun,
-    ];
-
     let registry = OpcodeRegistry::new();
 
-    for &fork in &forks {
+    for fork in Fork::execution_forks().filter(|fork| fork.changes_evm()) {
         let available = opcodes
             .iter()
             .all(|&opcode| registry.is_opcode_available(fork, opcode));
@@ -190,5 +241,114 @@ fn find_minimal_fork(opcodes: &[u8]) -> Fork {
         }
     }
 
-    Fork::Cancun // Fallback to latest
+    Fork::Prague // Fallback to latest
+}
+
+#[test]
+fn test_invalid_and_unassigned_bytes() {
+    use eot::ByteClass;
+
+    let registry = OpcodeRegistry::new();
+    assert_eq!(
+        registry.classify_byte(Fork::London, 0xfe),
+        ByteClass::DesignatedInvalid
+    );
+    assert_eq!(
+        registry.classify_byte(Fork::London, 0x5f),
+        ByteClass::Unassigned
+    );
+    assert_eq!(
+        registry.classify_byte(Fork::Shanghai, 0x5f),
+        ByteClass::Assigned
+    );
+    assert!(ByteClass::Unassigned.consumes_all_gas());
+
+    let invalid = &registry.get_opcodes(Fork::London)[&0xfe];
+    assert!(invalid.consumes_all_gas());
+
+    let unassigned = registry.unassigned_opcodes(Fork::London);
+    assert!(unassigned.contains(&0x5f) && unassigned.contains(&0x0c));
+    assert!(!unassigned.contains(&0xfe) && !unassigned.contains(&0x01));
+    assert!(unassigned
+        .iter()
+        .all(|byte| registry.classify_byte(Fork::London, *byte) == ByteClass::Unassigned));
+    assert!(!registry.unassigned_opcodes(Fork::Shanghai).contains(&0x5f));
+}
+
+#[test]
+fn test_push0_compatibility() {
+    let code = eot::evm_asm!(PUSH0; PUSH0; RETURN).bytecode().to_vec();
+    let error = eot::check_push0_compatibility(&code, Fork::London, false).unwrap_err();
+    assert!(error.contains("requires Shanghai"), "{error}");
+    assert!(OpcodeRegistry::new()
+        .validate_sequence(&code, Fork::London)
+        .is_err());
+
+    let lowered = eot::check_push0_compatibility(&code, Fork::London, true).unwrap();
+    assert_eq!(lowered, eot::evm_asm!(PUSH1 0; PUSH1 0; RETURN).bytecode());
+    assert_eq!(
+        eot::check_push0_compatibility(&code, Fork::Shanghai, false).unwrap(),
+        code
+    );
+}
+
+#[test]
+fn test_custom_fork_registration() {
+    use eot::{Group, OpcodeMetadata};
+
+    let mut registry = OpcodeRegistry::new();
+    let mut sload = registry.get_opcodes(Fork::Cancun)[&0x54].clone();
+    sload.gas_cost = 50;
+    let hint = OpcodeMetadata {
+        opcode: 0x0c,
+        name: "L2HINT",
+        gas_cost: 2,
+        stack_inputs: 1,
+        stack_outputs: 0,
+        description: "Chain-specific hint",
+        introduced_in: Fork::Cancun,
+        group: Group::StopArithmetic,
+        eip: None,
+        gas_history: &[],
+    };
+    registry
+        .register_custom_fork("devnet", Fork::Cancun, vec![hint], vec![sload.clone()])
+        .unwrap();
+
+    let opcodes = registry.get_custom_fork_opcodes("devnet").unwrap();
+    assert_eq!(opcodes[&0x0c].name, "L2HINT");
+    assert_eq!(opcodes[&0x54].gas_cost, 50);
+    assert_eq!(opcodes.len(), registry.get_opcodes(Fork::Cancun).len() + 1);
+    assert_eq!(registry.custom_fork_base("devnet"), Some(Fork::Cancun));
+    assert_eq!(registry.custom_forks().collect::<Vec<_>>(), vec!["devnet"]);
+
+    // Additions must not collide, names must be new
+    assert_eq!(
+        registry.register_custom_fork("other", Fork::Cancun, vec![sload], vec![]),
+        Err("Cannot add SLOAD as 0x54: the byte is already assigned in Cancun".to_string())
+    );
+    assert!(registry
+        .register_custom_fork("devnet", Fork::Cancun, vec![], vec![])
+        .is_err());
+    assert!(registry
+        .register_custom_fork("london", Fork::Cancun, vec![], vec![])
+        .is_err());
+}
+
+#[test]
+fn test_registry_methods_use_its_tables() {
+    let registry = OpcodeRegistry::new();
+    let opcodes = [0x01, 0x02, 0x00]; // ADD, MUL, STOP
+    let analysis = registry.analyze_gas(&opcodes, Fork::Cancun);
+    #[allow(deprecated)]
+    let legacy = <OpcodeRegistry as eot::OpcodeAnalysis>::analyze_gas_usage(&opcodes, Fork::Cancun);
+    assert_eq!(analysis.total_gas, legacy.total_gas);
+    assert_eq!(analysis.breakdown.len(), 3);
+
+    // A subset without MUL does not price it
+    let sandbox = registry.subset([0x00, 0x01]);
+    let restricted = sandbox.analyze_gas(&opcodes, Fork::Cancun);
+    assert_eq!(restricted.breakdown.len(), 2);
+    assert!(!restricted.warnings.is_empty());
+    assert!(sandbox.validate_sequence(&[0x00], Fork::Cancun).is_ok());
 }
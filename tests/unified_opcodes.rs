@@ -136,3 +136,91 @@ fn test_metadata_access() {
         assert_eq!(metadata.name, "ADD");
     }
 }
+
+#[test]
+fn test_invalid_vs_unassigned() {
+    assert!(UnifiedOpcode::INVALID.is_designated_invalid());
+    assert!(!UnifiedOpcode::INVALID.is_unassigned());
+    assert!(UnifiedOpcode::from_byte(0x0c).is_unassigned());
+    assert!(UnifiedOpcode::INVALID.consumes_all_gas());
+    assert!(UnifiedOpcode::from_byte(0x0c).consumes_all_gas());
+    assert!(!UnifiedOpcode::REVERT.consumes_all_gas());
+}
+
+#[test]
+fn test_relative_jumps() {
+    assert_eq!(UnifiedOpcode::from_byte(0xe0), UnifiedOpcode::RJUMP);
+    assert_eq!(UnifiedOpcode::from_str("RJUMPV").unwrap().to_byte(), 0xe2);
+    assert!(UnifiedOpcode::RJUMPI.is_control_flow());
+    // EOF only: no fork table has them
+    assert!(UnifiedOpcode::parse(0xe0).0.is_unassigned());
+
+    // RJUMP -3 at pc 10 jumps back to pc 10
+    let rjump = UnifiedOpcode::RJUMP;
+    assert_eq!(rjump.immediate_len(&[0xff, 0xfd]), Some(2));
+    assert_eq!(rjump.relative_offsets(&[0xff, 0xfd]), Some(vec![-3]));
+    assert_eq!(rjump.relative_targets(10, &[0xff, 0xfd]), Some(vec![10]));
+    assert_eq!(rjump.immediate_len(&[0xff]), None);
+
+    // RJUMPV with max index 1: offsets 0 and 5, relative to pc + 6
+    let table = [0x01, 0x00, 0x00, 0x00, 0x05];
+    let rjumpv = UnifiedOpcode::RJUMPV;
+    assert_eq!(rjumpv.immediate_len(&table), Some(5));
+    assert_eq!(rjumpv.relative_offsets(&table), Some(vec![0, 5]));
+    assert_eq!(rjumpv.relative_targets(0, &table), Some(vec![6, 11]));
+    assert_eq!(rjumpv.relative_offsets(&table[..4]), None);
+    assert_eq!(UnifiedOpcode::ADD.relative_offsets(&table), None);
+}
+
+#[test]
+fn test_function_opcodes() {
+    assert_eq!(UnifiedOpcode::from_byte(0xe3), UnifiedOpcode::CALLF);
+    assert_eq!(UnifiedOpcode::from_str("JUMPF").unwrap().to_byte(), 0xe5);
+
+    let callf = UnifiedOpcode::CALLF;
+    assert_eq!(callf.target_section(&[0x00, 0x02]), Some(2));
+    assert_eq!(callf.target_section(&[0x00]), None);
+    // CALLF returns to the next instruction, RETF and JUMPF leave the section
+    assert_eq!(callf.section_successors(4, &[0x00, 0x02]), Some(vec![7]));
+    assert_eq!(UnifiedOpcode::RETF.section_successors(4, &[]), Some(vec![]));
+    assert_eq!(
+        UnifiedOpcode::JUMPF.section_successors(4, &[0x00, 0x01]),
+        Some(vec![])
+    );
+    // RJUMPI falls through or jumps 2 forward
+    assert_eq!(
+        UnifiedOpcode::RJUMPI.section_successors(0, &[0x00, 0x02]),
+        Some(vec![3, 5])
+    );
+}
+
+#[test]
+fn test_data_section_opcodes() {
+    assert_eq!(UnifiedOpcode::from_byte(0xd1), UnifiedOpcode::DATALOADN);
+    assert_eq!(UnifiedOpcode::from_str("DATACOPY").unwrap().to_byte(), 0xd3);
+
+    let dataloadn = UnifiedOpcode::DATALOADN;
+    assert_eq!(dataloadn.immediate_len(&[0x00, 0x20]), Some(2));
+    assert_eq!(dataloadn.data_offset(&[0x00, 0x20]), Some(0x20));
+    assert_eq!(dataloadn.data_offset(&[0x00]), None);
+    assert_eq!(UnifiedOpcode::DATALOAD.immediate_len(&[]), Some(0));
+}
+
+#[test]
+fn test_eof_creation_opcodes() {
+    assert_eq!(UnifiedOpcode::from_byte(0xec), UnifiedOpcode::EOFCREATE);
+    assert_eq!(
+        UnifiedOpcode::from_str("RETURNCONTRACT").unwrap().to_byte(),
+        0xee
+    );
+
+    let eofcreate = UnifiedOpcode::EOFCREATE;
+    assert_eq!(eofcreate.immediate_len(&[0x01]), Some(1));
+    assert_eq!(eofcreate.container_index(&[0x01]), Some(1));
+    assert_eq!(eofcreate.container_index(&[]), None);
+    // RETURNCONTRACT ends the init code
+    assert_eq!(
+        UnifiedOpcode::RETURNCONTRACT.section_successors(0, &[0x00]),
+        Some(vec![])
+    );
+}